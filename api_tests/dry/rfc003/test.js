@@ -9,6 +9,7 @@ const alpha_ledger_name = "Bitcoin";
 const alpha_ledger_network = "regtest";
 
 const beta_ledger_name = "Ethereum";
+const beta_ledger_chain_id = 17;
 
 const alpha_asset_name = "Bitcoin";
 const alpha_asset_reasonable_quantity = "100000000";
@@ -99,6 +100,21 @@ describe("RFC003 HTTP API", () => {
             });
     });
 
+    it("[Alice] Lists the supported swap combinations", async () => {
+        await chai
+            .request(alice.comit_node_url())
+            .get("/swaps/rfc003/combinations")
+            .then(res => {
+                res.should.have.status(200);
+                res.body.should.deep.include({
+                    alpha_ledger: { name: "Bitcoin" },
+                    beta_ledger: { name: "Ethereum" },
+                    alpha_asset: { name: "Bitcoin" },
+                    beta_asset: { name: "Ether" },
+                });
+            });
+    });
+
     let alice_reasonable_swap_href;
     it("[Alice] Should be able to make first swap request via HTTP api", async () => {
         await chai
@@ -111,6 +127,7 @@ describe("RFC003 HTTP API", () => {
                 },
                 beta_ledger: {
                     name: beta_ledger_name,
+                    chain_id: beta_ledger_chain_id,
                 },
                 alpha_asset: {
                     name: alpha_asset_name,
@@ -160,6 +177,7 @@ describe("RFC003 HTTP API", () => {
                 },
                 beta_ledger: {
                     name: beta_ledger_name,
+                    chain_id: beta_ledger_chain_id,
                 },
                 alpha_asset: {
                     name: alpha_asset_name,
@@ -197,6 +215,7 @@ describe("RFC003 HTTP API", () => {
                 swap.alpha_ledger.name.should.equal(alpha_ledger_name);
                 swap.alpha_ledger.network.should.equal(alpha_ledger_network);
                 swap.beta_ledger.name.should.equal(beta_ledger_name);
+                swap.beta_ledger.chain_id.should.equal(beta_ledger_chain_id);
                 swap.alpha_asset.name.should.equal(alpha_asset_name);
                 swap.alpha_asset.quantity.should.equal(
                     alpha_asset_reasonable_quantity
@@ -286,6 +305,7 @@ describe("RFC003 HTTP API", () => {
                 swap.alpha_ledger.name.should.equal(alpha_ledger_name);
                 swap.alpha_ledger.network.should.equal(alpha_ledger_network);
                 swap.beta_ledger.name.should.equal(beta_ledger_name);
+                swap.beta_ledger.chain_id.should.equal(beta_ledger_chain_id);
                 swap.alpha_asset.name.should.equal(alpha_asset_name);
                 swap.alpha_asset.quantity.should.equal("100");
                 swap.beta_asset.name.should.equal(beta_asset_name);
@@ -73,6 +73,7 @@ describe("RFC003: Bitcoin for ERC20", () => {
                 },
                 beta_ledger: {
                     name: "Ethereum",
+                    chain_id: 17,
                 },
                 alpha_asset: {
                     name: "Bitcoin",
@@ -126,6 +126,7 @@ describe("RFC003: Bitcoin for Ether", () => {
                 },
                 beta_ledger: {
                     name: "Ethereum",
+                    chain_id: 17,
                 },
                 alpha_asset: {
                     name: "Bitcoin",
@@ -69,6 +69,7 @@ describe("RFC003: ERC20 for Bitcoin", () => {
             .send({
                 alpha_ledger: {
                     name: "Ethereum",
+                    chain_id: 17,
                 },
                 beta_ledger: {
                     name: "Bitcoin",
@@ -85,7 +86,9 @@ describe("RFC003: ERC20 for Bitcoin", () => {
                 },
                 alpha_ledger_refund_identity: bob_final_address,
                 beta_ledger_redeem_identity: null,
-                alpha_ledger_lock_duration: 21600,
+                // 24 hours, long enough for Bob's 12 hour beta HTLC plus the
+                // nodes' default lock_duration_safety_margin_secs of 1 hour
+                alpha_ledger_lock_duration: 86400,
             });
 
         res.should.have.status(201);
@@ -130,7 +133,9 @@ describe("RFC003: ERC20 for Bitcoin", () => {
         let bob_response = {
             beta_ledger_refund_identity: bob.wallet.eth_address(),
             alpha_ledger_redeem_identity: bob_final_address,
-            beta_ledger_lock_duration: 288,
+            // 72 blocks are 12 hours, the beta HTLC has to expire at least
+            // the safety margin before Alice's 24 hour alpha HTLC
+            beta_ledger_lock_duration: 72,
         };
 
         logger.info(
@@ -123,6 +123,7 @@ describe("RFC003: Ether for Bitcoin", () => {
             .send({
                 alpha_ledger: {
                     name: "Ethereum",
+                    chain_id: 17,
                 },
                 beta_ledger: {
                     name: "Bitcoin",
@@ -138,7 +139,9 @@ describe("RFC003: Ether for Bitcoin", () => {
                 },
                 alpha_ledger_refund_identity: alice.wallet.eth_address(),
                 beta_ledger_redeem_identity: null,
-                alpha_ledger_lock_duration: 144,
+                // 24 hours, long enough for Bob's 12 hour beta HTLC plus the
+                // nodes' default lock_duration_safety_margin_secs of 1 hour
+                alpha_ledger_lock_duration: 86400,
             })
             .then(res => {
                 res.should.have.status(201);
@@ -183,7 +186,9 @@ describe("RFC003: Ether for Bitcoin", () => {
         let bob_response = {
             beta_ledger_refund_identity: null,
             alpha_ledger_redeem_identity: bob_final_address,
-            beta_ledger_lock_duration: 43200,
+            // 72 blocks are 12 hours, the beta HTLC has to expire at least
+            // the safety margin before Alice's 24 hour alpha HTLC
+            beta_ledger_lock_duration: 72,
         };
 
         logger.info(
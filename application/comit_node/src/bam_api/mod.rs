@@ -29,16 +29,18 @@ mod ledger_impls {
     }
 
     impl FromBamHeader for Ethereum {
-        fn from_bam_header(header: Header) -> Result<Self, Error> {
+        fn from_bam_header(mut header: Header) -> Result<Self, Error> {
             header.has_value("Ethereum")?;
 
-            Ok(Ethereum {})
+            Ok(Ethereum {
+                chain_id: header.parameter("chain_id")?,
+            })
         }
     }
 
     impl ToBamHeader for Ethereum {
         fn to_bam_header(&self) -> Result<Header, Error> {
-            Ok(Header::with_value("Ethereum"))
+            Ok(Header::with_value("Ethereum").with_parameter("chain_id", self.chain_id)?)
         }
     }
 }
@@ -120,7 +122,10 @@ impl ToBamHeader for SwapProtocols {
 #[cfg(test)]
 mod tests {
 
-    use crate::bam_api::header::{Error, Header, ToBamHeader};
+    use crate::{
+        bam_api::header::{Error, FromBamHeader, Header, ToBamHeader},
+        swap_protocols::ledger::Ethereum,
+    };
     use ethereum_support::{Address, Erc20Quantity, U256};
 
     #[test]
@@ -138,4 +143,16 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn ethereum_ledger_carries_chain_id_in_bam_header() -> Result<(), Error> {
+        let header = Ethereum::new(3).to_bam_header()?;
+
+        assert_eq!(
+            header,
+            Header::with_value("Ethereum").with_parameter("chain_id", 3)?
+        );
+        assert_eq!(Ethereum::from_bam_header(header)?, Ethereum::new(3));
+
+        Ok(())
+    }
 }
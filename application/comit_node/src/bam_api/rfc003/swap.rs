@@ -1,138 +1,136 @@
 use crate::{
-    bam_api::header::FromBamHeader,
-    comit_client::{self, rfc003::RequestBody, SwapReject},
+    bam_api::header::{FromBamHeader, Header},
+    comit_client::{
+        self,
+        rfc003::{AbortRequestBody, RequestBody},
+        SwapReject,
+    },
+    logging::{self, Instrument},
     swap_protocols::{
         asset::Asset,
-        ledger::{Bitcoin, Ethereum},
-        rfc003::{self, bob::BobSpawner, state_machine::StateMachineResponse, Ledger},
-        SwapId, SwapProtocols,
+        rfc003::{
+            self,
+            bob::{policy::AutoRespond, BobSpawner},
+            state_machine::StateMachineResponse,
+            CreateLedgerEvents, Ledger, SwapCombination,
+        },
+        AssetKind, IntoMetadata, LedgerEventDependencies, LedgerKind, SwapId, SwapProtocols,
     },
 };
 use bam::{
-    config::Config,
+    config::{Config, ResponseFuture},
     json::{Request, Response},
     Status,
 };
-use bitcoin_support::BitcoinQuantity;
-use ethereum_support::{Erc20Quantity, EtherQuantity};
 use futures::future::{self, Future};
-use std::sync::Arc;
-
-pub fn swap_config<B: BobSpawner>(bob_spawner: Arc<B>) -> Config<Request, Response> {
-    Config::default().on_request(
-        "SWAP",
-        &[
-            "beta_ledger",
-            "alpha_ledger",
-            "beta_asset",
-            "alpha_asset",
-            "swap_protocol",
-        ],
-        move |request: Request| {
-            let swap_protocol = header!(request.get_header("swap_protocol"));
-            match SwapProtocols::from_bam_header(swap_protocol).unwrap() {
-                SwapProtocols::Rfc003 => {
-                    let swap_id = SwapId::default();
-
-                    if let Ok(swap_request) =
-                        decode_request::<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>(
-                            &request,
-                        )
-                    {
-                        let response_future = match bob_spawner.spawn(swap_id, swap_request) {
-                            Ok(response_future) => response_future,
-                            Err(e) => {
-                                error!("Unable to spawn Bob: {:?}", e);
-                                return Box::new(future::ok(Response::new(Status::RE(0))));
-                            }
-                        };
+use std::{net::SocketAddr, sync::Arc};
 
-                        Box::new(response_future.then(move |result| match result {
-                            Ok(response) => Ok(to_bam_response::<Bitcoin, Ethereum>(response)),
-                            Err(_) => {
-                                warn!(
-                                    "Failed to receive from oneshot channel for swap {}",
-                                    swap_id
-                                );
-                                Ok(Response::new(Status::SE(0)))
-                            }
-                        }))
-                    } else if let Ok(swap_request) =
-                        decode_request::<Bitcoin, Ethereum, BitcoinQuantity, Erc20Quantity>(
-                            &request,
-                        )
-                    {
-                        let response_future = match bob_spawner.spawn(swap_id, swap_request) {
-                            Ok(response_future) => response_future,
-                            Err(e) => {
-                                error!("Unable to spawn Bob: {:?}", e);
-                                return Box::new(future::ok(Response::new(Status::RE(0))));
-                            }
-                        };
+pub fn swap_config<B: BobSpawner>(
+    bob_spawner: Arc<B>,
+    counterparty: SocketAddr,
+) -> Config<Request, Response> {
+    let aborting_bob = Arc::clone(&bob_spawner);
 
-                        Box::new(response_future.then(move |result| match result {
-                            Ok(response) => Ok(to_bam_response::<Bitcoin, Ethereum>(response)),
+    Config::default()
+        .on_request(
+            "SWAP",
+            &[
+                "beta_ledger",
+                "alpha_ledger",
+                "beta_asset",
+                "alpha_asset",
+                "swap_protocol",
+            ],
+            move |request: Request| {
+                let swap_protocol = header!(request.get_header("swap_protocol"));
+                match SwapProtocols::from_bam_header(swap_protocol).unwrap() {
+                    SwapProtocols::Rfc003 => {
+                        let combination = match swap_combination(&request) {
+                            Ok(combination) => combination,
                             Err(_) => {
-                                warn!(
-                                    "Failed to receive from oneshot channel for swap {}",
-                                    swap_id
-                                );
-                                Ok(Response::new(Status::SE(0)))
-                            }
-                        }))
-                    } else if let Ok(swap_request) =
-                        decode_request::<Ethereum, Bitcoin, EtherQuantity, BitcoinQuantity>(
-                            &request,
-                        )
-                    {
-                        let response_future = match bob_spawner.spawn(swap_id, swap_request) {
-                            Ok(response_future) => response_future,
-                            Err(e) => {
-                                error!("Unable to spawn Bob: {:?}", e);
-                                return Box::new(future::ok(Response::new(Status::RE(0))));
+                                // TODO: Specify and implement response code
+                                return Box::new(future::ok(Response::new(Status::SE(0))));
                             }
                         };
 
-                        Box::new(response_future.then(move |result| match result {
-                            Ok(response) => Ok(to_bam_response::<Ethereum, Bitcoin>(response)),
-                            Err(_) => {
-                                warn!(
-                                    "Failed to receive from oneshot channel for swap {}",
-                                    swap_id
-                                );
-                                Ok(Response::new(Status::SE(0)))
+                        crate::with_swap_combination!(
+                            combination,
+                            match decode_request::<AL, BL, AA, BA>(&request) {
+                                Ok(swap_request) => {
+                                    spawn_bob(bob_spawner.as_ref(), counterparty, swap_request)
+                                }
+                                Err(_) => Box::new(future::ok(Response::new(Status::SE(0)))),
+                            },
+                            {
+                                // TODO: Specify and implement response code
+                                Box::new(future::ok(Response::new(Status::SE(0))))
                             }
-                        }))
-                    } else if let Ok(swap_request) =
-                        decode_request::<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuantity>(
-                            &request,
                         )
-                    {
-                        let response_future = match bob_spawner.spawn(swap_id, swap_request) {
-                            Ok(response_future) => response_future,
-                            Err(e) => {
-                                error!("Unable to spawn Bob: {:?}", e);
-                                return Box::new(future::ok(Response::new(Status::RE(0))));
-                            }
-                        };
-
-                        Box::new(response_future.then(move |result| match result {
-                            Ok(response) => Ok(to_bam_response::<Ethereum, Bitcoin>(response)),
-                            Err(_) => {
-                                warn!(
-                                    "Failed to receive from oneshot channel for swap {}",
-                                    swap_id
-                                );
-                                Ok(Response::new(Status::SE(0)))
-                            }
-                        }))
-                    } else {
-                        // TODO: Specify and implement response code
-                        Box::new(future::ok(Response::new(Status::SE(0))))
                     }
                 }
+            },
+        )
+        .on_request("SWAP_ABORT", &[], move |request: Request| {
+            let secret_hash = match request.get_body::<AbortRequestBody>() {
+                Some(Ok(body)) => body.secret_hash,
+                _ => return Box::new(future::ok(Response::new(Status::SE(0)))),
+            };
+
+            if aborting_bob.abort(counterparty, secret_hash) {
+                Box::new(future::ok(Response::new(Status::OK(20))))
+            } else {
+                warn!(
+                    "{} aborted a swap request which is not pending",
+                    counterparty
+                );
+                Box::new(future::ok(Response::new(Status::SE(0))))
             }
-        },
+        })
+}
+
+fn spawn_bob<B: BobSpawner, AL: Ledger, BL: Ledger, AA: Asset, BA: Asset>(
+    bob_spawner: &B,
+    counterparty: SocketAddr,
+    swap_request: rfc003::bob::SwapRequest<AL, BL, AA, BA>,
+) -> Box<ResponseFuture<Response>>
+where
+    LedgerEventDependencies: CreateLedgerEvents<AL, AA> + CreateLedgerEvents<BL, BA>,
+    rfc003::bob::SwapRequest<AL, BL, AA, BA>: IntoMetadata + AutoRespond<AL, BL>,
+{
+    let swap_id = SwapId::default();
+    let span = logging::swap_span(swap_id);
+
+    let spawned = span.enter(|| bob_spawner.spawn(counterparty, swap_id, swap_request));
+    let response_future = match spawned {
+        Ok(response_future) => response_future,
+        Err(rfc003::bob::Error::UnsupportedNetwork) => {
+            span.enter(|| {
+                warn!(
+                    "Declining swap {} because the node is not configured for its networks",
+                    swap_id
+                )
+            });
+            return Box::new(future::ok(Response::new(Status::SE(20))));
+        }
+        Err(e) => {
+            span.enter(|| error!("Unable to spawn Bob: {:?}", e));
+            return Box::new(future::ok(Response::new(Status::RE(0))));
+        }
+    };
+
+    Box::new(
+        response_future
+            .then(move |result| match result {
+                Ok(response) => Ok(to_bam_response::<AL, BL>(response)),
+                Err(_) => {
+                    warn!(
+                        "Failed to receive from oneshot channel for swap {}",
+                        swap_id
+                    );
+                    Ok(Response::new(Status::SE(0)))
+                }
+            })
+            .instrument(span),
     )
 }
 
@@ -206,7 +204,127 @@ fn decode_request<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset>(
     })
 }
 
+fn swap_combination(request: &Request) -> Result<SwapCombination, Error> {
+    Ok(SwapCombination {
+        alpha_ledger: LedgerKind::from_name(get_header(request, "alpha_ledger")?.value())
+            .ok_or(Error::Unsupported)?,
+        beta_ledger: LedgerKind::from_name(get_header(request, "beta_ledger")?.value())
+            .ok_or(Error::Unsupported)?,
+        alpha_asset: AssetKind::from_name(get_header(request, "alpha_asset")?.value())
+            .ok_or(Error::Unsupported)?,
+        beta_asset: AssetKind::from_name(get_header(request, "beta_asset")?.value())
+            .ok_or(Error::Unsupported)?,
+    })
+}
+
+fn get_header(request: &Request, key: &str) -> Result<Header, Error> {
+    request
+        .get_header(key)
+        .ok_or(Error::Missing)?
+        .map_err(|_| Error::Invalid)
+}
+
 enum Error {
     Missing,
     Invalid,
+    Unsupported,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bam_api::header::ToBamHeader,
+        swap_protocols::{
+            ledger::{Bitcoin, Ethereum},
+            rfc003::ethereum::Seconds,
+        },
+    };
+    use bitcoin_support::BitcoinQuantity;
+    use ethereum_support::{Address, Erc20Quantity, EtherQuantity, U256};
+    use rfc003::SecretHash;
+    use spectral::prelude::*;
+
+    fn token() -> Erc20Quantity {
+        Erc20Quantity::new(Address::from(0xAA), U256::from(1_000))
+    }
+
+    fn request<AA: ToBamHeader, BA: ToBamHeader>(alpha_asset: &AA, beta_asset: &BA) -> Request {
+        let header = |header: Result<Header, _>| serde_json::to_value(header.unwrap()).unwrap();
+
+        Request::new(
+            "SWAP".into(),
+            convert_args!(
+                keys = String::from,
+                values = header,
+                hashmap!(
+                    "alpha_ledger" => Ethereum::new(3).to_bam_header(),
+                    "beta_ledger" => Ethereum::new(3).to_bam_header(),
+                    "alpha_asset" => alpha_asset.to_bam_header(),
+                    "beta_asset" => beta_asset.to_bam_header(),
+                    "swap_protocol" => SwapProtocols::Rfc003.to_bam_header(),
+                )
+            ),
+            serde_json::to_value(comit_client::rfc003::RequestBody::<Ethereum, Ethereum> {
+                alpha_ledger_refund_identity: Address::from(0x01),
+                beta_ledger_redeem_identity: Address::from(0x02),
+                alpha_ledger_lock_duration: Seconds(86_400),
+                secret_hash: SecretHash::from(*b"hello world, you are beautiful!!"),
+            })
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn decodes_ether_for_erc20_request() {
+        let request = request(&EtherQuantity::from_eth(1.0), &token());
+
+        let combination = swap_combination(&request).ok().unwrap();
+        assert_that(&combination).is_equal_to(SwapCombination {
+            alpha_ledger: LedgerKind::Ethereum,
+            beta_ledger: LedgerKind::Ethereum,
+            alpha_asset: AssetKind::Ether,
+            beta_asset: AssetKind::Erc20,
+        });
+
+        let swap_request =
+            decode_request::<Ethereum, Ethereum, EtherQuantity, Erc20Quantity>(&request)
+                .ok()
+                .unwrap();
+        assert_that(&swap_request.alpha_asset).is_equal_to(EtherQuantity::from_eth(1.0));
+        assert_that(&swap_request.beta_asset).is_equal_to(token());
+        assert_that(&swap_request.alpha_ledger).is_equal_to(Ethereum::new(3));
+        assert_that(&swap_request.beta_ledger_redeem_identity).is_equal_to(Address::from(0x02));
+    }
+
+    #[test]
+    fn decodes_erc20_for_erc20_request() {
+        let other_token = Erc20Quantity::new(Address::from(0xBB), U256::from(2_000));
+        let request = request(&token(), &other_token);
+
+        let combination = swap_combination(&request).ok().unwrap();
+        assert_that(&combination).is_equal_to(SwapCombination {
+            alpha_ledger: LedgerKind::Ethereum,
+            beta_ledger: LedgerKind::Ethereum,
+            alpha_asset: AssetKind::Erc20,
+            beta_asset: AssetKind::Erc20,
+        });
+
+        let swap_request =
+            decode_request::<Ethereum, Ethereum, Erc20Quantity, Erc20Quantity>(&request)
+                .ok()
+                .unwrap();
+        assert_that(&swap_request.alpha_asset).is_equal_to(token());
+        assert_that(&swap_request.beta_asset).is_equal_to(other_token);
+        assert_that(&swap_request.alpha_ledger_lock_duration).is_equal_to(Seconds(86_400));
+    }
+
+    #[test]
+    fn erc20_body_does_not_decode_as_bitcoin_request() {
+        let request = request(&token(), &EtherQuantity::from_eth(1.0));
+
+        assert!(
+            decode_request::<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>(&request).is_err()
+        );
+    }
 }
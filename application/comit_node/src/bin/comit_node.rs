@@ -4,74 +4,214 @@
 #[macro_use]
 extern crate log;
 
+use bitcoin_rpc_client::BitcoinCoreClient;
 use comit_node::{
+    cli::{self, Command, ConfigCommand, HttpApiClient, Options, RunOptions, SwapsCommand},
     comit_client, comit_server,
-    http_api::route_factory,
+    fee_estimation::{
+        bitcoin::{BitcoindFeeEstimator, FeeEstimator as BitcoinFeeEstimator},
+        ethereum::{self as ethereum_gas, GasEstimator, NodeGasEstimator, StaticGas},
+        FeeEstimators,
+    },
+    http_api::{
+        auth::{ApiKey, ApiKeys},
+        route_factory,
+    },
     ledger_query_service::DefaultLedgerQueryServiceApiClient,
     logging,
-    settings::ComitNodeSettings,
+    settings::{self, ComitNodeSettings},
     swap_protocols::{
-        rfc003::{alice::AliceSpawner, bob::BobSpawner, state_store::InMemoryStateStore},
-        InMemoryMetadataStore, LedgerEventDependencies, ProtocolDependencies, SwapId,
+        ledger::Networks,
+        rfc003::{
+            alice::AliceSpawner,
+            bob::{
+                policy::{
+                    HttpRateSource, Policy, RateSource, ResponseParameters, StaticRateSource,
+                    Tokens,
+                },
+                Aborts, BobSpawner,
+            },
+            resumer::SwapResumer,
+            state_store::{InMemoryStateStore, SqliteStateStore, StateStore},
+        },
+        ExecutionLog, InMemoryMetadataStore, LedgerEventDependencies, MetadataStore,
+        ProtocolDependencies, SqliteMetadataStore, StateChanges, SwapId, TransitionStore,
+    },
+    wallet::{
+        ethereum::{InMemoryWallet, Web3Node},
+        BitcoinWallet, BitcoindWallet, EthereumWallet, NodeWallet, Wallets,
+    },
+    webhooks::{
+        InMemoryPendingDeliveries, PendingDeliveries, SqlitePendingDeliveries, Webhook, Webhooks,
     },
 };
 use ethereum_support::*;
-use std::{env::var, net::SocketAddr, sync::Arc};
+use futures::future;
+use reqwest::{r#async::Client, Certificate, Identity};
+use secp256k1_support::KeyPair;
+use serde_json::Value;
+use std::{fs, net::SocketAddr, path::PathBuf, process, sync::Arc, time::Duration};
+use structopt::StructOpt;
+use url::Url;
 
-// TODO: Make a nice command line interface here (using StructOpt f.e.) see #298
 fn main() -> Result<(), failure::Error> {
-    logging::set_up_logging();
-    let settings = load_settings()?;
+    let options = Options::from_args();
 
-    // TODO: Maybe not print settings because of private keys?
+    match options.command {
+        Some(Command::Run(run_options)) => start(&run_options),
+        // Keep `comit_node` without arguments working for existing deployments
+        None => start(&RunOptions::from_iter(&["comit_node"])),
+        Some(Command::Swaps {
+            url,
+            api_key,
+            command,
+        }) => swaps(url, api_key, command),
+        Some(Command::Config(ConfigCommand::Check(config_options))) => {
+            match config_options.load_settings() {
+                Ok(_) => {
+                    println!("Configuration is valid");
+                    Ok(())
+                }
+                Err(e) => {
+                    eprintln!("Configuration is invalid: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn start(run_options: &RunOptions) -> Result<(), failure::Error> {
+    let settings = run_options.load_settings()?;
+    logging::set_up_logging(settings.logging.level, settings.logging.format);
+
+    // Secrets in the settings are left out of their debug output
     info!("Starting up with {:#?}", settings);
 
-    let metadata_store = Arc::new(InMemoryMetadataStore::default());
-    let state_store = Arc::new(InMemoryStateStore::default());
-    let lqs_client = create_ledger_query_service_api_client(&settings);
+    match settings.state_store {
+        settings::StateStore::InMemory => run(
+            &settings,
+            Arc::new(InMemoryMetadataStore::default()),
+            Arc::new(InMemoryStateStore::default()),
+            Arc::new(InMemoryPendingDeliveries::default()),
+        ),
+        settings::StateStore::Sqlite { ref path } => run(
+            &settings,
+            Arc::new(SqliteMetadataStore::open(path)?),
+            Arc::new(SqliteStateStore::open(
+                path,
+                Arc::new(settings.comit.secret_seed),
+            )?),
+            Arc::new(SqlitePendingDeliveries::open(path)?),
+        ),
+    }
+}
+
+fn run<
+    T: MetadataStore<SwapId> + ExecutionLog<SwapId> + TransitionStore<SwapId>,
+    S: StateStore<SwapId>,
+>(
+    settings: &ComitNodeSettings,
+    metadata_store: Arc<T>,
+    state_store: Arc<S>,
+    pending_deliveries: Arc<dyn PendingDeliveries>,
+) -> Result<(), failure::Error> {
+    let lqs_client = create_ledger_query_service_api_client(settings)?;
     let comit_client_factory = Arc::new(comit_client::bam::BamClientPool::default());
+    let state_changes = Arc::new(StateChanges::default());
     let dependencies = Arc::new(create_dependencies(
-        &settings,
+        settings,
         Arc::clone(&metadata_store),
         Arc::clone(&state_store),
         Arc::clone(&lqs_client),
         comit_client_factory.clone(),
+        Arc::clone(&state_changes),
+        pending_deliveries,
     ));
 
     let mut runtime = tokio::runtime::Runtime::new()?;
 
+    resume_swaps(Arc::clone(&dependencies), &mut runtime);
+    resume_webhook_deliveries(Arc::clone(&dependencies.webhooks), &mut runtime);
+
     spawn_warp_instance(
-        &settings,
+        settings,
         Arc::clone(&metadata_store),
         Arc::clone(&state_store),
         dependencies.clone(),
         Arc::clone(&comit_client_factory),
+        state_changes,
         &mut runtime,
-    );
+    )?;
 
-    spawn_comit_server(&settings, dependencies.clone(), &mut runtime);
+    spawn_comit_server(settings, dependencies.clone(), &mut runtime);
 
     // Block the current thread.
     ::std::thread::park();
     Ok(())
 }
 
-fn load_settings() -> Result<ComitNodeSettings, config::ConfigError> {
-    let comit_config_path = var_or_default("COMIT_NODE_CONFIG_PATH", "~/.config/comit_node".into());
-    let run_mode_config = var_or_default("RUN_MODE", "development".into());
-    let default_config = format!("{}/{}", comit_config_path.trim(), "default");
-    let run_mode_config = format!("{}/{}", comit_config_path.trim(), run_mode_config);
+fn swaps(url: Url, api_key: Option<String>, command: SwapsCommand) -> Result<(), failure::Error> {
+    let client = HttpApiClient::new(url, api_key);
+
+    let result = match command {
+        SwapsCommand::List => client.get_swaps(),
+        SwapsCommand::Show { id } => client.get_swap(&id),
+        SwapsCommand::Accept {
+            id,
+            alpha_ledger_redeem_identity,
+            beta_ledger_refund_identity,
+            beta_ledger_lock_duration,
+        } => client
+            .accept(
+                &id,
+                alpha_ledger_redeem_identity,
+                beta_ledger_refund_identity,
+                beta_ledger_lock_duration,
+            )
+            .map(|_| Value::Null),
+        SwapsCommand::Decline { id, reason } => client.decline(&id, reason).map(|_| Value::Null),
+        SwapsCommand::Execute { id, action } => client.execute(&id, &action),
+    };
 
-    let settings = ComitNodeSettings::create(default_config, run_mode_config)?;
-    Ok(settings)
+    match result {
+        Ok(Value::Null) => Ok(()),
+        Ok(value) => {
+            println!("{}", serde_json::to_string_pretty(&value)?);
+            Ok(())
+        }
+        Err(cli::Error::Response { status, body }) => {
+            eprintln!("{}", status);
+            eprintln!("{}", body);
+            process::exit(1);
+        }
+        Err(e) => Err(e.into()),
+    }
 }
 
 fn create_ledger_query_service_api_client(
     settings: &ComitNodeSettings,
-) -> Arc<DefaultLedgerQueryServiceApiClient> {
-    Arc::new(DefaultLedgerQueryServiceApiClient::new(
+) -> Result<Arc<DefaultLedgerQueryServiceApiClient>, failure::Error> {
+    let mut client = Client::builder();
+
+    if let Some(ref tls_settings) = settings.ledger_query_service.tls {
+        // Adds to the built-in root certificates, which reqwest doesn't let us
+        // disable, so any publicly trusted certificate is accepted as well
+        client =
+            client.add_root_certificate(Certificate::from_pem(&fs::read(&tls_settings.ca_path)?)?);
+
+        if let Some(ref client_identity_path) = tls_settings.client_identity_path {
+            client = client.identity(Identity::from_pkcs12_der(
+                &fs::read(client_identity_path)?,
+                &tls_settings.client_identity_password,
+            )?);
+        }
+    }
+
+    Ok(Arc::new(DefaultLedgerQueryServiceApiClient::with_client(
         &settings.ledger_query_service.url,
-    ))
+        client.build()?,
+    )))
 }
 
 fn create_dependencies<T, S, C>(
@@ -80,6 +220,8 @@ fn create_dependencies<T, S, C>(
     state_store: Arc<S>,
     lqs_client: Arc<DefaultLedgerQueryServiceApiClient>,
     comit_client_factory: Arc<dyn comit_client::ClientFactory<C>>,
+    state_changes: Arc<StateChanges>,
+    pending_deliveries: Arc<dyn PendingDeliveries>,
 ) -> ProtocolDependencies<T, S, C> {
     ProtocolDependencies {
         ledger_events: LedgerEventDependencies {
@@ -92,31 +234,242 @@ fn create_dependencies<T, S, C>(
         comit_client_factory,
         seed: settings.comit.secret_seed,
         remote_comit_node: settings.comit.remote_comit_node_url,
+        bob_policy: settings.bob_policy.as_ref().map(create_bob_policy),
+        lock_duration_margin: settings.comit.lock_duration_safety_margin_secs,
+        networks: Networks {
+            bitcoin: settings.bitcoin.network,
+            ethereum_chain_id: settings.ethereum.chain_id,
+        },
+        state_changes,
+        webhooks: Arc::new(create_webhooks(settings, pending_deliveries)),
+        aborts: Arc::new(Aborts::default()),
     }
 }
 
-fn spawn_warp_instance<S: AliceSpawner, C: comit_client::ClientPool>(
+fn create_webhooks(
     settings: &ComitNodeSettings,
-    metadata_store: Arc<InMemoryMetadataStore<SwapId>>,
-    state_store: Arc<InMemoryStateStore<SwapId>>,
+    pending_deliveries: Arc<dyn PendingDeliveries>,
+) -> Webhooks {
+    Webhooks::with_pending_deliveries(
+        settings
+            .webhooks
+            .iter()
+            .map(|webhook| Webhook {
+                url: webhook.url.clone(),
+                secret: webhook.secret.clone(),
+            })
+            .collect(),
+        pending_deliveries,
+    )
+}
+
+fn create_bob_policy(settings: &settings::BobPolicy) -> Policy {
+    let rate_source: Arc<dyn RateSource> = match settings.rate_source {
+        settings::RateSource::Static { ref rates } => {
+            Arc::new(StaticRateSource::new(rates.clone()))
+        }
+        settings::RateSource::Http {
+            ref url,
+            timeout_secs,
+        } => Arc::new(HttpRateSource::new(
+            url.clone(),
+            Duration::from_secs(timeout_secs),
+        )),
+    };
+
+    Policy::new(
+        settings.rules.clone(),
+        settings.max_concurrent_swaps,
+        rate_source,
+        ResponseParameters {
+            ethereum_identity: settings.ethereum_identity,
+            bitcoin_lock_duration: settings.bitcoin_lock_duration,
+            ethereum_lock_duration: settings.ethereum_lock_duration,
+        },
+        Tokens::new(settings.tokens.clone()),
+    )
+}
+
+fn spawn_warp_instance<
+    T: MetadataStore<SwapId> + ExecutionLog<SwapId> + TransitionStore<SwapId>,
+    SS: StateStore<SwapId>,
+    S: AliceSpawner,
+    C: comit_client::ClientPool,
+>(
+    settings: &ComitNodeSettings,
+    metadata_store: Arc<T>,
+    state_store: Arc<SS>,
     alice_spawner: Arc<S>,
     comit_client_pool: Arc<C>,
+    state_changes: Arc<StateChanges>,
     runtime: &mut tokio::runtime::Runtime,
-) {
+) -> Result<(), failure::Error> {
+    let fee_estimators = create_fee_estimators(settings)?;
+    let wallets = create_wallets(settings, &fee_estimators)?;
     let routes = route_factory::create(
         metadata_store,
         state_store,
         alice_spawner,
         settings.comit.secret_seed,
         comit_client_pool,
+        settings.comit.lock_duration_safety_margin_secs,
+        wallets,
+        fee_estimators,
+        state_changes,
+        Arc::new(create_api_keys(settings)),
+        settings.http_api.public_metrics,
     );
 
-    let server = warp::serve(routes).bind(SocketAddr::new(
-        settings.http_api.address,
-        settings.http_api.port,
-    ));
+    let address = SocketAddr::new(settings.http_api.address, settings.http_api.port);
+
+    match settings.http_api.tls {
+        Some(ref tls_settings) => {
+            let acceptor = tls_support::acceptor(
+                &tls_settings.cert_path,
+                &tls_settings.key_path,
+                tls_settings.client_ca_path.as_ref().map(PathBuf::as_path),
+            )?;
+
+            info!("Serving the HTTP API over TLS on {}", address);
+            runtime.spawn(
+                warp::serve(routes).serve_incoming(tls_support::incoming(address, acceptor)?),
+            );
+        }
+        None => {
+            runtime.spawn(warp::serve(routes).bind(address));
+        }
+    }
+
+    Ok(())
+}
+
+fn create_api_keys(settings: &ComitNodeSettings) -> ApiKeys {
+    let api_keys = ApiKeys::new(
+        settings
+            .http_api
+            .api_keys
+            .iter()
+            .map(|api_key| ApiKey {
+                key: api_key.key.clone(),
+                scope: api_key.scope,
+            })
+            .collect(),
+    );
+
+    if !api_keys.is_empty() {
+        return api_keys;
+    }
+
+    if settings.http_api.allow_unauthenticated {
+        warn!("No API keys configured, the HTTP API is open to everyone who can reach it");
+        api_keys.allow_unauthenticated()
+    } else {
+        warn!("No API keys configured, every request to the HTTP API is rejected");
+        api_keys
+    }
+}
+
+fn create_fee_estimators(settings: &ComitNodeSettings) -> Result<FeeEstimators, failure::Error> {
+    let bitcoin: Arc<dyn BitcoinFeeEstimator> = match settings.bitcoin_fees {
+        settings::BitcoinFees::Static(ref fees) => Arc::new(fees.clone()),
+        settings::BitcoinFees::Bitcoind { ref fallback } => {
+            let wallet = settings.bitcoin_wallet.as_ref().ok_or_else(|| {
+                failure::err_msg("Estimating fees through bitcoind requires a `bitcoin_wallet`")
+            })?;
+
+            Arc::new(BitcoindFeeEstimator::new(
+                BitcoinCoreClient::new(
+                    wallet.node_url.as_str(),
+                    &wallet.node_username,
+                    &wallet.node_password,
+                ),
+                fallback.clone(),
+            ))
+        }
+    };
+
+    let ethereum: Arc<dyn GasEstimator> = match settings.ethereum_gas {
+        settings::EthereumGas::Static { gas_price_gwei } => {
+            Arc::new(StaticGas::new(ethereum_gas::gwei(gas_price_gwei)))
+        }
+        settings::EthereumGas::Node {
+            ref node_url,
+            fallback_gas_price_gwei,
+        } => Arc::new(NodeGasEstimator::new(
+            connect_to_ethereum_node(node_url)?,
+            StaticGas::new(ethereum_gas::gwei(fallback_gas_price_gwei)),
+        )),
+    };
+
+    Ok(FeeEstimators { bitcoin, ethereum })
+}
 
-    runtime.spawn(server);
+fn create_wallets(
+    settings: &ComitNodeSettings,
+    fee_estimators: &FeeEstimators,
+) -> Result<Wallets, failure::Error> {
+    let ethereum = match settings.ethereum_wallet {
+        Some(ref wallet) => {
+            let keypair = KeyPair::from_secret_key_slice(
+                &settings
+                    .comit
+                    .secret_seed
+                    .sha256_with_seed(&[b"ETHEREUM_WALLET"]),
+            )
+            .expect("seed should yield a valid secret key");
+            let gas_price = wallet
+                .gas_price_gwei
+                .map(|gwei| U256::from(gwei) * U256::from(1_000_000_000u64));
+
+            let wallet = NodeWallet::new(
+                connect_to_ethereum_node(&wallet.node_url)?,
+                InMemoryWallet::new(keypair, settings.ethereum.chain_id),
+                gas_price,
+            );
+            info!("Executing Ethereum actions from {:x}", wallet.address());
+
+            Some(Arc::new(wallet) as Arc<dyn EthereumWallet>)
+        }
+        None => None,
+    };
+
+    Ok(Wallets {
+        bitcoin: settings.bitcoin_wallet.as_ref().map(|wallet| {
+            Arc::new(BitcoindWallet::new(
+                wallet.node_url.as_str(),
+                &wallet.node_username,
+                &wallet.node_password,
+                Arc::clone(&fee_estimators.bitcoin),
+                wallet.fee_target,
+            )) as Arc<dyn BitcoinWallet>
+        }),
+        ethereum,
+    })
+}
+
+fn connect_to_ethereum_node(url: &Url) -> Result<Web3Node, failure::Error> {
+    Web3Node::new(url.as_str()).map_err(|e| {
+        failure::err_msg(format!(
+            "Unable to connect to the Ethereum node at {}: {:?}",
+            url, e
+        ))
+    })
+}
+
+fn resume_swaps<R: SwapResumer>(resumer: Arc<R>, runtime: &mut tokio::runtime::Runtime) {
+    runtime.spawn(future::lazy(move || {
+        if let Err(e) = resumer.resume_swaps() {
+            error!("Failed to resume swaps: {:?}", e);
+        }
+        Ok(())
+    }));
+}
+
+fn resume_webhook_deliveries(webhooks: Arc<Webhooks>, runtime: &mut tokio::runtime::Runtime) {
+    runtime.spawn(future::lazy(move || {
+        webhooks.resume();
+        Ok(())
+    }));
 }
 
 fn spawn_comit_server<B: BobSpawner>(
@@ -130,19 +483,3 @@ fn spawn_comit_server<B: BobSpawner>(
         }),
     );
 }
-
-fn var_or_default(name: &str, default: String) -> String {
-    match var(name) {
-        Ok(value) => {
-            info!("Set {}={}", name, value);
-            value
-        }
-        Err(_) => {
-            eprintln!(
-                "{} is not set, falling back to default: '{}' ",
-                name, default
-            );
-            default
-        }
-    }
-}
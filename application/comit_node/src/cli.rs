@@ -0,0 +1,311 @@
+use crate::{http_api, settings::ComitNodeSettings, swap_protocols::SwapId};
+use config::ConfigError;
+use log::LevelFilter;
+use reqwest::{self, header::AUTHORIZATION, RequestBuilder, StatusCode};
+use serde_json::{Map, Value};
+use std::net::{IpAddr, SocketAddr};
+use url::Url;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "comit_node", about = "A node for executing COMIT atomic swaps")]
+pub struct Options {
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Start the node, this is the default if no command is given
+    #[structopt(name = "run")]
+    Run(RunOptions),
+    /// Inspect and respond to swaps through the HTTP API of a running node
+    #[structopt(name = "swaps")]
+    Swaps {
+        /// The URL of the node's HTTP API
+        #[structopt(
+            long = "http-api-url",
+            default_value = "http://localhost:8000",
+            parse(try_from_str = "Url::parse")
+        )]
+        url: Url,
+        /// Required unless the node sets `http_api.allow_unauthenticated`
+        #[structopt(long = "api-key", env = "COMIT_NODE_API_KEY")]
+        api_key: Option<String>,
+        #[structopt(subcommand)]
+        command: SwapsCommand,
+    },
+    #[structopt(name = "config")]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, StructOpt)]
+pub enum SwapsCommand {
+    /// List all swaps
+    #[structopt(name = "list")]
+    List,
+    /// Show a single swap including its available actions
+    #[structopt(name = "show")]
+    Show { id: SwapId },
+    /// Accept a swap request as Bob
+    #[structopt(name = "accept")]
+    Accept {
+        id: SwapId,
+        #[structopt(long = "alpha-ledger-redeem-identity")]
+        alpha_ledger_redeem_identity: Option<String>,
+        #[structopt(long = "beta-ledger-refund-identity")]
+        beta_ledger_refund_identity: Option<String>,
+        /// In blocks if Bitcoin is the beta ledger, in seconds otherwise
+        #[structopt(long = "beta-ledger-lock-duration")]
+        beta_ledger_lock_duration: u64,
+    },
+    /// Decline a swap request as Bob
+    #[structopt(name = "decline")]
+    Decline {
+        id: SwapId,
+        /// One of the reasons defined by RFC003, e.g. "BadRate"
+        #[structopt(long = "reason")]
+        reason: Option<String>,
+    },
+    /// Let the node execute an action, e.g. "fund", with its own wallet
+    #[structopt(name = "execute")]
+    Execute { id: SwapId, action: String },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum ConfigCommand {
+    /// Load the configuration and report whether it is valid
+    #[structopt(name = "check")]
+    Check(ConfigOptions),
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ConfigOptions {
+    /// The directory containing `default.toml` and the run mode configuration
+    #[structopt(
+        long = "config-path",
+        env = "COMIT_NODE_CONFIG_PATH",
+        default_value = "~/.config/comit_node"
+    )]
+    pub config_path: String,
+    /// The name of the configuration file that is merged into `default.toml`
+    #[structopt(long = "run-mode", env = "RUN_MODE", default_value = "development")]
+    pub run_mode: String,
+}
+
+impl ConfigOptions {
+    pub fn load_settings(&self) -> Result<ComitNodeSettings, ConfigError> {
+        let config_path = self.config_path.trim();
+        let default_config = format!("{}/{}", config_path, "default");
+        let run_mode_config = format!("{}/{}", config_path, self.run_mode.trim());
+
+        ComitNodeSettings::create(default_config, run_mode_config)
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct RunOptions {
+    #[structopt(flatten)]
+    pub config: ConfigOptions,
+    /// Overrides `logging.level`, i.e. one of off, error, warn, info, debug or trace
+    #[structopt(long = "log-level")]
+    pub log_level: Option<LevelFilter>,
+    /// Overrides `comit.comit_listen`
+    #[structopt(long = "comit-listen")]
+    pub comit_listen: Option<SocketAddr>,
+    /// Overrides `http_api.address`
+    #[structopt(long = "http-api-address")]
+    pub http_api_address: Option<IpAddr>,
+    /// Overrides `http_api.port`
+    #[structopt(long = "http-api-port")]
+    pub http_api_port: Option<u16>,
+}
+
+impl RunOptions {
+    pub fn load_settings(&self) -> Result<ComitNodeSettings, ConfigError> {
+        let mut settings = self.config.load_settings()?;
+
+        if let Some(log_level) = self.log_level {
+            settings.logging.level = log_level;
+        }
+        if let Some(comit_listen) = self.comit_listen {
+            settings.comit.comit_listen = comit_listen;
+        }
+        if let Some(address) = self.http_api_address {
+            settings.http_api.address = address;
+        }
+        if let Some(port) = self.http_api_port {
+            settings.http_api.port = port;
+        }
+
+        Ok(settings)
+    }
+}
+
+#[derive(Debug, Fail)]
+pub enum Error {
+    #[fail(display = "Could not reach the HTTP API: {}", _0)]
+    Transport(reqwest::Error),
+    #[fail(display = "Could not build the URL of the HTTP API route: {}", _0)]
+    Url(url::ParseError),
+    #[fail(display = "The HTTP API responded with {}: {}", status, body)]
+    Response { status: StatusCode, body: String },
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Transport(e)
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(e: url::ParseError) -> Self {
+        Error::Url(e)
+    }
+}
+
+/// A client for the routes defined in `http_api::route_factory`
+#[derive(Debug)]
+pub struct HttpApiClient {
+    client: reqwest::Client,
+    url: Url,
+    api_key: Option<String>,
+}
+
+impl HttpApiClient {
+    pub fn new(url: Url, api_key: Option<String>) -> Self {
+        HttpApiClient {
+            client: reqwest::Client::new(),
+            url,
+            api_key,
+        }
+    }
+
+    pub fn get_swaps(&self) -> Result<Value, Error> {
+        let url = self.url.join(http_api::PATH)?;
+
+        Self::parse_response(self.authorized(self.client.get(url)).send()?)
+    }
+
+    pub fn get_swap(&self, id: &SwapId) -> Result<Value, Error> {
+        let url = self.rfc003_url(&id.to_string())?;
+
+        Self::parse_response(self.authorized(self.client.get(url)).send()?)
+    }
+
+    pub fn accept(
+        &self,
+        id: &SwapId,
+        alpha_ledger_redeem_identity: Option<String>,
+        beta_ledger_refund_identity: Option<String>,
+        beta_ledger_lock_duration: u64,
+    ) -> Result<(), Error> {
+        let mut body = Map::new();
+        if let Some(identity) = alpha_ledger_redeem_identity {
+            body.insert("alpha_ledger_redeem_identity".into(), identity.into());
+        }
+        if let Some(identity) = beta_ledger_refund_identity {
+            body.insert("beta_ledger_refund_identity".into(), identity.into());
+        }
+        body.insert(
+            "beta_ledger_lock_duration".into(),
+            beta_ledger_lock_duration.into(),
+        );
+
+        self.post_action(id, "accept", Value::Object(body))
+    }
+
+    pub fn decline(&self, id: &SwapId, reason: Option<String>) -> Result<(), Error> {
+        self.post_action(id, "decline", json!({ "reason": reason }))
+    }
+
+    pub fn execute(&self, id: &SwapId, action: &str) -> Result<Value, Error> {
+        let url = self.rfc003_url(&format!("{}/{}/execute", id, action))?;
+
+        Self::parse_response(self.authorized(self.client.post(url)).send()?)
+    }
+
+    fn post_action(&self, id: &SwapId, action: &str, body: Value) -> Result<(), Error> {
+        let url = self.rfc003_url(&format!("{}/{}", id, action))?;
+
+        Self::parse_response(self.authorized(self.client.post(url)).json(&body).send()?).map(|_| ())
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.api_key {
+            Some(ref api_key) => request.header(AUTHORIZATION, format!("Bearer {}", api_key)),
+            None => request,
+        }
+    }
+
+    fn rfc003_url(&self, path: &str) -> Result<Url, Error> {
+        Ok(self.url.join(&format!(
+            "{}/{}/{}",
+            http_api::PATH,
+            http_api::rfc003::swap::PROTOCOL_NAME,
+            path
+        ))?)
+    }
+
+    fn parse_response(mut response: reqwest::Response) -> Result<Value, Error> {
+        let status = response.status();
+        let body = response.text()?;
+
+        if !status.is_success() {
+            return Err(Error::Response { status, body });
+        }
+
+        if body.is_empty() {
+            return Ok(Value::Null);
+        }
+
+        Ok(serde_json::from_str(&body).unwrap_or_else(|_| Value::String(body)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    #[test]
+    fn no_command_means_run() {
+        let options = Options::from_iter(&["comit_node"]);
+
+        assert_that(&options.command.is_none()).is_true();
+    }
+
+    #[test]
+    fn can_parse_run_overrides() {
+        let options = Options::from_iter(&[
+            "comit_node",
+            "run",
+            "--log-level",
+            "info",
+            "--http-api-port",
+            "8080",
+        ]);
+
+        match options.command {
+            Some(Command::Run(run)) => {
+                assert_that(&run.log_level).contains_value(LevelFilter::Info);
+                assert_that(&run.http_api_port).contains_value(8080);
+                assert_that(&run.comit_listen).is_none();
+            }
+            command => panic!("expected run command, got {:?}", command),
+        }
+    }
+
+    #[test]
+    fn can_parse_swaps_show() {
+        let id = SwapId::default();
+        let options = Options::from_iter(&["comit_node", "swaps", "show", &id.to_string()]);
+
+        match options.command {
+            Some(Command::Swaps {
+                command: SwapsCommand::Show { id: parsed },
+                ..
+            }) => assert_that(&parsed).is_equal_to(id),
+            command => panic!("expected swaps show command, got {:?}", command),
+        }
+    }
+}
@@ -4,7 +4,8 @@ use crate::{
         rfc003, Client, ClientFactory, ClientFactoryError, ClientPool, SwapDeclineReason,
         SwapReject, SwapResponseError,
     },
-    swap_protocols::{self, asset::Asset, SwapProtocols},
+    metrics,
+    swap_protocols::{self, asset::Asset, rfc003::SecretHash, SwapProtocols},
 };
 use bam::{self, config::Config, connection::Connection, json, Status};
 use futures::Future;
@@ -140,6 +141,57 @@ impl Client for BamClient {
 
         Box::new(response)
     }
+
+    fn send_swap_abort(
+        &self,
+        secret_hash: SecretHash,
+    ) -> Box<dyn Future<Item = (), Error = SwapResponseError> + Send> {
+        let request = json::Request::new(
+            "SWAP_ABORT".into(),
+            HashMap::new(),
+            serde_json::to_value(rfc003::AbortRequestBody { secret_hash })
+                .expect("should not fail to serialize"),
+        );
+
+        debug!(
+            "Aborting swap request to {}: {:?}",
+            &self.comit_node_socket_addr, request,
+        );
+        let mut bam_client = self.bam_client.lock().unwrap();
+
+        let socket_addr = self.comit_node_socket_addr;
+
+        let response = bam_client
+            .send_request(request)
+            .then(move |result| match result {
+                Ok(response) => match response.status() {
+                    Status::OK(_) => {
+                        info!("{} acknowledged swap abort: {:?}", socket_addr, response);
+                        Ok(())
+                    }
+                    Status::SE(_) => {
+                        info!("{} rejected swap abort: {:?}", socket_addr, response);
+                        Err(SwapResponseError::InvalidResponse)
+                    }
+                    Status::RE(_) => {
+                        error!(
+                            "{} rejected swap abort because of an internal error: {:?}",
+                            socket_addr, response
+                        );
+                        Err(SwapResponseError::InternalError)
+                    }
+                },
+                Err(transport_error) => {
+                    error!(
+                        "transport error during request to {:?}:{:?}",
+                        socket_addr, transport_error
+                    );
+                    Err(SwapResponseError::TransportError)
+                }
+            });
+
+        Box::new(response)
+    }
 }
 
 fn to_json_value(
@@ -182,11 +234,15 @@ impl ClientFactory<BamClient> for BamClientPool {
                 let connection = Connection::new(config, codec, socket);
                 let (connection_future, client) = connection.start::<json::JsonFrameHandler>();
                 let socket_addr = comit_node_socket_addr;
-                tokio::spawn(connection_future.map_err(move |e| {
-                    error!(
-                        "Connection to {:?} prematurely closed: {:?}",
-                        socket_addr, e
-                    )
+                metrics::bam_connections(metrics::OUTBOUND).inc();
+                tokio::spawn(connection_future.then(move |result| {
+                    metrics::bam_connections(metrics::OUTBOUND).dec();
+                    result.map_err(|e| {
+                        error!(
+                            "Connection to {:?} prematurely closed: {:?}",
+                            socket_addr, e
+                        )
+                    })
                 }));
                 let client = Arc::new(BamClient::new(comit_node_socket_addr, client));
                 let mut clients = self.clients.write().unwrap();
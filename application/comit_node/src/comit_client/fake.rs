@@ -2,9 +2,10 @@ use crate::{
     comit_client::{
         rfc003, Client, ClientFactory, ClientFactoryError, SwapReject, SwapResponseError,
     },
-    swap_protocols::{self, asset::Asset},
+    swap_protocols::{self, asset::Asset, rfc003::SecretHash},
 };
 use futures::{
+    future,
     sync::oneshot::{self, Sender},
     Future,
 };
@@ -72,6 +73,13 @@ impl Client for FakeClient {
                 .to_owned()
         }))
     }
+
+    fn send_swap_abort(
+        &self,
+        _secret_hash: SecretHash,
+    ) -> Box<dyn Future<Item = (), Error = SwapResponseError> + Send> {
+        Box::new(future::ok(()))
+    }
 }
 
 #[derive(Debug, Default)]
@@ -2,7 +2,7 @@ pub mod bam;
 pub mod fake;
 pub mod rfc003;
 
-use crate::swap_protocols::{self, asset::Asset};
+use crate::swap_protocols::{self, asset::Asset, rfc003::SecretHash};
 use futures::Future;
 use std::{fmt::Debug, io, net::SocketAddr, sync::Arc};
 
@@ -21,6 +21,13 @@ pub trait Client: Send + Sync + 'static {
                 Error = SwapResponseError,
             > + Send,
     >;
+
+    /// Tells the counterparty that the swap with the given secret hash will
+    /// not go ahead even though it accepted the request
+    fn send_swap_abort(
+        &self,
+        secret_hash: SecretHash,
+    ) -> Box<dyn Future<Item = (), Error = SwapResponseError> + Send>;
 }
 
 pub trait ClientFactory<C>: Send + Sync + Debug {
@@ -31,10 +38,17 @@ pub trait ClientPool: Send + Sync + Debug + 'static {
     fn connected_addrs(&self) -> Vec<SocketAddr>;
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SwapReject {
-    Declined { reason: Option<SwapDeclineReason> },
+    Declined {
+        reason: Option<SwapDeclineReason>,
+    },
     Rejected,
+    /// The response was rejected because the beta HTLC would not expire
+    /// sufficiently long before the alpha HTLC
+    UnsafeLockDurations,
+    /// The request was aborted by the counterparty after it was accepted
+    Aborted,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -42,7 +56,7 @@ pub enum SwapDeclineReason {
     BadRate,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SwapResponseError {
     /// The counterparty had an internal error while processing the request
     InternalError,
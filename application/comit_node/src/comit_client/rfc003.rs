@@ -26,3 +26,14 @@ pub struct RequestBody<AL: Ledger, BL: Ledger> {
     pub alpha_ledger_lock_duration: AL::LockDuration,
     pub secret_hash: SecretHash,
 }
+
+/// The body of a `SWAP_ABORT` request, which has no headers. Alice sends it
+/// when she rejects the response of Bob to a `SWAP` request he accepted, e.g.
+/// because of unsafe lock durations, so that Bob stops waiting for the alpha
+/// HTLC. `secret_hash` identifies the `SWAP` request. Bob answers with
+/// `OK(20)` if the request was pending and made by the same node, otherwise
+/// with `SE(0)`.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AbortRequestBody {
+    pub secret_hash: SecretHash,
+}
@@ -1,4 +1,4 @@
-use crate::{bam_api::rfc003::swap_config, swap_protocols::rfc003::bob::BobSpawner};
+use crate::{bam_api::rfc003::swap_config, metrics, swap_protocols::rfc003::bob::BobSpawner};
 use bam::{connection::Connection, json};
 use futures::{Future, Stream};
 use std::{io, net::SocketAddr, sync::Arc};
@@ -12,15 +12,17 @@ pub fn listen<B: BobSpawner>(
     let socket = TcpListener::bind(&addr).unwrap();
 
     socket.incoming().for_each(move |connection| {
-        let peer_addr = connection.peer_addr();
+        let peer_addr = connection.peer_addr()?;
         let codec = json::JsonFrameCodec::default();
 
-        let config = swap_config(Arc::clone(&bob_spawner));
+        let config = swap_config(Arc::clone(&bob_spawner), peer_addr);
 
         let connection = Connection::new(config, codec, connection);
         let (close_future, _client) = connection.start::<json::JsonFrameHandler>();
+        metrics::bam_connections(metrics::INBOUND).inc();
 
         tokio::spawn(close_future.then(move |result| {
+            metrics::bam_connections(metrics::INBOUND).dec();
             match result {
                 Ok(()) => info!("Connection with {:?} closed", peer_addr),
                 Err(e) => error!(
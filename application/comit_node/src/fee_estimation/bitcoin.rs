@@ -0,0 +1,328 @@
+use bitcoin_rpc_client::{BitcoinCoreClient, BitcoinRpcApi};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The most blocks bitcoind's `estimatesmartfee` accepts as target
+pub const MAX_BLOCK_TARGET: u16 = 1008;
+
+/// Within how many blocks a transaction should confirm
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeeTarget {
+    Fast,
+    Normal,
+    Economy,
+    Blocks(u16),
+}
+
+impl FeeTarget {
+    pub fn blocks(self) -> u16 {
+        match self {
+            FeeTarget::Fast => 2,
+            FeeTarget::Normal => 6,
+            FeeTarget::Economy => 24,
+            FeeTarget::Blocks(blocks) => blocks,
+        }
+    }
+}
+
+impl Default for FeeTarget {
+    fn default() -> Self {
+        FeeTarget::Normal
+    }
+}
+
+impl FromStr for FeeTarget {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
+        match s {
+            "fast" => Ok(FeeTarget::Fast),
+            "normal" => Ok(FeeTarget::Normal),
+            "economy" => Ok(FeeTarget::Economy),
+            _ => match s.parse::<u16>() {
+                Ok(blocks) if blocks >= 1 && blocks <= MAX_BLOCK_TARGET => {
+                    Ok(FeeTarget::Blocks(blocks))
+                }
+                _ => Err(()),
+            },
+        }
+    }
+}
+
+pub trait FeeEstimator: Debug + Send + Sync + 'static {
+    /// The fee rate in satoshi per virtual byte for a transaction which should
+    /// confirm within `target`
+    fn fee_per_byte(&self, target: FeeTarget) -> f64;
+}
+
+/// Fee rates in satoshi per virtual byte, block targets in between are
+/// rounded up to the next slower rate
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StaticFees {
+    pub fast: f64,
+    pub normal: f64,
+    pub economy: f64,
+}
+
+impl Default for StaticFees {
+    fn default() -> Self {
+        StaticFees {
+            fast: 20.0,
+            normal: 10.0,
+            economy: 2.0,
+        }
+    }
+}
+
+impl FeeEstimator for StaticFees {
+    fn fee_per_byte(&self, target: FeeTarget) -> f64 {
+        let blocks = target.blocks();
+
+        if blocks <= FeeTarget::Fast.blocks() {
+            self.fast
+        } else if blocks <= FeeTarget::Normal.blocks() {
+            self.normal
+        } else {
+            self.economy
+        }
+    }
+}
+
+/// The part of bitcoind's RPC API which estimates fees
+pub trait SmartFee: Send + 'static {
+    /// The fee rate in satoshi per virtual byte for a transaction which should
+    /// confirm within `blocks`
+    fn smart_fee(&self, blocks: u16) -> Result<f64, String>;
+}
+
+const SATOSHI_PER_BYTE_PER_BTC_PER_KILOBYTE: f64 = 100_000.0;
+
+impl SmartFee for BitcoinCoreClient {
+    fn smart_fee(&self, blocks: u16) -> Result<f64, String> {
+        let estimate = BitcoinRpcApi::estimate_smart_fee(self, u32::from(blocks), None)
+            .map_err(|e| format!("{:?}", e))?
+            .map_err(|e| format!("{:?}", e))?;
+
+        // `feerate` is in BTC per kilo virtual byte and missing if bitcoind
+        // has not seen enough blocks yet
+        match estimate.feerate {
+            Some(feerate) => Ok(feerate * SATOSHI_PER_BYTE_PER_BTC_PER_KILOBYTE),
+            None => Err(estimate.errors.unwrap_or_default().join(", ")),
+        }
+    }
+}
+
+/// How long an estimate is used before bitcoind is asked again
+const ESTIMATE_MAX_AGE_SECS: u64 = 60;
+
+#[derive(Clone, Copy, Debug)]
+struct Estimate {
+    fee_per_byte: f64,
+    made_at: Instant,
+}
+
+impl Estimate {
+    fn is_fresh(&self) -> bool {
+        self.made_at.elapsed() < Duration::from_secs(ESTIMATE_MAX_AGE_SECS)
+    }
+}
+
+type Estimates = Arc<Mutex<HashMap<u16, Estimate>>>;
+
+/// Asks bitcoind through `estimatesmartfee` on a thread of its own, so HTTP
+/// requests never wait for bitcoind. Until bitcoind estimated a target, or if
+/// it cannot, the static fees are used.
+#[derive(DebugStub)]
+pub struct BitcoindFeeEstimator {
+    estimates: Estimates,
+    #[debug_stub = "Sender"]
+    requests: Mutex<mpsc::Sender<u16>>,
+    fallback: StaticFees,
+}
+
+impl BitcoindFeeEstimator {
+    pub fn new<C: SmartFee>(client: C, fallback: StaticFees) -> Self {
+        let estimates = Estimates::default();
+        let (requests, receiver) = mpsc::channel();
+
+        let thread_estimates = Arc::clone(&estimates);
+        thread::spawn(move || estimate_requested_targets(client, receiver, thread_estimates));
+
+        for target in &[FeeTarget::Fast, FeeTarget::Normal, FeeTarget::Economy] {
+            let _ = requests.send(target.blocks());
+        }
+
+        BitcoindFeeEstimator {
+            estimates,
+            requests: Mutex::new(requests),
+            fallback,
+        }
+    }
+}
+
+/// Runs until the estimator is dropped
+fn estimate_requested_targets<C: SmartFee>(
+    client: C,
+    requests: mpsc::Receiver<u16>,
+    estimates: Estimates,
+) {
+    for blocks in requests {
+        // Targets are requested again until the estimate arrives
+        if estimates
+            .lock()
+            .unwrap()
+            .get(&blocks)
+            .map_or(false, Estimate::is_fresh)
+        {
+            continue;
+        }
+
+        match client.smart_fee(blocks) {
+            Ok(fee_per_byte) => {
+                estimates.lock().unwrap().insert(
+                    blocks,
+                    Estimate {
+                        fee_per_byte,
+                        made_at: Instant::now(),
+                    },
+                );
+            }
+            Err(e) => warn!(
+                "bitcoind could not estimate the fee for {} blocks: {}",
+                blocks, e
+            ),
+        }
+    }
+}
+
+impl FeeEstimator for BitcoindFeeEstimator {
+    fn fee_per_byte(&self, target: FeeTarget) -> f64 {
+        let blocks = target.blocks();
+        let estimate = self.estimates.lock().unwrap().get(&blocks).cloned();
+
+        // A stale estimate is still better than the static fees while the
+        // new one is made
+        if !estimate.as_ref().map_or(false, Estimate::is_fresh)
+            && self.requests.lock().unwrap().send(blocks).is_err()
+        {
+            error!("The thread asking bitcoind for fee estimates is gone");
+        }
+
+        match estimate {
+            Some(estimate) => estimate.fee_per_byte,
+            None => {
+                debug!(
+                    "No fee estimate for {:?} yet, using the static fees",
+                    target
+                );
+                self.fallback.fee_per_byte(target)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    #[test]
+    fn parses_named_and_block_targets() {
+        assert_that(&FeeTarget::from_str("fast")).is_ok_containing(FeeTarget::Fast);
+        assert_that(&FeeTarget::from_str("economy")).is_ok_containing(FeeTarget::Economy);
+        assert_that(&FeeTarget::from_str("3")).is_ok_containing(FeeTarget::Blocks(3));
+        assert_that(&FeeTarget::from_str("0")).is_err();
+        assert_that(&FeeTarget::from_str("1009")).is_err();
+        assert_that(&FeeTarget::from_str("soon")).is_err();
+    }
+
+    #[test]
+    fn static_fees_round_block_targets_to_the_next_slower_rate() {
+        let fees = StaticFees::default();
+
+        assert_that(&fees.fee_per_byte(FeeTarget::Blocks(1))).is_equal_to(20.0);
+        assert_that(&fees.fee_per_byte(FeeTarget::Blocks(3))).is_equal_to(10.0);
+        assert_that(&fees.fee_per_byte(FeeTarget::Normal)).is_equal_to(10.0);
+        assert_that(&fees.fee_per_byte(FeeTarget::Blocks(100))).is_equal_to(2.0);
+    }
+
+    /// Tells the test about every target it is asked to estimate
+    struct FakeBitcoind {
+        fee_per_byte: Result<f64, String>,
+        asked: mpsc::Sender<u16>,
+    }
+
+    impl SmartFee for FakeBitcoind {
+        fn smart_fee(&self, blocks: u16) -> Result<f64, String> {
+            let _ = self.asked.send(blocks);
+            self.fee_per_byte.clone()
+        }
+    }
+
+    fn estimator(fee_per_byte: Result<f64, String>) -> (BitcoindFeeEstimator, mpsc::Receiver<u16>) {
+        let (asked, receiver) = mpsc::channel();
+        let fees = BitcoindFeeEstimator::new(
+            FakeBitcoind {
+                fee_per_byte,
+                asked,
+            },
+            StaticFees::default(),
+        );
+
+        (fees, receiver)
+    }
+
+    /// Only requested by the tests, to learn when the requests before it are
+    /// done
+    const SYNC_TARGET: u16 = MAX_BLOCK_TARGET;
+
+    /// Waits until the estimates of all targets requested so far are stored,
+    /// returning the targets bitcoind was asked about in the meantime. The
+    /// thread handles one request after the other, so once bitcoind is asked
+    /// about the sync target, the estimates before it are stored.
+    fn estimated_targets(fees: &BitcoindFeeEstimator, asked: &mpsc::Receiver<u16>) -> Vec<u16> {
+        fees.estimates.lock().unwrap().remove(&SYNC_TARGET);
+        fees.requests.lock().unwrap().send(SYNC_TARGET).unwrap();
+
+        asked
+            .iter()
+            .take_while(|blocks| *blocks != SYNC_TARGET)
+            .collect()
+    }
+
+    #[test]
+    fn bitcoind_estimates_are_cached() {
+        let (fees, asked) = estimator(Ok(42.0));
+        assert_that(&estimated_targets(&fees, &asked)).is_equal_to(vec![2, 6, 24]);
+
+        assert_that(&fees.fee_per_byte(FeeTarget::Fast)).is_equal_to(42.0);
+        assert_that(&fees.fee_per_byte(FeeTarget::Normal)).is_equal_to(42.0);
+        assert_that(&fees.fee_per_byte(FeeTarget::Economy)).is_equal_to(42.0);
+        assert_that(&estimated_targets(&fees, &asked)).is_empty();
+    }
+
+    #[test]
+    fn static_fees_are_used_until_bitcoind_estimated_the_target() {
+        let (fees, asked) = estimator(Ok(42.0));
+        estimated_targets(&fees, &asked);
+
+        assert_that(&fees.fee_per_byte(FeeTarget::Blocks(100))).is_equal_to(2.0);
+        assert_that(&estimated_targets(&fees, &asked)).is_equal_to(vec![100]);
+        assert_that(&fees.fee_per_byte(FeeTarget::Blocks(100))).is_equal_to(42.0);
+    }
+
+    #[test]
+    fn static_fees_are_used_if_bitcoind_cannot_estimate() {
+        let (fees, asked) = estimator(Err(String::from("Insufficient data or no feerate found")));
+        estimated_targets(&fees, &asked);
+
+        assert_that(&fees.fee_per_byte(FeeTarget::Fast)).is_equal_to(20.0);
+    }
+}
@@ -0,0 +1,412 @@
+use crate::wallet::ethereum::EthereumNode;
+use ethereum_support::{Address, Bytes, EtherQuantity, U256};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+const WEI_PER_GWEI: u64 = 1_000_000_000;
+
+pub fn gwei(gwei: u64) -> U256 {
+    U256::from(gwei) * U256::from(WEI_PER_GWEI)
+}
+
+pub trait GasEstimator: Debug + Send + Sync + 'static {
+    /// The gas limit of a transaction `from` sends, a missing `to` address
+    /// deploys `data` as a contract. `fallback` is used if the limit cannot
+    /// be estimated (yet).
+    fn gas_limit(
+        &self,
+        from: Address,
+        to: Option<Address>,
+        value: EtherQuantity,
+        data: &Bytes,
+        fallback: U256,
+    ) -> U256;
+
+    /// The gas price in wei
+    fn gas_price(&self) -> U256;
+}
+
+/// Keeps the gas limits of the actions and always suggests the same price
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaticGas {
+    gas_price: U256,
+}
+
+impl StaticGas {
+    pub fn new(gas_price: U256) -> Self {
+        StaticGas { gas_price }
+    }
+}
+
+impl Default for StaticGas {
+    fn default() -> Self {
+        StaticGas::new(gwei(10))
+    }
+}
+
+impl GasEstimator for StaticGas {
+    fn gas_limit(
+        &self,
+        _: Address,
+        _: Option<Address>,
+        _: EtherQuantity,
+        _: &Bytes,
+        fallback: U256,
+    ) -> U256 {
+        fallback
+    }
+
+    fn gas_price(&self) -> U256 {
+        self.gas_price
+    }
+}
+
+/// The estimate only holds for the state of the chain it was made on, the
+/// gas limit leaves this much room in percent for the state to change until
+/// the transaction is mined
+const GAS_LIMIT_MARGIN_PERCENT: u64 = 20;
+
+/// How long an estimate is used before the node is asked again
+const ESTIMATE_MAX_AGE_SECS: u64 = 60;
+
+/// A transaction whose gas limit is estimated
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Transaction {
+    from: Address,
+    to: Option<Address>,
+    value: EtherQuantity,
+    data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Request {
+    GasPrice,
+    GasLimit(Transaction),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Estimate {
+    value: U256,
+    made_at: Instant,
+}
+
+impl Estimate {
+    fn is_fresh(&self) -> bool {
+        self.made_at.elapsed() < Duration::from_secs(ESTIMATE_MAX_AGE_SECS)
+    }
+}
+
+type Estimates = Arc<Mutex<HashMap<Request, Estimate>>>;
+
+/// Asks the node through `eth_estimateGas` and `eth_gasPrice` on a thread of
+/// its own, so HTTP requests never wait for the node. Until the node answered,
+/// or if it cannot, the static gas is used.
+#[derive(DebugStub)]
+pub struct NodeGasEstimator {
+    estimates: Estimates,
+    #[debug_stub = "Sender"]
+    requests: Mutex<mpsc::Sender<Request>>,
+    fallback: StaticGas,
+}
+
+impl NodeGasEstimator {
+    pub fn new<N: EthereumNode>(node: N, fallback: StaticGas) -> Self {
+        let estimates = Estimates::default();
+        let (requests, receiver) = mpsc::channel();
+
+        let thread_estimates = Arc::clone(&estimates);
+        thread::spawn(move || estimate_requested_gas(node, receiver, thread_estimates));
+
+        let _ = requests.send(Request::GasPrice);
+
+        NodeGasEstimator {
+            estimates,
+            requests: Mutex::new(requests),
+            fallback,
+        }
+    }
+
+    fn estimate(&self, request: Request) -> Option<U256> {
+        let estimate = self.estimates.lock().unwrap().get(&request).cloned();
+
+        // A stale estimate is still better than the static gas while the new
+        // one is made
+        if !estimate.as_ref().map_or(false, Estimate::is_fresh)
+            && self.requests.lock().unwrap().send(request).is_err()
+        {
+            error!("The thread asking the Ethereum node for gas estimates is gone");
+        }
+
+        estimate.map(|estimate| estimate.value)
+    }
+}
+
+/// Runs until the estimator is dropped
+fn estimate_requested_gas<N: EthereumNode>(
+    node: N,
+    requests: mpsc::Receiver<Request>,
+    estimates: Estimates,
+) {
+    for request in requests {
+        // Requests are repeated until the estimate arrives
+        if estimates
+            .lock()
+            .unwrap()
+            .get(&request)
+            .map_or(false, Estimate::is_fresh)
+        {
+            continue;
+        }
+
+        let value = match request {
+            Request::GasPrice => node.gas_price(),
+            Request::GasLimit(ref transaction) => node
+                .estimate_gas(
+                    transaction.from,
+                    transaction.to,
+                    transaction.value,
+                    &Bytes(transaction.data.clone()),
+                )
+                .map(|estimate| {
+                    estimate * U256::from(100 + GAS_LIMIT_MARGIN_PERCENT) / U256::from(100)
+                }),
+        };
+
+        match value {
+            Ok(value) => {
+                let mut estimates = estimates.lock().unwrap();
+                // Every swap has transactions of its own, their stale gas
+                // limits are dropped so the cache does not keep growing
+                estimates.retain(|request, estimate| {
+                    *request == Request::GasPrice || estimate.is_fresh()
+                });
+                estimates.insert(
+                    request,
+                    Estimate {
+                        value,
+                        made_at: Instant::now(),
+                    },
+                );
+            }
+            Err(e) => warn!("Ethereum node could not estimate {:?}: {:?}", request, e),
+        }
+    }
+}
+
+impl GasEstimator for NodeGasEstimator {
+    fn gas_limit(
+        &self,
+        from: Address,
+        to: Option<Address>,
+        value: EtherQuantity,
+        data: &Bytes,
+        fallback: U256,
+    ) -> U256 {
+        let transaction = Transaction {
+            from,
+            to,
+            value,
+            data: data.0.clone(),
+        };
+
+        self.estimate(Request::GasLimit(transaction))
+            .unwrap_or_else(|| {
+                debug!(
+                    "No gas estimate for the transaction yet, using {}",
+                    fallback
+                );
+                fallback
+            })
+    }
+
+    fn gas_price(&self) -> U256 {
+        self.estimate(Request::GasPrice).unwrap_or_else(|| {
+            debug!(
+                "No gas price suggested by the Ethereum node yet, using {}",
+                self.fallback.gas_price
+            );
+            self.fallback.gas_price
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::ethereum::Error;
+    use ethereum_support::{web3, H256};
+    use spectral::prelude::*;
+
+    /// Where the tests learn what the nodes are asked, the address of the
+    /// sender for gas limits and none for the gas price
+    type Asked = Mutex<mpsc::Sender<Option<Address>>>;
+
+    struct UnreachableNode {
+        asked: Asked,
+    }
+
+    impl EthereumNode for UnreachableNode {
+        fn gas_price(&self) -> Result<U256, Error> {
+            let _ = self.asked.lock().unwrap().send(None);
+            Err(Error::Node(web3::ErrorKind::Unreachable.into()))
+        }
+
+        fn transaction_count(&self, _address: Address) -> Result<U256, Error> {
+            Err(Error::Node(web3::ErrorKind::Unreachable.into()))
+        }
+
+        fn send_raw_transaction(&self, _transaction: Bytes) -> Result<H256, Error> {
+            Err(Error::Node(web3::ErrorKind::Unreachable.into()))
+        }
+
+        fn estimate_gas(
+            &self,
+            from: Address,
+            _to: Option<Address>,
+            _value: EtherQuantity,
+            _data: &Bytes,
+        ) -> Result<U256, Error> {
+            let _ = self.asked.lock().unwrap().send(Some(from));
+            Err(Error::Node(web3::ErrorKind::Unreachable.into()))
+        }
+    }
+
+    /// Estimates the gas of transactions from `sender` only
+    struct SenderAwareNode {
+        sender: Address,
+        asked: Asked,
+    }
+
+    impl EthereumNode for SenderAwareNode {
+        fn gas_price(&self) -> Result<U256, Error> {
+            let _ = self.asked.lock().unwrap().send(None);
+            Ok(gwei(1))
+        }
+
+        fn transaction_count(&self, _address: Address) -> Result<U256, Error> {
+            Ok(U256::zero())
+        }
+
+        fn send_raw_transaction(&self, _transaction: Bytes) -> Result<H256, Error> {
+            Ok(H256::zero())
+        }
+
+        fn estimate_gas(
+            &self,
+            from: Address,
+            _to: Option<Address>,
+            _value: EtherQuantity,
+            _data: &Bytes,
+        ) -> Result<U256, Error> {
+            let _ = self.asked.lock().unwrap().send(Some(from));
+            if from == self.sender {
+                Ok(U256::from(50_000))
+            } else {
+                Err(Error::Node(web3::ErrorKind::Unreachable.into()))
+            }
+        }
+    }
+
+    /// Only requested by the tests, to learn when the requests before it are
+    /// done
+    fn sync_request() -> Request {
+        Request::GasLimit(Transaction {
+            from: Address::from(0xFF),
+            to: None,
+            value: EtherQuantity::zero(),
+            data: vec![],
+        })
+    }
+
+    /// Waits until the estimates of everything requested so far are stored,
+    /// returning what the node was asked in the meantime. The thread handles
+    /// one request after the other, so once the node is asked about the sync
+    /// request, the estimates before it are stored.
+    fn asked_since(
+        estimator: &NodeGasEstimator,
+        asked: &mpsc::Receiver<Option<Address>>,
+    ) -> Vec<Option<Address>> {
+        estimator.estimates.lock().unwrap().remove(&sync_request());
+        estimator
+            .requests
+            .lock()
+            .unwrap()
+            .send(sync_request())
+            .unwrap();
+
+        asked
+            .iter()
+            .take_while(|asked| *asked != Some(Address::from(0xFF)))
+            .collect()
+    }
+
+    fn gas_limit(estimator: &NodeGasEstimator, from: Address) -> U256 {
+        estimator.gas_limit(
+            from,
+            Some(Address::from(0xBB)),
+            EtherQuantity::zero(),
+            &Bytes(vec![]),
+            U256::from(100_000),
+        )
+    }
+
+    #[test]
+    fn unreachable_node_falls_back_to_static_gas() {
+        let (asked, receiver) = mpsc::channel();
+        let estimator = NodeGasEstimator::new(
+            UnreachableNode {
+                asked: Mutex::new(asked),
+            },
+            StaticGas::new(gwei(5)),
+        );
+
+        assert_that(&gas_limit(&estimator, Address::from(0xAA))).is_equal_to(U256::from(100_000));
+        assert_that(&asked_since(&estimator, &receiver))
+            .is_equal_to(vec![None, Some(Address::from(0xAA))]);
+
+        assert_that(&gas_limit(&estimator, Address::from(0xAA))).is_equal_to(U256::from(100_000));
+        assert_that(&estimator.gas_price()).is_equal_to(U256::from(5_000_000_000u64));
+    }
+
+    #[test]
+    fn estimates_the_gas_of_the_sender_with_a_margin() {
+        let (asked, receiver) = mpsc::channel();
+        let estimator = NodeGasEstimator::new(
+            SenderAwareNode {
+                sender: Address::from(0xAA),
+                asked: Mutex::new(asked),
+            },
+            StaticGas::default(),
+        );
+
+        assert_that(&gas_limit(&estimator, Address::from(0xAA))).is_equal_to(U256::from(100_000));
+        asked_since(&estimator, &receiver);
+
+        assert_that(&gas_limit(&estimator, Address::from(0xAA))).is_equal_to(U256::from(60_000));
+        assert_that(&estimator.gas_price()).is_equal_to(gwei(1));
+    }
+
+    #[test]
+    fn estimates_are_cached() {
+        let (asked, receiver) = mpsc::channel();
+        let estimator = NodeGasEstimator::new(
+            SenderAwareNode {
+                sender: Address::from(0xAA),
+                asked: Mutex::new(asked),
+            },
+            StaticGas::default(),
+        );
+        gas_limit(&estimator, Address::from(0xAA));
+        asked_since(&estimator, &receiver);
+
+        gas_limit(&estimator, Address::from(0xAA));
+        estimator.gas_price();
+
+        assert_that(&asked_since(&estimator, &receiver)).is_empty();
+    }
+}
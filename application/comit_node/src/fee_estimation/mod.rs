@@ -0,0 +1,20 @@
+pub mod bitcoin;
+pub mod ethereum;
+
+use std::sync::Arc;
+
+/// Where the fees of the transactions in action payloads come from
+#[derive(Clone, Debug)]
+pub struct FeeEstimators {
+    pub bitcoin: Arc<dyn bitcoin::FeeEstimator>,
+    pub ethereum: Arc<dyn ethereum::GasEstimator>,
+}
+
+impl Default for FeeEstimators {
+    fn default() -> Self {
+        FeeEstimators {
+            bitcoin: Arc::new(bitcoin::StaticFees::default()),
+            ethereum: Arc::new(ethereum::StaticGas::default()),
+        }
+    }
+}
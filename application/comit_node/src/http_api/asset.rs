@@ -9,6 +9,10 @@ pub struct HttpAsset {
 }
 
 impl HttpAsset {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn is_asset(&self, name: &'static str) -> Result<(), Error> {
         if self.name == name {
             Ok(())
@@ -0,0 +1,220 @@
+use crate::http_api::problem::{self, HttpApiProblemStdError};
+use crypto::util::fixed_time_eq;
+use http::header::{HeaderMap, AUTHORIZATION};
+use http_api_problem::HttpApiProblem;
+use std::{fmt, sync::Arc};
+use warp::{self, filters::BoxedFilter, Filter, Rejection};
+
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Inspecting swaps and the node
+    ReadOnly,
+    /// Everything, including creating and responding to swaps and
+    /// retrieving or executing their actions
+    Trading,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scope::ReadOnly => write!(f, "read_only"),
+            Scope::Trading => write!(f, "trading"),
+        }
+    }
+}
+
+impl Scope {
+    fn grants(self, required: Scope) -> bool {
+        self == Scope::Trading || required == Scope::ReadOnly
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ApiKey {
+    pub key: String,
+    pub scope: Scope,
+}
+
+/// The API keys which are allowed to use the HTTP API. Without any, every
+/// request is rejected unless unauthenticated access is explicitly allowed.
+#[derive(Clone, Debug, Default)]
+pub struct ApiKeys {
+    keys: Vec<ApiKey>,
+    allow_unauthenticated: bool,
+}
+
+impl ApiKeys {
+    pub fn new(keys: Vec<ApiKey>) -> Self {
+        ApiKeys {
+            keys,
+            allow_unauthenticated: false,
+        }
+    }
+
+    /// Opens the HTTP API to everyone who can reach it, as long as no keys
+    /// are configured
+    pub fn allow_unauthenticated(self) -> Self {
+        ApiKeys {
+            allow_unauthenticated: true,
+            ..self
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    fn scope_of(&self, key: &str) -> Option<Scope> {
+        self.keys
+            .iter()
+            .find(|api_key| fixed_time_eq(api_key.key.as_bytes(), key.as_bytes()))
+            .map(|api_key| api_key.scope)
+    }
+}
+
+/// Proof that the credentials of a request grant the scope of the route
+#[derive(Clone, Copy, Debug)]
+pub struct Authorized;
+
+/// Rejects requests whose credentials do not grant the `required` scope. The
+/// key is either sent as bearer token in the `Authorization` header or in the
+/// `X-API-Key` header.
+pub fn authorize(api_keys: Arc<ApiKeys>, required: Scope) -> BoxedFilter<(Authorized,)> {
+    warp::header::headers_cloned()
+        .and_then(move |headers: HeaderMap| {
+            check(&api_keys, required, &headers)
+                .map_err(|problem| warp::reject::custom(HttpApiProblemStdError::new(problem)))
+        })
+        .boxed()
+}
+
+/// Extracts with `filter` once the request is authorized.
+///
+/// Placed after the path and method filters of a route, a request is only
+/// rejected for insufficient scope by the route it is meant for.
+pub fn authorized<F, T>(authorize: BoxedFilter<(Authorized,)>, filter: F) -> BoxedFilter<(T,)>
+where
+    F: Filter<Extract = (T,), Error = Rejection> + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    authorize
+        .and(filter)
+        .map(|_: Authorized, value: T| value)
+        .boxed()
+}
+
+fn check(
+    api_keys: &ApiKeys,
+    required: Scope,
+    headers: &HeaderMap,
+) -> Result<Authorized, HttpApiProblem> {
+    if api_keys.is_empty() {
+        return if api_keys.allow_unauthenticated {
+            Ok(Authorized)
+        } else {
+            Err(problem::no_api_keys())
+        };
+    }
+
+    let key = credentials(headers).ok_or_else(problem::unauthorized)?;
+
+    match api_keys.scope_of(key) {
+        Some(scope) if scope.grants(required) => Ok(Authorized),
+        Some(_) => Err(problem::insufficient_scope(required)),
+        None => Err(problem::unauthorized()),
+    }
+}
+
+fn credentials(headers: &HeaderMap) -> Option<&str> {
+    let bearer_token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| value.starts_with("Bearer "))
+        .map(|value| value["Bearer ".len()..].trim());
+
+    bearer_token.or_else(|| {
+        headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::header::HeaderValue;
+    use spectral::prelude::*;
+
+    fn api_keys() -> ApiKeys {
+        ApiKeys::new(vec![
+            ApiKey {
+                key: String::from("reader"),
+                scope: Scope::ReadOnly,
+            },
+            ApiKey {
+                key: String::from("trader"),
+                scope: Scope::Trading,
+            },
+        ])
+    }
+
+    fn headers(name: &'static str, value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn no_api_keys_authorize_nothing() {
+        let result = check(&ApiKeys::default(), Scope::ReadOnly, &HeaderMap::new());
+
+        assert_that(&result.unwrap_err().status.map(|s| s.to_u16())).is_equal_to(Some(401));
+    }
+
+    #[test]
+    fn no_api_keys_authorize_everything_if_allowed() {
+        let api_keys = ApiKeys::default().allow_unauthenticated();
+
+        assert_that(&check(&api_keys, Scope::Trading, &HeaderMap::new())).is_ok();
+    }
+
+    #[test]
+    fn configured_api_keys_take_precedence_over_allowing_unauthenticated_access() {
+        let api_keys = api_keys().allow_unauthenticated();
+
+        let result = check(&api_keys, Scope::ReadOnly, &HeaderMap::new());
+
+        assert_that(&result.unwrap_err().status.map(|s| s.to_u16())).is_equal_to(Some(401));
+    }
+
+    #[test]
+    fn missing_or_unknown_key_is_unauthorized() {
+        let missing = check(&api_keys(), Scope::ReadOnly, &HeaderMap::new());
+        let unknown = check(&api_keys(), Scope::ReadOnly, &headers("x-api-key", "foo"));
+
+        assert_that(&missing.unwrap_err().status.map(|s| s.to_u16())).is_equal_to(Some(401));
+        assert_that(&unknown.unwrap_err().status.map(|s| s.to_u16())).is_equal_to(Some(401));
+    }
+
+    #[test]
+    fn read_only_key_cannot_trade() {
+        let result = check(
+            &api_keys(),
+            Scope::Trading,
+            &headers("authorization", "Bearer reader"),
+        );
+
+        assert_that(&result.unwrap_err().status.map(|s| s.to_u16())).is_equal_to(Some(403));
+    }
+
+    #[test]
+    fn trading_key_can_read_and_trade() {
+        let headers = headers("authorization", "Bearer trader");
+
+        assert_that(&check(&api_keys(), Scope::ReadOnly, &headers)).is_ok();
+        assert_that(&check(&api_keys(), Scope::Trading, &headers)).is_ok();
+    }
+}
@@ -9,6 +9,10 @@ pub struct HttpLedger {
 }
 
 impl HttpLedger {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn is_ledger(&self, name: &'static str) -> Result<(), Error> {
         if self.name == name {
             Ok(())
@@ -1,3 +1,4 @@
+pub mod auth;
 pub mod rfc003;
 pub mod route_factory;
 
@@ -25,8 +26,7 @@ mod ledger_impls {
     use crate::swap_protocols::ledger::{Bitcoin, Ethereum};
 
     impl_http_ledger!(Bitcoin { network });
-    impl_http_ledger!(Ethereum);
-
+    impl_http_ledger!(Ethereum { chain_id });
 }
 
 mod asset_impls {
@@ -70,6 +70,10 @@ mod lock_duration_impls {
     impl_to_http_lock_duration!(Seconds);
 }
 
+pub fn get_metrics() -> Result<impl Reply, Rejection> {
+    Ok(crate::metrics::render())
+}
+
 #[derive(Debug, Serialize)]
 struct GetPeers {
     pub peers: Vec<SocketAddr>,
@@ -118,7 +122,10 @@ mod tests {
             &ether_serialized,
             r#"{"name":"Ether","quantity":"1000000000000000000"}"#
         );
-        assert_eq!(&pay_serialized, r#"{"name":"ERC20","quantity":"100000000000","token_contract":"0xb97048628db6b661d4c2aa833e95dbe1a905b280"}"#);
+        assert_eq!(
+            &pay_serialized,
+            r#"{"name":"ERC20","quantity":"100000000000","token_contract":"0xb97048628db6b661d4c2aa833e95dbe1a905b280"}"#
+        );
     }
 
     #[test]
@@ -126,7 +133,7 @@ mod tests {
         let bitcoin = Bitcoin {
             network: Network::Regtest,
         };
-        let ethereum = Ethereum {};
+        let ethereum = Ethereum { chain_id: 17 };
 
         let bitcoin = bitcoin.to_http_ledger().unwrap();
         let ethereum = ethereum.to_http_ledger().unwrap();
@@ -138,7 +145,6 @@ mod tests {
             &bitcoin_serialized,
             r#"{"name":"Bitcoin","network":"regtest"}"#
         );
-        assert_eq!(&ethereum_serialized, r#"{"name":"Ethereum"}"#);
+        assert_eq!(&ethereum_serialized, r#"{"name":"Ethereum","chain_id":17}"#);
     }
-
 }
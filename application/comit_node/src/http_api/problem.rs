@@ -1,7 +1,11 @@
-use crate::swap_protocols::{
-    metadata_store,
-    rfc003::{self, state_store},
+use crate::{
+    http_api::auth::Scope,
+    swap_protocols::{
+        metadata_store,
+        rfc003::{self, state_store},
+    },
 };
+use bitcoin_support::Network;
 use http::StatusCode;
 use http_api_problem::{HttpApiProblem, HttpStatusCode};
 use std::{error::Error, fmt};
@@ -46,10 +50,34 @@ pub fn swap_not_found() -> HttpApiProblem {
     HttpApiProblem::new("swap-not-found").set_status(404)
 }
 
+pub fn unauthorized() -> HttpApiProblem {
+    HttpApiProblem::new("unauthorized")
+        .set_status(401)
+        .set_detail("A valid API key is required")
+}
+
+pub fn no_api_keys() -> HttpApiProblem {
+    HttpApiProblem::new("unauthorized")
+        .set_status(401)
+        .set_detail("No API keys are configured and unauthenticated access is not allowed")
+}
+
+pub fn insufficient_scope(required: Scope) -> HttpApiProblem {
+    HttpApiProblem::new("insufficient-scope")
+        .set_status(403)
+        .set_detail(format!("The API key does not grant the {} scope", required))
+}
+
 pub fn unsupported() -> HttpApiProblem {
     HttpApiProblem::new("swap-not-supported").set_status(400)
 }
 
+pub fn invalid_query(detail: &str) -> HttpApiProblem {
+    HttpApiProblem::new("invalid-query")
+        .set_status(400)
+        .set_detail(detail.to_string())
+}
+
 pub fn serde(_e: &serde_json::Error) -> HttpApiProblem {
     // FIXME: Use error to give more detail to the user
     HttpApiProblem::new("invalid-body")
@@ -66,6 +94,49 @@ pub fn action_already_taken() -> HttpApiProblem {
     HttpApiProblem::new("action-already-taken").set_status(400)
 }
 
+pub fn action_already_executed() -> HttpApiProblem {
+    HttpApiProblem::new("action-already-executed")
+        .set_status(400)
+        .set_detail("A transaction is being or was already broadcast for this action")
+}
+
+pub fn action_not_executable() -> HttpApiProblem {
+    HttpApiProblem::new("action-not-executable")
+        .set_status(400)
+        .set_detail("The action has to be taken through its own endpoint")
+}
+
+pub fn unsafe_lock_durations(e: &rfc003::UnsafeLockDurations) -> HttpApiProblem {
+    HttpApiProblem::new("unsafe-lock-durations")
+        .set_status(400)
+        .set_detail(e.to_string())
+}
+
+pub fn address_on_wrong_network(expected: Network) -> HttpApiProblem {
+    HttpApiProblem::new("address-on-wrong-network")
+        .set_status(400)
+        .set_detail(format!(
+            "The address does not belong to the {} network of the swap",
+            expected
+        ))
+}
+
+pub fn missing_wallet(ledger: &str) -> HttpApiProblem {
+    HttpApiProblem::new("missing-wallet")
+        .set_status(400)
+        .set_detail(format!(
+            "No {} wallet is configured to execute this action",
+            ledger
+        ))
+}
+
+pub fn wallet<E: fmt::Debug>(e: &E) -> HttpApiProblem {
+    error!("Wallet failed to execute action: {:?}", e);
+    HttpApiProblem::new("wallet-failure")
+        .set_status(500)
+        .set_detail("The wallet failed to execute the action")
+}
+
 impl From<state_store::Error> for HttpApiProblem {
     fn from(_e: state_store::Error) -> Self {
         HttpApiProblem::with_title_and_type_from_status(500).set_detail("Storage layer failure")
@@ -1,9 +1,9 @@
 use crate::{
     comit_client::SwapDeclineReason,
+    fee_estimation::{bitcoin::FeeTarget, FeeEstimators},
     http_api::{problem, HttpApiProblemStdError},
     swap_protocols::{
         ledger::{Bitcoin, Ethereum},
-        metadata_store::Metadata,
         rfc003::{
             alice, bitcoin,
             bob::{
@@ -13,15 +13,17 @@ use crate::{
             ethereum,
             state_machine::StateMachineResponse,
             state_store::StateStore,
-            Actions, Alice, Bob, Ledger, SecretSource,
+            validate_lock_durations, Actions, Ledger, SecretSource,
         },
-        AssetKind, LedgerKind, MetadataStore, RoleKind, SwapId,
+        ExecutedTransaction, ExecutionLog, LedgerKind, MetadataStore, SwapId,
     },
+    wallet::Wallets,
 };
 use bitcoin_support::{self, serialize::serialize_hex, BitcoinQuantity};
-use ethereum_support::{self, Erc20Quantity, EtherQuantity};
+use ethereum_support::{self, EtherQuantity};
+use futures::{future, Future};
 use http_api_problem::HttpApiProblem;
-use std::{str::FromStr, sync::Arc};
+use std::{fmt, str::FromStr, sync::Arc, time::Duration};
 use warp::{self, Rejection, Reply};
 
 #[derive(Clone, Copy, Debug)]
@@ -48,6 +50,8 @@ trait ExecuteAccept<AL: Ledger, BL: Ledger> {
         body: AcceptSwapRequestHttpBody<AL, BL>,
         secret_source: &dyn SecretSource,
         id: SwapId,
+        alpha_ledger_lock_duration: AL::LockDuration,
+        lock_duration_margin: Duration,
     ) -> Result<(), HttpApiProblem>;
 }
 
@@ -61,13 +65,21 @@ where
         body: AcceptSwapRequestHttpBody<AL, BL>,
         secret_source: &dyn SecretSource,
         id: SwapId,
+        alpha_ledger_lock_duration: AL::LockDuration,
+        lock_duration_margin: Duration,
     ) -> Result<(), HttpApiProblem> {
-        self.accept(StateMachineResponse::from_accept_swap_request_http_body(
-            body,
-            id,
-            secret_source,
-        )?)
-        .map_err(|_| problem::action_already_taken())
+        let response =
+            StateMachineResponse::from_accept_swap_request_http_body(body, id, secret_source)?;
+
+        validate_lock_durations(
+            alpha_ledger_lock_duration,
+            response.beta_ledger_lock_duration.clone(),
+            lock_duration_margin,
+        )
+        .map_err(|e| problem::unsafe_lock_durations(&e))?;
+
+        self.accept(response)
+            .map_err(|_| problem::action_already_taken())
     }
 }
 
@@ -130,6 +142,35 @@ impl FromAcceptSwapRequestHttpBody<Ethereum, Bitcoin>
     }
 }
 
+impl FromAcceptSwapRequestHttpBody<Ethereum, Ethereum>
+    for StateMachineResponse<
+        ethereum_support::Address,
+        ethereum_support::Address,
+        ethereum::Seconds,
+    >
+{
+    fn from_accept_swap_request_http_body(
+        body: AcceptSwapRequestHttpBody<Ethereum, Ethereum>,
+        _: SwapId,
+        _: &dyn SecretSource,
+    ) -> Result<Self, HttpApiProblem> {
+        match body {
+            AcceptSwapRequestHttpBody::RefundAndRedeem {
+                alpha_ledger_redeem_identity,
+                beta_ledger_refund_identity,
+                beta_ledger_lock_duration,
+            } => Ok(StateMachineResponse {
+                alpha_ledger_redeem_identity,
+                beta_ledger_refund_identity,
+                beta_ledger_lock_duration,
+            }),
+            _ => Err(HttpApiProblem::with_title_and_type_from_status(400).set_detail(
+                "Both alpha_ledger_redeem_identity and beta_ledger_refund_identity are required",
+            )),
+        }
+    }
+}
+
 trait ExecuteDecline {
     fn execute(&self, reason: Option<SwapDeclineReason>) -> Result<(), HttpApiProblem>;
 }
@@ -146,7 +187,10 @@ impl<AL: Ledger, BL: Ledger> ExecuteDecline for Decline<AL, BL> {
 pub enum GetActionQueryParams {
     BitcoinAddressAndFee {
         address: bitcoin_support::Address,
-        fee_per_byte: String,
+        #[serde(default)]
+        fee_per_byte: Option<String>,
+        #[serde(default)]
+        fee_target: Option<String>,
     },
     None {},
 }
@@ -160,12 +204,23 @@ pub enum ActionResponseBody {
     },
     BroadcastSignedBitcoinTransaction {
         hex: String,
+        fee: BitcoinQuantity,
+        fee_per_byte: f64,
+        vsize: u64,
     },
     SendEthereumTransaction {
+        from: ethereum_support::Address,
         to: Option<ethereum_support::Address>,
         data: ethereum_support::Bytes,
         value: EtherQuantity,
+        /// Like all gas quantities a hex encoded number, as in Ethereum's
+        /// JSON-RPC
         gas_limit: ethereum_support::U256,
+        /// The price of a unit of gas in wei
+        gas_price: ethereum_support::U256,
+        /// The gas limit times the gas price in wei
+        estimated_cost: ethereum_support::U256,
+        chain_id: u64,
     },
 }
 
@@ -173,6 +228,7 @@ pub trait IntoResponseBody {
     fn into_response_body(
         self,
         query_params: GetActionQueryParams,
+        fee_estimators: &FeeEstimators,
     ) -> Result<ActionResponseBody, HttpApiProblem>;
 }
 
@@ -180,6 +236,7 @@ impl IntoResponseBody for bitcoin::SendToAddress {
     fn into_response_body(
         self,
         query_params: GetActionQueryParams,
+        _: &FeeEstimators,
     ) -> Result<ActionResponseBody, HttpApiProblem> {
         match query_params {
             GetActionQueryParams::None {} => {
@@ -205,33 +262,65 @@ impl IntoResponseBody for bitcoin::SpendOutput {
     fn into_response_body(
         self,
         query_params: GetActionQueryParams,
+        fee_estimators: &FeeEstimators,
     ) -> Result<ActionResponseBody, HttpApiProblem> {
         match query_params {
+            GetActionQueryParams::BitcoinAddressAndFee { ref address, .. }
+                if address.network != self.network =>
+            {
+                Err(problem::address_on_wrong_network(self.network))
+            }
             GetActionQueryParams::BitcoinAddressAndFee {
                 address,
                 fee_per_byte,
-            } => match fee_per_byte.parse::<f64>() {
-                Ok(fee_per_byte) => {
-                    let transaction = self.spend_to(address).sign_with_rate(fee_per_byte);
-                    match serialize_hex(&transaction) {
-                        Ok(hex) => {
-                            Ok(ActionResponseBody::BroadcastSignedBitcoinTransaction { hex })
-                        }
-                        Err(e) => {
-                            error!("Could not serialized signed Bitcoin transaction: {:?}", e);
-                            Err(
-                                HttpApiProblem::with_title_and_type_from_status(500).set_detail(
-                                    "Issue encountered when serializing Bitcoin transaction",
-                                ),
-                            )
-                        }
+                fee_target,
+            } => {
+                let fee_per_byte = match (fee_per_byte, fee_target) {
+                    (Some(_), Some(_)) => {
+                        return Err(HttpApiProblem::with_title_and_type_from_status(400)
+                            .set_detail(
+                                "Either fee_per_byte or fee_target can be given, not both",
+                            ))
+                    }
+                    (Some(fee_per_byte), None) => fee_per_byte.parse::<f64>().map_err(|_| {
+                        HttpApiProblem::with_title_and_type_from_status(400)
+                            .set_detail("fee-per-byte is not a valid float")
+                    })?,
+                    (None, fee_target) => {
+                        let fee_target = match fee_target {
+                            Some(fee_target) => fee_target.parse::<FeeTarget>().map_err(|_| {
+                                HttpApiProblem::with_title_and_type_from_status(400).set_detail(
+                                    "fee_target is neither fast, normal, economy nor a number of blocks",
+                                )
+                            })?,
+                            None => FeeTarget::default(),
+                        };
+                        fee_estimators.bitcoin.fee_per_byte(fee_target)
+                    }
+                };
+
+                let transaction = self.spend_to(address);
+                let input_value = transaction.total_input_value();
+                let transaction = transaction.sign_with_rate(fee_per_byte);
+                let fee = input_value - BitcoinQuantity::from_satoshi(transaction.output[0].value);
+                let vsize = (transaction.get_weight() + 3) / 4;
+
+                match serialize_hex(&transaction) {
+                    Ok(hex) => Ok(ActionResponseBody::BroadcastSignedBitcoinTransaction {
+                        hex,
+                        fee,
+                        fee_per_byte,
+                        vsize,
+                    }),
+                    Err(e) => {
+                        error!("Could not serialized signed Bitcoin transaction: {:?}", e);
+                        Err(HttpApiProblem::with_title_and_type_from_status(500)
+                            .set_detail("Issue encountered when serializing Bitcoin transaction"))
                     }
                 }
-                Err(_) => Err(HttpApiProblem::with_title_and_type_from_status(400)
-                    .set_detail("fee-per-byte is not a valid float")),
-            },
+            }
             _ => {
-                error!("Unexpected GET parameters for a bitcoin::SpendOutput action type. Expected: address and fee-per-byte.");
+                error!("Unexpected GET parameters for a bitcoin::SpendOutput action type. Expected: address and optionally fee-per-byte or fee-target.");
                 let mut problem = HttpApiProblem::with_title_and_type_from_status(400)
                     .set_detail("This action requires additional query parameters");
                 problem
@@ -249,7 +338,17 @@ impl IntoResponseBody for bitcoin::SpendOutput {
                         &MissingQueryParameter {
                             data_type: "float",
                             description:
-                                "The fee-per-byte you want to pay for the redeem transaction in satoshis",
+                                "Optional, the fee-per-byte you want to pay for the redeem transaction in satoshis",
+                        },
+                    )
+                    .expect("invalid use of HttpApiProblem");
+                problem
+                    .set_value(
+                        "fee_target",
+                        &MissingQueryParameter {
+                            data_type: "string",
+                            description:
+                                "Optional, fast, normal (default), economy or the number of blocks in which the transaction should confirm",
                         },
                     )
                     .expect("invalid use of HttpApiProblem");
@@ -260,23 +359,57 @@ impl IntoResponseBody for bitcoin::SpendOutput {
     }
 }
 
+/// Estimates the gas of the transaction, keeping the gas limit of the action
+/// if it cannot be estimated
+fn send_ethereum_transaction(
+    from: ethereum_support::Address,
+    to: Option<ethereum_support::Address>,
+    data: ethereum_support::Bytes,
+    value: EtherQuantity,
+    gas_limit: ethereum_support::U256,
+    chain_id: u64,
+    fee_estimators: &FeeEstimators,
+) -> ActionResponseBody {
+    let gas_limit = fee_estimators
+        .ethereum
+        .gas_limit(from, to, value, &data, gas_limit);
+    let gas_price = fee_estimators.ethereum.gas_price();
+
+    ActionResponseBody::SendEthereumTransaction {
+        from,
+        to,
+        data,
+        value,
+        gas_limit,
+        gas_price,
+        estimated_cost: gas_limit * gas_price,
+        chain_id,
+    }
+}
+
 impl IntoResponseBody for ethereum::ContractDeploy {
     fn into_response_body(
         self,
         query_params: GetActionQueryParams,
+        fee_estimators: &FeeEstimators,
     ) -> Result<ActionResponseBody, HttpApiProblem> {
         let ethereum::ContractDeploy {
+            from,
             data,
             value,
             gas_limit,
+            chain_id,
         } = self;
         match query_params {
-            GetActionQueryParams::None {} => Ok(ActionResponseBody::SendEthereumTransaction {
-                to: None,
+            GetActionQueryParams::None {} => Ok(send_ethereum_transaction(
+                from,
+                None,
                 data,
                 value,
                 gas_limit,
-            }),
+                chain_id,
+                fee_estimators,
+            )),
             _ => {
                 error!("Unexpected GET parameters for an ethereum::ContractDeploy action type. Expected: None.");
                 Err(HttpApiProblem::with_title_and_type_from_status(400)
@@ -290,20 +423,26 @@ impl IntoResponseBody for ethereum::SendTransaction {
     fn into_response_body(
         self,
         query_params: GetActionQueryParams,
+        fee_estimators: &FeeEstimators,
     ) -> Result<ActionResponseBody, HttpApiProblem> {
         let ethereum::SendTransaction {
+            from,
             to,
             data,
             value,
             gas_limit,
+            chain_id,
         } = self;
         match query_params {
-            GetActionQueryParams::None {} => Ok(ActionResponseBody::SendEthereumTransaction {
-                to: Some(to),
+            GetActionQueryParams::None {} => Ok(send_ethereum_transaction(
+                from,
+                Some(to),
                 data,
                 value,
                 gas_limit,
-            }),
+                chain_id,
+                fee_estimators,
+            )),
             _ => {
                 error!("Unexpected GET parameters for an ethereum::SendTransaction action. Expected: None.");
                 Err(HttpApiProblem::with_title_and_type_from_status(400)
@@ -317,6 +456,7 @@ impl IntoResponseBody for () {
     fn into_response_body(
         self,
         _: GetActionQueryParams,
+        _: &FeeEstimators,
     ) -> Result<ActionResponseBody, HttpApiProblem> {
         error!("IntoResponseBody should not be called for the unit type");
         Err(HttpApiProblem::with_title_and_type_from_status(500))
@@ -334,12 +474,21 @@ where
     fn into_response_body(
         self,
         query_params: GetActionQueryParams,
+        fee_estimators: &FeeEstimators,
     ) -> Result<ActionResponseBody, HttpApiProblem> {
         match self {
-            alice::ActionKind::Deploy(payload) => payload.into_response_body(query_params),
-            alice::ActionKind::Fund(payload) => payload.into_response_body(query_params),
-            alice::ActionKind::Redeem(payload) => payload.into_response_body(query_params),
-            alice::ActionKind::Refund(payload) => payload.into_response_body(query_params),
+            alice::ActionKind::Deploy(payload) => {
+                payload.into_response_body(query_params, fee_estimators)
+            }
+            alice::ActionKind::Fund(payload) => {
+                payload.into_response_body(query_params, fee_estimators)
+            }
+            alice::ActionKind::Redeem(payload) => {
+                payload.into_response_body(query_params, fee_estimators)
+            }
+            alice::ActionKind::Refund(payload) => {
+                payload.into_response_body(query_params, fee_estimators)
+            }
         }
     }
 }
@@ -355,12 +504,21 @@ where
     fn into_response_body(
         self,
         query_params: GetActionQueryParams,
+        fee_estimators: &FeeEstimators,
     ) -> Result<ActionResponseBody, HttpApiProblem> {
         match self {
-            bob::ActionKind::Deploy(payload) => payload.into_response_body(query_params),
-            bob::ActionKind::Fund(payload) => payload.into_response_body(query_params),
-            bob::ActionKind::Redeem(payload) => payload.into_response_body(query_params),
-            bob::ActionKind::Refund(payload) => payload.into_response_body(query_params),
+            bob::ActionKind::Deploy(payload) => {
+                payload.into_response_body(query_params, fee_estimators)
+            }
+            bob::ActionKind::Fund(payload) => {
+                payload.into_response_body(query_params, fee_estimators)
+            }
+            bob::ActionKind::Redeem(payload) => {
+                payload.into_response_body(query_params, fee_estimators)
+            }
+            bob::ActionKind::Refund(payload) => {
+                payload.into_response_body(query_params, fee_estimators)
+            }
             _ => {
                 error!("IntoResponseBody is not implemented for Accept/Decline");
                 Err(HttpApiProblem::with_title_and_type_from_status(500))
@@ -369,6 +527,179 @@ where
     }
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct ExecuteActionResponseBody {
+    ledger: LedgerKind,
+    transaction_id: String,
+}
+
+pub trait ExecuteAction {
+    /// Ethereum transactions are sent with the gas limit the action's
+    /// response body suggests
+    fn execute_action(
+        self,
+        wallets: &Wallets,
+        fee_estimators: &FeeEstimators,
+    ) -> Result<ExecuteActionResponseBody, HttpApiProblem>;
+}
+
+impl ExecuteAction for bitcoin::SendToAddress {
+    fn execute_action(
+        self,
+        wallets: &Wallets,
+        _: &FeeEstimators,
+    ) -> Result<ExecuteActionResponseBody, HttpApiProblem> {
+        let wallet = wallets
+            .bitcoin
+            .as_ref()
+            .ok_or_else(|| problem::missing_wallet("Bitcoin"))?;
+
+        let transaction_id = wallet
+            .send_to_address(self.address, self.value)
+            .map_err(|e| problem::wallet(&e))?;
+
+        Ok(ExecuteActionResponseBody {
+            ledger: LedgerKind::Bitcoin,
+            transaction_id: transaction_id.to_string(),
+        })
+    }
+}
+
+impl ExecuteAction for bitcoin::SpendOutput {
+    fn execute_action(
+        self,
+        wallets: &Wallets,
+        _: &FeeEstimators,
+    ) -> Result<ExecuteActionResponseBody, HttpApiProblem> {
+        let wallet = wallets
+            .bitcoin
+            .as_ref()
+            .ok_or_else(|| problem::missing_wallet("Bitcoin"))?;
+
+        let transaction_id = wallet.spend_output(self).map_err(|e| problem::wallet(&e))?;
+
+        Ok(ExecuteActionResponseBody {
+            ledger: LedgerKind::Bitcoin,
+            transaction_id: transaction_id.to_string(),
+        })
+    }
+}
+
+impl ExecuteAction for ethereum::ContractDeploy {
+    fn execute_action(
+        self,
+        wallets: &Wallets,
+        fee_estimators: &FeeEstimators,
+    ) -> Result<ExecuteActionResponseBody, HttpApiProblem> {
+        let wallet = wallets
+            .ethereum
+            .as_ref()
+            .ok_or_else(|| problem::missing_wallet("Ethereum"))?;
+
+        let gas_limit = fee_estimators.ethereum.gas_limit(
+            self.from,
+            None,
+            self.value,
+            &self.data,
+            self.gas_limit,
+        );
+        let transaction_id = wallet
+            .send_transaction(None, self.value, self.data, gas_limit)
+            .map_err(|e| problem::wallet(&e))?;
+
+        Ok(ExecuteActionResponseBody {
+            ledger: LedgerKind::Ethereum,
+            transaction_id: format!("{:?}", transaction_id),
+        })
+    }
+}
+
+impl ExecuteAction for ethereum::SendTransaction {
+    fn execute_action(
+        self,
+        wallets: &Wallets,
+        fee_estimators: &FeeEstimators,
+    ) -> Result<ExecuteActionResponseBody, HttpApiProblem> {
+        let wallet = wallets
+            .ethereum
+            .as_ref()
+            .ok_or_else(|| problem::missing_wallet("Ethereum"))?;
+
+        let gas_limit = fee_estimators.ethereum.gas_limit(
+            self.from,
+            Some(self.to),
+            self.value,
+            &self.data,
+            self.gas_limit,
+        );
+        let transaction_id = wallet
+            .send_transaction(Some(self.to), self.value, self.data, gas_limit)
+            .map_err(|e| problem::wallet(&e))?;
+
+        Ok(ExecuteActionResponseBody {
+            ledger: LedgerKind::Ethereum,
+            transaction_id: format!("{:?}", transaction_id),
+        })
+    }
+}
+
+impl ExecuteAction for () {
+    fn execute_action(
+        self,
+        _: &Wallets,
+        _: &FeeEstimators,
+    ) -> Result<ExecuteActionResponseBody, HttpApiProblem> {
+        error!("ExecuteAction should not be called for the unit type");
+        Err(HttpApiProblem::with_title_and_type_from_status(500))
+    }
+}
+
+impl<Deploy, Fund, Redeem, Refund> ExecuteAction for alice::ActionKind<Deploy, Fund, Redeem, Refund>
+where
+    Deploy: ExecuteAction,
+    Fund: ExecuteAction,
+    Redeem: ExecuteAction,
+    Refund: ExecuteAction,
+{
+    fn execute_action(
+        self,
+        wallets: &Wallets,
+        fee_estimators: &FeeEstimators,
+    ) -> Result<ExecuteActionResponseBody, HttpApiProblem> {
+        match self {
+            alice::ActionKind::Deploy(payload) => payload.execute_action(wallets, fee_estimators),
+            alice::ActionKind::Fund(payload) => payload.execute_action(wallets, fee_estimators),
+            alice::ActionKind::Redeem(payload) => payload.execute_action(wallets, fee_estimators),
+            alice::ActionKind::Refund(payload) => payload.execute_action(wallets, fee_estimators),
+        }
+    }
+}
+
+impl<Accept, Decline, Deploy, Fund, Redeem, Refund> ExecuteAction
+    for bob::ActionKind<Accept, Decline, Deploy, Fund, Redeem, Refund>
+where
+    Deploy: ExecuteAction,
+    Fund: ExecuteAction,
+    Redeem: ExecuteAction,
+    Refund: ExecuteAction,
+{
+    fn execute_action(
+        self,
+        wallets: &Wallets,
+        fee_estimators: &FeeEstimators,
+    ) -> Result<ExecuteActionResponseBody, HttpApiProblem> {
+        match self {
+            bob::ActionKind::Deploy(payload) => payload.execute_action(wallets, fee_estimators),
+            bob::ActionKind::Fund(payload) => payload.execute_action(wallets, fee_estimators),
+            bob::ActionKind::Redeem(payload) => payload.execute_action(wallets, fee_estimators),
+            bob::ActionKind::Refund(payload) => payload.execute_action(wallets, fee_estimators),
+            bob::ActionKind::Accept(_) | bob::ActionKind::Decline(_) => {
+                Err(problem::action_not_executable())
+            }
+        }
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(untagged)]
 #[allow(dead_code)] // TODO: Remove once we have ledgers where we use all the combinations
@@ -401,6 +732,7 @@ pub fn post<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     metadata_store: Arc<T>,
     state_store: Arc<S>,
     secret_source: Arc<dyn SecretSource>,
+    lock_duration_margin: Duration,
     id: SwapId,
     action: PostAction,
     body: serde_json::Value,
@@ -409,6 +741,7 @@ pub fn post<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
         metadata_store.as_ref(),
         state_store.as_ref(),
         secret_source.as_ref(),
+        lock_duration_margin,
         id,
         action,
         body,
@@ -423,11 +756,11 @@ pub fn handle_post<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     metadata_store: &T,
     state_store: &S,
     secret_source: &dyn SecretSource,
+    lock_duration_margin: Duration,
     id: SwapId,
     action: PostAction,
     body: serde_json::Value,
 ) -> Result<(), HttpApiProblem> {
-    use crate::swap_protocols::{AssetKind, LedgerKind, Metadata, RoleKind};
     trace!("accept action requested on {:?}", id);
     let metadata = metadata_store
         .get(&id)?
@@ -462,7 +795,14 @@ pub fn handle_post<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
                             })?
                     };
 
-                    ExecuteAccept::execute(&accept_action, accept_body, secret_source, id)
+                    ExecuteAccept::execute(
+                        &accept_action,
+                        accept_body,
+                        secret_source,
+                        id,
+                        state.start_state()?.alpha_ledger_lock_duration,
+                        lock_duration_margin,
+                    )
                 }),
             PostAction::Decline => {
                 serde_json::from_value::<DeclineSwapRequestHttpBody>(body.clone())
@@ -541,6 +881,18 @@ impl<Accept, Decline, Deploy, Fund, Redeem, Refund>
     }
 }
 
+impl fmt::Display for GetAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GetAction::Deploy => "deploy",
+            GetAction::Fund => "fund",
+            GetAction::Redeem => "redeem",
+            GetAction::Refund => "refund",
+        };
+        f.write_str(name)
+    }
+}
+
 impl FromStr for GetAction {
     type Err = ();
 
@@ -559,6 +911,7 @@ impl FromStr for GetAction {
 pub fn get<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     metadata_store: Arc<T>,
     state_store: Arc<S>,
+    fee_estimators: FeeEstimators,
     id: SwapId,
     action: GetAction,
     query_params: GetActionQueryParams,
@@ -566,6 +919,7 @@ pub fn get<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     handle_get(
         metadata_store.as_ref(),
         state_store,
+        &fee_estimators,
         &id,
         action,
         &query_params,
@@ -577,6 +931,7 @@ pub fn get<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
 fn handle_get<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     metadata_store: &T,
     state_store: Arc<S>,
+    fee_estimators: &FeeEstimators,
     id: &SwapId,
     action: GetAction,
     query_params: &GetActionQueryParams,
@@ -601,7 +956,7 @@ fn handle_get<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
                         Some(
                             state_action
                                 .clone()
-                                .into_response_body(query_params.clone())
+                                .into_response_body(query_params.clone(), fee_estimators)
                                 .map(|body| {
                                     trace!("Swap {}: Returning {:?} for {:?}", id, body, action);
                                     warp::reply::json(&body)
@@ -619,9 +974,121 @@ fn handle_get<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     )
 }
 
+pub fn execute<T: MetadataStore<SwapId> + ExecutionLog<SwapId>, S: StateStore<SwapId>>(
+    metadata_store: Arc<T>,
+    state_store: Arc<S>,
+    wallets: Wallets,
+    fee_estimators: FeeEstimators,
+    id: SwapId,
+    action: GetAction,
+) -> impl Future<Item = impl Reply, Error = Rejection> {
+    // The wallets block on the RPC calls to the ledger nodes
+    future::poll_fn(move || {
+        tokio_threadpool::blocking(|| {
+            handle_execute(
+                metadata_store.as_ref(),
+                Arc::clone(&state_store),
+                &wallets,
+                &fee_estimators,
+                &id,
+                action,
+            )
+        })
+    })
+    .map_err(|e| {
+        error!("Could not execute action outside of the runtime: {:?}", e);
+        HttpApiProblem::with_title_and_type_from_status(500)
+    })
+    .and_then(|result| result)
+    .map_err(HttpApiProblemStdError::from)
+    .map_err(warp::reject::custom)
+}
+
+fn handle_execute<T: MetadataStore<SwapId> + ExecutionLog<SwapId>, S: StateStore<SwapId>>(
+    metadata_store: &T,
+    state_store: Arc<S>,
+    wallets: &Wallets,
+    fee_estimators: &FeeEstimators,
+    id: &SwapId,
+    action: GetAction,
+) -> Result<impl Reply, HttpApiProblem> {
+    let metadata = metadata_store
+        .get(id)?
+        .ok_or_else(problem::swap_not_found)?;
+
+    // Until the ledger query service saw the transaction the action is still
+    // offered, executing it again would e.g. fund the HTLC twice
+    let action_name = action.to_string();
+    if !metadata_store.start_execution(id, &action_name)? {
+        return Err(problem::action_already_executed());
+    }
+
+    let executed = with_swap_types!(
+        &metadata,
+        (|| {
+            let state = state_store
+                .get::<Role>(id)?
+                .ok_or_else(problem::state_store)?;
+            trace!("Retrieved state for {}: {:?}", id, state);
+
+            state
+                .actions()
+                .into_iter()
+                .find(|state_action| action.matches(state_action))
+                .map(|state_action| state_action.execute_action(wallets, fee_estimators))
+                .unwrap_or_else(|| {
+                    Err(HttpApiProblem::with_title_and_type_from_status(400)
+                        .set_detail("Requested action is not supported for this swap"))
+                })
+        })
+    );
+
+    match executed {
+        Ok(body) => {
+            info!("Swap {}: Executed {:?} in {:?}", id, action, body);
+
+            // The transaction is out, failing the request now would only
+            // invite the user to broadcast another one
+            if let Err(e) = metadata_store.record_transaction(
+                id,
+                ExecutedTransaction::new(action, body.ledger, body.transaction_id.clone()),
+            ) {
+                error!(
+                    "Swap {}: Could not record transaction {} of {:?}: {:?}",
+                    id, body.transaction_id, action, e
+                );
+            }
+
+            Ok(warp::reply::json(&body))
+        }
+        Err(problem) => {
+            if let Err(e) = metadata_store.abort_execution(id, &action_name) {
+                error!(
+                    "Swap {}: Could not clear execution of {:?}: {:?}",
+                    id, action, e
+                );
+            }
+
+            Err(problem)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        fee_estimation::ethereum::GasEstimator,
+        swap_protocols::{
+            metadata_store::{AssetKind, InMemoryMetadataStore, Metadata, RoleKind},
+            rfc003::state_store::InMemoryStateStore,
+        },
+        wallet::{self, EthereumWallet},
+    };
+    use bitcoin_support::{OutPoint, Sha256dHash};
+    use bitcoin_witness::{PrimedInput, UnlockP2wpkh};
+    use secp256k1_support::KeyPair;
+    use std::sync::Mutex;
 
     #[test]
     fn given_no_query_parameters_deserialize_to_none() {
@@ -640,8 +1107,329 @@ mod test {
             res,
             Ok(GetActionQueryParams::BitcoinAddressAndFee {
                 address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
-                fee_per_byte: "10.59".to_string(),
+                fee_per_byte: Some("10.59".to_string()),
+                fee_target: None,
+            })
+        );
+    }
+
+    #[test]
+    fn given_bitcoin_identity_and_fee_target_deserialize_to_ditto() {
+        let s = "address=1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa&fee_target=fast";
+
+        let res = serde_urlencoded::from_str::<GetActionQueryParams>(s);
+        assert_eq!(
+            res,
+            Ok(GetActionQueryParams::BitcoinAddressAndFee {
+                address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
+                fee_per_byte: None,
+                fee_target: Some("fast".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn executing_a_bitcoin_action_without_wallet_is_a_problem() {
+        let action = bitcoin::SendToAddress {
+            address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
+            value: BitcoinQuantity::from_bitcoin(1.0),
+        };
+
+        let problem = action
+            .execute_action(&Wallets::default(), &FeeEstimators::default())
+            .unwrap_err();
+
+        assert_eq!(problem.title, "missing-wallet");
+    }
+
+    #[test]
+    fn accept_and_decline_cannot_be_executed() {
+        let accept = bob::ActionKind::<(), (), (), (), (), ()>::Accept(());
+        let decline = bob::ActionKind::<(), (), (), (), (), ()>::Decline(());
+
+        let accept_problem = accept
+            .execute_action(&Wallets::default(), &FeeEstimators::default())
+            .unwrap_err();
+        let decline_problem = decline
+            .execute_action(&Wallets::default(), &FeeEstimators::default())
+            .unwrap_err();
+
+        assert_eq!(accept_problem.title, "action-not-executable");
+        assert_eq!(accept_problem.status.map(|s| s.to_u16()), Some(400));
+        assert_eq!(decline_problem.title, "action-not-executable");
+    }
+
+    #[test]
+    fn executing_an_action_twice_is_a_problem() {
+        let metadata_store = InMemoryMetadataStore::default();
+        let id = SwapId::default();
+        metadata_store
+            .insert(
+                id,
+                Metadata::new(
+                    LedgerKind::Bitcoin,
+                    LedgerKind::Ethereum,
+                    AssetKind::Bitcoin,
+                    AssetKind::Ether,
+                    RoleKind::Alice,
+                    "127.0.0.1:8011".parse().unwrap(),
+                    Some(bitcoin_support::Network::Regtest),
+                ),
+            )
+            .unwrap();
+        metadata_store
+            .record_transaction(
+                &id,
+                ExecutedTransaction::new(GetAction::Fund, LedgerKind::Bitcoin, "abcd".into()),
+            )
+            .unwrap();
+
+        let result = handle_execute(
+            &metadata_store,
+            Arc::new(InMemoryStateStore::default()),
+            &Wallets::default(),
+            &FeeEstimators::default(),
+            &id,
+            GetAction::Fund,
+        );
+
+        assert_eq!(
+            result.err().map(|problem| problem.title),
+            Some(String::from("action-already-executed"))
+        );
+    }
+
+    #[test]
+    fn failed_execution_can_be_retried() {
+        let metadata_store = InMemoryMetadataStore::default();
+        let id = SwapId::default();
+        metadata_store
+            .insert(
+                id,
+                Metadata::new(
+                    LedgerKind::Bitcoin,
+                    LedgerKind::Ethereum,
+                    AssetKind::Bitcoin,
+                    AssetKind::Ether,
+                    RoleKind::Alice,
+                    "127.0.0.1:8011".parse().unwrap(),
+                    Some(bitcoin_support::Network::Regtest),
+                ),
+            )
+            .unwrap();
+
+        let result = handle_execute(
+            &metadata_store,
+            Arc::new(InMemoryStateStore::default()),
+            &Wallets::default(),
+            &FeeEstimators::default(),
+            &id,
+            GetAction::Fund,
+        );
+
+        assert!(result.is_err());
+        assert!(metadata_store.start_execution(&id, "fund").unwrap());
+    }
+
+    fn spend_output() -> bitcoin::SpendOutput {
+        let keypair = KeyPair::from_secret_key_slice(&[1u8; 32]).unwrap();
+
+        bitcoin::SpendOutput {
+            output: PrimedInput::new(
+                OutPoint {
+                    txid: Sha256dHash::from_data(b"htlc"),
+                    vout: 0,
+                },
+                BitcoinQuantity::from_bitcoin(1.0),
+                keypair.p2wpkh_unlock_parameters(),
+            ),
+            network: bitcoin_support::Network::Regtest,
+        }
+    }
+
+    fn fee_per_byte_of(body: ActionResponseBody) -> f64 {
+        match body {
+            ActionResponseBody::BroadcastSignedBitcoinTransaction { fee_per_byte, .. } => {
+                fee_per_byte
+            }
+            body => panic!("expected a signed Bitcoin transaction, got {:?}", body),
+        }
+    }
+
+    #[test]
+    fn spend_output_without_fee_uses_the_estimated_normal_rate() {
+        let query_params = GetActionQueryParams::BitcoinAddressAndFee {
+            address: "bcrt1qcqslz7lfn34dl096t5uwurff9spen5h4v2pmap"
+                .parse()
+                .unwrap(),
+            fee_per_byte: None,
+            fee_target: None,
+        };
+
+        let body = spend_output()
+            .into_response_body(query_params, &FeeEstimators::default())
+            .unwrap();
+
+        assert_eq!(fee_per_byte_of(body), 10.0);
+    }
+
+    #[test]
+    fn spend_output_uses_the_estimated_rate_of_the_fee_target() {
+        let query_params = GetActionQueryParams::BitcoinAddressAndFee {
+            address: "bcrt1qcqslz7lfn34dl096t5uwurff9spen5h4v2pmap"
+                .parse()
+                .unwrap(),
+            fee_per_byte: None,
+            fee_target: Some("fast".to_string()),
+        };
+
+        let body = spend_output()
+            .into_response_body(query_params, &FeeEstimators::default())
+            .unwrap();
+
+        assert_eq!(fee_per_byte_of(body), 20.0);
+    }
+
+    #[test]
+    fn spend_output_rejects_fee_per_byte_combined_with_fee_target() {
+        let query_params = GetActionQueryParams::BitcoinAddressAndFee {
+            address: "bcrt1qcqslz7lfn34dl096t5uwurff9spen5h4v2pmap"
+                .parse()
+                .unwrap(),
+            fee_per_byte: Some("10".to_string()),
+            fee_target: Some("fast".to_string()),
+        };
+
+        let problem = spend_output()
+            .into_response_body(query_params, &FeeEstimators::default())
+            .unwrap_err();
+
+        assert_eq!(problem.status.map(|s| s.to_u16()), Some(400));
+    }
+
+    #[test]
+    fn spend_output_rejects_address_of_other_network() {
+        let action = spend_output();
+        let query_params = GetActionQueryParams::BitcoinAddressAndFee {
+            address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
+            fee_per_byte: Some("10".to_string()),
+            fee_target: None,
+        };
+
+        let problem = action
+            .into_response_body(query_params, &FeeEstimators::default())
+            .unwrap_err();
+
+        assert_eq!(problem.title, "address-on-wrong-network");
+    }
+
+    #[test]
+    fn ethereum_action_includes_gas_price_and_estimated_cost() {
+        let action = ethereum::SendTransaction {
+            from: ethereum_support::Address::from(0xAA),
+            to: ethereum_support::Address::from(0xBB),
+            data: ethereum_support::Bytes(vec![]),
+            gas_limit: ethereum_support::U256::from(50_000),
+            value: EtherQuantity::zero(),
+            chain_id: 17,
+        };
+
+        let body = action
+            .into_response_body(GetActionQueryParams::None {}, &FeeEstimators::default())
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(body).unwrap(),
+            json!({
+                "from": "0x00000000000000000000000000000000000000aa",
+                "to": "0x00000000000000000000000000000000000000bb",
+                "data": "0x",
+                "value": "0",
+                "gas_limit": "0xc350",
+                "gas_price": "0x2540be400",
+                "estimated_cost": "0x1c6bf52634000",
+                "chain_id": 17,
             })
         );
     }
+
+    /// Always estimates the same gas limit
+    #[derive(Debug)]
+    struct FixedGas;
+
+    impl GasEstimator for FixedGas {
+        fn gas_limit(
+            &self,
+            _: ethereum_support::Address,
+            _: Option<ethereum_support::Address>,
+            _: EtherQuantity,
+            _: &ethereum_support::Bytes,
+            _: ethereum_support::U256,
+        ) -> ethereum_support::U256 {
+            ethereum_support::U256::from(60_000)
+        }
+
+        fn gas_price(&self) -> ethereum_support::U256 {
+            ethereum_support::U256::from(1)
+        }
+    }
+
+    /// Remembers the gas limits of the transactions it sends
+    #[derive(Default)]
+    struct GasLimitsWallet {
+        gas_limits: Mutex<Vec<ethereum_support::U256>>,
+    }
+
+    impl EthereumWallet for GasLimitsWallet {
+        fn send_transaction(
+            &self,
+            _: Option<ethereum_support::Address>,
+            _: EtherQuantity,
+            _: ethereum_support::Bytes,
+            gas_limit: ethereum_support::U256,
+        ) -> Result<ethereum_support::H256, wallet::ethereum::Error> {
+            self.gas_limits.lock().unwrap().push(gas_limit);
+            Ok(ethereum_support::H256::zero())
+        }
+
+        fn address(&self) -> ethereum_support::Address {
+            ethereum_support::Address::from(0xAA)
+        }
+    }
+
+    #[test]
+    fn ethereum_actions_are_executed_with_the_gas_limit_of_their_response_body() {
+        let wallet = Arc::new(GasLimitsWallet::default());
+        let wallets = Wallets {
+            bitcoin: None,
+            ethereum: Some(Arc::clone(&wallet) as Arc<dyn EthereumWallet>),
+        };
+        let fee_estimators = FeeEstimators {
+            ethereum: Arc::new(FixedGas),
+            ..FeeEstimators::default()
+        };
+        let action = ethereum::SendTransaction {
+            from: ethereum_support::Address::from(0xAA),
+            to: ethereum_support::Address::from(0xBB),
+            data: ethereum_support::Bytes(vec![]),
+            gas_limit: ethereum_support::U256::from(50_000),
+            value: EtherQuantity::zero(),
+            chain_id: 17,
+        };
+
+        let body = action
+            .clone()
+            .into_response_body(GetActionQueryParams::None {}, &fee_estimators)
+            .unwrap();
+        action.execute_action(&wallets, &fee_estimators).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(body).unwrap()["gas_limit"],
+            json!("0xea60")
+        );
+        assert_eq!(
+            *wallet.gas_limits.lock().unwrap(),
+            vec![ethereum_support::U256::from(60_000)]
+        );
+    }
 }
@@ -0,0 +1,258 @@
+use crate::{
+    http_api::{
+        problem::HttpApiProblemStdError,
+        rfc003::swap::{self, ActionName},
+    },
+    swap_protocols::{
+        rfc003::state_store::StateStore, ExecutedTransaction, ExecutionLog, HtlcEvent,
+        MetadataStore, StateChange, StateChanges, SwapId, TransitionStore,
+    },
+};
+use futures::{stream, Stream};
+use http_api_problem::HttpApiProblem;
+use hyper::{header, Body, Chunk, Response};
+use std::{
+    io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::timer::Interval;
+use warp::{self, Rejection, Reply};
+
+/// How often a comment is sent on an idle stream, so proxies and clients
+/// don't close it
+const KEEP_ALIVE_SECS: u64 = 15;
+
+/// A state transition of a swap, as seen by the node right after it was saved
+#[derive(Debug, Serialize)]
+pub struct StateChangeEvent {
+    pub id: SwapId,
+    pub state: String,
+    /// The event on a ledger which triggered the transition, if any
+    #[serde(flatten)]
+    pub htlc_event: Option<HtlcEvent>,
+    pub actions: Vec<ActionName>,
+    pub executed_transactions: Vec<ExecutedTransaction>,
+}
+
+impl StateChangeEvent {
+    fn to_server_sent_event(&self) -> Chunk {
+        let data = serde_json::to_string(self).expect("event should always serialize to json");
+
+        Chunk::from(format!("event: state-change\ndata: {}\n\n", data))
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_swap_events<
+    T: MetadataStore<SwapId> + ExecutionLog<SwapId> + TransitionStore<SwapId>,
+    S: StateStore<SwapId>,
+>(
+    metadata_store: Arc<T>,
+    state_store: Arc<S>,
+    state_changes: Arc<StateChanges>,
+    id: SwapId,
+) -> Result<impl Reply, Rejection> {
+    // Subscribe before reading the current state, so no change is missed
+    let changes = state_changes.subscribe();
+    let current = current_state_event(metadata_store.as_ref(), state_store.as_ref(), id)
+        .map_err(|e| warp::reject::custom(HttpApiProblemStdError::new(e)))?;
+
+    let changes = changes
+        .filter(move |change| change.id == id)
+        .filter_map(move |change| {
+            log_failure(state_change_event(
+                metadata_store.as_ref(),
+                state_store.as_ref(),
+                change,
+            ))
+        });
+
+    Ok(server_sent_events(stream::once(Ok(current)).chain(changes)))
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_events<
+    T: MetadataStore<SwapId> + ExecutionLog<SwapId> + TransitionStore<SwapId>,
+    S: StateStore<SwapId>,
+>(
+    metadata_store: Arc<T>,
+    state_store: Arc<S>,
+    state_changes: Arc<StateChanges>,
+) -> Result<impl Reply, Rejection> {
+    let changes = state_changes.subscribe().filter_map(move |change| {
+        log_failure(state_change_event(
+            metadata_store.as_ref(),
+            state_store.as_ref(),
+            change,
+        ))
+    });
+
+    Ok(server_sent_events(changes))
+}
+
+fn current_state_event<
+    T: MetadataStore<SwapId> + ExecutionLog<SwapId> + TransitionStore<SwapId>,
+    S: StateStore<SwapId>,
+>(
+    metadata_store: &T,
+    state_store: &S,
+    id: SwapId,
+) -> Result<StateChangeEvent, HttpApiProblem> {
+    let (swap, actions) = swap::handle_get_swap(metadata_store, state_store, &id)?;
+
+    Ok(StateChangeEvent {
+        id,
+        state: swap.state,
+        htlc_event: None,
+        actions,
+        executed_transactions: swap.executed_transactions,
+    })
+}
+
+/// The state and what triggered it are taken from the change as published,
+/// only the actions and transactions which come with it are looked up
+fn state_change_event<
+    T: MetadataStore<SwapId> + ExecutionLog<SwapId> + TransitionStore<SwapId>,
+    S: StateStore<SwapId>,
+>(
+    metadata_store: &T,
+    state_store: &S,
+    change: StateChange,
+) -> Result<StateChangeEvent, HttpApiProblem> {
+    let (swap, actions) = swap::handle_get_swap(metadata_store, state_store, &change.id)?;
+
+    Ok(StateChangeEvent {
+        id: change.id,
+        state: change.transition.state,
+        htlc_event: change.transition.htlc_event,
+        actions,
+        executed_transactions: swap.executed_transactions,
+    })
+}
+
+fn log_failure(event: Result<StateChangeEvent, HttpApiProblem>) -> Option<StateChangeEvent> {
+    event
+        .map_err(|e| error!("Failed to retrieve state change: {:?}", e))
+        .ok()
+}
+
+fn keep_alive() -> Chunk {
+    Chunk::from(":keep-alive\n\n")
+}
+
+/// Streams the events as `text/event-stream` until they end or the client
+/// disconnects
+fn server_sent_events<E: Stream<Item = StateChangeEvent, Error = ()> + Send + 'static>(
+    events: E,
+) -> Response<Body> {
+    let period = Duration::from_secs(KEEP_ALIVE_SECS);
+    let keep_alives = Interval::new(Instant::now() + period, period)
+        .map(|_| Some(keep_alive()))
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e));
+
+    // The keep-alives never end, hence the end of the events is marked with
+    // `None` to end the body
+    let body = events
+        .map(|event| Some(event.to_server_sent_event()))
+        .map_err(|()| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "state changes are no longer published",
+            )
+        })
+        .chain(stream::once(Ok(None)))
+        .select(keep_alives)
+        .take_while(|chunk| Ok(chunk.is_some()))
+        .filter_map(|chunk| chunk);
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::wrap_stream(body))
+        .expect("response with valid headers should always build")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::{HtlcEventKind, SwapLedger};
+    use futures::sync::mpsc;
+    use spectral::prelude::*;
+
+    #[test]
+    fn state_change_is_formatted_as_server_sent_event() {
+        let id = SwapId::default();
+        let event = StateChangeEvent {
+            id,
+            state: String::from("Accepted"),
+            htlc_event: None,
+            actions: vec![String::from("fund")],
+            executed_transactions: vec![],
+        };
+
+        let chunk = event.to_server_sent_event();
+
+        assert_that(&String::from_utf8(chunk.to_vec()).unwrap()).is_equal_to(format!(
+            "event: state-change\ndata: {{\"id\":\"{}\",\"state\":\"Accepted\",\"actions\":[\"fund\"],\"executed_transactions\":[]}}\n\n",
+            id
+        ));
+    }
+
+    #[test]
+    fn htlc_event_is_part_of_the_state_change() {
+        let id = SwapId::default();
+        let event = StateChangeEvent {
+            id,
+            state: String::from("AlphaFunded"),
+            htlc_event: Some(HtlcEvent {
+                ledger: SwapLedger::Alpha,
+                event: HtlcEventKind::Funded,
+                transaction_id: Some(String::from("abcd")),
+                htlc_location: None,
+                secret: None,
+            }),
+            actions: vec![],
+            executed_transactions: vec![],
+        };
+
+        let chunk = event.to_server_sent_event();
+
+        assert_that(&String::from_utf8(chunk.to_vec()).unwrap()).is_equal_to(format!(
+            "event: state-change\ndata: {{\"id\":\"{}\",\"state\":\"AlphaFunded\",\"ledger\":\"alpha\",\"event\":\"funded\",\"transaction_id\":\"abcd\",\"actions\":[],\"executed_transactions\":[]}}\n\n",
+            id
+        ));
+    }
+
+    #[test]
+    fn body_ends_once_the_events_end() {
+        let id = SwapId::default();
+        let (sender, receiver) = mpsc::unbounded();
+        sender
+            .unbounded_send(StateChangeEvent {
+                id,
+                state: String::from("Accepted"),
+                htlc_event: None,
+                actions: vec![],
+                executed_transactions: vec![],
+            })
+            .unwrap();
+        drop(sender);
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let body = runtime
+            .block_on(server_sent_events(receiver).into_body().concat2())
+            .unwrap();
+
+        assert_that(&String::from_utf8(body.to_vec()).unwrap()).is_equal_to(format!(
+            "event: state-change\ndata: {{\"id\":\"{}\",\"state\":\"Accepted\",\"actions\":[],\"executed_transactions\":[]}}\n\n",
+            id
+        ));
+    }
+
+    #[test]
+    fn keep_alive_is_a_server_sent_event_comment() {
+        assert_that(&String::from_utf8(keep_alive().to_vec()).unwrap())
+            .is_equal_to(String::from(":keep-alive\n\n"));
+    }
+}
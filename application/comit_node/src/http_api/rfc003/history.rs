@@ -0,0 +1,60 @@
+use crate::{
+    http_api::{
+        problem::{self, HttpApiProblemStdError},
+        rfc003::swap,
+    },
+    swap_protocols::{MetadataStore, SwapId, Transition, TransitionStore},
+};
+use http_api_problem::HttpApiProblem;
+use rustic_hal::HalResource;
+use std::sync::Arc;
+use warp::{self, Rejection, Reply};
+
+/// The states a swap went through, oldest first
+#[derive(Debug, Serialize)]
+pub struct SwapHistory {
+    pub transitions: Vec<Transition>,
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_swap_history<T: MetadataStore<SwapId> + TransitionStore<SwapId>>(
+    metadata_store: Arc<T>,
+    id: SwapId,
+) -> Result<impl Reply, Rejection> {
+    let history = handle_get_swap_history(metadata_store.as_ref(), &id)
+        .map_err(|e| warp::reject::custom(HttpApiProblemStdError::new(e)))?;
+
+    let mut response = HalResource::new(history);
+    response.with_link("swap", swap::swap_path(id));
+
+    Ok(warp::reply::json(&response))
+}
+
+fn handle_get_swap_history<T: MetadataStore<SwapId> + TransitionStore<SwapId>>(
+    metadata_store: &T,
+    id: &SwapId,
+) -> Result<SwapHistory, HttpApiProblem> {
+    let _ = metadata_store
+        .get(id)?
+        .ok_or_else(problem::swap_not_found)?;
+
+    Ok(SwapHistory {
+        transitions: metadata_store.transitions(id)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::InMemoryMetadataStore;
+    use spectral::prelude::*;
+
+    #[test]
+    fn unknown_swap_has_no_history() {
+        let metadata_store = InMemoryMetadataStore::<SwapId>::default();
+
+        let result = handle_get_swap_history(&metadata_store, &SwapId::default());
+
+        assert_that(&result.unwrap_err().status.map(|s| s.to_u16())).is_equal_to(Some(404));
+    }
+}
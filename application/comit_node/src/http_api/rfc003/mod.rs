@@ -1,4 +1,6 @@
 #[macro_use]
 mod with_swap_types;
 pub mod action;
+pub mod events;
+pub mod history;
 pub mod swap;
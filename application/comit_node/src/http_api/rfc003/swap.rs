@@ -12,42 +12,25 @@ use crate::{
         rfc003::{
             self,
             alice::{AliceSpawner, SwapRequestIdentities},
+            state_machine::HtlcExpiry,
             state_store::StateStore,
-            Actions, Alice, Bob, Ledger, SecretSource,
+            Actions, Ledger, SecretHash, SecretSource, SwapCombination,
         },
-        AssetKind, LedgerKind, Metadata, MetadataStore, RoleKind, SwapId,
+        AssetKind, ExecutedTransaction, ExecutionLog, HtlcEventKind, LedgerKind, Metadata,
+        MetadataStore, RoleKind, SwapId, SwapLedger, SwapProtocols, SwapsQuery, Transition,
+        TransitionStore,
     },
 };
-use bitcoin_support::{self, BitcoinQuantity};
-use ethereum_support::{self, Erc20Quantity, EtherQuantity};
+use bitcoin_support::{self, Network};
+use chrono::{DateTime, TimeZone, Utc};
 use http_api_problem::HttpApiProblem;
 use hyper::header;
 use rustic_hal::HalResource;
-use std::sync::Arc;
+use std::{cmp, fmt, net::SocketAddr, str::FromStr, sync::Arc};
 use warp::{self, Rejection, Reply};
 
 pub const PROTOCOL_NAME: &str = "rfc003";
 
-#[derive(Clone, Debug, Deserialize, PartialEq)]
-#[serde(untagged)]
-pub enum SwapRequestBodyKind {
-    BitcoinEthereumBitcoinQuantityErc20Quantity(
-        SwapRequestBody<Bitcoin, Ethereum, BitcoinQuantity, Erc20Quantity>,
-    ),
-    BitcoinEthereumBitcoinQuantityEtherQuantity(
-        SwapRequestBody<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>,
-    ),
-    EthereumBitcoinErc20QuantityBitcoinQuantity(
-        SwapRequestBody<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuantity>,
-    ),
-    EthereumBitcoinEtherQuantityBitcoinQuantity(
-        SwapRequestBody<Ethereum, Bitcoin, EtherQuantity, BitcoinQuantity>,
-    ),
-    // It is important that these two come last because untagged enums are tried in order
-    UnsupportedCombination(Box<UnsupportedSwapRequestBody>),
-    MalformedRequest(serde_json::Value),
-}
-
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct SwapRequestBody<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> {
     #[serde(with = "http_api::asset::serde")]
@@ -61,6 +44,8 @@ pub struct SwapRequestBody<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> {
     alpha_ledger_lock_duration: AL::LockDuration,
     #[serde(flatten)]
     identities: SwapRequestBodyIdentities<AL::Identity, BL::Identity>,
+    #[serde(default)]
+    peer: Option<SocketAddr>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
@@ -144,6 +129,31 @@ impl FromSwapRequestBodyIdentities<Ethereum, Bitcoin>
     }
 }
 
+impl FromSwapRequestBodyIdentities<Ethereum, Ethereum>
+    for rfc003::alice::SwapRequestIdentities<Ethereum, Ethereum>
+{
+    fn from_swap_request_body_identities(
+        identities: SwapRequestBodyIdentities<ethereum_support::Address, ethereum_support::Address>,
+        _: SwapId,
+        _: &dyn SecretSource,
+    ) -> Result<Self, HttpApiProblem> {
+        match identities {
+            SwapRequestBodyIdentities::OnlyRedeem { .. }
+            | SwapRequestBodyIdentities::OnlyRefund { .. }
+            | SwapRequestBodyIdentities::None {} => {
+                Err(HttpApiProblem::with_title_and_type_from_status(400))
+            }
+            SwapRequestBodyIdentities::RefundAndRedeem {
+                alpha_ledger_refund_identity,
+                beta_ledger_redeem_identity,
+            } => Ok(rfc003::alice::SwapRequestIdentities {
+                alpha_ledger_refund_identity,
+                beta_ledger_redeem_identity,
+            }),
+        }
+    }
+}
+
 trait FromSwapRequestBody<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset>
 where
     Self: Sized,
@@ -176,19 +186,60 @@ where
                 id,
                 secret_source,
             )?,
+            peer: body.peer,
         })
     }
 }
 
+/// The part of a swap request that decides which `SwapRequestBody` the rest
+/// of it is decoded into
 #[derive(Clone, Debug, Deserialize, PartialEq)]
-pub struct UnsupportedSwapRequestBody {
+struct SwapRequestBodyLedgersAndAssets {
     alpha_asset: HttpAsset,
     beta_asset: HttpAsset,
     alpha_ledger: HttpLedger,
     beta_ledger: HttpLedger,
-    alpha_ledger_refund_identity: Option<String>,
-    beta_ledger_redeem_identity: Option<String>,
-    alpha_ledger_lock_duration: i64,
+}
+
+fn swap_combination(body: &serde_json::Value) -> Result<SwapCombination, HttpApiProblem> {
+    let ledgers_and_assets =
+        serde_json::from_value::<SwapRequestBodyLedgersAndAssets>(body.clone())
+            .map_err(|_| malformed_request(body))?;
+
+    match (
+        LedgerKind::from_name(ledgers_and_assets.alpha_ledger.name()),
+        LedgerKind::from_name(ledgers_and_assets.beta_ledger.name()),
+        AssetKind::from_name(ledgers_and_assets.alpha_asset.name()),
+        AssetKind::from_name(ledgers_and_assets.beta_asset.name()),
+    ) {
+        (Some(alpha_ledger), Some(beta_ledger), Some(alpha_asset), Some(beta_asset)) => {
+            Ok(SwapCombination {
+                alpha_ledger,
+                beta_ledger,
+                alpha_asset,
+                beta_asset,
+            })
+        }
+        _ => {
+            error!(
+                "Swapping {:?} for {:?} from {:?} to {:?} is not supported",
+                ledgers_and_assets.alpha_asset,
+                ledgers_and_assets.beta_asset,
+                ledgers_and_assets.alpha_ledger,
+                ledgers_and_assets.beta_ledger
+            );
+            Err(problem::unsupported())
+        }
+    }
+}
+
+fn malformed_request(body: &serde_json::Value) -> HttpApiProblem {
+    error!(
+        "Malformed request body: {}",
+        serde_json::to_string(body).expect("failed to serialize serde_json::Value as string ?!")
+    );
+    HttpApiProblem::with_title_and_type_from_status(400)
+        .set_detail("The request body was malformed")
 }
 
 #[derive(Serialize, Debug)]
@@ -196,7 +247,7 @@ pub struct SwapCreated {
     pub id: SwapId,
 }
 
-fn swap_path(id: SwapId) -> String {
+pub(crate) fn swap_path(id: SwapId) -> String {
     format!("/{}/{}/{}", http_api::PATH, PROTOCOL_NAME, id)
 }
 
@@ -205,70 +256,76 @@ fn swap_path(id: SwapId) -> String {
 pub fn post_swap<A: AliceSpawner>(
     alice_spawner: Arc<A>,
     secret_source: Arc<dyn SecretSource>,
-    request_body_kind: SwapRequestBodyKind,
+    body: serde_json::Value,
 ) -> Result<impl Reply, Rejection> {
-    handle_post_swap(
-        alice_spawner.as_ref(),
-        secret_source.as_ref(),
-        request_body_kind,
-    )
-    .map(|swap_created| {
-        let body = warp::reply::json(&swap_created);
-        let response = warp::reply::with_header(body, header::LOCATION, swap_path(swap_created.id));
-        warp::reply::with_status(response, warp::http::StatusCode::CREATED)
-    })
-    .map_err(|problem| warp::reject::custom(HttpApiProblemStdError::from(problem)))
+    handle_post_swap(alice_spawner.as_ref(), secret_source.as_ref(), body)
+        .map(|swap_created| {
+            let body = warp::reply::json(&swap_created);
+            let response =
+                warp::reply::with_header(body, header::LOCATION, swap_path(swap_created.id));
+            warp::reply::with_status(response, warp::http::StatusCode::CREATED)
+        })
+        .map_err(|problem| warp::reject::custom(HttpApiProblemStdError::from(problem)))
 }
 
 fn handle_post_swap<A: AliceSpawner>(
     alice_spawner: &A,
     secret_source: &dyn SecretSource,
-    request_body_kind: SwapRequestBodyKind,
+    body: serde_json::Value,
 ) -> Result<SwapCreated, HttpApiProblem> {
     let id = SwapId::default();
+    let combination = swap_combination(&body)?;
 
-    match request_body_kind {
-        SwapRequestBodyKind::BitcoinEthereumBitcoinQuantityErc20Quantity(body) => alice_spawner
-            .spawn(
-                id,
-                rfc003::alice::SwapRequest::from_swap_request_body(body, id, secret_source)?,
-            )?,
-        SwapRequestBodyKind::BitcoinEthereumBitcoinQuantityEtherQuantity(body) => alice_spawner
-            .spawn(
-                id,
-                rfc003::alice::SwapRequest::from_swap_request_body(body, id, secret_source)?,
-            )?,
-        SwapRequestBodyKind::EthereumBitcoinEtherQuantityBitcoinQuantity(body) => alice_spawner
-            .spawn(
-                id,
-                rfc003::alice::SwapRequest::from_swap_request_body(body, id, secret_source)?,
-            )?,
-        SwapRequestBodyKind::EthereumBitcoinErc20QuantityBitcoinQuantity(body) => alice_spawner
-            .spawn(
+    crate::with_swap_combination!(
+        combination,
+        {
+            let body = serde_json::from_value::<SwapRequestBody<AL, BL, AA, BA>>(body.clone())
+                .map_err(|_| malformed_request(&body))?;
+
+            alice_spawner.spawn(
                 id,
                 rfc003::alice::SwapRequest::from_swap_request_body(body, id, secret_source)?,
-            )?,
-        SwapRequestBodyKind::UnsupportedCombination(body) => {
-            error!(
-                "Swapping {:?} for {:?} from {:?} to {:?} is not supported",
-                body.alpha_asset, body.beta_asset, body.alpha_ledger, body.beta_ledger
-            );
+            )?
+        },
+        {
+            error!("Swap combination {:?} is not supported", combination);
             return Err(problem::unsupported());
         }
-        SwapRequestBodyKind::MalformedRequest(body) => {
-            error!(
-                "Malformed request body: {}",
-                serde_json::to_string(&body)
-                    .expect("failed to serialize serde_json::Value as string ?!")
-            );
-            return Err(HttpApiProblem::with_title_and_type_from_status(400)
-                .set_detail("The request body was malformed"));
-        }
-    };
+    );
 
     Ok(SwapCreated { id })
 }
 
+/// A ledger and asset combination the node can swap, with the names used in
+/// swap requests
+#[derive(Debug, Serialize)]
+pub struct SwapCombinationResource {
+    alpha_ledger: HttpLedger,
+    beta_ledger: HttpLedger,
+    alpha_asset: HttpAsset,
+    beta_asset: HttpAsset,
+}
+
+impl From<SwapCombination> for SwapCombinationResource {
+    fn from(combination: SwapCombination) -> Self {
+        SwapCombinationResource {
+            alpha_ledger: HttpLedger::with_ledger(combination.alpha_ledger.name()),
+            beta_ledger: HttpLedger::with_ledger(combination.beta_ledger.name()),
+            alpha_asset: HttpAsset::with_asset(combination.alpha_asset.name()),
+            beta_asset: HttpAsset::with_asset(combination.beta_asset.name()),
+        }
+    }
+}
+
+pub fn get_combinations() -> Result<impl Reply, Rejection> {
+    let combinations: Vec<SwapCombinationResource> = SwapCombination::supported()
+        .into_iter()
+        .map(SwapCombinationResource::from)
+        .collect();
+
+    Ok(warp::reply::json(&combinations))
+}
+
 #[derive(Debug, Serialize)]
 pub struct SwapDescription {
     alpha_ledger: HttpLedger,
@@ -280,21 +337,107 @@ pub struct SwapDescription {
     beta_lock_duration: Option<HttpLockDuration>,
 }
 
+/// `refundable_at` is a block height on Bitcoin and a UNIX timestamp on
+/// Ethereum
 #[derive(Debug, Serialize)]
-struct GetSwapResource {
+struct HttpHtlcExpiry {
+    refundable_at: Option<u64>,
+    refundable_now: bool,
+}
+
+impl<L: Ledger> From<&HtlcExpiry<L>> for HttpHtlcExpiry {
+    fn from(htlc_expiry: &HtlcExpiry<L>) -> Self {
+        HttpHtlcExpiry {
+            refundable_at: htlc_expiry.refundable_at.clone().map(Into::into),
+            refundable_now: htlc_expiry.refundable_now,
+        }
+    }
+}
+
+/// Where the HTLC on one ledger of a swap lives and the transactions which
+/// touched it, to look them up in a block explorer
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub(crate) struct HttpHtlc {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    funding_transaction_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redeem_transaction_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refund_transaction_id: Option<String>,
+}
+
+/// The on-chain details of a swap, collected from the ledger events which
+/// made it transition
+#[derive(Debug, Default, PartialEq)]
+struct OnChainDetails {
+    alpha_htlc: HttpHtlc,
+    beta_htlc: HttpHtlc,
+    secret: Option<String>,
+}
+
+impl OnChainDetails {
+    fn from_transitions(transitions: Vec<Transition>) -> Self {
+        let mut details = OnChainDetails::default();
+
+        for htlc_event in transitions.into_iter().filter_map(|t| t.htlc_event) {
+            let htlc = match htlc_event.ledger {
+                SwapLedger::Alpha => &mut details.alpha_htlc,
+                SwapLedger::Beta => &mut details.beta_htlc,
+            };
+
+            match htlc_event.event {
+                HtlcEventKind::Deployed => htlc.location = htlc_event.htlc_location,
+                HtlcEventKind::Funded => htlc.funding_transaction_id = htlc_event.transaction_id,
+                HtlcEventKind::Redeemed => htlc.redeem_transaction_id = htlc_event.transaction_id,
+                HtlcEventKind::Refunded => htlc.refund_transaction_id = htlc_event.transaction_id,
+            }
+
+            if htlc_event.secret.is_some() {
+                details.secret = htlc_event.secret;
+            }
+        }
+
+        details
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct GetSwapResource {
     pub swap: SwapDescription,
     pub role: String,
     pub state: String,
+    pub protocol: SwapProtocols,
+    pub counterparty: SocketAddr,
+    pub bitcoin_network: Option<Network>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alpha_htlc_expiry: Option<HttpHtlcExpiry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub beta_htlc_expiry: Option<HttpHtlcExpiry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub executed_transactions: Vec<ExecutedTransaction>,
+    pub secret_hash: SecretHash,
+    /// Only shown once the secret was revealed on one of the ledgers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub alpha_htlc: HttpHtlc,
+    pub beta_htlc: HttpHtlc,
 }
 
 #[allow(clippy::needless_pass_by_value)]
-pub fn get_swap<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
+pub fn get_swap<
+    T: MetadataStore<SwapId> + ExecutionLog<SwapId> + TransitionStore<SwapId>,
+    S: StateStore<SwapId>,
+>(
     metadata_store: Arc<T>,
     state_store: Arc<S>,
     id: SwapId,
 ) -> Result<impl Reply, Rejection> {
     let result: Result<(GetSwapResource, Vec<ActionName>), HttpApiProblem> =
-        handle_get_swap(&metadata_store, &state_store, &id);
+        handle_get_swap(metadata_store.as_ref(), state_store.as_ref(), &id);
 
     match result {
         Ok((swap_resource, actions)) => {
@@ -309,11 +452,14 @@ pub fn get_swap<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     }
 }
 
-type ActionName = String;
+pub type ActionName = String;
 
-fn handle_get_swap<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
-    metadata_store: &Arc<T>,
-    state_store: &Arc<S>,
+pub(crate) fn handle_get_swap<
+    T: MetadataStore<SwapId> + ExecutionLog<SwapId> + TransitionStore<SwapId>,
+    S: StateStore<SwapId>,
+>(
+    metadata_store: &T,
+    state_store: &S,
     id: &SwapId,
 ) -> Result<(GetSwapResource, Vec<ActionName>), HttpApiProblem> {
     let metadata = metadata_store
@@ -332,6 +478,15 @@ fn handle_get_swap<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
 
             let actions: Vec<ActionName> =
                 state.actions().iter().map(|action| action.name()).collect();
+            let mut on_chain = OnChainDetails::from_transitions(metadata_store.transitions(id)?);
+            // The history only keeps what happened after it was recorded, the
+            // state knows the HTLC locations for as long as they matter
+            if let Some(location) = state.alpha_htlc_location() {
+                on_chain.alpha_htlc.location = serde_json::to_value(location).ok();
+            }
+            if let Some(location) = state.beta_htlc_location() {
+                on_chain.beta_htlc.location = serde_json::to_value(location).ok();
+            }
             (Ok((
                 GetSwapResource {
                     state: state.name(),
@@ -349,6 +504,18 @@ fn handle_get_swap<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
                             .map(|lock| lock.to_http_lock_duration().unwrap()),
                     },
                     role: format!("{}", metadata.role),
+                    protocol: metadata.protocol,
+                    counterparty: metadata.counterparty,
+                    bitcoin_network: metadata.bitcoin_network,
+                    created_at: metadata.created_at,
+                    updated_at: metadata.updated_at,
+                    alpha_htlc_expiry: state.alpha_htlc_expiry().map(HttpHtlcExpiry::from),
+                    beta_htlc_expiry: state.beta_htlc_expiry().map(HttpHtlcExpiry::from),
+                    executed_transactions: metadata_store.transactions(id)?,
+                    secret_hash: start_state.secret.into(),
+                    secret: on_chain.secret,
+                    alpha_htlc: on_chain.alpha_htlc,
+                    beta_htlc: on_chain.beta_htlc,
                 },
                 actions,
             )))
@@ -356,21 +523,163 @@ fn handle_get_swap<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     )
 }
 
+/// Swaps are listed newest first unless sorted by `created_at`
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum SwapsSort {
+    #[serde(rename = "created_at")]
+    OldestFirst,
+    #[serde(rename = "-created_at")]
+    NewestFirst,
+}
+
+impl Default for SwapsSort {
+    fn default() -> Self {
+        SwapsSort::NewestFirst
+    }
+}
+
+pub const DEFAULT_SWAPS_PAGE_SIZE: usize = 50;
+pub const MAX_SWAPS_PAGE_SIZE: usize = 500;
+
+/// `after` and `before` take the cursors of the `next` and `prev` links
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct GetSwapsQueryParams {
+    state: Option<String>,
+    role: Option<RoleKind>,
+    alpha_ledger: Option<String>,
+    beta_ledger: Option<String>,
+    alpha_asset: Option<String>,
+    beta_asset: Option<String>,
+    counterparty: Option<SocketAddr>,
+    #[serde(default)]
+    sort: SwapsSort,
+    limit: Option<usize>,
+    after: Option<String>,
+    before: Option<String>,
+}
+
+impl GetSwapsQueryParams {
+    /// Selects the swaps which come after the cursor while walking the list in
+    /// the given direction, plus one to tell if there are more
+    fn query(&self, cursor: Option<Cursor>, backwards: bool, limit: usize) -> SwapsQuery<SwapId> {
+        SwapsQuery {
+            role: self.role,
+            alpha_ledger: self.alpha_ledger.clone(),
+            beta_ledger: self.beta_ledger.clone(),
+            alpha_asset: self.alpha_asset.clone(),
+            beta_asset: self.beta_asset.clone(),
+            counterparty: self.counterparty,
+            state: self.state.clone(),
+            newest_first: (self.sort == SwapsSort::NewestFirst) != backwards,
+            after: cursor.map(|cursor| (cursor.created_at, cursor.id)),
+            limit: limit + 1,
+        }
+    }
+
+    fn limit(&self) -> usize {
+        cmp::min(
+            self.limit.unwrap_or(DEFAULT_SWAPS_PAGE_SIZE),
+            MAX_SWAPS_PAGE_SIZE,
+        )
+    }
+
+    fn link(&self, after: Option<&Cursor>, before: Option<&Cursor>) -> String {
+        let params = GetSwapsQueryParams {
+            after: after.map(Cursor::to_string),
+            before: before.map(Cursor::to_string),
+            ..self.clone()
+        };
+        let query =
+            serde_urlencoded::to_string(&params).expect("query parameters should always serialize");
+
+        format!("/{}?{}", http_api::PATH, query)
+    }
+}
+
+/// The position of a swap in the list, which stays valid while new swaps are
+/// added, unlike an offset
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Cursor {
+    created_at: DateTime<Utc>,
+    id: SwapId,
+}
+
+impl Cursor {
+    fn of(id: SwapId, metadata: &Metadata) -> Self {
+        Cursor {
+            created_at: metadata.created_at,
+            id,
+        }
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.created_at.timestamp_nanos(), self.id)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '_');
+        let created_at = parts
+            .next()
+            .and_then(|nanos| nanos.parse::<i64>().ok())
+            .filter(|nanos| *nanos >= 0)
+            .and_then(|nanos| {
+                Utc.timestamp_opt(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+                    .single()
+            })
+            .ok_or(())?;
+        let id = parts
+            .next()
+            .and_then(|id| id.parse::<SwapId>().ok())
+            .ok_or(())?;
+
+        Ok(Cursor { created_at, id })
+    }
+}
+
 #[derive(Serialize, Debug)]
 pub struct EmbeddedSwapResource {
     state: String,
-    protocol: String,
+    protocol: SwapProtocols,
+    role: String,
+    counterparty: SocketAddr,
+    alpha_ledger: HttpLedger,
+    beta_ledger: HttpLedger,
+    alpha_asset: HttpAsset,
+    beta_asset: HttpAsset,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct SwapsPage {
+    swaps: Vec<HalResource>,
+    next: Option<Cursor>,
+    prev: Option<Cursor>,
 }
 
 #[allow(clippy::needless_pass_by_value)]
 pub fn get_swaps<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     metadata_store: Arc<T>,
     state_store: Arc<S>,
+    query_params: GetSwapsQueryParams,
 ) -> Result<impl Reply, Rejection> {
-    match handle_get_swaps(metadata_store.as_ref(), state_store.as_ref()) {
-        Ok(swaps) => {
+    match handle_get_swaps(metadata_store.as_ref(), state_store.as_ref(), &query_params) {
+        Ok(page) => {
             let mut response = HalResource::new("");
-            response.with_resources("swaps", swaps);
+            response.with_resources("swaps", page.swaps);
+            response.with_link("self", query_params.link(None, None));
+            if let Some(next) = page.next {
+                response.with_link("next", query_params.link(Some(&next), None));
+            }
+            if let Some(prev) = page.prev {
+                response.with_link("prev", query_params.link(None, Some(&prev)));
+            }
             Ok(warp::reply::json(&response))
         }
         Err(e) => {
@@ -381,42 +690,120 @@ pub fn get_swaps<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     }
 }
 
+fn parse_cursor(name: &str, cursor: &Option<String>) -> Result<Option<Cursor>, HttpApiProblem> {
+    match cursor {
+        Some(cursor) => cursor
+            .parse()
+            .map(Some)
+            .map_err(|_| problem::invalid_query(&format!("`{}` is not a valid cursor", name))),
+        None => Ok(None),
+    }
+}
+
 fn handle_get_swaps<T: MetadataStore<SwapId>, S: StateStore<SwapId>>(
     metadata_store: &T,
     state_store: &S,
-) -> Result<Vec<HalResource>, HttpApiProblem> {
-    let mut resources = vec![];
-    for (id, metadata) in metadata_store.all()?.into_iter() {
-        with_swap_types!(
-            &metadata,
-            (|| -> Result<(), HttpApiProblem> {
-                let state = state_store.get::<Role>(&id)?;
-
-                match state {
-                    Some(state) => {
-                        let swap = EmbeddedSwapResource {
-                            state: state.name(),
-                            protocol: PROTOCOL_NAME.into(),
-                        };
-
-                        let mut hal_resource = HalResource::new(swap);
-                        hal_resource.with_link("self", swap_path(id));
-                        resources.push(hal_resource);
-                    }
-                    None => error!("Couldn't find state for {} despite having the metadata", id),
-                };
-                Ok(())
-            })
-        )?;
+    query_params: &GetSwapsQueryParams,
+) -> Result<SwapsPage, HttpApiProblem> {
+    let after = parse_cursor("after", &query_params.after)?;
+    let before = parse_cursor("before", &query_params.before)?;
+    let limit = query_params.limit();
+
+    // Paging backwards walks the list from the `before` cursor to the front
+    let (cursor, backwards) = match (after, before) {
+        (Some(_), Some(_)) => {
+            return Err(problem::invalid_query(
+                "`after` and `before` cannot be combined",
+            ));
+        }
+        (Some(after), None) => (Some(after), false),
+        (None, Some(before)) => (Some(before), true),
+        (None, None) => (None, false),
+    };
+
+    let mut swaps = metadata_store.query(&query_params.query(cursor, backwards, limit))?;
+    let has_more = swaps.len() > limit;
+    swaps.truncate(limit);
+    if backwards {
+        swaps.reverse();
     }
 
-    Ok(resources)
+    let mut page = Vec::new();
+    for (id, metadata) in swaps {
+        if let Some(resource) = embedded_swap(state_store, id, &metadata)? {
+            page.push((Cursor::of(id, &metadata), resource));
+        }
+    }
+
+    // Coming from a cursor, the swap of the cursor is on the other side
+    let first = page.first().map(|(cursor, _)| *cursor);
+    let last = page.last().map(|(cursor, _)| *cursor);
+    let (next, prev) = if backwards {
+        (last, first.filter(|_| has_more))
+    } else {
+        (last.filter(|_| has_more), first.filter(|_| after.is_some()))
+    };
+
+    let swaps = page
+        .into_iter()
+        .map(|(cursor, resource)| {
+            let mut hal_resource = HalResource::new(resource);
+            hal_resource.with_link("self", swap_path(cursor.id));
+            hal_resource
+        })
+        .collect();
+
+    Ok(SwapsPage { swaps, next, prev })
+}
+
+fn embedded_swap<S: StateStore<SwapId>>(
+    state_store: &S,
+    id: SwapId,
+    metadata: &Metadata,
+) -> Result<Option<EmbeddedSwapResource>, HttpApiProblem> {
+    with_swap_types!(
+        metadata,
+        (|| {
+            let state = match state_store.get::<Role>(&id)? {
+                Some(state) => state,
+                None => {
+                    error!("Couldn't find state for {} despite having the metadata", id);
+                    return Ok(None);
+                }
+            };
+            let start_state = state.start_state()?;
+
+            Ok(Some(EmbeddedSwapResource {
+                state: state.name(),
+                protocol: metadata.protocol,
+                role: format!("{}", metadata.role),
+                counterparty: metadata.counterparty,
+                alpha_ledger: start_state.alpha_ledger.to_http_ledger().unwrap(),
+                beta_ledger: start_state.beta_ledger.to_http_ledger().unwrap(),
+                alpha_asset: start_state.alpha_asset.to_http_asset().unwrap(),
+                beta_asset: start_state.beta_asset.to_http_asset().unwrap(),
+                created_at: metadata.created_at,
+                updated_at: metadata.updated_at,
+            }))
+        })
+    )
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::swap_protocols::{
+        rfc003::{
+            role::test::Alisha,
+            state_machine::{Start, SwapStates},
+            state_store::InMemoryStateStore,
+            SaveState, Secret,
+        },
+        HtlcEvent, InMemoryMetadataStore,
+    };
+    use bitcoin_support::BitcoinQuantity;
+    use ethereum_support::{Erc20Quantity, EtherQuantity};
     use spectral::prelude::*;
 
     #[test]
@@ -427,7 +814,8 @@ mod tests {
                     "network": "regtest"
                 },
                 "beta_ledger": {
-                    "name": "Ethereum"
+                    "name": "Ethereum",
+                    "chain_id": 17
                 },
                 "alpha_asset": {
                     "name": "Bitcoin",
@@ -439,7 +827,8 @@ mod tests {
                 },
                 "alpha_ledger_refund_identity": null,
                 "beta_ledger_redeem_identity": "0x00a329c0648769a73afac7f9381e08fb43dbea72",
-                "alpha_ledger_lock_duration": 144
+                "alpha_ledger_lock_duration": 144,
+                "peer": "127.0.0.1:8011"
             }"#;
 
         let body = serde_json::from_str(body);
@@ -448,14 +837,435 @@ mod tests {
             alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
             beta_asset: EtherQuantity::from_eth(10.0),
             alpha_ledger: Bitcoin::regtest(),
-            beta_ledger: Ethereum::default(),
+            beta_ledger: Ethereum::dev(),
             alpha_ledger_lock_duration: bitcoin_support::Blocks::new(144),
             identities: SwapRequestBodyIdentities::OnlyRedeem {
                 beta_ledger_redeem_identity: ethereum_support::Address::from(
                     "0x00a329c0648769a73afac7f9381e08fb43dbea72",
                 ),
             },
+            peer: Some("127.0.0.1:8011".parse().unwrap()),
+        })
+    }
+
+    #[test]
+    fn ether_for_erc20_request_is_an_ethereum_ethereum_combination() {
+        let body = json!({
+            "alpha_ledger": {
+                "name": "Ethereum",
+                "chain_id": 17
+            },
+            "beta_ledger": {
+                "name": "Ethereum",
+                "chain_id": 17
+            },
+            "alpha_asset": {
+                "name": "Ether",
+                "quantity": "10000000000000000000"
+            },
+            "beta_asset": {
+                "name": "ERC20",
+                "quantity": "1000",
+                "token_contract": "0xb97048628db6b661d4c2aa833e95dbe1a905b280"
+            },
+            "alpha_ledger_refund_identity": "0x00a329c0648769a73afac7f9381e08fb43dbea72",
+            "beta_ledger_redeem_identity": "0x00a329c0648769a73afac7f9381e08fb43dbea72",
+            "alpha_ledger_lock_duration": 86400
+        });
+
+        assert_that(&swap_combination(&body)).is_ok_containing(SwapCombination {
+            alpha_ledger: LedgerKind::Ethereum,
+            beta_ledger: LedgerKind::Ethereum,
+            alpha_asset: AssetKind::Ether,
+            beta_asset: AssetKind::Erc20,
+        });
+
+        let body = serde_json::from_value::<
+            SwapRequestBody<Ethereum, Ethereum, EtherQuantity, Erc20Quantity>,
+        >(body)
+        .unwrap();
+
+        assert_that(&body.beta_asset).is_equal_to(Erc20Quantity::new(
+            ethereum_support::Address::from("0xb97048628db6b661d4c2aa833e95dbe1a905b280"),
+            ethereum_support::U256::from(1000),
+        ));
+    }
+
+    #[test]
+    fn unknown_asset_is_an_unsupported_combination() {
+        let body = json!({
+            "alpha_ledger": {
+                "name": "Bitcoin",
+                "network": "regtest"
+            },
+            "beta_ledger": {
+                "name": "Ethereum",
+                "chain_id": 17
+            },
+            "alpha_asset": {
+                "name": "Bitcoin",
+                "quantity": "100000000"
+            },
+            "beta_asset": {
+                "name": "DAI",
+                "quantity": "1000"
+            },
+            "alpha_ledger_lock_duration": 144
+        });
+
+        let error = swap_combination(&body).unwrap_err();
+
+        assert_that(&error.title).is_equal_to(problem::unsupported().title);
+    }
+
+    #[test]
+    fn lists_supported_combinations_with_request_names() {
+        let combination = SwapCombinationResource::from(SwapCombination {
+            alpha_ledger: LedgerKind::Bitcoin,
+            beta_ledger: LedgerKind::Ethereum,
+            alpha_asset: AssetKind::Bitcoin,
+            beta_asset: AssetKind::Erc20,
+        });
+
+        assert_that(&serde_json::to_value(&combination).unwrap()).is_equal_to(json!({
+            "alpha_ledger": { "name": "Bitcoin" },
+            "beta_ledger": { "name": "Ethereum" },
+            "alpha_asset": { "name": "Bitcoin" },
+            "beta_asset": { "name": "ERC20" }
+        }));
+    }
+
+    #[test]
+    fn can_deserialize_get_swaps_query_params() {
+        let query_params = serde_urlencoded::from_str::<GetSwapsQueryParams>(
+            "role=Bob&alpha_asset=ERC20&counterparty=127.0.0.1:8011&sort=created_at&limit=10",
+        );
+
+        assert_that(&query_params).is_ok_containing(GetSwapsQueryParams {
+            role: Some(RoleKind::Bob),
+            alpha_asset: Some(String::from("ERC20")),
+            counterparty: Some("127.0.0.1:8011".parse().unwrap()),
+            sort: SwapsSort::OldestFirst,
+            limit: Some(10),
+            ..GetSwapsQueryParams::default()
+        });
+    }
+
+    #[test]
+    fn swaps_are_listed_newest_first_by_default() {
+        let query_params = serde_urlencoded::from_str::<GetSwapsQueryParams>("");
+
+        assert_that(&query_params.map(|params| params.sort))
+            .is_ok_containing(SwapsSort::NewestFirst);
+    }
+
+    #[test]
+    fn cursor_survives_round_trip_through_link() {
+        let cursor = Cursor {
+            created_at: Utc.timestamp(1_545_000_000, 123_456_789),
+            id: SwapId::default(),
+        };
+
+        let parsed = cursor.to_string().parse::<Cursor>();
+
+        assert_that(&parsed).is_ok_containing(cursor);
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        let id = "4c4bc00a-5e0c-4c3e-9e4b-0a1bc4b2e0f3";
+
+        assert_that(&"foo".parse::<Cursor>()).is_err();
+        assert_that(&format!("-1_{}", id).parse::<Cursor>()).is_err();
+        assert_that(&format!("99999999999999999999_{}", id).parse::<Cursor>()).is_err();
+        assert_that(&"1545000000000000000_foo".parse::<Cursor>()).is_err();
+    }
+
+    #[test]
+    fn next_link_keeps_filters() {
+        let query_params = GetSwapsQueryParams {
+            state: Some(String::from("Accepted")),
+            limit: Some(10),
+            ..GetSwapsQueryParams::default()
+        };
+        let cursor = Cursor {
+            created_at: Utc.timestamp(1_545_000_000, 0),
+            id: "4c4bc00a-5e0c-4c3e-9e4b-0a1bc4b2e0f3".parse().unwrap(),
+        };
+
+        let link = query_params.link(Some(&cursor), None);
+
+        assert_that(&link).is_equal_to(String::from(
+            "/swaps?state=Accepted&sort=-created_at&limit=10&after=1545000000000000000_4c4bc00a-5e0c-4c3e-9e4b-0a1bc4b2e0f3",
+        ));
+    }
+
+    /// Stores `count` swaps created a second apart, returning their ids oldest
+    /// first
+    fn store_swaps(
+        count: i64,
+    ) -> (
+        InMemoryMetadataStore<SwapId>,
+        InMemoryStateStore<SwapId>,
+        Vec<SwapId>,
+    ) {
+        let metadata_store = InMemoryMetadataStore::default();
+        let state_store = InMemoryStateStore::default();
+        let start_state = Start::<Alisha> {
+            alpha_ledger_refund_identity: secp256k1_support::KeyPair::from_secret_key_slice(
+                &[1u8; 32],
+            )
+            .unwrap(),
+            beta_ledger_redeem_identity: "8457037fcd80a8650c4692d7fcfc1d0a96b92867"
+                .parse()
+                .unwrap(),
+            alpha_ledger: Bitcoin::regtest(),
+            beta_ledger: Ethereum::dev(),
+            alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
+            beta_asset: EtherQuantity::from_eth(10.0),
+            alpha_ledger_lock_duration: bitcoin_support::Blocks::from(144),
+            secret: Secret::from(*b"hello world, you are beautiful!!"),
+            role: Alisha::default(),
+        };
+
+        let ids = (0..count)
+            .map(|i| {
+                let id = SwapId::default();
+                let mut metadata = Metadata::new(
+                    LedgerKind::Bitcoin,
+                    LedgerKind::Ethereum,
+                    AssetKind::Bitcoin,
+                    AssetKind::Ether,
+                    RoleKind::Alice,
+                    "127.0.0.1:8011".parse().unwrap(),
+                    Some(Network::Regtest),
+                );
+                metadata.created_at = Utc.timestamp(1_545_000_000 + i, 0);
+                metadata_store.insert(id, metadata).unwrap();
+                metadata_store.touch(&id, "Start").unwrap();
+                state_store
+                    .new_save_state::<Alisha>(id)
+                    .unwrap()
+                    .save(SwapStates::from(start_state.clone()));
+                id
+            })
+            .collect();
+
+        (metadata_store, state_store, ids)
+    }
+
+    fn swap_paths(page: &SwapsPage) -> Vec<String> {
+        page.swaps
+            .iter()
+            .map(|swap| {
+                serde_json::to_value(swap).unwrap()["_links"]["self"]["href"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn paths(ids: &[SwapId]) -> Vec<String> {
+        ids.iter().cloned().map(swap_path).collect()
+    }
+
+    fn oldest_first(limit: usize) -> GetSwapsQueryParams {
+        GetSwapsQueryParams {
+            sort: SwapsSort::OldestFirst,
+            limit: Some(limit),
+            ..GetSwapsQueryParams::default()
+        }
+    }
+
+    #[test]
+    fn pages_forward_through_swaps() {
+        let (metadata_store, state_store, ids) = store_swaps(5);
+
+        let first = handle_get_swaps(&metadata_store, &state_store, &oldest_first(2)).unwrap();
+        assert_that(&swap_paths(&first)).is_equal_to(paths(&ids[0..2]));
+        assert_that(&first.prev).is_none();
+
+        let second = handle_get_swaps(
+            &metadata_store,
+            &state_store,
+            &GetSwapsQueryParams {
+                after: first.next.map(|cursor| cursor.to_string()),
+                ..oldest_first(2)
+            },
+        )
+        .unwrap();
+        assert_that(&swap_paths(&second)).is_equal_to(paths(&ids[2..4]));
+        assert_that(&second.prev.map(|cursor| cursor.id)).is_equal_to(Some(ids[2]));
+
+        let last = handle_get_swaps(
+            &metadata_store,
+            &state_store,
+            &GetSwapsQueryParams {
+                after: second.next.map(|cursor| cursor.to_string()),
+                ..oldest_first(2)
+            },
+        )
+        .unwrap();
+        assert_that(&swap_paths(&last)).is_equal_to(paths(&ids[4..]));
+        assert_that(&last.next).is_none();
+        assert_that(&last.prev.map(|cursor| cursor.id)).is_equal_to(Some(ids[4]));
+    }
+
+    #[test]
+    fn pages_backward_from_before_cursor() {
+        let (metadata_store, state_store, ids) = store_swaps(5);
+        let before = Cursor {
+            created_at: Utc.timestamp(1_545_000_004, 0),
+            id: ids[4],
+        };
+
+        let previous = handle_get_swaps(
+            &metadata_store,
+            &state_store,
+            &GetSwapsQueryParams {
+                before: Some(before.to_string()),
+                ..oldest_first(2)
+            },
+        )
+        .unwrap();
+        assert_that(&swap_paths(&previous)).is_equal_to(paths(&ids[2..4]));
+        assert_that(&previous.next.map(|cursor| cursor.id)).is_equal_to(Some(ids[3]));
+        assert_that(&previous.prev.map(|cursor| cursor.id)).is_equal_to(Some(ids[2]));
+
+        let first = handle_get_swaps(
+            &metadata_store,
+            &state_store,
+            &GetSwapsQueryParams {
+                before: previous.prev.map(|cursor| cursor.to_string()),
+                ..oldest_first(2)
+            },
+        )
+        .unwrap();
+        assert_that(&swap_paths(&first)).is_equal_to(paths(&ids[0..2]));
+        assert_that(&first.prev).is_none();
+    }
+
+    #[test]
+    fn lists_newest_first_without_more_pages() {
+        let (metadata_store, state_store, ids) = store_swaps(3);
+
+        let page = handle_get_swaps(
+            &metadata_store,
+            &state_store,
+            &GetSwapsQueryParams::default(),
+        )
+        .unwrap();
+
+        assert_that(&swap_paths(&page)).is_equal_to(paths(&[ids[2], ids[1], ids[0]]));
+        assert_that(&page.next).is_none();
+        assert_that(&page.prev).is_none();
+    }
+
+    #[test]
+    fn filters_swaps_by_metadata_and_state() {
+        let (metadata_store, state_store, ids) = store_swaps(3);
+        metadata_store.touch(&ids[1], "Accepted").unwrap();
+
+        let accepted = handle_get_swaps(
+            &metadata_store,
+            &state_store,
+            &GetSwapsQueryParams {
+                role: Some(RoleKind::Alice),
+                beta_asset: Some(String::from("Ether")),
+                state: Some(String::from("Accepted")),
+                ..GetSwapsQueryParams::default()
+            },
+        )
+        .unwrap();
+        let bob = handle_get_swaps(
+            &metadata_store,
+            &state_store,
+            &GetSwapsQueryParams {
+                role: Some(RoleKind::Bob),
+                ..GetSwapsQueryParams::default()
+            },
+        )
+        .unwrap();
+
+        assert_that(&swap_paths(&accepted)).is_equal_to(paths(&ids[1..2]));
+        assert_that(&swap_paths(&bob)).is_empty();
+    }
+
+    #[test]
+    fn invalid_cursor_or_combined_cursors_are_invalid_queries() {
+        let (metadata_store, state_store, _) = store_swaps(1);
+        let invalid = GetSwapsQueryParams {
+            after: Some(String::from("-1_4c4bc00a-5e0c-4c3e-9e4b-0a1bc4b2e0f3")),
+            ..GetSwapsQueryParams::default()
+        };
+        let combined = GetSwapsQueryParams {
+            after: Some(String::from("1_4c4bc00a-5e0c-4c3e-9e4b-0a1bc4b2e0f3")),
+            before: Some(String::from("2_4c4bc00a-5e0c-4c3e-9e4b-0a1bc4b2e0f3")),
+            ..GetSwapsQueryParams::default()
+        };
+
+        for query_params in &[invalid, combined] {
+            let problem =
+                handle_get_swaps(&metadata_store, &state_store, query_params).unwrap_err();
+
+            assert_that(&problem.title).is_equal_to(String::from("invalid-query"));
+            assert_that(&problem.status.map(|s| s.to_u16())).is_equal_to(Some(400));
+        }
+    }
+
+    fn htlc_event(
+        ledger: SwapLedger,
+        event: HtlcEventKind,
+        transaction_id: Option<&str>,
+    ) -> Option<HtlcEvent> {
+        Some(HtlcEvent {
+            ledger,
+            event,
+            transaction_id: transaction_id.map(String::from),
+            htlc_location: None,
+            secret: None,
         })
     }
 
+    #[test]
+    fn on_chain_details_are_collected_from_transitions() {
+        let alpha_deployed = Some(HtlcEvent {
+            htlc_location: Some(json!("0x0a81e8be41b21f651a71aab1a85c6813b8bbccf8")),
+            ..htlc_event(SwapLedger::Alpha, HtlcEventKind::Deployed, None).unwrap()
+        });
+        let beta_redeemed = Some(HtlcEvent {
+            secret: Some(String::from("68656c6c6f")),
+            ..htlc_event(SwapLedger::Beta, HtlcEventKind::Redeemed, Some("0x03")).unwrap()
+        });
+        let transitions = vec![
+            Transition::new("Accepted".into(), None),
+            Transition::new("AlphaDeployed".into(), alpha_deployed),
+            Transition::new(
+                "AlphaFunded".into(),
+                htlc_event(SwapLedger::Alpha, HtlcEventKind::Funded, Some("0x01")),
+            ),
+            Transition::new(
+                "BothFunded".into(),
+                htlc_event(SwapLedger::Beta, HtlcEventKind::Funded, Some("0x02")),
+            ),
+            Transition::new("AlphaFundedBetaRedeemed".into(), beta_redeemed),
+        ];
+
+        let details = OnChainDetails::from_transitions(transitions);
+
+        assert_that(&details).is_equal_to(OnChainDetails {
+            alpha_htlc: HttpHtlc {
+                location: Some(json!("0x0a81e8be41b21f651a71aab1a85c6813b8bbccf8")),
+                funding_transaction_id: Some(String::from("0x01")),
+                ..HttpHtlc::default()
+            },
+            beta_htlc: HttpHtlc {
+                funding_transaction_id: Some(String::from("0x02")),
+                redeem_transaction_id: Some(String::from("0x03")),
+                ..HttpHtlc::default()
+            },
+            secret: Some(String::from("68656c6c6f")),
+        });
+    }
 }
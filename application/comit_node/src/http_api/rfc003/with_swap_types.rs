@@ -1,15 +1,17 @@
+#[doc(hidden)]
+#[macro_export]
 macro_rules! _match_role {
-    ($role:ident, $fn:tt) => {
+    ($role:expr, $fn:tt) => {
         #[allow(clippy::redundant_closure_call)]
         match $role {
-            RoleKind::Alice => {
+            $crate::swap_protocols::RoleKind::Alice => {
                 #[allow(dead_code)]
-                type Role = Alice<AL, BL, AA, BA>;
+                type Role = $crate::swap_protocols::rfc003::Alice<AL, BL, AA, BA>;
                 $fn()
             }
-            RoleKind::Bob => {
+            $crate::swap_protocols::RoleKind::Bob => {
                 #[allow(dead_code)]
-                type Role = Bob<AL, BL, AA, BA>;
+                type Role = $crate::swap_protocols::rfc003::Bob<AL, BL, AA, BA>;
                 $fn()
             }
         }
@@ -19,99 +21,31 @@ macro_rules! _match_role {
 #[macro_export]
 macro_rules! with_swap_types {
     ($metadata:expr, $fn:tt) => {{
-        use bitcoin_support::BitcoinQuantity;
-        use ethereum_support::EtherQuantity;
         let metadata = $metadata;
 
-        match metadata {
-            Metadata {
-                alpha_ledger: LedgerKind::Bitcoin,
-                beta_ledger: LedgerKind::Ethereum,
-                alpha_asset: AssetKind::Bitcoin,
-                beta_asset: AssetKind::Ether,
-                role,
-            } => {
-                #[allow(dead_code)]
-                type AL = Bitcoin;
-                #[allow(dead_code)]
-                type BL = Ethereum;
-                #[allow(dead_code)]
-                type AA = BitcoinQuantity;
-                #[allow(dead_code)]
-                type BA = EtherQuantity;
-
-                _match_role!(role, $fn)
-            }
-            Metadata {
-                alpha_ledger: LedgerKind::Bitcoin,
-                beta_ledger: LedgerKind::Ethereum,
-                alpha_asset: AssetKind::Bitcoin,
-                beta_asset: AssetKind::Erc20,
-                role,
-            } => {
-                #[allow(dead_code)]
-                type AL = Bitcoin;
-                #[allow(dead_code)]
-                type BL = Ethereum;
-                #[allow(dead_code)]
-                type AA = BitcoinQuantity;
-                #[allow(dead_code)]
-                type BA = Erc20Quantity;
-
-                _match_role!(role, $fn)
-            }
-            Metadata {
-                alpha_ledger: LedgerKind::Ethereum,
-                beta_ledger: LedgerKind::Bitcoin,
-                alpha_asset: AssetKind::Ether,
-                beta_asset: AssetKind::Bitcoin,
-                role,
-            } => {
-                #[allow(dead_code)]
-                type AL = Ethereum;
-                #[allow(dead_code)]
-                type BL = Bitcoin;
-                #[allow(dead_code)]
-                type AA = EtherQuantity;
-                #[allow(dead_code)]
-                type BA = BitcoinQuantity;
-
-                _match_role!(role, $fn)
-            }
-            Metadata {
-                alpha_ledger: LedgerKind::Ethereum,
-                beta_ledger: LedgerKind::Bitcoin,
-                alpha_asset: AssetKind::Erc20,
-                beta_asset: AssetKind::Bitcoin,
-                role,
-            } => {
-                #[allow(dead_code)]
-                type AL = Ethereum;
-                #[allow(dead_code)]
-                type BL = Bitcoin;
-                #[allow(dead_code)]
-                type AA = Erc20Quantity;
-                #[allow(dead_code)]
-                type BA = BitcoinQuantity;
-
-                _match_role!(role, $fn)
-            }
-            _ => unimplemented!(),
-        }
+        $crate::with_swap_combination!(
+            $crate::swap_protocols::rfc003::SwapCombination::from(metadata),
+            $crate::_match_role!(metadata.role, $fn),
+            Err($crate::http_api::problem::unsupported())
+        )
     }};
 }
 
+#[doc(hidden)]
+#[macro_export]
 macro_rules! _match_role_bob {
-    ($role:ident, $fn:tt) => {
+    ($role:expr, $fn:tt) => {
         #[allow(clippy::redundant_closure_call)]
         match $role {
-            RoleKind::Bob => {
+            $crate::swap_protocols::RoleKind::Bob => {
                 #[allow(dead_code)]
-                type Role = Bob<AL, BL, AA, BA>;
+                type Role = $crate::swap_protocols::rfc003::Bob<AL, BL, AA, BA>;
                 $fn()
             }
-            _ => Err(HttpApiProblem::with_title_and_type_from_status(400)
-                .set_detail("Requested action is not supported for this role")),
+            _ => Err(
+                http_api_problem::HttpApiProblem::with_title_and_type_from_status(400)
+                    .set_detail("Requested action is not supported for this role"),
+            ),
         }
     };
 }
@@ -119,84 +53,12 @@ macro_rules! _match_role_bob {
 #[macro_export]
 macro_rules! with_swap_types_bob {
     ($metadata:expr, $fn:tt) => {{
-        use bitcoin_support::BitcoinQuantity;
-        use ethereum_support::EtherQuantity;
         let metadata = $metadata;
 
-        match metadata {
-            Metadata {
-                alpha_ledger: LedgerKind::Bitcoin,
-                beta_ledger: LedgerKind::Ethereum,
-                alpha_asset: AssetKind::Bitcoin,
-                beta_asset: AssetKind::Ether,
-                role,
-            } => {
-                #[allow(dead_code)]
-                type AL = Bitcoin;
-                #[allow(dead_code)]
-                type BL = Ethereum;
-                #[allow(dead_code)]
-                type AA = BitcoinQuantity;
-                #[allow(dead_code)]
-                type BA = EtherQuantity;
-
-                _match_role_bob!(role, $fn)
-            }
-            Metadata {
-                alpha_ledger: LedgerKind::Bitcoin,
-                beta_ledger: LedgerKind::Ethereum,
-                alpha_asset: AssetKind::Bitcoin,
-                beta_asset: AssetKind::Erc20,
-                role,
-            } => {
-                #[allow(dead_code)]
-                type AL = Bitcoin;
-                #[allow(dead_code)]
-                type BL = Ethereum;
-                #[allow(dead_code)]
-                type AA = BitcoinQuantity;
-                #[allow(dead_code)]
-                type BA = Erc20Quantity;
-
-                _match_role_bob!(role, $fn)
-            }
-            Metadata {
-                alpha_ledger: LedgerKind::Ethereum,
-                beta_ledger: LedgerKind::Bitcoin,
-                alpha_asset: AssetKind::Ether,
-                beta_asset: AssetKind::Bitcoin,
-                role,
-            } => {
-                #[allow(dead_code)]
-                type AL = Ethereum;
-                #[allow(dead_code)]
-                type BL = Bitcoin;
-                #[allow(dead_code)]
-                type AA = EtherQuantity;
-                #[allow(dead_code)]
-                type BA = BitcoinQuantity;
-
-                _match_role_bob!(role, $fn)
-            }
-            Metadata {
-                alpha_ledger: LedgerKind::Ethereum,
-                beta_ledger: LedgerKind::Bitcoin,
-                alpha_asset: AssetKind::Erc20,
-                beta_asset: AssetKind::Bitcoin,
-                role,
-            } => {
-                #[allow(dead_code)]
-                type AL = Ethereum;
-                #[allow(dead_code)]
-                type BL = Bitcoin;
-                #[allow(dead_code)]
-                type AA = Erc20Quantity;
-                #[allow(dead_code)]
-                type BA = BitcoinQuantity;
-
-                _match_role_bob!(role, $fn)
-            }
-            _ => unimplemented!(),
-        }
+        $crate::with_swap_combination!(
+            $crate::swap_protocols::rfc003::SwapCombination::from(metadata),
+            $crate::_match_role_bob!(metadata.role, $fn),
+            Err($crate::http_api::problem::unsupported())
+        )
     }};
 }
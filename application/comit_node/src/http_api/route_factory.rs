@@ -1,17 +1,23 @@
 use crate::{
     comit_client::ClientPool,
-    http_api::{self, rfc003::action::GetActionQueryParams},
+    fee_estimation::FeeEstimators,
+    http_api::{
+        self,
+        auth::{self, ApiKeys, Authorized, Scope},
+        rfc003::{action::GetActionQueryParams, swap::GetSwapsQueryParams},
+    },
     seed::Seed,
     swap_protocols::{
         rfc003::{alice::AliceSpawner, state_store, SecretSource},
-        MetadataStore, SwapId,
+        ExecutionLog, MetadataStore, StateChanges, SwapId, TransitionStore,
     },
+    wallet::Wallets,
 };
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use warp::{self, filters::BoxedFilter, Filter, Reply};
 
 pub fn create<
-    T: MetadataStore<SwapId>,
+    T: MetadataStore<SwapId> + ExecutionLog<SwapId> + TransitionStore<SwapId>,
     S: state_store::StateStore<SwapId>,
     A: AliceSpawner,
     C: ClientPool,
@@ -21,6 +27,12 @@ pub fn create<
     alice_spawner: Arc<A>,
     seed: Seed,
     comit_client_pool: Arc<C>,
+    lock_duration_margin: Duration,
+    wallets: Wallets,
+    fee_estimators: FeeEstimators,
+    state_changes: Arc<StateChanges>,
+    api_keys: Arc<ApiKeys>,
+    public_metrics: bool,
 ) -> BoxedFilter<(impl Reply,)> {
     let seed = Arc::new(seed);
     let path = warp::path(http_api::PATH);
@@ -31,15 +43,26 @@ pub fn create<
     let empty_json_body = warp::any().map(|| json!({}));
     let alice_spawner = warp::any().map(move || alice_spawner.clone());
     let comit_client_pool = warp::any().map(move || comit_client_pool.clone());
+    let wallets = warp::any().map(move || wallets.clone());
+    let fee_estimators = warp::any().map(move || fee_estimators.clone());
+    let state_changes = warp::any().map(move || state_changes.clone());
+    let read_only = auth::authorize(api_keys.clone(), Scope::ReadOnly);
+    let trading = auth::authorize(api_keys, Scope::Trading);
 
     let rfc003_post_swap = rfc003
         .and(warp::path::end())
         .and(warp::post2())
-        .and(alice_spawner)
+        .and(auth::authorized(trading.clone(), alice_spawner))
         .and(rfc003_secret_gen.clone())
         .and(warp::body::json())
         .and_then(http_api::rfc003::swap::post_swap);
 
+    let rfc003_get_combinations = rfc003
+        .and(warp::path("combinations"))
+        .and(warp::get2())
+        .and(warp::path::end())
+        .and_then(http_api::rfc003::swap::get_combinations);
+
     let rfc003_get_swap = rfc003
         .and(warp::get2())
         .and(metadata_store.clone())
@@ -48,46 +71,122 @@ pub fn create<
         .and(warp::path::end())
         .and_then(http_api::rfc003::swap::get_swap);
 
+    let rfc003_get_swap_events = rfc003
+        .and(warp::get2())
+        .and(metadata_store.clone())
+        .and(state_store.clone())
+        .and(state_changes.clone())
+        .and(warp::path::param())
+        .and(warp::path("events"))
+        .and(warp::path::end())
+        .and_then(http_api::rfc003::events::get_swap_events);
+
+    let rfc003_get_swap_history = rfc003
+        .and(warp::get2())
+        .and(metadata_store.clone())
+        .and(warp::path::param())
+        .and(warp::path("history"))
+        .and(warp::path::end())
+        .and_then(http_api::rfc003::history::get_swap_history);
+
+    let get_events = path
+        .and(warp::path("events"))
+        .and(warp::get2())
+        .and(warp::path::end())
+        .and(metadata_store.clone())
+        .and(state_store.clone())
+        .and(state_changes)
+        .and_then(http_api::rfc003::events::get_events);
+
     let get_swaps = path
         .and(warp::get2())
         .and(warp::path::end())
         .and(metadata_store.clone())
         .and(state_store.clone())
+        .and(warp::query::<GetSwapsQueryParams>())
         .and_then(http_api::rfc003::swap::get_swaps);
 
     let rfc003_post_action = rfc003
         .and(metadata_store.clone())
         .and(state_store.clone())
         .and(rfc003_secret_gen.clone())
+        .and(warp::any().map(move || lock_duration_margin))
         .and(warp::path::param::<SwapId>())
         .and(warp::path::param::<http_api::rfc003::action::PostAction>())
         .and(warp::post2())
         .and(warp::path::end())
-        .and(warp::body::json().or(empty_json_body).unify())
+        .and(auth::authorized(
+            trading.clone(),
+            warp::body::json().or(empty_json_body).unify(),
+        ))
         .and_then(http_api::rfc003::action::post);
 
     let rfc003_get_action = rfc003
         .and(metadata_store.clone())
         .and(state_store.clone())
+        .and(fee_estimators.clone())
         .and(warp::path::param::<SwapId>())
         .and(warp::path::param::<http_api::rfc003::action::GetAction>())
-        .and(warp::query::<GetActionQueryParams>())
         .and(warp::get2())
         .and(warp::path::end())
+        .and(auth::authorized(
+            trading.clone(),
+            warp::query::<GetActionQueryParams>(),
+        ))
         .and_then(http_api::rfc003::action::get);
 
+    let rfc003_execute_action = rfc003
+        .and(warp::post2())
+        .and(metadata_store.clone())
+        .and(state_store.clone())
+        .and(wallets)
+        .and(fee_estimators)
+        .and(warp::path::param::<SwapId>())
+        .and(auth::authorized(
+            trading,
+            warp::path::param::<http_api::rfc003::action::GetAction>(),
+        ))
+        .and(warp::path("execute"))
+        .and(warp::path::end())
+        .and_then(http_api::rfc003::action::execute);
+
     let get_peers = warp::path("peers")
         .and(comit_client_pool.clone())
         .and(warp::get2())
         .and(warp::path::end())
         .and_then(http_api::peers);
 
-    rfc003_get_swap
+    let get_metrics = warp::path("metrics")
+        .and(warp::get2())
+        .and(warp::path::end())
+        .and_then(http_api::get_metrics)
+        .boxed();
+    let get_metrics = if public_metrics {
+        get_metrics
+    } else {
+        auth::authorized(read_only.clone(), get_metrics)
+    };
+
+    // The trading routes check the API key only once the path and method
+    // matched, otherwise a read-only key would be rejected for every request
+    // which one of them does not match
+    let read = read_only
+        .and(
+            rfc003_get_combinations
+                .or(rfc003_get_swap)
+                .or(rfc003_get_swap_events)
+                .or(rfc003_get_swap_history)
+                .or(get_swaps)
+                .or(get_events)
+                .or(get_peers),
+        )
+        .map(|_: Authorized, reply| reply);
+
+    read.or(get_metrics)
         .or(rfc003_post_swap)
         .or(rfc003_post_action)
         .or(rfc003_get_action)
-        .or(get_swaps)
-        .or(get_peers)
+        .or(rfc003_execute_action)
         .with(warp::log("http"))
         .recover(http_api::unpack_problem)
         .boxed()
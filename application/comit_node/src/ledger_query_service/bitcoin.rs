@@ -1,5 +1,11 @@
-use crate::ledger_query_service::Query;
+use crate::{
+    ledger_query_service::{Error, Query, QueryId},
+    swap_protocols::ledger::Bitcoin,
+};
+use bitcoin_support::BlockHeight;
+use futures::Future;
 use serde::Serialize;
+use std::fmt::Debug;
 
 #[derive(Debug, Clone, Serialize, Eq, Hash, PartialEq)]
 #[serde(untagged)]
@@ -11,11 +17,28 @@ pub enum BitcoinQuery {
     },
     Block {
         min_height: Option<u32>,
+        /// Only matches the block which includes a transaction matching this
+        /// query
+        #[serde(skip_serializing_if = "Option::is_none")]
+        containing_transaction: Option<Box<BitcoinQuery>>,
     },
 }
 
 impl Query for BitcoinQuery {}
 
+/// The expanded form of a block matching a `BitcoinQuery::Block`
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct BitcoinBlockSummary {
+    pub height: BlockHeight,
+}
+
+pub trait FetchBlockHeights: 'static + Send + Sync + Debug {
+    fn fetch_block_heights(
+        &self,
+        query: &QueryId<Bitcoin>,
+    ) -> Box<dyn Future<Item = Vec<BlockHeight>, Error = Error> + Send>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,11 +84,31 @@ mod tests {
     fn given_a_bitcoin_block_query_with_min_height_it_serializes_ok() {
         let query = BitcoinQuery::Block {
             min_height: Some(42),
+            containing_transaction: None,
         };
         let query = serde_json::to_string(&query).unwrap();
         assert_eq!(query, r#"{"min_height":42}"#)
     }
 
+    #[test]
+    fn given_a_bitcoin_block_query_containing_a_transaction_it_serializes_ok() {
+        let query = BitcoinQuery::Block {
+            min_height: None,
+            containing_transaction: Some(Box::new(BitcoinQuery::Transaction {
+                to_address: Some(
+                    Address::from_str("bcrt1qcqslz7lfn34dl096t5uwurff9spen5h4v2pmap").unwrap(),
+                ),
+                from_outpoint: None,
+                unlock_script: None,
+            })),
+        };
+        let query = serde_json::to_string(&query).unwrap();
+        assert_eq!(
+            query,
+            r#"{"min_height":null,"containing_transaction":{"to_address":"bcrt1qcqslz7lfn34dl096t5uwurff9spen5h4v2pmap","from_outpoint":null,"unlock_script":null}}"#
+        )
+    }
+
     #[test]
     fn given_a_bitcoin_transaction_query_with_from_outpoint_it_serializes_ok() {
         let to_address = None;
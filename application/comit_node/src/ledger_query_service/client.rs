@@ -1,13 +1,20 @@
 use crate::{
     ledger_query_service::{
-        bitcoin::BitcoinQuery, ethereum::EthereumQuery, CreateQuery, Error, FetchFullQueryResults,
-        FetchQueryResults, LedgerQueryServiceApiClient, Query, QueryId,
+        bitcoin::{BitcoinBlockSummary, BitcoinQuery, FetchBlockHeights},
+        ethereum::{EthereumBlockSummary, EthereumQuery, FetchBlockTimestamps},
+        CreateQuery, Error, FetchFullQueryResults, FetchQueryResults, LedgerQueryServiceApiClient,
+        Query, QueryId,
+    },
+    metrics,
+    swap_protocols::{
+        ledger::{Bitcoin, Ethereum, Ledger},
+        rfc003::ethereum::Timestamp,
     },
-    swap_protocols::ledger::{Bitcoin, Ethereum, Ledger},
 };
+use bitcoin_support::BlockHeight;
 use futures::{stream::Stream, Async};
 use reqwest::{header::LOCATION, r#async::Client, StatusCode, Url};
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 use tokio::prelude::future::Future;
 
 #[derive(Debug)]
@@ -26,8 +33,14 @@ pub struct QueryResponse<T> {
 
 impl DefaultLedgerQueryServiceApiClient {
     pub fn new(endpoint: &Url) -> Self {
+        Self::with_client(endpoint, Client::new())
+    }
+
+    /// Lets the caller configure the client, e.g. to trust the CA of a ledger
+    /// query service which is served over HTTPS
+    pub fn with_client(endpoint: &Url, client: Client) -> Self {
         DefaultLedgerQueryServiceApiClient {
-            client: Client::new(),
+            client,
             create_bitcoin_transaction_query_endpoint: endpoint
                 .join("queries/bitcoin/transactions")
                 .expect("invalid url"),
@@ -102,7 +115,7 @@ impl DefaultLedgerQueryServiceApiClient {
             })
             .map(QueryId::new);
 
-        Box::new(query_id)
+        Box::new(metrics::observe_lqs_request("create_query", query_id))
     }
 
     fn fetch_results<L: Ledger>(
@@ -123,13 +136,13 @@ impl DefaultLedgerQueryServiceApiClient {
             })
             .map(|response| response.matches);
 
-        Box::new(transactions)
+        Box::new(metrics::observe_lqs_request("fetch_results", transactions))
     }
 
-    fn fetch_full_results<L: Ledger>(
+    fn fetch_full_results<L: Ledger, T: DeserializeOwned + Send + 'static>(
         &self,
         query: &QueryId<L>,
-    ) -> Box<dyn Future<Item = Vec<L::Transaction>, Error = Error> + Send> {
+    ) -> Box<dyn Future<Item = Vec<T>, Error = Error> + Send> {
         let mut url = query.as_ref().clone();
         url.set_query(Some("expand_results=true"));
 
@@ -137,7 +150,7 @@ impl DefaultLedgerQueryServiceApiClient {
             .client
             .get(url.clone())
             .send()
-            .and_then(|mut response| response.json::<QueryResponse<L::Transaction>>())
+            .and_then(|mut response| response.json::<QueryResponse<T>>())
             .map_err(move |e| {
                 Error::FailedRequest(format!(
                     "Failed to fetch results for {:?} because {:?}",
@@ -146,14 +159,18 @@ impl DefaultLedgerQueryServiceApiClient {
             })
             .map(|response| response.matches);
 
-        Box::new(transactions)
+        Box::new(metrics::observe_lqs_request(
+            "fetch_full_results",
+            transactions,
+        ))
     }
 
     fn _delete<L: Ledger>(
         &self,
         query: &QueryId<L>,
     ) -> Box<dyn Future<Item = (), Error = Error> + Send> {
-        Box::new(
+        Box::new(metrics::observe_lqs_request(
+            "delete_query",
             self.client
                 .delete(query.as_ref().clone())
                 .send()
@@ -161,7 +178,7 @@ impl DefaultLedgerQueryServiceApiClient {
                 .map_err(|e| {
                     Error::FailedRequest(format!("Failed to delete query because {:?}", e))
                 }),
-        )
+        ))
     }
 }
 
@@ -198,6 +215,18 @@ impl FetchFullQueryResults<Bitcoin> for DefaultLedgerQueryServiceApiClient {
     }
 }
 
+impl FetchBlockHeights for DefaultLedgerQueryServiceApiClient {
+    fn fetch_block_heights(
+        &self,
+        query: &QueryId<Bitcoin>,
+    ) -> Box<dyn Future<Item = Vec<BlockHeight>, Error = Error> + Send> {
+        Box::new(
+            self.fetch_full_results::<Bitcoin, BitcoinBlockSummary>(query)
+                .map(|blocks| blocks.into_iter().map(|block| block.height).collect()),
+        )
+    }
+}
+
 impl LedgerQueryServiceApiClient<Bitcoin, BitcoinQuery> for DefaultLedgerQueryServiceApiClient {
     fn delete(&self, query: &QueryId<Bitcoin>) -> Box<dyn Future<Item = (), Error = Error> + Send> {
         self._delete(&query)
@@ -237,6 +266,18 @@ impl FetchFullQueryResults<Ethereum> for DefaultLedgerQueryServiceApiClient {
     }
 }
 
+impl FetchBlockTimestamps for DefaultLedgerQueryServiceApiClient {
+    fn fetch_block_timestamps(
+        &self,
+        query: &QueryId<Ethereum>,
+    ) -> Box<dyn Future<Item = Vec<Timestamp>, Error = Error> + Send> {
+        Box::new(
+            self.fetch_full_results::<Ethereum, EthereumBlockSummary>(query)
+                .map(|blocks| blocks.into_iter().map(|block| block.timestamp).collect()),
+        )
+    }
+}
+
 impl LedgerQueryServiceApiClient<Ethereum, EthereumQuery> for DefaultLedgerQueryServiceApiClient {
     fn delete(
         &self,
@@ -1,6 +1,11 @@
-use crate::ledger_query_service::Query;
+use crate::{
+    ledger_query_service::{Error, Query, QueryId},
+    swap_protocols::{ledger::Ethereum, rfc003::ethereum::Timestamp},
+};
 use ethereum_support::web3::types::{Address, Bytes};
+use futures::Future;
 use serde::Serialize;
+use std::fmt::Debug;
 
 #[derive(Debug, Clone, Serialize, Eq, Hash, PartialEq)]
 #[serde(untagged)]
@@ -13,12 +18,29 @@ pub enum EthereumQuery {
         transaction_data_length: Option<usize>,
     },
     Block {
-        min_timestamp_secs: Option<u32>,
+        min_timestamp_secs: Option<u64>,
+        /// Only matches the block which includes a transaction matching this
+        /// query
+        #[serde(skip_serializing_if = "Option::is_none")]
+        containing_transaction: Option<Box<EthereumQuery>>,
     },
 }
 
 impl Query for EthereumQuery {}
 
+/// The expanded form of a block matching an `EthereumQuery::Block`
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct EthereumBlockSummary {
+    pub timestamp: Timestamp,
+}
+
+pub trait FetchBlockTimestamps: 'static + Send + Sync + Debug {
+    fn fetch_block_timestamps(
+        &self,
+        query: &QueryId<Ethereum>,
+    ) -> Box<dyn Future<Item = Vec<Timestamp>, Error = Error> + Send>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,6 +86,7 @@ mod tests {
     fn given_a_ethereum_block_query_with_min_timestamp_it_serializes_ok() {
         let query = EthereumQuery::Block {
             min_timestamp_secs: Some(10),
+            containing_transaction: None,
         };
         let query = serde_json::to_string(&query).unwrap();
         assert_eq!(query, r#"{"min_timestamp_secs":10}"#)
@@ -79,6 +102,9 @@ mod tests {
             transaction_data_length: Some(12),
         };
         let query = serde_json::to_string(&query).unwrap();
-        assert_eq!(query, r#"{"from_address":null,"to_address":null,"is_contract_creation":null,"transaction_data":"0x68656c6c6f20776f726c6421","transaction_data_length":12}"#)
+        assert_eq!(
+            query,
+            r#"{"from_address":null,"to_address":null,"is_contract_creation":null,"transaction_data":"0x68656c6c6f20776f726c6421","transaction_data_length":12}"#
+        )
     }
 }
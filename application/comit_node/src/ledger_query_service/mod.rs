@@ -34,7 +34,7 @@ impl<L: Ledger> QueryId<L> {
     }
 }
 
-#[derive(Fail, Debug, PartialEq, Clone)]
+#[derive(Fail, Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Error {
     #[fail(display = "The request failed to send.")]
     FailedRequest(String),
@@ -23,14 +23,25 @@ extern crate maplit;
 extern crate frunk;
 #[macro_use]
 extern crate binary_macros;
+#[macro_use]
+extern crate structopt;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate prometheus;
 
 pub mod bam_api;
+pub mod cli;
 pub mod comit_client;
 pub mod comit_server;
+pub mod fee_estimation;
 pub mod http_api;
 pub mod item_cache;
 pub mod ledger_query_service;
 pub mod logging;
+pub mod metrics;
 pub mod seed;
 pub mod settings;
 pub mod swap_protocols;
+pub mod wallet;
+pub mod webhooks;
@@ -1,27 +1,111 @@
+use crate::swap_protocols::SwapId;
 use fern::{
     colors::{Color, ColoredLevelConfig},
     Dispatch, FormatCallback,
 };
+use futures::{Future, Poll};
 use log::{LevelFilter, Record};
-use std::{cell::RefCell, fmt::Arguments, io::stdout};
+use serde_json::{Map, Value};
+use std::{cell::RefCell, cmp, fmt::Arguments, io::stdout, sync::Arc};
 
-#[allow(dead_code)]
-thread_local!(static LOG_CONTEXT: RefCell<Option<String>> = RefCell::new(None) );
+thread_local!(static ENTERED_SPANS: RefCell<Vec<Span>> = RefCell::new(Vec::new()));
 
-pub fn set_context<S: ToString>(input: &S) {
-    LOG_CONTEXT.with(|context| {
-        *context.borrow_mut() = Some(input.to_string());
-    });
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Colored lines for humans
+    Text,
+    /// One JSON object per line for log shippers
+    Json,
 }
 
-pub fn set_up_logging() {
+/// Fields which are added to every log line while the span is entered.
+///
+/// Spans are entered for the duration of a closure or, through `Instrument`,
+/// for every poll of a future. Unlike a context which is set once per thread
+/// this is correct on a multi-threaded runtime, where the futures of many
+/// swaps are polled on the same threads.
+#[derive(Clone, Debug, Default)]
+pub struct Span {
+    fields: Arc<Vec<(&'static str, String)>>,
+}
+
+impl Span {
+    pub fn with_field<V: ToString>(mut self, name: &'static str, value: V) -> Self {
+        Arc::make_mut(&mut self.fields).push((name, value.to_string()));
+        self
+    }
+
+    pub fn enter<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        ENTERED_SPANS.with(|spans| spans.borrow_mut().push(self.clone()));
+        let _exit = Exit;
+
+        f()
+    }
+}
+
+/// Leaves the innermost span, even if the closure panics
+struct Exit;
+
+impl Drop for Exit {
+    fn drop(&mut self) {
+        ENTERED_SPANS.with(|spans| spans.borrow_mut().pop());
+    }
+}
+
+pub fn swap_span(id: SwapId) -> Span {
+    Span::default().with_field("swap_id", id)
+}
+
+pub trait Instrument: Sized {
+    /// Enters the span whenever the future is polled
+    fn instrument(self, span: Span) -> Instrumented<Self> {
+        Instrumented { inner: self, span }
+    }
+}
+
+impl<F: Future> Instrument for F {}
+
+#[derive(Debug)]
+pub struct Instrumented<F> {
+    inner: F,
+    span: Span,
+}
+
+impl<F: Future> Future for Instrumented<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let inner = &mut self.inner;
+
+        self.span.enter(|| inner.poll())
+    }
+}
+
+fn entered_fields() -> Vec<(&'static str, String)> {
+    ENTERED_SPANS.with(|spans| {
+        spans
+            .borrow()
+            .iter()
+            .flat_map(|span| span.fields.iter().cloned())
+            .collect()
+    })
+}
+
+pub fn set_up_logging(level: LevelFilter, format: Format) {
     Dispatch::new()
-        .format(move |out, message, record| formatter(out, message, record))
-        // TODO: get level from config file once implemented with #136
+        .format(move |out, message, record| match format {
+            Format::Text => text_formatter(out, message, record),
+            Format::Json => json_formatter(out, message, record),
+        })
         .level(LevelFilter::Debug)
-        .level_for("comit_node", LevelFilter::Trace)
-        .level_for("bam", LevelFilter::Trace)
-        .level_for("comit_node::ledger_query_service", LevelFilter::Info)
+        .level_for("comit_node", level)
+        .level_for("bam", level)
+        .level_for(
+            "comit_node::ledger_query_service",
+            cmp::min(level, LevelFilter::Info),
+        )
         .level_for("tokio_core::reactor", LevelFilter::Info)
         .level_for("tokio_reactor", LevelFilter::Info)
         .level_for("hyper", LevelFilter::Info)
@@ -32,28 +116,99 @@ pub fn set_up_logging() {
         .unwrap();
 }
 
-fn formatter(out: FormatCallback<'_>, message: &Arguments<'_>, record: &Record<'_>) {
+fn text_formatter(out: FormatCallback<'_>, message: &Arguments<'_>, record: &Record<'_>) {
     // configure colors for the whole line
     let colors_line = ColoredLevelConfig::default()
         .info(Color::Green)
         .debug(Color::Blue)
         .trace(Color::Cyan);
 
-    LOG_CONTEXT.with(|context| {
-        let context = {
-            match *context.borrow() {
-                Some(ref context) => format!("[{}] ", context),
-                None => "".to_string(),
-            }
-        };
-
-        out.finish(format_args!(
-            "[{date}][{target}][{level}] {context}{message}",
-            date = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
-            target = record.target(),
-            level = colors_line.color(record.level()),
-            context = context,
-            message = message,
-        ))
-    });
+    let fields = entered_fields();
+    let context = if fields.is_empty() {
+        String::new()
+    } else {
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        format!("[{}] ", fields.join(" "))
+    };
+
+    out.finish(format_args!(
+        "[{date}][{target}][{level}] {context}{message}",
+        date = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+        target = record.target(),
+        level = colors_line.color(record.level()),
+        context = context,
+        message = message,
+    ))
+}
+
+fn json_formatter(out: FormatCallback<'_>, message: &Arguments<'_>, record: &Record<'_>) {
+    out.finish(format_args!(
+        "{}",
+        json_line(record, &message.to_string(), entered_fields())
+    ))
+}
+
+fn json_line(record: &Record<'_>, message: &str, fields: Vec<(&'static str, String)>) -> Value {
+    let mut line = Map::new();
+    line.insert("timestamp".into(), chrono::Utc::now().to_rfc3339().into());
+    line.insert("level".into(), record.level().to_string().into());
+    line.insert("target".into(), record.target().into());
+    line.insert("message".into(), message.into());
+    for (name, value) in fields {
+        line.insert(name.into(), value.into());
+    }
+
+    Value::Object(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+    use log::Level;
+    use spectral::prelude::*;
+
+    #[test]
+    fn fields_are_only_present_while_span_is_entered() {
+        let span = Span::default().with_field("swap_id", "foo");
+
+        let fields = span.enter(entered_fields);
+
+        assert_that(&fields).is_equal_to(vec![("swap_id", String::from("foo"))]);
+        assert_that(&entered_fields()).is_empty();
+    }
+
+    #[test]
+    fn instrumented_future_enters_span_when_polled() {
+        let span = Span::default().with_field("swap_id", "foo");
+
+        let fields = future::lazy(|| Ok::<_, ()>(entered_fields()))
+            .instrument(span)
+            .wait();
+
+        assert_that(&fields).is_ok_containing(vec![("swap_id", String::from("foo"))]);
+        assert_that(&entered_fields()).is_empty();
+    }
+
+    #[test]
+    fn json_line_carries_fields_of_entered_spans() {
+        let record = Record::builder()
+            .level(Level::Info)
+            .target("comit_node::test")
+            .build();
+
+        let line = json_line(
+            &record,
+            "Swap accepted",
+            vec![("swap_id", String::from("foo"))],
+        );
+
+        assert_that(&line["level"]).is_equal_to(json!("INFO"));
+        assert_that(&line["target"]).is_equal_to(json!("comit_node::test"));
+        assert_that(&line["message"]).is_equal_to(json!("Swap accepted"));
+        assert_that(&line["swap_id"]).is_equal_to(json!("foo"));
+    }
 }
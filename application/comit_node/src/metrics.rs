@@ -0,0 +1,169 @@
+use crate::swap_protocols::metadata_store::Metadata;
+use futures::Future;
+use hyper::{header, Body, Response};
+use prometheus::{Encoder, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder};
+use std::time::Duration;
+
+lazy_static! {
+    pub static ref SWAPS: IntGaugeVec = register_int_gauge_vec!(
+        "comit_node_swaps",
+        "Number of swaps per state, role and asset pair",
+        &["state", "role", "alpha_asset", "beta_asset"]
+    )
+    .unwrap();
+    pub static ref SWAP_STATE_DURATION: HistogramVec = register_histogram_vec!(
+        "comit_node_swap_state_duration_seconds",
+        "Time swaps spent in a state before transitioning to the next one",
+        &["state"],
+        // From a second up to three days, a swap waits for blocks in most states
+        prometheus::exponential_buckets(1.0, 4.0, 10).unwrap()
+    )
+    .unwrap();
+    pub static ref SWAP_OUTCOMES: IntCounterVec = register_int_counter_vec!(
+        "comit_node_swap_outcomes_total",
+        "Number of swaps per final outcome and role",
+        &["outcome", "role"]
+    )
+    .unwrap();
+    pub static ref LQS_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "comit_node_ledger_query_service_request_duration_seconds",
+        "Latency of requests to the ledger query service",
+        &["operation"]
+    )
+    .unwrap();
+    pub static ref LQS_REQUEST_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "comit_node_ledger_query_service_request_errors_total",
+        "Number of failed requests to the ledger query service",
+        &["operation"]
+    )
+    .unwrap();
+    pub static ref PERSISTENCE_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "comit_node_persistence_errors_total",
+        "Number of swap states and transitions which could not be stored",
+        &["operation"]
+    )
+    .unwrap();
+    pub static ref BAM_CONNECTIONS: IntGaugeVec = register_int_gauge_vec!(
+        "comit_node_bam_connections",
+        "Number of open BAM connections to other COMIT nodes",
+        &["direction"]
+    )
+    .unwrap();
+}
+
+/// Connections other nodes opened to our COMIT server
+pub const INBOUND: &str = "inbound";
+/// Connections we opened to other nodes
+pub const OUTBOUND: &str = "outbound";
+
+pub fn bam_connections(direction: &str) -> IntGauge {
+    BAM_CONNECTIONS.with_label_values(&[direction])
+}
+
+fn swaps_in_state(metadata: &Metadata, state: &str) -> IntGauge {
+    let role = metadata.role.to_string();
+
+    SWAPS.with_label_values(&[
+        state,
+        &role,
+        metadata.alpha_asset.name(),
+        metadata.beta_asset.name(),
+    ])
+}
+
+pub fn swap_entered_state(metadata: &Metadata, state: &str) {
+    swaps_in_state(metadata, state).inc();
+}
+
+pub fn swap_left_state(metadata: &Metadata, state: &str, time_in_state: Duration) {
+    swaps_in_state(metadata, state).dec();
+
+    let seconds = time_in_state.as_secs() as f64 + f64::from(time_in_state.subsec_nanos()) / 1e9;
+    SWAP_STATE_DURATION
+        .with_label_values(&[state])
+        .observe(seconds);
+}
+
+pub fn swap_finished(metadata: &Metadata, outcome: &str) {
+    let role = metadata.role.to_string();
+
+    SWAP_OUTCOMES.with_label_values(&[outcome, &role]).inc();
+}
+
+/// A swap state was not saved to the state store
+pub const SAVE_STATE: &str = "save_state";
+/// A state transition was not recorded in the history of a swap
+pub const RECORD_TRANSITION: &str = "record_transition";
+
+pub fn persistence_failed(operation: &str) {
+    PERSISTENCE_ERRORS.with_label_values(&[operation]).inc();
+}
+
+/// Records how long the request took and whether it failed
+pub fn observe_lqs_request<F: Future>(
+    operation: &'static str,
+    request: F,
+) -> impl Future<Item = F::Item, Error = F::Error> {
+    let timer = LQS_REQUEST_DURATION
+        .with_label_values(&[operation])
+        .start_timer();
+
+    request.then(move |result| {
+        timer.observe_duration();
+        if result.is_err() {
+            LQS_REQUEST_ERRORS.with_label_values(&[operation]).inc();
+        }
+        result
+    })
+}
+
+/// Renders all metrics in the Prometheus text format
+pub fn render() -> Response<Body> {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    encoder
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("metrics should always encode");
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, encoder.format_type())
+        .body(Body::from(buffer))
+        .expect("response with valid headers should always build")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::metadata_store::{AssetKind, LedgerKind, RoleKind};
+    use futures::future;
+    use spectral::prelude::*;
+
+    #[test]
+    fn swap_is_only_counted_in_its_current_state() {
+        let metadata = Metadata::new(
+            LedgerKind::Bitcoin,
+            LedgerKind::Ethereum,
+            AssetKind::Bitcoin,
+            AssetKind::Erc20,
+            RoleKind::Bob,
+            "127.0.0.1:8011".parse().unwrap(),
+            None,
+        );
+
+        swap_entered_state(&metadata, "TestStart");
+        swap_left_state(&metadata, "TestStart", Duration::from_secs(5));
+        swap_entered_state(&metadata, "TestAccepted");
+
+        assert_that(&swaps_in_state(&metadata, "TestStart").get()).is_equal_to(0);
+        assert_that(&swaps_in_state(&metadata, "TestAccepted").get()).is_equal_to(1);
+    }
+
+    #[test]
+    fn failed_lqs_request_is_counted_as_error() {
+        let errors = LQS_REQUEST_ERRORS.with_label_values(&["test"]);
+
+        let _ = observe_lqs_request("test", future::err::<(), ()>(())).wait();
+
+        assert_that(&errors.get()).is_equal_to(1);
+    }
+}
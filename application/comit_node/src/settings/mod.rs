@@ -1,29 +1,93 @@
 mod serde;
 
-use crate::seed::Seed;
+use crate::{
+    fee_estimation::bitcoin::{FeeTarget, StaticFees},
+    http_api::auth,
+    logging,
+    seed::Seed,
+    swap_protocols::rfc003::{
+        bob::policy::{Rule, StaticRate, Token},
+        ethereum::Seconds,
+    },
+};
 use ::serde::Deserialize;
+use bitcoin_support::Blocks;
 use config::{Config, ConfigError, File};
+use log::LevelFilter;
 use std::{
     ffi::OsStr,
     net::{IpAddr, SocketAddr},
-    path::Path,
+    path::{Path, PathBuf},
     time::Duration,
 };
 
 #[derive(Debug, Deserialize)]
 pub struct ComitNodeSettings {
     pub comit: Comit,
+    #[serde(default)]
+    pub bitcoin: Bitcoin,
+    pub ethereum: Ethereum,
     pub http_api: HttpApi,
     pub ledger_query_service: LedgerQueryService,
+    #[serde(default)]
+    pub state_store: StateStore,
+    #[serde(default)]
+    pub bob_policy: Option<BobPolicy>,
+    #[serde(default)]
+    pub bitcoin_wallet: Option<BitcoinWallet>,
+    #[serde(default)]
+    pub ethereum_wallet: Option<EthereumWallet>,
+    #[serde(default)]
+    pub bitcoin_fees: BitcoinFees,
+    #[serde(default)]
+    pub ethereum_gas: EthereumGas,
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+    #[serde(default)]
+    pub logging: Logging,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Comit {
-    #[serde(with = "serde::socket_addr")]
-    pub remote_comit_node_url: SocketAddr,
+    #[serde(default, with = "serde::socket_addr::option")]
+    pub remote_comit_node_url: Option<SocketAddr>,
     #[serde(with = "serde::socket_addr")]
     pub comit_listen: SocketAddr,
     pub secret_seed: Seed,
+    /// The minimum time between the expiry of the beta and the alpha HTLC
+    #[serde(
+        default = "default_lock_duration_safety_margin",
+        with = "serde::duration"
+    )]
+    pub lock_duration_safety_margin_secs: Duration,
+}
+
+fn default_lock_duration_safety_margin() -> Duration {
+    Duration::from_secs(60 * 60)
+}
+
+/// Swaps on any other Bitcoin network are rejected
+#[derive(Debug, Deserialize)]
+pub struct Bitcoin {
+    pub network: bitcoin_support::Network,
+}
+
+/// Nodes configured before the network became a setting only swapped on
+/// regtest
+impl Default for Bitcoin {
+    fn default() -> Self {
+        Bitcoin {
+            network: bitcoin_support::Network::Regtest,
+        }
+    }
+}
+
+/// Swaps on any other Ethereum chain are rejected
+#[derive(Debug, Deserialize)]
+pub struct Ethereum {
+    /// The EIP-155 chain id, also used by the Ethereum wallet to sign
+    /// transactions
+    pub chain_id: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,6 +95,59 @@ pub struct HttpApi {
     pub address: IpAddr,
     pub port: u16,
     pub logging: bool,
+    /// Without any, every request is rejected unless
+    /// `allow_unauthenticated` is set
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+    /// Opens the HTTP API to everyone who can reach it if no API keys are
+    /// configured
+    #[serde(default)]
+    pub allow_unauthenticated: bool,
+    /// Serves `/metrics` without an API key, e.g. for a Prometheus scraper
+    /// which cannot send one
+    #[serde(default)]
+    pub public_metrics: bool,
+    /// If omitted, the HTTP API is served over plain HTTP
+    #[serde(default)]
+    pub tls: Option<Tls>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Tls {
+    /// PEM encoded certificate chain, starting with the server's certificate
+    pub cert_path: PathBuf,
+    /// PEM encoded private key of the server's certificate
+    pub key_path: PathBuf,
+    /// If given, clients have to present a certificate signed by this CA
+    #[serde(default)]
+    pub client_ca_path: Option<PathBuf>,
+}
+
+#[derive(DebugStub, Deserialize, PartialEq)]
+pub struct ApiKey {
+    /// Sent as bearer token in the `Authorization` header or in the
+    /// `X-API-Key` header
+    #[debug_stub = "ApiKey"]
+    pub key: String,
+    pub scope: auth::Scope,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Logging {
+    /// The log level of the node itself, the `--log-level` option takes
+    /// precedence
+    pub level: LevelFilter,
+    pub format: logging::Format,
+}
+
+impl Default for Logging {
+    fn default() -> Self {
+        Logging {
+            level: LevelFilter::Trace,
+            format: logging::Format::Text,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -39,6 +156,25 @@ pub struct LedgerQueryService {
     pub url: url::Url,
     pub bitcoin: PollParameters,
     pub ethereum: PollParameters,
+    /// Only used if `url` is an `https` URL
+    #[serde(default)]
+    pub tls: Option<LedgerQueryServiceTls>,
+}
+
+#[derive(DebugStub, Deserialize, PartialEq)]
+pub struct LedgerQueryServiceTls {
+    /// PEM encoded CA certificate which is trusted to have signed the
+    /// ledger query service's certificate. It is trusted in addition to the
+    /// system's root certificates, not instead of them, so this does not pin
+    /// the ledger query service to certificates of this CA.
+    pub ca_path: PathBuf,
+    /// PKCS #12 archive with the client certificate and key, required if the
+    /// ledger query service verifies client certificates
+    #[serde(default)]
+    pub client_identity_path: Option<PathBuf>,
+    #[serde(default)]
+    #[debug_stub = "Password"]
+    pub client_identity_password: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +183,145 @@ pub struct PollParameters {
     pub poll_interval_secs: Duration,
 }
 
+/// A bitcoind wallet which executes Bitcoin actions on behalf of the user
+#[derive(DebugStub, Deserialize)]
+pub struct BitcoinWallet {
+    #[serde(with = "serde::url")]
+    pub node_url: url::Url,
+    pub node_username: String,
+    #[debug_stub = "Password"]
+    pub node_password: String,
+    /// Within how many blocks the redeem and refund transactions the node
+    /// executes should confirm, the fee rate for it comes from `bitcoin_fees`
+    #[serde(default, with = "serde::fee_target")]
+    pub fee_target: FeeTarget,
+}
+
+/// Where the fee rates of Bitcoin redeem and refund transactions come from,
+/// if the user does not give one
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BitcoinFees {
+    Static(StaticFees),
+    /// Asks the bitcoind node of the `bitcoin_wallet` through
+    /// `estimatesmartfee`, the static fees are used if it cannot estimate
+    Bitcoind {
+        #[serde(default)]
+        fallback: StaticFees,
+    },
+}
+
+impl Default for BitcoinFees {
+    fn default() -> Self {
+        BitcoinFees::Static(StaticFees::default())
+    }
+}
+
+/// Where the gas limits and prices of Ethereum actions come from
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EthereumGas {
+    /// Keeps the gas limits of the actions
+    Static {
+        #[serde(default = "default_gas_price_gwei")]
+        gas_price_gwei: u64,
+    },
+    /// Asks the node through `eth_estimateGas` and `eth_gasPrice`, the static
+    /// gas price is used if it cannot answer
+    Node {
+        #[serde(with = "serde::url")]
+        node_url: url::Url,
+        #[serde(default = "default_gas_price_gwei")]
+        fallback_gas_price_gwei: u64,
+    },
+}
+
+impl Default for EthereumGas {
+    fn default() -> Self {
+        EthereumGas::Static {
+            gas_price_gwei: default_gas_price_gwei(),
+        }
+    }
+}
+
+fn default_gas_price_gwei() -> u64 {
+    10
+}
+
+/// A key derived from the seed which signs the transactions of Ethereum
+/// actions on behalf of the user
+#[derive(Debug, Deserialize)]
+pub struct EthereumWallet {
+    #[serde(with = "serde::url")]
+    pub node_url: url::Url,
+    /// If omitted, the gas price suggested by the node is used
+    #[serde(default)]
+    pub gas_price_gwei: Option<u64>,
+}
+
+/// Notified when a swap is requested, accepted, rejected, funded, redeemed,
+/// refunded or failed
+#[derive(DebugStub, Deserialize, PartialEq)]
+pub struct Webhook {
+    #[serde(with = "serde::url")]
+    pub url: url::Url,
+    /// Keys the HMAC-SHA256 signature of every notification
+    #[debug_stub = "Secret"]
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StateStore {
+    InMemory,
+    Sqlite { path: PathBuf },
+}
+
+/// Lets the node answer incoming swap requests on its own
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct BobPolicy {
+    /// Used as the Ethereum refund and/or redeem identity when accepting a swap
+    pub ethereum_identity: ethereum_support::Address,
+    /// The lock duration offered if Bitcoin is the beta ledger
+    pub bitcoin_lock_duration: Blocks,
+    /// The lock duration offered if Ethereum is the beta ledger
+    pub ethereum_lock_duration: Seconds,
+    #[serde(default)]
+    pub max_concurrent_swaps: Option<usize>,
+    pub rate_source: RateSource,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    /// The decimals of the ERC20 tokens in the rules, requests for other
+    /// tokens are declined
+    #[serde(default)]
+    pub tokens: Vec<Token>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RateSource {
+    Static {
+        rates: Vec<StaticRate>,
+    },
+    Http {
+        #[serde(with = "serde::url")]
+        url: url::Url,
+        /// After which the decision is left to the user
+        #[serde(default = "default_rate_source_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+fn default_rate_source_timeout_secs() -> u64 {
+    5
+}
+
+impl Default for StateStore {
+    fn default() -> Self {
+        StateStore::InMemory
+    }
+}
+
 impl ComitNodeSettings {
     pub fn create<D: AsRef<OsStr>, R: AsRef<OsStr>>(
         default_config: D,
@@ -81,6 +356,7 @@ impl ComitNodeSettings {
 mod tests {
 
     use super::*;
+    use config::FileFormat;
     use spectral::prelude::*;
 
     fn comit_settings() -> Result<ComitNodeSettings, ConfigError> {
@@ -109,4 +385,327 @@ mod tests {
         .is_equal_to(&Duration::from_secs(20));
     }
 
+    #[test]
+    fn state_store_defaults_to_in_memory() {
+        let settings = comit_settings();
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap().state_store).is_equal_to(&StateStore::InMemory);
+    }
+
+    #[test]
+    fn webhooks_default_to_none() {
+        let settings = comit_settings();
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap().webhooks).is_empty();
+    }
+
+    #[test]
+    fn can_read_api_keys() {
+        let mut config = Config::new();
+        config
+            .merge(File::from_str(
+                r#"
+                address = "0.0.0.0"
+                port = 8000
+                logging = true
+                [[api_keys]]
+                key = "foo"
+                scope = "read_only"
+                [[api_keys]]
+                key = "bar"
+                scope = "trading"
+                "#,
+                FileFormat::Toml,
+            ))
+            .unwrap();
+
+        let settings = config.try_into::<HttpApi>();
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap().api_keys).is_equal_to(vec![
+            ApiKey {
+                key: String::from("foo"),
+                scope: auth::Scope::ReadOnly,
+            },
+            ApiKey {
+                key: String::from("bar"),
+                scope: auth::Scope::Trading,
+            },
+        ]);
+    }
+
+    #[test]
+    fn bitcoin_wallet_takes_named_and_block_fee_targets() {
+        let wallet = |fee_target: &str| {
+            let mut config = Config::new();
+            config
+                .merge(File::from_str(
+                    &format!(
+                        r#"
+                        node_url = "http://localhost:18443"
+                        node_username = "bitcoin"
+                        node_password = "secret"
+                        {}
+                        "#,
+                        fee_target
+                    ),
+                    FileFormat::Toml,
+                ))
+                .unwrap();
+            config
+                .try_into::<BitcoinWallet>()
+                .map(|wallet| wallet.fee_target)
+        };
+
+        assert_that(&wallet("")).is_ok_containing(FeeTarget::Normal);
+        assert_that(&wallet(r#"fee_target = "fast""#)).is_ok_containing(FeeTarget::Fast);
+        assert_that(&wallet("fee_target = 3")).is_ok_containing(FeeTarget::Blocks(3));
+        assert_that(&wallet(r#"fee_target = "soon""#)).is_err();
+    }
+
+    #[test]
+    fn secrets_are_not_part_of_the_debug_output() {
+        let api_key = ApiKey {
+            key: String::from("foo"),
+            scope: auth::Scope::ReadOnly,
+        };
+        let webhook = Webhook {
+            url: "http://localhost:8000".parse().unwrap(),
+            secret: String::from("bar"),
+        };
+        let tls = LedgerQueryServiceTls {
+            ca_path: PathBuf::from("ca.pem"),
+            client_identity_path: None,
+            client_identity_password: String::from("baz"),
+        };
+        let wallet = BitcoinWallet {
+            node_url: "http://localhost:18443".parse().unwrap(),
+            node_username: String::from("bitcoin"),
+            node_password: String::from("qux"),
+            fee_target: FeeTarget::default(),
+        };
+
+        let debug_output = format!("{:?} {:?} {:?} {:?}", api_key, webhook, tls, wallet);
+
+        for secret in &["foo", "bar", "baz", "qux"] {
+            assert!(
+                !debug_output.contains(secret),
+                "{} is part of {}",
+                secret,
+                debug_output
+            );
+        }
+    }
+
+    #[test]
+    fn unauthenticated_access_and_public_metrics_are_off_by_default() {
+        let mut config = Config::new();
+        config
+            .merge(File::from_str(
+                r#"
+                address = "0.0.0.0"
+                port = 8000
+                logging = true
+                "#,
+                FileFormat::Toml,
+            ))
+            .unwrap();
+
+        let settings = config.try_into::<HttpApi>().unwrap();
+
+        assert_that(&settings.allow_unauthenticated).is_false();
+        assert_that(&settings.public_metrics).is_false();
+    }
+
+    #[test]
+    fn can_read_tls() {
+        let mut config = Config::new();
+        config
+            .merge(File::from_str(
+                r#"
+                address = "0.0.0.0"
+                port = 8000
+                logging = true
+                [tls]
+                cert_path = "/etc/comit_node/cert.pem"
+                key_path = "/etc/comit_node/key.pem"
+                "#,
+                FileFormat::Toml,
+            ))
+            .unwrap();
+
+        let settings = config.try_into::<HttpApi>();
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap().tls).contains_value(Tls {
+            cert_path: PathBuf::from("/etc/comit_node/cert.pem"),
+            key_path: PathBuf::from("/etc/comit_node/key.pem"),
+            client_ca_path: None,
+        });
+    }
+
+    #[test]
+    fn can_read_bitcoin_fees() {
+        let mut config = Config::new();
+        config
+            .merge(File::from_str(
+                r#"
+                type = "bitcoind"
+                [fallback]
+                economy = 1.0
+                "#,
+                FileFormat::Toml,
+            ))
+            .unwrap();
+
+        let settings = config.try_into::<BitcoinFees>();
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap()).is_equal_to(BitcoinFees::Bitcoind {
+            fallback: StaticFees {
+                economy: 1.0,
+                ..StaticFees::default()
+            },
+        });
+    }
+
+    #[test]
+    fn can_read_ethereum_gas() {
+        let mut config = Config::new();
+        config
+            .merge(File::from_str(
+                r#"
+                type = "node"
+                node_url = "http://localhost:8545"
+                "#,
+                FileFormat::Toml,
+            ))
+            .unwrap();
+
+        let settings = config.try_into::<EthereumGas>();
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap()).is_equal_to(EthereumGas::Node {
+            node_url: url::Url::parse("http://localhost:8545").unwrap(),
+            fallback_gas_price_gwei: 10,
+        });
+    }
+
+    #[test]
+    fn can_read_bitcoin_network() {
+        let settings = comit_settings();
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap().bitcoin.network)
+            .is_equal_to(&bitcoin_support::Network::Regtest);
+    }
+
+    #[test]
+    fn bitcoin_network_defaults_to_regtest() {
+        let mut config = Config::new();
+        config
+            .merge(File::from_str(
+                r#"
+                [comit]
+                comit_listen = "0.0.0.0:8011"
+                secret_seed = "c1fd6fc5bde7fee2c2fb6d868dc0f40368051fede1d83f814839d562c210aa27"
+
+                [ethereum]
+                chain_id = 17
+
+                [http_api]
+                address = "0.0.0.0"
+                port = 8000
+                logging = true
+
+                [ledger_query_service]
+                url = "http://localhost:8001"
+                [ledger_query_service.bitcoin]
+                poll_interval_secs = 300
+                [ledger_query_service.ethereum]
+                poll_interval_secs = 20
+                "#,
+                FileFormat::Toml,
+            ))
+            .unwrap();
+
+        let settings = config.try_into::<ComitNodeSettings>();
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap().bitcoin.network)
+            .is_equal_to(&bitcoin_support::Network::Regtest);
+    }
+
+    #[test]
+    fn can_read_ethereum_chain_id() {
+        let settings = comit_settings();
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap().ethereum.chain_id).is_equal_to(&17);
+    }
+
+    #[test]
+    fn can_read_logging() {
+        let mut config = Config::new();
+        config
+            .merge(File::from_str(
+                r#"
+                level = "info"
+                format = "json"
+                "#,
+                FileFormat::Toml,
+            ))
+            .unwrap();
+
+        let settings = config.try_into::<Logging>();
+
+        assert_that(&settings).is_ok();
+        let settings = settings.unwrap();
+        assert_that(&settings.level).is_equal_to(LevelFilter::Info);
+        assert_that(&settings.format).is_equal_to(logging::Format::Json);
+    }
+
+    #[test]
+    fn can_read_bob_policy() {
+        let mut config = Config::new();
+        config
+            .merge(File::from_str(
+                r#"
+                ethereum_identity = "0x00a329c0648769a73afac7f9381e08fb43dbea72"
+                bitcoin_lock_duration = 72
+                ethereum_lock_duration = 43200
+                max_concurrent_swaps = 5
+
+                [rate_source]
+                type = "static"
+                [[rate_source.rates]]
+                alpha_asset = { name = "Bitcoin" }
+                beta_asset = { name = "Ether" }
+                min_rate = 0.025
+
+                [[rules]]
+                alpha_asset = { name = "Bitcoin" }
+                beta_asset = { name = "Ether" }
+                max_alpha_quantity = 1.5
+
+                [[tokens]]
+                token_contract = "0xb97048628db6b661d4c2aa833e95dbe1a905b280"
+                decimals = 6
+                "#,
+                FileFormat::Toml,
+            ))
+            .unwrap();
+
+        let policy = config.try_into::<BobPolicy>();
+
+        assert_that(&policy).is_ok();
+        let policy = policy.unwrap();
+        assert_that(&policy.bitcoin_lock_duration).is_equal_to(Blocks::new(72));
+        assert_that(&policy.rules).has_length(1);
+        assert_that(&policy.rules[0].max_alpha_quantity).contains_value(1.5);
+        assert_that(&policy.tokens).has_length(1);
+        assert_that(&policy.tokens[0].decimals).is_equal_to(6);
+    }
 }
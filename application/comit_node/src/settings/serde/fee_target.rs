@@ -0,0 +1,42 @@
+use crate::fee_estimation::bitcoin::FeeTarget;
+use serde::{de, export::fmt, Deserializer};
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<FeeTarget, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = FeeTarget;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("fast, normal, economy or a number of blocks")
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<FeeTarget, E>
+        where
+            E: de::Error,
+        {
+            value
+                .parse()
+                .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<FeeTarget, E>
+        where
+            E: de::Error,
+        {
+            self.visit_str(&value.to_string())
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<FeeTarget, E>
+        where
+            E: de::Error,
+        {
+            self.visit_str(&value.to_string())
+        }
+    }
+
+    deserializer.deserialize_any(Visitor)
+}
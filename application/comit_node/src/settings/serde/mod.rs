@@ -1,3 +1,4 @@
 pub mod duration;
+pub mod fee_target;
 pub mod socket_addr;
 pub mod url;
@@ -24,3 +24,15 @@ where
 
     deserializer.deserialize_str(Visitor)
 }
+
+pub mod option {
+    use serde::Deserializer;
+    use std::net::SocketAddr;
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<SocketAddr>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize(deserializer).map(Some)
+    }
+}
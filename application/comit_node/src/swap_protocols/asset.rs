@@ -4,6 +4,7 @@ use crate::{
 };
 use bitcoin_support::BitcoinQuantity;
 use ethereum_support::{Erc20Quantity, EtherQuantity};
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
     fmt::{Debug, Display},
     hash::Hash,
@@ -19,6 +20,8 @@ pub trait Asset:
     + PartialEq
     + Eq
     + Hash
+    + Serialize
+    + DeserializeOwned
     + FromHttpAsset
     + ToHttpAsset
     + FromBamHeader
@@ -1,6 +1,17 @@
 use crate::{
-    comit_client::ClientFactory, ledger_query_service::DefaultLedgerQueryServiceApiClient,
+    comit_client::ClientFactory,
+    ledger_query_service::DefaultLedgerQueryServiceApiClient,
     seed::Seed,
+    swap_protocols::{
+        ledger::Networks,
+        rfc003::{
+            bob::{policy::Policy, Aborts},
+            NotifyWebhooks, PublishStateChanges, RecordMetrics, RecordTransitions, Role, SaveState,
+            TouchMetadata,
+        },
+        MetadataStore, StateChanges, SwapId, TransitionStore,
+    },
+    webhooks::Webhooks,
 };
 use std::{net::SocketAddr, sync::Arc, time::Duration};
 
@@ -13,7 +24,61 @@ pub struct ProtocolDependencies<T, S, C> {
     pub state_store: Arc<S>,
     pub comit_client_factory: Arc<dyn ClientFactory<C>>,
     pub seed: Seed,
-    pub remote_comit_node: SocketAddr,
+    pub remote_comit_node: Option<SocketAddr>,
+    /// Without a policy every swap request has to be answered manually
+    pub bob_policy: Option<Policy>,
+    /// The minimum time between the expiry of the beta and the alpha HTLC
+    pub lock_duration_margin: Duration,
+    pub networks: Networks,
+    pub state_changes: Arc<StateChanges>,
+    pub webhooks: Arc<Webhooks>,
+    /// Aborts of swap requests Bob accepted
+    pub aborts: Arc<Aborts>,
+}
+
+impl<T: MetadataStore<SwapId> + TransitionStore<SwapId>, S, C> ProtocolDependencies<T, S, C> {
+    /// Wraps the `SaveState` of a swap to keep its metadata, history and the
+    /// metrics up to date and tell everyone interested about its state
+    /// transitions
+    pub fn observe_save_state<R: Role>(
+        &self,
+        save_state: Arc<dyn SaveState<R>>,
+        id: SwapId,
+    ) -> Arc<dyn SaveState<R>> {
+        let save_state: Arc<dyn SaveState<R>> = match self.metadata_store.get(&id) {
+            Ok(Some(metadata)) => Arc::new(RecordMetrics::new(save_state, metadata)),
+            result => {
+                error!(
+                    "Not recording metrics of swap {} without metadata: {:?}",
+                    id, result
+                );
+                save_state
+            }
+        };
+        let save_state = Arc::new(TouchMetadata::new(
+            save_state,
+            Arc::clone(&self.metadata_store),
+            id,
+        ));
+        let save_state = Arc::new(NotifyWebhooks::new(
+            save_state,
+            Arc::clone(&self.webhooks),
+            id,
+        ));
+
+        let save_state = Arc::new(PublishStateChanges::new(
+            save_state,
+            Arc::clone(&self.state_changes),
+            id,
+        ));
+
+        // Outermost, as the other wrappers only forward the saved state
+        Arc::new(RecordTransitions::new(
+            save_state,
+            Arc::clone(&self.metadata_store),
+            id,
+        ))
+    }
 }
 
 #[derive(Debug)]
@@ -22,3 +87,41 @@ pub struct LedgerEventDependencies {
     pub lqs_bitcoin_poll_interval: Duration,
     pub lqs_ethereum_poll_interval: Duration,
 }
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::comit_client::fake::{FakeClient, FakeClientFactory};
+    use bitcoin_support::Network;
+
+    /// Dependencies of a node on regtest and Ethereum chain 17 which answers
+    /// swap requests manually and does not reach any ledger
+    pub fn dependencies<T, S>(
+        metadata_store: Arc<T>,
+        state_store: Arc<S>,
+    ) -> ProtocolDependencies<T, S, FakeClient> {
+        ProtocolDependencies {
+            ledger_events: LedgerEventDependencies {
+                lqs_client: Arc::new(DefaultLedgerQueryServiceApiClient::new(
+                    &"http://localhost:8080".parse().unwrap(),
+                )),
+                lqs_bitcoin_poll_interval: Duration::from_secs(1),
+                lqs_ethereum_poll_interval: Duration::from_secs(1),
+            },
+            metadata_store,
+            state_store,
+            comit_client_factory: Arc::new(FakeClientFactory::default()),
+            seed: Seed::from(*b"hello world, you are beautiful!!"),
+            remote_comit_node: None,
+            bob_policy: None,
+            lock_duration_margin: Duration::from_secs(3600),
+            networks: Networks {
+                bitcoin: Network::Regtest,
+                ethereum_chain_id: 17,
+            },
+            state_changes: Arc::new(StateChanges::default()),
+            webhooks: Arc::new(Webhooks::new(Vec::new())),
+            aborts: Arc::new(Aborts::default()),
+        }
+    }
+}
@@ -1,10 +1,10 @@
-use crate::swap_protocols::ledger::Ledger;
+use crate::swap_protocols::ledger::{Ledger, Networks};
 use bitcoin_support::{
     Address, BitcoinQuantity, IntoP2wpkhAddress, Network, PubkeyHash, Transaction, TransactionId,
 };
 use secp256k1_support::PublicKey;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Bitcoin {
     pub network: Network,
 }
@@ -21,15 +21,6 @@ impl Bitcoin {
     }
 }
 
-// TODO: fix with #376
-impl Default for Bitcoin {
-    fn default() -> Self {
-        Bitcoin {
-            network: Network::Regtest,
-        }
-    }
-}
-
 impl Ledger for Bitcoin {
     type Quantity = BitcoinQuantity;
     type TxId = TransactionId;
@@ -41,4 +32,12 @@ impl Ledger for Bitcoin {
     fn address_for_identity(&self, pubkeyhash: PubkeyHash) -> Address {
         pubkeyhash.into_p2wpkh_address(self.network)
     }
+
+    fn transaction_id(transaction: &Transaction) -> String {
+        transaction.txid().to_string()
+    }
+
+    fn is_on(&self, networks: &Networks) -> bool {
+        self.network == networks.bitcoin
+    }
 }
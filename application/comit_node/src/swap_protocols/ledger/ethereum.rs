@@ -1,9 +1,23 @@
-use crate::swap_protocols::ledger::Ledger;
+use crate::swap_protocols::ledger::{Ledger, Networks};
 use ethereum_support::{Address, EtherQuantity, Transaction, H256};
 use secp256k1_support::PublicKey;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
-pub struct Ethereum {}
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Ethereum {
+    /// The EIP-155 chain id, e.g. 1 for mainnet or 3 for Ropsten
+    pub chain_id: u64,
+}
+
+impl Ethereum {
+    pub fn new(chain_id: u64) -> Self {
+        Ethereum { chain_id }
+    }
+
+    /// The chain id of a Parity development chain
+    pub fn dev() -> Self {
+        Ethereum { chain_id: 17 }
+    }
+}
 
 impl Ledger for Ethereum {
     type Quantity = EtherQuantity;
@@ -16,4 +30,12 @@ impl Ledger for Ethereum {
     fn address_for_identity(&self, address: Address) -> Address {
         address
     }
+
+    fn transaction_id(transaction: &Transaction) -> String {
+        format!("{:?}", transaction.hash)
+    }
+
+    fn is_on(&self, networks: &Networks) -> bool {
+        self.chain_id == networks.ethereum_chain_id
+    }
 }
@@ -1,3 +1,4 @@
+use bitcoin_support::Network;
 use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Debug;
 
@@ -8,6 +9,7 @@ pub use self::{bitcoin::Bitcoin, ethereum::Ethereum};
 use crate::{
     bam_api::header::{FromBamHeader, ToBamHeader},
     http_api::ledger::{FromHttpLedger, ToHttpLedger},
+    swap_protocols::rfc003::state_store::Persist,
 };
 use std::hash::Hash;
 
@@ -17,10 +19,11 @@ pub trait Ledger:
     + Send
     + Sync
     + 'static
-    + Default
     + PartialEq
     + Eq
     + Hash
+    + Serialize
+    + DeserializeOwned
     + FromHttpLedger
     + ToHttpLedger
     + FromBamHeader
@@ -40,7 +43,8 @@ pub trait Ledger:
         + 'static
         + From<Self::Address>
         + Serialize
-        + DeserializeOwned;
+        + DeserializeOwned
+        + Persist;
     type Transaction: Debug
         + Clone
         + DeserializeOwned
@@ -51,4 +55,18 @@ pub trait Ledger:
         + 'static;
 
     fn address_for_identity(&self, identity: Self::Identity) -> Self::Address;
+
+    /// The id of the transaction as it is shown by block explorers
+    fn transaction_id(transaction: &Self::Transaction) -> String;
+
+    /// Whether the ledger is on one of the networks the node is configured for
+    fn is_on(&self, networks: &Networks) -> bool;
+}
+
+/// The networks the node is configured for. Swaps on any other network are
+/// rejected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Networks {
+    pub bitcoin: Network,
+    pub ethereum_chain_id: u64,
 }
@@ -0,0 +1,426 @@
+mod sqlite;
+
+pub use self::sqlite::SqliteMetadataStore;
+
+use crate::swap_protocols::SwapProtocols;
+use bitcoin_support::Network;
+use chrono::{DateTime, Utc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Debug, Display},
+    hash::Hash,
+    net::SocketAddr,
+    sync::Mutex,
+};
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Serialize, Deserialize)]
+pub enum RoleKind {
+    Alice,
+    Bob,
+}
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Serialize, Deserialize)]
+pub enum LedgerKind {
+    Bitcoin,
+    Ethereum,
+}
+
+impl LedgerKind {
+    /// The name of the ledger in the HTTP API and in BAM headers
+    pub fn name(self) -> &'static str {
+        match self {
+            LedgerKind::Bitcoin => "Bitcoin",
+            LedgerKind::Ethereum => "Ethereum",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Bitcoin" => Some(LedgerKind::Bitcoin),
+            "Ethereum" => Some(LedgerKind::Ethereum),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssetKind {
+    Bitcoin,
+    Ether,
+    Erc20,
+}
+
+impl AssetKind {
+    /// The name of the asset in the HTTP API and in BAM headers
+    pub fn name(self) -> &'static str {
+        match self {
+            AssetKind::Bitcoin => "Bitcoin",
+            AssetKind::Ether => "Ether",
+            AssetKind::Erc20 => "ERC20",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Bitcoin" => Some(AssetKind::Bitcoin),
+            "Ether" => Some(AssetKind::Ether),
+            "ERC20" => Some(AssetKind::Erc20),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Metadata {
+    pub alpha_ledger: LedgerKind,
+    pub beta_ledger: LedgerKind,
+    pub alpha_asset: AssetKind,
+    pub beta_asset: AssetKind,
+    pub role: RoleKind,
+    pub protocol: SwapProtocols,
+    pub counterparty: SocketAddr,
+    pub bitcoin_network: Option<Network>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Metadata {
+    pub fn new(
+        alpha_ledger: LedgerKind,
+        beta_ledger: LedgerKind,
+        alpha_asset: AssetKind,
+        beta_asset: AssetKind,
+        role: RoleKind,
+        counterparty: SocketAddr,
+        bitcoin_network: Option<Network>,
+    ) -> Self {
+        let now = Utc::now();
+
+        Metadata {
+            alpha_ledger,
+            beta_ledger,
+            alpha_asset,
+            beta_asset,
+            role,
+            protocol: SwapProtocols::Rfc003,
+            counterparty,
+            bitcoin_network,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// A transaction the node signed and broadcast for one of the actions of a
+/// swap
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExecutedTransaction {
+    pub action: String,
+    pub ledger: LedgerKind,
+    pub transaction_id: String,
+    pub executed_at: DateTime<Utc>,
+}
+
+impl ExecutedTransaction {
+    pub fn new<A: Display>(action: A, ledger: LedgerKind, transaction_id: String) -> Self {
+        ExecutedTransaction {
+            action: action.to_string(),
+            ledger,
+            transaction_id,
+            executed_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwapLedger {
+    Alpha,
+    Beta,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HtlcEventKind {
+    Deployed,
+    Funded,
+    Redeemed,
+    Refunded,
+}
+
+/// What happened to the HTLC on one of the ledgers of a swap
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HtlcEvent {
+    pub ledger: SwapLedger,
+    pub event: HtlcEventKind,
+    /// The ledger transaction behind the event, if it is known
+    pub transaction_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub htlc_location: Option<serde_json::Value>,
+    /// The secret revealed by redeeming the HTLC, hex encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+/// A state a swap transitioned to
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub state: String,
+    /// The event on a ledger which triggered the transition, if any
+    #[serde(flatten)]
+    pub htlc_event: Option<HtlcEvent>,
+    pub at: DateTime<Utc>,
+}
+
+impl Transition {
+    pub fn new(state: String, htlc_event: Option<HtlcEvent>) -> Self {
+        Transition {
+            state,
+            htlc_event,
+            at: Utc::now(),
+        }
+    }
+}
+
+/// Selects the swaps of a page of the swap list, ordered by their creation and
+/// then by their key
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapsQuery<K> {
+    pub role: Option<RoleKind>,
+    pub alpha_ledger: Option<String>,
+    pub beta_ledger: Option<String>,
+    pub alpha_asset: Option<String>,
+    pub beta_asset: Option<String>,
+    pub counterparty: Option<SocketAddr>,
+    /// The name of the state the swap is currently in
+    pub state: Option<String>,
+    pub newest_first: bool,
+    /// Only swaps which come after the given position in the order
+    pub after: Option<(DateTime<Utc>, K)>,
+    pub limit: usize,
+}
+
+impl<K: Display> SwapsQuery<K> {
+    fn matches(&self, key: &K, metadata: &Metadata, state: Option<&String>) -> bool {
+        fn matches<T: PartialEq>(expected: &Option<T>, actual: T) -> bool {
+            expected
+                .as_ref()
+                .map_or(true, |expected| *expected == actual)
+        }
+
+        let comes_after = self.after.as_ref().map_or(true, |(created_at, after)| {
+            let position = (metadata.created_at, key.to_string());
+            let after = (*created_at, after.to_string());
+            if self.newest_first {
+                position < after
+            } else {
+                position > after
+            }
+        });
+
+        comes_after
+            && matches(&self.role, metadata.role)
+            && matches(
+                &self.alpha_ledger.as_ref().map(String::as_str),
+                metadata.alpha_ledger.name(),
+            )
+            && matches(
+                &self.beta_ledger.as_ref().map(String::as_str),
+                metadata.beta_ledger.name(),
+            )
+            && matches(
+                &self.alpha_asset.as_ref().map(String::as_str),
+                metadata.alpha_asset.name(),
+            )
+            && matches(
+                &self.beta_asset.as_ref().map(String::as_str),
+                metadata.beta_asset.name(),
+            )
+            && matches(&self.counterparty, metadata.counterparty)
+            && self
+                .state
+                .as_ref()
+                .map_or(true, |expected| Some(expected) == state)
+    }
+}
+
+pub trait IntoMetadata {
+    fn into_metadata(self, counterparty: SocketAddr) -> Metadata;
+}
+
+#[derive(Debug, Fail)]
+pub enum Error {
+    #[fail(display = "Metadata already exists")]
+    DuplicateKey,
+    #[fail(display = "The storage backend failed: {}", _0)]
+    Backend(String),
+    #[fail(display = "The metadata could not be (de)serialized: {}", _0)]
+    Serialization(String),
+}
+
+pub trait MetadataStore<K>: Send + Sync + 'static {
+    fn get(&self, key: &K) -> Result<Option<Metadata>, Error>;
+    fn insert<M: Into<Metadata>>(&self, key: K, metadata: M) -> Result<(), Error>;
+    fn all(&self) -> Result<Vec<(K, Metadata)>, Error>;
+    /// Sets the current state of the given swap and its last-update
+    /// timestamp to now. Unknown keys are ignored.
+    fn touch(&self, key: &K, state: &str) -> Result<(), Error>;
+    /// The swaps matching the query, in its order and at most `limit` of them.
+    fn query(&self, query: &SwapsQuery<K>) -> Result<Vec<(K, Metadata)>, Error>;
+}
+
+/// Keeps track of the actions the node executes for a swap
+pub trait ExecutionLog<K>: Send + Sync + 'static {
+    /// Remembers a transaction executed for the given swap.
+    fn record_transaction(&self, key: &K, transaction: ExecutedTransaction) -> Result<(), Error>;
+    /// All transactions recorded for the given swap, oldest first.
+    fn transactions(&self, key: &K) -> Result<Vec<ExecutedTransaction>, Error>;
+    /// Marks the given action of the swap as being executed. Returns `false`
+    /// if the action is already being executed or a transaction was recorded
+    /// for it.
+    fn start_execution(&self, key: &K, action: &str) -> Result<bool, Error>;
+    /// Clears the mark of an execution which did not broadcast a transaction.
+    fn abort_execution(&self, key: &K, action: &str) -> Result<(), Error>;
+}
+
+/// Keeps the history of the state transitions of a swap
+pub trait TransitionStore<K>: Send + Sync + 'static {
+    /// Remembers a state transition of the given swap.
+    fn record_transition(&self, key: &K, transition: Transition) -> Result<(), Error>;
+    /// All transitions recorded for the given swap, oldest first.
+    fn transitions(&self, key: &K) -> Result<Vec<Transition>, Error>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryMetadataStore<K: Hash + Eq> {
+    metadata: Mutex<HashMap<K, Metadata>>,
+    states: Mutex<HashMap<K, String>>,
+    transactions: Mutex<HashMap<K, Vec<ExecutedTransaction>>>,
+    executions: Mutex<HashMap<K, HashSet<String>>>,
+    transitions: Mutex<HashMap<K, Vec<Transition>>>,
+}
+
+impl<K: Debug + Display + Hash + Eq + Clone + Send + Sync + 'static> MetadataStore<K>
+    for InMemoryMetadataStore<K>
+{
+    fn get(&self, key: &K) -> Result<Option<Metadata>, Error> {
+        let metadata = self.metadata.lock().unwrap();
+        trace!("Fetched metadata of swap with id {}: {:?}", key, metadata);
+
+        Ok(metadata.get(&key).map(Clone::clone))
+    }
+
+    fn insert<M: Into<Metadata>>(&self, key: K, value: M) -> Result<(), Error> {
+        let mut metadata = self.metadata.lock().unwrap();
+
+        if metadata.contains_key(&key) {
+            return Err(Error::DuplicateKey);
+        }
+
+        let _ = metadata.insert(key, value.into());
+        Ok(())
+    }
+    fn all(&self) -> Result<Vec<(K, Metadata)>, Error> {
+        let metadata = self.metadata.lock().unwrap();
+
+        Ok(metadata
+            .iter()
+            .map(|(key, value)| (key.clone(), *value))
+            .collect())
+    }
+
+    fn touch(&self, key: &K, state: &str) -> Result<(), Error> {
+        let mut metadata = self.metadata.lock().unwrap();
+        let mut states = self.states.lock().unwrap();
+
+        if let Some(metadata) = metadata.get_mut(key) {
+            metadata.updated_at = Utc::now();
+            let _ = states.insert(key.clone(), state.to_string());
+        }
+        Ok(())
+    }
+
+    fn query(&self, query: &SwapsQuery<K>) -> Result<Vec<(K, Metadata)>, Error> {
+        let metadata = self.metadata.lock().unwrap();
+        let states = self.states.lock().unwrap();
+
+        let mut swaps: Vec<(K, Metadata)> = metadata
+            .iter()
+            .filter(|(key, value)| query.matches(key, value, states.get(key)))
+            .map(|(key, value)| (key.clone(), *value))
+            .collect();
+        swaps.sort_by_key(|(key, value)| (value.created_at, key.to_string()));
+        if query.newest_first {
+            swaps.reverse();
+        }
+        swaps.truncate(query.limit);
+
+        Ok(swaps)
+    }
+}
+
+impl<K: Debug + Display + Hash + Eq + Clone + Send + Sync + 'static> ExecutionLog<K>
+    for InMemoryMetadataStore<K>
+{
+    fn record_transaction(&self, key: &K, transaction: ExecutedTransaction) -> Result<(), Error> {
+        let mut transactions = self.transactions.lock().unwrap();
+
+        transactions
+            .entry(key.clone())
+            .or_insert_with(Vec::new)
+            .push(transaction);
+        Ok(())
+    }
+
+    fn transactions(&self, key: &K) -> Result<Vec<ExecutedTransaction>, Error> {
+        let transactions = self.transactions.lock().unwrap();
+
+        Ok(transactions.get(key).cloned().unwrap_or_default())
+    }
+
+    fn start_execution(&self, key: &K, action: &str) -> Result<bool, Error> {
+        let transactions = self.transactions.lock().unwrap();
+        let mut executions = self.executions.lock().unwrap();
+
+        let executed = transactions.get(key).map_or(false, |transactions| {
+            transactions
+                .iter()
+                .any(|transaction| transaction.action == action)
+        });
+
+        Ok(!executed
+            && executions
+                .entry(key.clone())
+                .or_insert_with(HashSet::new)
+                .insert(action.to_string()))
+    }
+
+    fn abort_execution(&self, key: &K, action: &str) -> Result<(), Error> {
+        let mut executions = self.executions.lock().unwrap();
+
+        if let Some(actions) = executions.get_mut(key) {
+            actions.remove(action);
+        }
+        Ok(())
+    }
+}
+
+impl<K: Debug + Display + Hash + Eq + Clone + Send + Sync + 'static> TransitionStore<K>
+    for InMemoryMetadataStore<K>
+{
+    fn record_transition(&self, key: &K, transition: Transition) -> Result<(), Error> {
+        let mut transitions = self.transitions.lock().unwrap();
+
+        transitions
+            .entry(key.clone())
+            .or_insert_with(Vec::new)
+            .push(transition);
+        Ok(())
+    }
+
+    fn transitions(&self, key: &K) -> Result<Vec<Transition>, Error> {
+        let transitions = self.transitions.lock().unwrap();
+
+        Ok(transitions.get(key).cloned().unwrap_or_default())
+    }
+}
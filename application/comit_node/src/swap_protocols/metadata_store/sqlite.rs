@@ -0,0 +1,590 @@
+use crate::swap_protocols::metadata_store::{
+    Error, ExecutedTransaction, ExecutionLog, Metadata, MetadataStore, SwapsQuery, Transition,
+    TransitionStore,
+};
+use chrono::Utc;
+use rusqlite::{types::ToSql, Connection, OptionalExtension, NO_PARAMS};
+use std::{
+    fmt::Display,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+/// Persists the metadata of every swap as JSON in a SQLite database. The
+/// fields the swap list is filtered and ordered by are kept in columns of
+/// their own.
+#[derive(Clone, DebugStub)]
+pub struct SqliteMetadataStore {
+    #[debug_stub = "Connection"]
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteMetadataStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, Error> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS swap_metadata (
+                id TEXT PRIMARY KEY NOT NULL,
+                metadata TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                role TEXT NOT NULL,
+                alpha_ledger TEXT NOT NULL,
+                beta_ledger TEXT NOT NULL,
+                alpha_asset TEXT NOT NULL,
+                beta_asset TEXT NOT NULL,
+                counterparty TEXT NOT NULL,
+                state TEXT
+            )",
+            NO_PARAMS,
+        )?;
+        connection.execute(
+            "CREATE INDEX IF NOT EXISTS swap_metadata_by_creation
+                ON swap_metadata (created_at, id)",
+            NO_PARAMS,
+        )?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS executed_transactions (
+                swap_id TEXT NOT NULL,
+                executed_transaction TEXT NOT NULL
+            )",
+            NO_PARAMS,
+        )?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS action_executions (
+                swap_id TEXT NOT NULL,
+                action TEXT NOT NULL,
+                PRIMARY KEY (swap_id, action)
+            )",
+            NO_PARAMS,
+        )?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS swap_transitions (
+                swap_id TEXT NOT NULL,
+                transition TEXT NOT NULL
+            )",
+            NO_PARAMS,
+        )?;
+
+        Ok(SqliteMetadataStore {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+}
+
+impl<K> MetadataStore<K> for SqliteMetadataStore
+where
+    K: Display + FromStr + Clone + Send + Sync + 'static,
+    <K as FromStr>::Err: Display,
+{
+    fn get(&self, key: &K) -> Result<Option<Metadata>, Error> {
+        let connection = self.connection.lock().unwrap();
+
+        let metadata: Option<String> = connection
+            .query_row(
+                "SELECT metadata FROM swap_metadata WHERE id = ?1",
+                &[&key.to_string() as &dyn ToSql],
+                |row| row.get(0),
+            )
+            .optional()?;
+        trace!("Fetched metadata of swap with id {}: {:?}", key, metadata);
+
+        match metadata {
+            Some(metadata) => Ok(Some(serde_json::from_str(&metadata)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn insert<M: Into<Metadata>>(&self, key: K, metadata: M) -> Result<(), Error> {
+        let id = key.to_string();
+        let metadata = metadata.into();
+        let json = serde_json::to_string(&metadata)?;
+        let connection = self.connection.lock().unwrap();
+
+        let existing = connection
+            .query_row(
+                "SELECT id FROM swap_metadata WHERE id = ?1",
+                &[&id as &dyn ToSql],
+                |_| (),
+            )
+            .optional()?;
+
+        if existing.is_some() {
+            return Err(Error::DuplicateKey);
+        }
+
+        connection.execute(
+            "INSERT INTO swap_metadata (
+                id, metadata, created_at, role, alpha_ledger, beta_ledger, alpha_asset,
+                beta_asset, counterparty
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            &[
+                &id as &dyn ToSql,
+                &json,
+                &metadata.created_at.timestamp_nanos(),
+                &metadata.role.to_string(),
+                &metadata.alpha_ledger.name(),
+                &metadata.beta_ledger.name(),
+                &metadata.alpha_asset.name(),
+                &metadata.beta_asset.name(),
+                &metadata.counterparty.to_string(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn all(&self) -> Result<Vec<(K, Metadata)>, Error> {
+        let connection = self.connection.lock().unwrap();
+
+        let mut statement = connection.prepare("SELECT id, metadata FROM swap_metadata")?;
+        let rows = statement.query_map(NO_PARAMS, |row| {
+            let id: String = row.get(0);
+            let metadata: String = row.get(1);
+            (id, metadata)
+        })?;
+
+        let mut all = Vec::new();
+        for row in rows {
+            let (id, metadata) = row?;
+            let key = K::from_str(&id).map_err(|e| Error::Backend(e.to_string()))?;
+            all.push((key, serde_json::from_str(&metadata)?));
+        }
+
+        Ok(all)
+    }
+
+    fn touch(&self, key: &K, state: &str) -> Result<(), Error> {
+        let id = key.to_string();
+        let connection = self.connection.lock().unwrap();
+
+        let metadata: Option<String> = connection
+            .query_row(
+                "SELECT metadata FROM swap_metadata WHERE id = ?1",
+                &[&id as &dyn ToSql],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if let Some(metadata) = metadata {
+            let mut metadata: Metadata = serde_json::from_str(&metadata)?;
+            metadata.updated_at = Utc::now();
+
+            connection.execute(
+                "UPDATE swap_metadata SET metadata = ?1, state = ?2 WHERE id = ?3",
+                &[
+                    &serde_json::to_string(&metadata)? as &dyn ToSql,
+                    &state,
+                    &id,
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn query(&self, query: &SwapsQuery<K>) -> Result<Vec<(K, Metadata)>, Error> {
+        let mut conditions = Vec::new();
+        let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+
+        let mut filter = |column: &str, value: Option<String>| {
+            if let Some(value) = value {
+                params.push(Box::new(value));
+                conditions.push(format!("{} = ?{}", column, params.len()));
+            }
+        };
+        filter("role", query.role.map(|role| role.to_string()));
+        filter("alpha_ledger", query.alpha_ledger.clone());
+        filter("beta_ledger", query.beta_ledger.clone());
+        filter("alpha_asset", query.alpha_asset.clone());
+        filter("beta_asset", query.beta_asset.clone());
+        filter(
+            "counterparty",
+            query
+                .counterparty
+                .map(|counterparty| counterparty.to_string()),
+        );
+        filter("state", query.state.clone());
+
+        let (comparison, direction) = if query.newest_first {
+            ("<", "DESC")
+        } else {
+            (">", "ASC")
+        };
+        if let Some((created_at, key)) = &query.after {
+            params.push(Box::new(created_at.timestamp_nanos()));
+            params.push(Box::new(key.to_string()));
+            conditions.push(format!(
+                "(created_at {0} ?{1} OR (created_at = ?{1} AND id {0} ?{2}))",
+                comparison,
+                params.len() - 1,
+                params.len()
+            ));
+        }
+        params.push(Box::new(query.limit as i64));
+
+        let sql = format!(
+            "SELECT id, metadata FROM swap_metadata {0} ORDER BY created_at {1}, id {1} LIMIT ?{2}",
+            if conditions.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", conditions.join(" AND "))
+            },
+            direction,
+            params.len(),
+        );
+
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(&sql)?;
+        let params: Vec<&dyn ToSql> = params.iter().map(|param| &**param).collect();
+        let rows = statement.query_map(&params, |row| {
+            let id: String = row.get(0);
+            let metadata: String = row.get(1);
+            (id, metadata)
+        })?;
+
+        let mut swaps = Vec::new();
+        for row in rows {
+            let (id, metadata) = row?;
+            let key = K::from_str(&id).map_err(|e| Error::Backend(e.to_string()))?;
+            swaps.push((key, serde_json::from_str(&metadata)?));
+        }
+
+        Ok(swaps)
+    }
+}
+
+impl<K> ExecutionLog<K> for SqliteMetadataStore
+where
+    K: Display + FromStr + Clone + Send + Sync + 'static,
+    <K as FromStr>::Err: Display,
+{
+    fn record_transaction(&self, key: &K, transaction: ExecutedTransaction) -> Result<(), Error> {
+        let connection = self.connection.lock().unwrap();
+
+        connection.execute(
+            "INSERT INTO executed_transactions (swap_id, executed_transaction) VALUES (?1, ?2)",
+            &[
+                &key.to_string() as &dyn ToSql,
+                &serde_json::to_string(&transaction)?,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn transactions(&self, key: &K) -> Result<Vec<ExecutedTransaction>, Error> {
+        let connection = self.connection.lock().unwrap();
+
+        transactions(&connection, &key.to_string())
+    }
+
+    fn start_execution(&self, key: &K, action: &str) -> Result<bool, Error> {
+        let id = key.to_string();
+        let connection = self.connection.lock().unwrap();
+
+        if transactions(&connection, &id)?
+            .iter()
+            .any(|transaction| transaction.action == action)
+        {
+            return Ok(false);
+        }
+
+        let inserted = connection.execute(
+            "INSERT OR IGNORE INTO action_executions (swap_id, action) VALUES (?1, ?2)",
+            &[&id as &dyn ToSql, &action],
+        )?;
+
+        Ok(inserted == 1)
+    }
+
+    fn abort_execution(&self, key: &K, action: &str) -> Result<(), Error> {
+        let connection = self.connection.lock().unwrap();
+
+        connection.execute(
+            "DELETE FROM action_executions WHERE swap_id = ?1 AND action = ?2",
+            &[&key.to_string() as &dyn ToSql, &action],
+        )?;
+
+        Ok(())
+    }
+}
+
+impl<K> TransitionStore<K> for SqliteMetadataStore
+where
+    K: Display + FromStr + Clone + Send + Sync + 'static,
+    <K as FromStr>::Err: Display,
+{
+    fn record_transition(&self, key: &K, transition: Transition) -> Result<(), Error> {
+        let connection = self.connection.lock().unwrap();
+
+        connection.execute(
+            "INSERT INTO swap_transitions (swap_id, transition) VALUES (?1, ?2)",
+            &[
+                &key.to_string() as &dyn ToSql,
+                &serde_json::to_string(&transition)?,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn transitions(&self, key: &K) -> Result<Vec<Transition>, Error> {
+        let connection = self.connection.lock().unwrap();
+
+        let mut statement = connection
+            .prepare("SELECT transition FROM swap_transitions WHERE swap_id = ?1 ORDER BY rowid")?;
+        let rows = statement.query_map(&[&key.to_string() as &dyn ToSql], |row| {
+            let transition: String = row.get(0);
+            transition
+        })?;
+
+        let mut transitions = Vec::new();
+        for row in rows {
+            transitions.push(serde_json::from_str(&row?)?);
+        }
+
+        Ok(transitions)
+    }
+}
+
+fn transactions(connection: &Connection, id: &str) -> Result<Vec<ExecutedTransaction>, Error> {
+    let mut statement = connection.prepare(
+        "SELECT executed_transaction FROM executed_transactions WHERE swap_id = ?1 ORDER BY rowid",
+    )?;
+    let rows = statement.query_map(&[&id as &dyn ToSql], |row| {
+        let transaction: String = row.get(0);
+        transaction
+    })?;
+
+    let mut transactions = Vec::new();
+    for row in rows {
+        transactions.push(serde_json::from_str(&row?)?);
+    }
+
+    Ok(transactions)
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Backend(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Serialization(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::{
+        metadata_store::{
+            AssetKind, ExecutedTransaction, HtlcEvent, HtlcEventKind, LedgerKind, RoleKind,
+            SwapLedger, Transition,
+        },
+        SwapId,
+    };
+    use bitcoin_support::Network;
+    use spectral::prelude::*;
+
+    fn metadata() -> Metadata {
+        Metadata::new(
+            LedgerKind::Bitcoin,
+            LedgerKind::Ethereum,
+            AssetKind::Bitcoin,
+            AssetKind::Ether,
+            RoleKind::Bob,
+            "127.0.0.1:8011".parse().unwrap(),
+            Some(Network::Regtest),
+        )
+    }
+
+    #[test]
+    fn insert_get_and_list_metadata() {
+        let metadata_store = SqliteMetadataStore::open_in_memory().unwrap();
+        let id = SwapId::default();
+
+        assert_that(&metadata_store.insert(id, metadata())).is_ok();
+
+        let res = metadata_store.get(&id).unwrap().unwrap();
+        assert_that(&res.role.to_string()).is_equal_to(String::from("Bob"));
+
+        let all: Vec<(SwapId, Metadata)> = metadata_store.all().unwrap();
+        assert_that(&all).has_length(1);
+        assert_that(&all[0].0).is_equal_to(id);
+    }
+
+    #[test]
+    fn touch_updates_the_last_update_timestamp() {
+        let metadata_store = SqliteMetadataStore::open_in_memory().unwrap();
+        let id = SwapId::default();
+
+        metadata_store.insert(id, metadata()).unwrap();
+        let before = metadata_store.get(&id).unwrap().unwrap();
+
+        metadata_store.touch(&id, "Start").unwrap();
+        let after = metadata_store.get(&id).unwrap().unwrap();
+
+        assert_that(&after.created_at).is_equal_to(before.created_at);
+        assert!(after.updated_at >= before.updated_at);
+    }
+
+    #[test]
+    fn insert_rejects_duplicate_key() {
+        let metadata_store = SqliteMetadataStore::open_in_memory().unwrap();
+        let id = SwapId::default();
+
+        metadata_store.insert(id, metadata()).unwrap();
+
+        assert!(match metadata_store.insert(id, metadata()) {
+            Err(Error::DuplicateKey) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn recorded_transactions_are_returned_in_order() {
+        let metadata_store = SqliteMetadataStore::open_in_memory().unwrap();
+        let id = SwapId::default();
+        let deploy = ExecutedTransaction::new("deploy", LedgerKind::Ethereum, "0x01".into());
+        let fund = ExecutedTransaction::new("fund", LedgerKind::Ethereum, "0x02".into());
+
+        metadata_store
+            .record_transaction(&id, deploy.clone())
+            .unwrap();
+        metadata_store
+            .record_transaction(&id, fund.clone())
+            .unwrap();
+
+        assert_that(&metadata_store.transactions(&id).unwrap()).is_equal_to(vec![deploy, fund]);
+        assert_that(&metadata_store.transactions(&SwapId::default()).unwrap()).is_empty();
+    }
+
+    #[test]
+    fn an_action_is_executed_once() {
+        let metadata_store = SqliteMetadataStore::open_in_memory().unwrap();
+        let id = SwapId::default();
+
+        assert_that(&metadata_store.start_execution(&id, "fund")).is_ok_containing(true);
+        assert_that(&metadata_store.start_execution(&id, "fund")).is_ok_containing(false);
+        assert_that(&metadata_store.start_execution(&id, "redeem")).is_ok_containing(true);
+
+        metadata_store.abort_execution(&id, "redeem").unwrap();
+        assert_that(&metadata_store.start_execution(&id, "redeem")).is_ok_containing(true);
+    }
+
+    #[test]
+    fn an_action_with_a_recorded_transaction_is_not_executed_again() {
+        let metadata_store = SqliteMetadataStore::open_in_memory().unwrap();
+        let id = SwapId::default();
+
+        metadata_store
+            .record_transaction(
+                &id,
+                ExecutedTransaction::new("fund", LedgerKind::Bitcoin, "abcd".into()),
+            )
+            .unwrap();
+
+        assert_that(&metadata_store.start_execution(&id, "fund")).is_ok_containing(false);
+    }
+
+    #[test]
+    fn recorded_transitions_are_returned_in_order() {
+        let metadata_store = SqliteMetadataStore::open_in_memory().unwrap();
+        let id = SwapId::default();
+        let accepted = Transition::new("Accepted".into(), None);
+        let alpha_funded = Transition::new(
+            "AlphaFunded".into(),
+            Some(HtlcEvent {
+                ledger: SwapLedger::Alpha,
+                event: HtlcEventKind::Funded,
+                transaction_id: Some("0x01".into()),
+                htlc_location: None,
+                secret: None,
+            }),
+        );
+
+        metadata_store
+            .record_transition(&id, accepted.clone())
+            .unwrap();
+        metadata_store
+            .record_transition(&id, alpha_funded.clone())
+            .unwrap();
+
+        assert_that(&metadata_store.transitions(&id).unwrap())
+            .is_equal_to(vec![accepted, alpha_funded]);
+        assert_that(&metadata_store.transitions(&SwapId::default()).unwrap()).is_empty();
+    }
+
+    #[test]
+    fn query_filters_orders_and_pages_swaps() {
+        let metadata_store = SqliteMetadataStore::open_in_memory().unwrap();
+        let mut ids: Vec<SwapId> = (0..4).map(|_| SwapId::default()).collect();
+        ids.sort_by_key(|id| id.to_string());
+        let created_at = Utc::now();
+
+        for (i, id) in ids.iter().enumerate() {
+            let mut metadata = metadata();
+            // Two swaps share a creation timestamp so their ids decide the order
+            metadata.created_at = created_at + chrono::Duration::seconds(i as i64 / 2);
+            metadata_store.insert(*id, metadata).unwrap();
+        }
+        metadata_store.touch(&ids[1], "Accepted").unwrap();
+        metadata_store.touch(&ids[3], "Accepted").unwrap();
+
+        let all = SwapsQuery {
+            role: Some(RoleKind::Bob),
+            alpha_ledger: None,
+            beta_ledger: Some(String::from("Ethereum")),
+            alpha_asset: None,
+            beta_asset: None,
+            counterparty: None,
+            state: None,
+            newest_first: false,
+            after: None,
+            limit: 10,
+        };
+        let keys = |query: &SwapsQuery<SwapId>| -> Vec<SwapId> {
+            metadata_store
+                .query(query)
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect()
+        };
+
+        assert_that(&keys(&all)).is_equal_to(ids.clone());
+        assert_that(&keys(&SwapsQuery {
+            after: Some((created_at, ids[0])),
+            limit: 2,
+            ..all.clone()
+        }))
+        .is_equal_to(vec![ids[1], ids[2]]);
+        assert_that(&keys(&SwapsQuery {
+            newest_first: true,
+            after: Some((created_at + chrono::Duration::seconds(1), ids[2])),
+            ..all.clone()
+        }))
+        .is_equal_to(vec![ids[1], ids[0]]);
+        assert_that(&keys(&SwapsQuery {
+            state: Some(String::from("Accepted")),
+            ..all.clone()
+        }))
+        .is_equal_to(vec![ids[1], ids[3]]);
+        assert_that(&keys(&SwapsQuery {
+            role: Some(RoleKind::Alice),
+            ..all
+        }))
+        .is_empty();
+    }
+}
@@ -4,16 +4,21 @@ pub mod ledger;
 pub mod rfc003;
 
 pub mod metadata_store;
+mod state_changes;
 
 pub use self::{
     dependencies::*,
     ledger::Ledger,
     metadata_store::{
-        AssetKind, InMemoryMetadataStore, LedgerKind, Metadata, MetadataStore, RoleKind,
+        AssetKind, ExecutedTransaction, ExecutionLog, HtlcEvent, HtlcEventKind,
+        InMemoryMetadataStore, IntoMetadata, LedgerKind, Metadata, MetadataStore, RoleKind,
+        SqliteMetadataStore, SwapLedger, SwapsQuery, Transition, TransitionStore,
     },
+    state_changes::{StateChange, StateChanges},
 };
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SwapProtocols {
     Rfc003,
 }
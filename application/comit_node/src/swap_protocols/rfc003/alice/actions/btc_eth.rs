@@ -27,6 +27,7 @@ impl OngoingSwap<Alice<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>> {
                 bitcoin::Htlc::from(self.alpha_htlc_params())
                     .unlock_after_timeout(self.alpha_ledger_refund_identity),
             ),
+            network: self.alpha_ledger.network,
         }
     }
 
@@ -38,10 +39,12 @@ impl OngoingSwap<Alice<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>> {
         let gas_limit = EtherHtlc::tx_gas_limit();
 
         ethereum::SendTransaction {
+            from: self.beta_ledger_redeem_identity,
             to: beta_htlc_location,
             data,
             gas_limit,
             value: EtherQuantity::from_wei(U256::zero()),
+            chain_id: self.beta_ledger.chain_id,
         }
     }
 }
@@ -63,22 +66,32 @@ impl Actions for SwapStates<Alice<Bitcoin, Ethereum, BitcoinQuantity, EtherQuant
             SS::BothFunded(BothFunded {
                 ref alpha_htlc_location,
                 ref beta_htlc_location,
+                ref alpha_htlc_expiry,
                 ref swap,
                 ..
-            }) => vec![
-                alice::ActionKind::Redeem(swap.redeem_action(*beta_htlc_location)),
-                alice::ActionKind::Refund(swap.refund_action(*alpha_htlc_location)),
-            ],
+            }) => {
+                let mut actions = vec![alice::ActionKind::Redeem(
+                    swap.redeem_action(*beta_htlc_location),
+                )];
+                if alpha_htlc_expiry.refundable_now {
+                    actions.push(alice::ActionKind::Refund(
+                        swap.refund_action(*alpha_htlc_location),
+                    ));
+                }
+                actions
+            }
             SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
                 ref swap,
                 ref alpha_htlc_location,
+                ref alpha_htlc_expiry,
                 ..
             })
             | SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
                 ref swap,
                 ref alpha_htlc_location,
+                ref alpha_htlc_expiry,
                 ..
-            }) => vec![alice::ActionKind::Refund(
+            }) if alpha_htlc_expiry.refundable_now => vec![alice::ActionKind::Refund(
                 swap.refund_action(*alpha_htlc_location),
             )],
             SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
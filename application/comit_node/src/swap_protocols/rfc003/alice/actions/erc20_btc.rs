@@ -18,9 +18,11 @@ impl OngoingSwap<Alice<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuantity>> {
         let gas_limit = htlc.deployment_gas_limit();
 
         ethereum::ContractDeploy {
+            from: self.alpha_ledger_refund_identity,
             data,
             value: EtherQuantity::zero(),
             gas_limit,
+            chain_id: self.alpha_ledger.chain_id,
         }
     }
 
@@ -32,10 +34,12 @@ impl OngoingSwap<Alice<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuantity>> {
         let gas_limit = Erc20Htlc::fund_tx_gas_limit();
 
         ethereum::SendTransaction {
+            from: self.alpha_ledger_refund_identity,
             to: self.alpha_asset.token_contract(),
             data: htlc.funding_tx_payload(alpha_htlc_location),
             gas_limit,
             value: EtherQuantity::zero(),
+            chain_id: self.alpha_ledger.chain_id,
         }
     }
 
@@ -47,10 +51,12 @@ impl OngoingSwap<Alice<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuantity>> {
         let gas_limit = Erc20Htlc::tx_gas_limit();
 
         ethereum::SendTransaction {
+            from: self.alpha_ledger_refund_identity,
             to: alpha_htlc_location,
             data,
             gas_limit,
             value: EtherQuantity::zero(),
+            chain_id: self.alpha_ledger.chain_id,
         }
     }
 
@@ -63,6 +69,7 @@ impl OngoingSwap<Alice<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuantity>> {
                 self.beta_asset,
                 htlc.unlock_with_secret(self.beta_ledger_redeem_identity, &self.secret),
             ),
+            network: self.beta_ledger.network,
         }
     }
 }
@@ -91,22 +98,32 @@ impl Actions for SwapStates<Alice<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuant
             SS::BothFunded(BothFunded {
                 ref alpha_htlc_location,
                 ref beta_htlc_location,
+                ref alpha_htlc_expiry,
                 ref swap,
                 ..
-            }) => vec![
-                alice::ActionKind::Redeem(swap.redeem_action(*beta_htlc_location)),
-                alice::ActionKind::Refund(swap.refund_action(*alpha_htlc_location)),
-            ],
+            }) => {
+                let mut actions = vec![alice::ActionKind::Redeem(
+                    swap.redeem_action(*beta_htlc_location),
+                )];
+                if alpha_htlc_expiry.refundable_now {
+                    actions.push(alice::ActionKind::Refund(
+                        swap.refund_action(*alpha_htlc_location),
+                    ));
+                }
+                actions
+            }
             SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
                 ref swap,
                 ref alpha_htlc_location,
+                ref alpha_htlc_expiry,
                 ..
             })
             | SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
                 ref swap,
                 ref alpha_htlc_location,
+                ref alpha_htlc_expiry,
                 ..
-            }) => vec![alice::ActionKind::Refund(
+            }) if alpha_htlc_expiry.refundable_now => vec![alice::ActionKind::Refund(
                 swap.refund_action(*alpha_htlc_location),
             )],
             SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
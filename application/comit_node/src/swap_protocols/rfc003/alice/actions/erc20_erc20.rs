@@ -0,0 +1,216 @@
+use crate::swap_protocols::{
+    ledger::Ethereum,
+    rfc003::{
+        alice,
+        ethereum::{self, Erc20Htlc, Htlc},
+        state_machine::*,
+        Actions, Alice,
+    },
+};
+use ethereum_support::{Bytes, Erc20Quantity, EtherQuantity};
+
+impl OngoingSwap<Alice<Ethereum, Ethereum, Erc20Quantity, Erc20Quantity>> {
+    pub fn deploy_action(&self) -> ethereum::ContractDeploy {
+        let htlc = Erc20Htlc::from(self.alpha_htlc_params());
+        let data = htlc.compile_to_hex().into();
+        let gas_limit = htlc.deployment_gas_limit();
+
+        ethereum::ContractDeploy {
+            from: self.alpha_ledger_refund_identity,
+            data,
+            value: EtherQuantity::zero(),
+            gas_limit,
+            chain_id: self.alpha_ledger.chain_id,
+        }
+    }
+
+    pub fn fund_action(
+        &self,
+        alpha_htlc_location: ethereum_support::Address,
+    ) -> ethereum::SendTransaction {
+        let htlc = Erc20Htlc::from(self.alpha_htlc_params());
+        let gas_limit = Erc20Htlc::fund_tx_gas_limit();
+
+        ethereum::SendTransaction {
+            from: self.alpha_ledger_refund_identity,
+            to: self.alpha_asset.token_contract(),
+            data: htlc.funding_tx_payload(alpha_htlc_location),
+            gas_limit,
+            value: EtherQuantity::zero(),
+            chain_id: self.alpha_ledger.chain_id,
+        }
+    }
+
+    pub fn refund_action(
+        &self,
+        alpha_htlc_location: ethereum_support::Address,
+    ) -> ethereum::SendTransaction {
+        let data = Bytes::default();
+        let gas_limit = Erc20Htlc::tx_gas_limit();
+
+        ethereum::SendTransaction {
+            from: self.alpha_ledger_refund_identity,
+            to: alpha_htlc_location,
+            data,
+            gas_limit,
+            value: EtherQuantity::zero(),
+            chain_id: self.alpha_ledger.chain_id,
+        }
+    }
+
+    pub fn redeem_action(
+        &self,
+        beta_htlc_location: ethereum_support::Address,
+    ) -> ethereum::SendTransaction {
+        let data = Bytes::from(self.secret.raw_secret().to_vec());
+        let gas_limit = Erc20Htlc::tx_gas_limit();
+
+        ethereum::SendTransaction {
+            from: self.beta_ledger_redeem_identity,
+            to: beta_htlc_location,
+            data,
+            gas_limit,
+            value: EtherQuantity::zero(),
+            chain_id: self.beta_ledger.chain_id,
+        }
+    }
+}
+
+impl Actions for SwapStates<Alice<Ethereum, Ethereum, Erc20Quantity, Erc20Quantity>> {
+    type ActionKind = alice::ActionKind<
+        ethereum::ContractDeploy,
+        ethereum::SendTransaction,
+        ethereum::SendTransaction,
+        ethereum::SendTransaction,
+    >;
+
+    fn actions(&self) -> Vec<Self::ActionKind> {
+        use self::SwapStates as SS;
+        match *self {
+            SS::Accepted(Accepted { ref swap, .. }) => {
+                vec![alice::ActionKind::Deploy(swap.deploy_action())]
+            }
+            SS::AlphaDeployed(AlphaDeployed {
+                ref swap,
+                ref alpha_htlc_location,
+                ..
+            }) => vec![alice::ActionKind::Fund(
+                swap.fund_action(*alpha_htlc_location),
+            )],
+            SS::BothFunded(BothFunded {
+                ref alpha_htlc_location,
+                ref beta_htlc_location,
+                ref alpha_htlc_expiry,
+                ref swap,
+                ..
+            }) => {
+                let mut actions = vec![alice::ActionKind::Redeem(
+                    swap.redeem_action(*beta_htlc_location),
+                )];
+                if alpha_htlc_expiry.refundable_now {
+                    actions.push(alice::ActionKind::Refund(
+                        swap.refund_action(*alpha_htlc_location),
+                    ));
+                }
+                actions
+            }
+            SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
+                ref swap,
+                ref alpha_htlc_location,
+                ref alpha_htlc_expiry,
+                ..
+            })
+            | SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
+                ref swap,
+                ref alpha_htlc_location,
+                ref alpha_htlc_expiry,
+                ..
+            }) if alpha_htlc_expiry.refundable_now => vec![alice::ActionKind::Refund(
+                swap.refund_action(*alpha_htlc_location),
+            )],
+            SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
+                ref beta_htlc_location,
+                ref swap,
+                ..
+            })
+            | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
+                ref beta_htlc_location,
+                ref swap,
+                ..
+            }) => vec![alice::ActionKind::Redeem(
+                swap.redeem_action(*beta_htlc_location),
+            )],
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::rfc003::{ethereum::Seconds, Secret};
+    use ethereum_support::{Address, U256};
+
+    fn swap() -> OngoingSwap<Alice<Ethereum, Ethereum, Erc20Quantity, Erc20Quantity>> {
+        OngoingSwap {
+            alpha_ledger: Ethereum::dev(),
+            beta_ledger: Ethereum::dev(),
+            alpha_asset: Erc20Quantity::new(Address::from(0xAA), U256::from(1_000)),
+            beta_asset: Erc20Quantity::new(Address::from(0xBB), U256::from(2_000)),
+            alpha_ledger_redeem_identity: Address::from(0x01),
+            alpha_ledger_refund_identity: Address::from(0x02),
+            beta_ledger_redeem_identity: Address::from(0x03),
+            beta_ledger_refund_identity: Address::from(0x04),
+            alpha_ledger_lock_duration: Seconds(86_400),
+            beta_ledger_lock_duration: Seconds(43_200),
+            secret: Secret::from(*b"hello world, you are beautiful!!"),
+            role: Alice::default(),
+        }
+    }
+
+    #[test]
+    fn alpha_erc20_htlc_is_deployed_and_then_funded() {
+        let accepted = SwapStates::from(Accepted { swap: swap() });
+
+        match accepted.actions().as_slice() {
+            [alice::ActionKind::Deploy(deploy)] => {
+                assert_eq!(deploy.value, EtherQuantity::zero())
+            }
+            actions => panic!("Unexpected actions: {:?}", actions),
+        }
+
+        let deployed = SwapStates::from(AlphaDeployed {
+            swap: swap(),
+            alpha_htlc_location: Address::from(0xCC),
+            alpha_htlc_expiry: HtlcExpiry::default(),
+        });
+
+        match deployed.actions().as_slice() {
+            [alice::ActionKind::Fund(fund)] => {
+                assert_eq!(fund.to, Address::from(0xAA));
+                assert_eq!(fund.value, EtherQuantity::zero());
+            }
+            actions => panic!("Unexpected actions: {:?}", actions),
+        }
+    }
+
+    #[test]
+    fn redeems_beta_htlc_with_secret() {
+        let both_funded = SwapStates::from(BothFunded {
+            swap: swap(),
+            alpha_htlc_location: Address::from(0xCC),
+            beta_htlc_location: Address::from(0xDD),
+            alpha_htlc_expiry: HtlcExpiry::default(),
+            beta_htlc_expiry: HtlcExpiry::default(),
+        });
+
+        match both_funded.actions().as_slice() {
+            [alice::ActionKind::Redeem(redeem)] => {
+                assert_eq!(redeem.to, Address::from(0xDD));
+                assert_eq!(redeem.data.0, b"hello world, you are beautiful!!".to_vec());
+                assert_eq!(redeem.gas_limit, Erc20Htlc::tx_gas_limit());
+            }
+            actions => panic!("Unexpected actions: {:?}", actions),
+        }
+    }
+}
@@ -18,9 +18,11 @@ impl OngoingSwap<Alice<Ethereum, Bitcoin, EtherQuantity, BitcoinQuantity>> {
         let gas_limit = htlc.deployment_gas_limit();
 
         ethereum::ContractDeploy {
+            from: self.alpha_ledger_refund_identity,
             data,
             value: self.alpha_asset,
             gas_limit,
+            chain_id: self.alpha_ledger.chain_id,
         }
     }
 
@@ -32,10 +34,12 @@ impl OngoingSwap<Alice<Ethereum, Bitcoin, EtherQuantity, BitcoinQuantity>> {
         let gas_limit = EtherHtlc::tx_gas_limit();
 
         ethereum::SendTransaction {
+            from: self.alpha_ledger_refund_identity,
             to: alpha_htlc_location,
             data,
             gas_limit,
             value: EtherQuantity::zero(),
+            chain_id: self.alpha_ledger.chain_id,
         }
     }
 
@@ -48,6 +52,7 @@ impl OngoingSwap<Alice<Ethereum, Bitcoin, EtherQuantity, BitcoinQuantity>> {
                 self.beta_asset,
                 htlc.unlock_with_secret(self.beta_ledger_redeem_identity, &self.secret),
             ),
+            network: self.beta_ledger.network,
         }
     }
 }
@@ -69,22 +74,32 @@ impl Actions for SwapStates<Alice<Ethereum, Bitcoin, EtherQuantity, BitcoinQuant
             SS::BothFunded(BothFunded {
                 ref alpha_htlc_location,
                 ref beta_htlc_location,
+                ref alpha_htlc_expiry,
                 ref swap,
                 ..
-            }) => vec![
-                alice::ActionKind::Redeem(swap.redeem_action(*beta_htlc_location)),
-                alice::ActionKind::Refund(swap.refund_action(*alpha_htlc_location)),
-            ],
+            }) => {
+                let mut actions = vec![alice::ActionKind::Redeem(
+                    swap.redeem_action(*beta_htlc_location),
+                )];
+                if alpha_htlc_expiry.refundable_now {
+                    actions.push(alice::ActionKind::Refund(
+                        swap.refund_action(*alpha_htlc_location),
+                    ));
+                }
+                actions
+            }
             SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
                 ref swap,
                 ref alpha_htlc_location,
+                ref alpha_htlc_expiry,
                 ..
             })
             | SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
                 ref swap,
                 ref alpha_htlc_location,
+                ref alpha_htlc_expiry,
                 ..
-            }) => vec![alice::ActionKind::Refund(
+            }) if alpha_htlc_expiry.refundable_now => vec![alice::ActionKind::Refund(
                 swap.refund_action(*alpha_htlc_location),
             )],
             SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
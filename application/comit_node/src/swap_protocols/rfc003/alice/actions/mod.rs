@@ -1,7 +1,10 @@
 mod btc_erc20;
 mod btc_eth;
 mod erc20_btc;
+mod erc20_erc20;
+mod erc20_eth;
 mod eth_btc;
+mod eth_erc20;
 
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum ActionKind<Deploy, Fund, Redeem, Refund> {
@@ -4,13 +4,15 @@ use crate::{
         asset::Asset,
         rfc003::{
             self,
-            events::{CommunicationEvents, ResponseFuture, StateMachineResponseFuture},
+            events::{
+                CommunicationEvents, RequestAborted, ResponseFuture, StateMachineResponseFuture,
+            },
             ledger::Ledger,
             Alice,
         },
     },
 };
-use futures::Future;
+use futures::{future, Future};
 use std::sync::Arc;
 
 #[allow(missing_debug_implementations)]
@@ -19,6 +21,7 @@ pub struct AliceToBob<C, AL: Ledger, BL: Ledger> {
     response_future:
         Option<Box<StateMachineResponseFuture<AL::Identity, BL::Identity, BL::LockDuration>>>,
     client: Arc<C>,
+    request_aborted: Box<RequestAborted>,
 }
 
 impl<C, AL: Ledger, BL: Ledger> AliceToBob<C, AL, BL> {
@@ -26,6 +29,7 @@ impl<C, AL: Ledger, BL: Ledger> AliceToBob<C, AL, BL> {
         AliceToBob {
             client,
             response_future: None,
+            request_aborted: Box::new(future::empty()),
         }
     }
 }
@@ -47,4 +51,17 @@ impl<C: comit_client::Client, AL: Ledger, BL: Ledger, AA: Asset, BA: Asset>
             )
         })
     }
+
+    fn abort_request(&mut self, request: &comit_client::rfc003::Request<AL, BL, AA, BA>) {
+        tokio::spawn(
+            self.client
+                .send_swap_abort(request.secret_hash.clone())
+                .map_err(|e| warn!("Could not tell the counterparty about the abort: {:?}", e)),
+        );
+    }
+
+    /// Only Bob is ever told that a request was aborted
+    fn request_aborted(&mut self) -> &mut RequestAborted {
+        &mut self.request_aborted
+    }
 }
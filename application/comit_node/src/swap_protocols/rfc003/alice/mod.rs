@@ -26,9 +26,10 @@ use crate::{
     },
 };
 use futures::{future, Future};
-use std::{marker::PhantomData, net::SocketAddr, sync::Arc};
+use std::{marker::PhantomData, net::SocketAddr, sync::Arc, time::Duration};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct Alice<AL, BL, AA, BA> {
     phantom_data: PhantomData<(AL, BL, AA, BA)>,
 }
@@ -41,6 +42,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> Alice<AL, BL, AA, BA> {
         comit_client_factory: Arc<dyn ClientFactory<C>>,
         comit_node_addr: SocketAddr,
         save_state: Arc<dyn SaveState<Self>>,
+        lock_duration_margin: Duration,
     ) -> Box<dyn Future<Item = SwapOutcome<Self>, Error = rfc003::Error> + Send> {
         let start_state = Start {
             alpha_ledger: initiation.alpha_ledger,
@@ -67,6 +69,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> Alice<AL, BL, AA, BA> {
             beta_ledger_events,
             communication_events: Box::new(AliceToBob::new(Arc::clone(&comit_client))),
             state_repo: save_state,
+            lock_duration_margin,
         };
 
         Box::new(Swap::start_in(start_state, context))
@@ -1,19 +1,20 @@
 use crate::{
     comit_client,
+    logging::{self, Instrument},
     swap_protocols::{
         asset::Asset,
         dependencies::{LedgerEventDependencies, ProtocolDependencies},
-        metadata_store::{self, Metadata, MetadataStore},
+        metadata_store::{self, IntoMetadata, MetadataStore, TransitionStore},
         rfc003::{
             alice::SwapRequest,
+            save_failure,
             state_store::{self, StateStore},
-            Alice, CreateLedgerEvents, Initiation, Ledger, SecretSource,
+            Alice, CreateLedgerEvents, Initiation, Ledger, SaveState, SecretSource,
         },
         SwapId,
     },
 };
 
-use futures::Future;
 use http_api_problem::HttpApiProblem;
 use std::sync::Arc;
 
@@ -21,6 +22,8 @@ use std::sync::Arc;
 pub enum Error {
     Storage(state_store::Error),
     Metadata(metadata_store::Error),
+    MissingPeer,
+    UnsupportedNetwork,
 }
 
 impl From<Error> for HttpApiProblem {
@@ -29,6 +32,12 @@ impl From<Error> for HttpApiProblem {
         match e {
             Storage(e) => e.into(),
             Metadata(e) => e.into(),
+            MissingPeer => HttpApiProblem::new("missing-peer")
+                .set_status(400)
+                .set_detail("No peer was given and no default remote COMIT node is configured"),
+            UnsupportedNetwork => HttpApiProblem::new("unsupported-network")
+                .set_status(400)
+                .set_detail("The node is not configured for the network of one of the ledgers"),
         }
     }
 }
@@ -41,11 +50,14 @@ pub trait AliceSpawner: Send + Sync + 'static {
     ) -> Result<(), Error>
     where
         LedgerEventDependencies: CreateLedgerEvents<AL, AA> + CreateLedgerEvents<BL, BA>,
-        SwapRequest<AL, BL, AA, BA>: Into<Metadata>;
+        SwapRequest<AL, BL, AA, BA>: IntoMetadata;
 }
 
-impl<T: MetadataStore<SwapId>, S: StateStore<SwapId>, C: comit_client::Client> AliceSpawner
-    for ProtocolDependencies<T, S, C>
+impl<
+        T: MetadataStore<SwapId> + TransitionStore<SwapId>,
+        S: StateStore<SwapId>,
+        C: comit_client::Client,
+    > AliceSpawner for ProtocolDependencies<T, S, C>
 {
     fn spawn<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset>(
         &self,
@@ -54,15 +66,28 @@ impl<T: MetadataStore<SwapId>, S: StateStore<SwapId>, C: comit_client::Client> A
     ) -> Result<(), Error>
     where
         LedgerEventDependencies: CreateLedgerEvents<AL, AA> + CreateLedgerEvents<BL, BA>,
-        SwapRequest<AL, BL, AA, BA>: Into<Metadata>,
+        SwapRequest<AL, BL, AA, BA>: IntoMetadata,
     {
+        if !swap_request.alpha_ledger.is_on(&self.networks)
+            || !swap_request.beta_ledger.is_on(&self.networks)
+        {
+            return Err(Error::UnsupportedNetwork);
+        }
+
+        let peer = swap_request
+            .peer
+            .or(self.remote_comit_node)
+            .ok_or(Error::MissingPeer)?;
+
         let save_state = self
             .state_store
             .new_save_state(id)
             .map_err(Error::Storage)?;
         self.metadata_store
-            .insert(id, swap_request.clone())
+            .insert(id, swap_request.clone().into_metadata(peer))
             .map_err(Error::Metadata)?;
+        let save_state: Arc<dyn SaveState<Alice<AL, BL, AA, BA>>> =
+            self.observe_save_state(save_state, id);
 
         let initiation = Initiation {
             alpha_asset: swap_request.alpha_asset,
@@ -80,18 +105,13 @@ impl<T: MetadataStore<SwapId>, S: StateStore<SwapId>, C: comit_client::Client> A
             self.ledger_events.create_ledger_events(),
             self.ledger_events.create_ledger_events(),
             Arc::clone(&self.comit_client_factory),
-            self.remote_comit_node,
-            save_state,
+            peer,
+            Arc::clone(&save_state),
+            self.lock_duration_margin,
         );
 
         tokio::spawn(
-            state_machine_future
-                .map(move |outcome| {
-                    info!("Swap {} finished with {:?}", id, outcome);
-                })
-                .map_err(move |e| {
-                    error!("Swap {} failed with {:?}", id, e);
-                }),
+            save_failure(state_machine_future, save_state, id).instrument(logging::swap_span(id)),
         );
 
         Ok(())
@@ -2,7 +2,7 @@ use crate::swap_protocols::{
     ledger::Bitcoin,
     rfc003::{state_machine::HtlcParams, Ledger},
 };
-use bitcoin_support::{Address, BitcoinQuantity, Blocks, OutPoint};
+use bitcoin_support::{Address, BitcoinQuantity, BlockHeight, Blocks, OutPoint};
 use secp256k1_support::KeyPair;
 
 mod actions;
@@ -19,6 +19,7 @@ pub use self::{
 
 impl Ledger for Bitcoin {
     type LockDuration = Blocks;
+    type Expiry = BlockHeight;
     type HtlcLocation = OutPoint;
     type HtlcIdentity = KeyPair;
 }
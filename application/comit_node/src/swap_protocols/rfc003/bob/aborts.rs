@@ -0,0 +1,118 @@
+use crate::swap_protocols::rfc003::SecretHash;
+use futures::sync::oneshot;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    net::{IpAddr, SocketAddr},
+    sync::Mutex,
+};
+
+/// Hands aborts of accepted swap requests to the state machines of the
+/// swaps. A swap is identified by its secret hash, only the counterparty
+/// that made the request can abort it. Every connection of the counterparty
+/// comes from another port, so only its IP address is compared.
+#[derive(Debug, Default)]
+pub struct Aborts {
+    pending: Mutex<HashMap<SecretHash, Pending>>,
+}
+
+#[derive(Debug)]
+struct Pending {
+    counterparty: IpAddr,
+    sender: oneshot::Sender<()>,
+}
+
+impl Aborts {
+    /// A request for a secret hash which is already pending can never be
+    /// aborted, the abort belongs to the first request
+    pub fn register(
+        &self,
+        counterparty: SocketAddr,
+        secret_hash: SecretHash,
+    ) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+
+        if let Entry::Vacant(entry) = self.pending.lock().unwrap().entry(secret_hash) {
+            entry.insert(Pending {
+                counterparty: counterparty.ip(),
+                sender,
+            });
+        }
+
+        receiver
+    }
+
+    /// Returns whether a swap was waiting for the abort
+    pub fn abort(&self, counterparty: SocketAddr, secret_hash: SecretHash) -> bool {
+        self.take(counterparty, secret_hash)
+            .map_or(false, |pending| pending.sender.send(()).is_ok())
+    }
+
+    pub fn remove(&self, counterparty: SocketAddr, secret_hash: SecretHash) {
+        let _ = self.take(counterparty, secret_hash);
+    }
+
+    fn take(&self, counterparty: SocketAddr, secret_hash: SecretHash) -> Option<Pending> {
+        let mut pending = self.pending.lock().unwrap();
+
+        match pending.get(&secret_hash) {
+            Some(swap) if swap.counterparty == counterparty.ip() => pending.remove(&secret_hash),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::Future;
+    use spectral::prelude::*;
+
+    fn secret_hash() -> SecretHash {
+        SecretHash::from(*b"hello world, you are beautiful!!")
+    }
+
+    #[test]
+    fn abort_reaches_the_registered_swap() {
+        let aborts = Aborts::default();
+        let alice = "127.0.0.1:9939".parse().unwrap();
+        let aborted = aborts.register(alice, secret_hash());
+
+        assert_that(&aborts.abort(alice, secret_hash())).is_true();
+        assert_that(&aborted.wait()).is_ok();
+    }
+
+    #[test]
+    fn only_the_requesting_counterparty_can_abort() {
+        let aborts = Aborts::default();
+        let alice = "127.0.0.1:9939".parse().unwrap();
+        let mallory = "127.0.0.2:9939".parse().unwrap();
+        let _aborted = aborts.register(alice, secret_hash());
+
+        assert_that(&aborts.abort(mallory, secret_hash())).is_false();
+        assert_that(&aborts.abort(alice, secret_hash())).is_true();
+    }
+
+    #[test]
+    fn counterparty_can_abort_from_another_connection() {
+        let aborts = Aborts::default();
+        let alice = "127.0.0.1:9939".parse().unwrap();
+        let alice_reconnected = "127.0.0.1:52817".parse().unwrap();
+        let aborted = aborts.register(alice, secret_hash());
+
+        assert_that(&aborts.abort(alice_reconnected, secret_hash())).is_true();
+        assert_that(&aborted.wait()).is_ok();
+    }
+
+    #[test]
+    fn second_request_with_the_same_secret_hash_does_not_take_over_the_abort() {
+        let aborts = Aborts::default();
+        let alice = "127.0.0.1:9939".parse().unwrap();
+        let mallory = "127.0.0.2:9939".parse().unwrap();
+        let aborted = aborts.register(alice, secret_hash());
+        let _mallorys_abort = aborts.register(mallory, secret_hash());
+
+        assert_that(&aborts.abort(mallory, secret_hash())).is_false();
+        assert_that(&aborts.abort(alice, secret_hash())).is_true();
+        assert_that(&aborted.wait()).is_ok();
+    }
+}
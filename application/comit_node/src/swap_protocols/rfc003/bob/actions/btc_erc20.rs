@@ -23,9 +23,11 @@ impl OngoingSwap<Bob<Bitcoin, Ethereum, BitcoinQuantity, Erc20Quantity>> {
         let gas_limit = htlc.deployment_gas_limit();
 
         ethereum::ContractDeploy {
+            from: self.beta_ledger_refund_identity,
             data,
             value: EtherQuantity::zero(),
             gas_limit,
+            chain_id: self.beta_ledger.chain_id,
         }
     }
 
@@ -37,10 +39,12 @@ impl OngoingSwap<Bob<Bitcoin, Ethereum, BitcoinQuantity, Erc20Quantity>> {
         let gas_limit = Erc20Htlc::tx_gas_limit();
 
         ethereum::SendTransaction {
+            from: self.beta_ledger_refund_identity,
             to: beta_htlc_location,
             data,
             gas_limit,
             value: EtherQuantity::zero(),
+            chain_id: self.beta_ledger.chain_id,
         }
     }
 
@@ -52,10 +56,12 @@ impl OngoingSwap<Bob<Bitcoin, Ethereum, BitcoinQuantity, Erc20Quantity>> {
         let gas_limit = Erc20Htlc::fund_tx_gas_limit();
 
         ethereum::SendTransaction {
+            from: self.beta_ledger_refund_identity,
             to: self.beta_asset.token_contract(),
             data: htlc.funding_tx_payload(beta_htlc_location),
             gas_limit,
             value: EtherQuantity::zero(),
+            chain_id: self.beta_ledger.chain_id,
         }
     }
 
@@ -71,6 +77,7 @@ impl OngoingSwap<Bob<Bitcoin, Ethereum, BitcoinQuantity, Erc20Quantity>> {
                 bitcoin::Htlc::from(self.alpha_htlc_params())
                     .unlock_with_secret(self.alpha_ledger_redeem_identity, &secret),
             ),
+            network: self.alpha_ledger.network,
         }
     }
 }
@@ -102,19 +109,22 @@ impl Actions for SwapStates<Bob<Bitcoin, Ethereum, BitcoinQuantity, Erc20Quantit
             }) => vec![bob::ActionKind::Fund(swap.fund_action(*beta_htlc_location))],
             SS::BothFunded(BothFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
             })
             | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
             })
             | SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
-            }) => vec![bob::ActionKind::Refund(
+            }) if beta_htlc_expiry.refundable_now => vec![bob::ActionKind::Refund(
                 swap.refund_action(*beta_htlc_location),
             )],
             SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
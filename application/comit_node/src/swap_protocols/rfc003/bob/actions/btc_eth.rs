@@ -23,9 +23,11 @@ impl OngoingSwap<Bob<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>> {
         let gas_limit = htlc.deployment_gas_limit();
 
         ethereum::ContractDeploy {
+            from: self.beta_ledger_refund_identity,
             data,
             value: self.beta_asset,
             gas_limit,
+            chain_id: self.beta_ledger.chain_id,
         }
     }
 
@@ -37,10 +39,12 @@ impl OngoingSwap<Bob<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>> {
         let gas_limit = EtherHtlc::tx_gas_limit();
 
         ethereum::SendTransaction {
+            from: self.beta_ledger_refund_identity,
             to: beta_htlc_location,
             data,
             gas_limit,
             value: EtherQuantity::zero(),
+            chain_id: self.beta_ledger.chain_id,
         }
     }
 
@@ -57,6 +61,7 @@ impl OngoingSwap<Bob<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>> {
                 self.alpha_asset,
                 htlc.unlock_with_secret(self.alpha_ledger_redeem_identity, &secret),
             ),
+            network: self.alpha_ledger.network,
         }
     }
 }
@@ -83,19 +88,22 @@ impl Actions for SwapStates<Bob<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantit
             }
             SS::BothFunded(BothFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
             })
             | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
             })
             | SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
-            }) => vec![bob::ActionKind::Refund(
+            }) if beta_htlc_expiry.refundable_now => vec![bob::ActionKind::Refund(
                 swap.refund_action(*beta_htlc_location),
             )],
             SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
@@ -129,8 +137,8 @@ mod tests {
             beta_ledger_redeem_identity: "8457037fcd80a8650c4692d7fcfc1d0a96b92867"
                 .parse()
                 .unwrap(),
-            alpha_ledger: Bitcoin::default(),
-            beta_ledger: Ethereum::default(),
+            alpha_ledger: Bitcoin::regtest(),
+            beta_ledger: Ethereum::dev(),
             alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
             beta_asset: EtherQuantity::from_eth(10.0),
             alpha_ledger_lock_duration: bitcoin_support::Blocks::from(144),
@@ -149,5 +157,4 @@ mod tests {
             })
             .is_some());
     }
-
 }
@@ -31,6 +31,7 @@ impl OngoingSwap<Bob<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuantity>> {
                 bitcoin::Htlc::from(self.beta_htlc_params())
                     .unlock_after_timeout(self.beta_ledger_refund_identity),
             ),
+            network: self.beta_ledger.network,
         }
     }
 
@@ -43,10 +44,12 @@ impl OngoingSwap<Bob<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuantity>> {
         let gas_limit = Erc20Htlc::tx_gas_limit();
 
         ethereum::SendTransaction {
+            from: self.alpha_ledger_redeem_identity,
             to: alpha_htlc_location,
             data,
             gas_limit,
             value: EtherQuantity::zero(),
+            chain_id: self.alpha_ledger.chain_id,
         }
     }
 }
@@ -73,19 +76,22 @@ impl Actions for SwapStates<Bob<Ethereum, Bitcoin, Erc20Quantity, BitcoinQuantit
             }
             SS::BothFunded(BothFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
             })
             | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
             })
             | SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
-            }) => vec![bob::ActionKind::Refund(
+            }) if beta_htlc_expiry.refundable_now => vec![bob::ActionKind::Refund(
                 swap.refund_action(*beta_htlc_location),
             )],
             SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
@@ -0,0 +1,220 @@
+use crate::swap_protocols::{
+    ledger::Ethereum,
+    rfc003::{
+        bob::{
+            self,
+            actions::{Accept, Decline},
+        },
+        ethereum::{self, Erc20Htlc, Htlc},
+        state_machine::*,
+        Actions, Bob, Secret,
+    },
+};
+use ethereum_support::{Bytes, Erc20Quantity, EtherQuantity};
+
+impl OngoingSwap<Bob<Ethereum, Ethereum, Erc20Quantity, Erc20Quantity>> {
+    pub fn deploy_action(&self) -> ethereum::ContractDeploy {
+        let htlc = Erc20Htlc::from(self.beta_htlc_params());
+        let data = htlc.compile_to_hex().into();
+        let gas_limit = htlc.deployment_gas_limit();
+
+        ethereum::ContractDeploy {
+            from: self.beta_ledger_refund_identity,
+            data,
+            value: EtherQuantity::zero(),
+            gas_limit,
+            chain_id: self.beta_ledger.chain_id,
+        }
+    }
+
+    pub fn refund_action(
+        &self,
+        beta_htlc_location: ethereum_support::Address,
+    ) -> ethereum::SendTransaction {
+        let data = Bytes::default();
+        let gas_limit = Erc20Htlc::tx_gas_limit();
+
+        ethereum::SendTransaction {
+            from: self.beta_ledger_refund_identity,
+            to: beta_htlc_location,
+            data,
+            gas_limit,
+            value: EtherQuantity::zero(),
+            chain_id: self.beta_ledger.chain_id,
+        }
+    }
+
+    pub fn fund_action(
+        &self,
+        beta_htlc_location: ethereum_support::Address,
+    ) -> ethereum::SendTransaction {
+        let htlc = Erc20Htlc::from(self.beta_htlc_params());
+        let gas_limit = Erc20Htlc::fund_tx_gas_limit();
+
+        ethereum::SendTransaction {
+            from: self.beta_ledger_refund_identity,
+            to: self.beta_asset.token_contract(),
+            data: htlc.funding_tx_payload(beta_htlc_location),
+            gas_limit,
+            value: EtherQuantity::zero(),
+            chain_id: self.beta_ledger.chain_id,
+        }
+    }
+
+    pub fn redeem_action(
+        &self,
+        alpha_htlc_location: ethereum_support::Address,
+        secret: Secret,
+    ) -> ethereum::SendTransaction {
+        let data = Bytes::from(secret.raw_secret().to_vec());
+        let gas_limit = Erc20Htlc::tx_gas_limit();
+
+        ethereum::SendTransaction {
+            from: self.alpha_ledger_redeem_identity,
+            to: alpha_htlc_location,
+            data,
+            gas_limit,
+            value: EtherQuantity::zero(),
+            chain_id: self.alpha_ledger.chain_id,
+        }
+    }
+}
+
+impl Actions for SwapStates<Bob<Ethereum, Ethereum, Erc20Quantity, Erc20Quantity>> {
+    type ActionKind = bob::ActionKind<
+        Accept<Ethereum, Ethereum>,
+        Decline<Ethereum, Ethereum>,
+        ethereum::ContractDeploy,
+        ethereum::SendTransaction,
+        ethereum::SendTransaction,
+        ethereum::SendTransaction,
+    >;
+
+    fn actions(&self) -> Vec<Self::ActionKind> {
+        use self::SwapStates as SS;
+        match *self {
+            SS::Start(Start { ref role, .. }) => vec![
+                bob::ActionKind::Accept(role.accept_action()),
+                bob::ActionKind::Decline(role.decline_action()),
+            ],
+            SS::AlphaFunded(AlphaFunded { ref swap, .. }) => {
+                vec![bob::ActionKind::Deploy(swap.deploy_action())]
+            }
+            SS::AlphaFundedBetaDeployed(AlphaFundedBetaDeployed {
+                ref swap,
+                ref beta_htlc_location,
+                ..
+            }) => vec![bob::ActionKind::Fund(swap.fund_action(*beta_htlc_location))],
+            SS::BothFunded(BothFunded {
+                ref beta_htlc_location,
+                ref beta_htlc_expiry,
+                ref swap,
+                ..
+            })
+            | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
+                ref beta_htlc_location,
+                ref beta_htlc_expiry,
+                ref swap,
+                ..
+            })
+            | SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
+                ref beta_htlc_location,
+                ref beta_htlc_expiry,
+                ref swap,
+                ..
+            }) if beta_htlc_expiry.refundable_now => vec![bob::ActionKind::Refund(
+                swap.refund_action(*beta_htlc_location),
+            )],
+            SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
+                ref swap,
+                ref alpha_htlc_location,
+                ref beta_redeemed_tx,
+                ..
+            }) => vec![bob::ActionKind::Redeem(
+                swap.redeem_action(*alpha_htlc_location, beta_redeemed_tx.secret),
+            )],
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::rfc003::{ethereum::Seconds, Secret};
+    use ethereum_support::{Address, U256};
+
+    fn swap() -> OngoingSwap<Bob<Ethereum, Ethereum, Erc20Quantity, Erc20Quantity>> {
+        OngoingSwap {
+            alpha_ledger: Ethereum::dev(),
+            beta_ledger: Ethereum::dev(),
+            alpha_asset: Erc20Quantity::new(Address::from(0xAA), U256::from(1_000)),
+            beta_asset: Erc20Quantity::new(Address::from(0xBB), U256::from(2_000)),
+            alpha_ledger_redeem_identity: Address::from(0x01),
+            alpha_ledger_refund_identity: Address::from(0x02),
+            beta_ledger_redeem_identity: Address::from(0x03),
+            beta_ledger_refund_identity: Address::from(0x04),
+            alpha_ledger_lock_duration: Seconds(86_400),
+            beta_ledger_lock_duration: Seconds(43_200),
+            secret: Secret::from(*b"hello world, you are beautiful!!").hash(),
+            role: Bob::create().0,
+        }
+    }
+
+    #[test]
+    fn beta_erc20_htlc_is_deployed_and_then_funded() {
+        let alpha_funded = SwapStates::from(AlphaFunded {
+            swap: swap(),
+            alpha_htlc_location: Address::from(0xCC),
+            alpha_htlc_expiry: HtlcExpiry::default(),
+        });
+
+        match alpha_funded.actions().as_slice() {
+            [bob::ActionKind::Deploy(deploy)] => {
+                assert_eq!(deploy.value, EtherQuantity::zero())
+            }
+            actions => panic!("Unexpected actions: {:?}", actions),
+        }
+
+        let beta_deployed = SwapStates::from(AlphaFundedBetaDeployed {
+            swap: swap(),
+            alpha_htlc_location: Address::from(0xCC),
+            beta_htlc_location: Address::from(0xDD),
+            alpha_htlc_expiry: HtlcExpiry::default(),
+            beta_htlc_expiry: HtlcExpiry::default(),
+        });
+
+        match beta_deployed.actions().as_slice() {
+            [bob::ActionKind::Fund(fund)] => {
+                assert_eq!(fund.to, Address::from(0xBB));
+                assert_eq!(fund.value, EtherQuantity::zero());
+            }
+            actions => panic!("Unexpected actions: {:?}", actions),
+        }
+    }
+
+    #[test]
+    fn refunds_beta_htlc_only_once_it_expired() {
+        let both_funded = |refundable_now| {
+            SwapStates::from(BothFunded {
+                swap: swap(),
+                alpha_htlc_location: Address::from(0xCC),
+                beta_htlc_location: Address::from(0xDD),
+                alpha_htlc_expiry: HtlcExpiry::default(),
+                beta_htlc_expiry: HtlcExpiry {
+                    refundable_at: None,
+                    refundable_now,
+                },
+            })
+        };
+
+        assert!(both_funded(false).actions().is_empty());
+        match both_funded(true).actions().as_slice() {
+            [bob::ActionKind::Refund(refund)] => {
+                assert_eq!(refund.to, Address::from(0xDD));
+                assert_eq!(refund.gas_limit, Erc20Htlc::tx_gas_limit());
+            }
+            actions => panic!("Unexpected actions: {:?}", actions),
+        }
+    }
+}
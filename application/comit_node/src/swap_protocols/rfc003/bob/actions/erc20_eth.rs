@@ -0,0 +1,180 @@
+use crate::swap_protocols::{
+    ledger::Ethereum,
+    rfc003::{
+        bob::{
+            self,
+            actions::{Accept, Decline},
+        },
+        ethereum::{self, Erc20Htlc, EtherHtlc, Htlc},
+        state_machine::*,
+        Actions, Bob, Secret,
+    },
+};
+use ethereum_support::{Bytes, Erc20Quantity, EtherQuantity};
+
+impl OngoingSwap<Bob<Ethereum, Ethereum, Erc20Quantity, EtherQuantity>> {
+    pub fn fund_action(&self) -> ethereum::ContractDeploy {
+        let htlc = EtherHtlc::from(self.beta_htlc_params());
+        let data = htlc.compile_to_hex().into();
+        let gas_limit = htlc.deployment_gas_limit();
+
+        ethereum::ContractDeploy {
+            from: self.beta_ledger_refund_identity,
+            data,
+            value: self.beta_asset,
+            gas_limit,
+            chain_id: self.beta_ledger.chain_id,
+        }
+    }
+
+    pub fn refund_action(
+        &self,
+        beta_htlc_location: ethereum_support::Address,
+    ) -> ethereum::SendTransaction {
+        let data = Bytes::default();
+        let gas_limit = EtherHtlc::tx_gas_limit();
+
+        ethereum::SendTransaction {
+            from: self.beta_ledger_refund_identity,
+            to: beta_htlc_location,
+            data,
+            gas_limit,
+            value: EtherQuantity::zero(),
+            chain_id: self.beta_ledger.chain_id,
+        }
+    }
+
+    pub fn redeem_action(
+        &self,
+        alpha_htlc_location: ethereum_support::Address,
+        secret: Secret,
+    ) -> ethereum::SendTransaction {
+        let data = Bytes::from(secret.raw_secret().to_vec());
+        let gas_limit = Erc20Htlc::tx_gas_limit();
+
+        ethereum::SendTransaction {
+            from: self.alpha_ledger_redeem_identity,
+            to: alpha_htlc_location,
+            data,
+            gas_limit,
+            value: EtherQuantity::zero(),
+            chain_id: self.alpha_ledger.chain_id,
+        }
+    }
+}
+
+impl Actions for SwapStates<Bob<Ethereum, Ethereum, Erc20Quantity, EtherQuantity>> {
+    type ActionKind = bob::ActionKind<
+        Accept<Ethereum, Ethereum>,
+        Decline<Ethereum, Ethereum>,
+        (),
+        ethereum::ContractDeploy,
+        ethereum::SendTransaction,
+        ethereum::SendTransaction,
+    >;
+
+    fn actions(&self) -> Vec<Self::ActionKind> {
+        use self::SwapStates as SS;
+        match *self {
+            SS::Start(Start { ref role, .. }) => vec![
+                bob::ActionKind::Accept(role.accept_action()),
+                bob::ActionKind::Decline(role.decline_action()),
+            ],
+            SS::AlphaFunded(AlphaFunded { ref swap, .. }) => {
+                vec![bob::ActionKind::Fund(swap.fund_action())]
+            }
+            SS::BothFunded(BothFunded {
+                ref beta_htlc_location,
+                ref beta_htlc_expiry,
+                ref swap,
+                ..
+            })
+            | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
+                ref beta_htlc_location,
+                ref beta_htlc_expiry,
+                ref swap,
+                ..
+            })
+            | SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
+                ref beta_htlc_location,
+                ref beta_htlc_expiry,
+                ref swap,
+                ..
+            }) if beta_htlc_expiry.refundable_now => vec![bob::ActionKind::Refund(
+                swap.refund_action(*beta_htlc_location),
+            )],
+            SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
+                ref swap,
+                ref alpha_htlc_location,
+                ref beta_redeemed_tx,
+                ..
+            }) => vec![bob::ActionKind::Redeem(
+                swap.redeem_action(*alpha_htlc_location, beta_redeemed_tx.secret),
+            )],
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::rfc003::{ethereum::Seconds, Secret};
+    use ethereum_support::{Address, U256};
+
+    fn swap() -> OngoingSwap<Bob<Ethereum, Ethereum, Erc20Quantity, EtherQuantity>> {
+        OngoingSwap {
+            alpha_ledger: Ethereum::dev(),
+            beta_ledger: Ethereum::dev(),
+            alpha_asset: Erc20Quantity::new(Address::from(0xAA), U256::from(1_000)),
+            beta_asset: EtherQuantity::from_eth(1.0),
+            alpha_ledger_redeem_identity: Address::from(0x01),
+            alpha_ledger_refund_identity: Address::from(0x02),
+            beta_ledger_redeem_identity: Address::from(0x03),
+            beta_ledger_refund_identity: Address::from(0x04),
+            alpha_ledger_lock_duration: Seconds(86_400),
+            beta_ledger_lock_duration: Seconds(43_200),
+            secret: Secret::from(*b"hello world, you are beautiful!!").hash(),
+            role: Bob::create().0,
+        }
+    }
+
+    #[test]
+    fn beta_ether_htlc_is_funded_on_deployment() {
+        let alpha_funded = SwapStates::from(AlphaFunded {
+            swap: swap(),
+            alpha_htlc_location: Address::from(0xCC),
+            alpha_htlc_expiry: HtlcExpiry::default(),
+        });
+
+        match alpha_funded.actions().as_slice() {
+            [bob::ActionKind::Fund(fund)] => assert_eq!(fund.value, EtherQuantity::from_eth(1.0)),
+            actions => panic!("Unexpected actions: {:?}", actions),
+        }
+    }
+
+    #[test]
+    fn refunds_beta_htlc_only_once_it_expired() {
+        let both_funded = |refundable_now| {
+            SwapStates::from(BothFunded {
+                swap: swap(),
+                alpha_htlc_location: Address::from(0xCC),
+                beta_htlc_location: Address::from(0xDD),
+                alpha_htlc_expiry: HtlcExpiry::default(),
+                beta_htlc_expiry: HtlcExpiry {
+                    refundable_at: None,
+                    refundable_now,
+                },
+            })
+        };
+
+        assert!(both_funded(false).actions().is_empty());
+        match both_funded(true).actions().as_slice() {
+            [bob::ActionKind::Refund(refund)] => {
+                assert_eq!(refund.to, Address::from(0xDD));
+                assert_eq!(refund.gas_limit, EtherHtlc::tx_gas_limit());
+            }
+            actions => panic!("Unexpected actions: {:?}", actions),
+        }
+    }
+}
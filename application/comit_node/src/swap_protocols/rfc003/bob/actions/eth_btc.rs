@@ -32,6 +32,7 @@ impl OngoingSwap<Bob<Ethereum, Bitcoin, EtherQuantity, BitcoinQuantity>> {
                 bitcoin::Htlc::from(self.beta_htlc_params())
                     .unlock_after_timeout(self.beta_ledger_refund_identity),
             ),
+            network: self.beta_ledger.network,
         }
     }
 
@@ -44,10 +45,12 @@ impl OngoingSwap<Bob<Ethereum, Bitcoin, EtherQuantity, BitcoinQuantity>> {
         let gas_limit = EtherHtlc::tx_gas_limit();
 
         ethereum::SendTransaction {
+            from: self.alpha_ledger_redeem_identity,
             to: alpha_htlc_location,
             data,
             gas_limit,
             value: EtherQuantity::from_wei(U256::zero()),
+            chain_id: self.alpha_ledger.chain_id,
         }
     }
 }
@@ -74,19 +77,22 @@ impl Actions for SwapStates<Bob<Ethereum, Bitcoin, EtherQuantity, BitcoinQuantit
             }
             SS::BothFunded(BothFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
             })
             | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
             })
             | SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
                 ref beta_htlc_location,
+                ref beta_htlc_expiry,
                 ref swap,
                 ..
-            }) => vec![bob::ActionKind::Refund(
+            }) if beta_htlc_expiry.refundable_now => vec![bob::ActionKind::Refund(
                 swap.refund_action(*beta_htlc_location),
             )],
             SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
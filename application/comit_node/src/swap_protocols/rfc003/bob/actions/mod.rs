@@ -1,7 +1,10 @@
 mod btc_erc20;
 mod btc_eth;
 mod erc20_btc;
+mod erc20_erc20;
+mod erc20_eth;
 mod eth_btc;
+mod eth_erc20;
 
 use crate::{
     comit_client::{SwapDeclineReason, SwapReject},
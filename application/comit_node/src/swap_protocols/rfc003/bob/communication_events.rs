@@ -3,7 +3,7 @@ use crate::{
     swap_protocols::{
         asset::Asset,
         rfc003::{
-            events::{CommunicationEvents, ResponseFuture},
+            events::{CommunicationEvents, RequestAborted, ResponseFuture},
             ledger::Ledger,
             Bob,
         },
@@ -14,11 +14,19 @@ use crate::{
 pub struct BobToAlice<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> {
     #[debug_stub = "ResponseFuture"]
     response_future: Box<ResponseFuture<Bob<AL, BL, AA, BA>>>,
+    #[debug_stub = "RequestAborted"]
+    request_aborted: Box<RequestAborted>,
 }
 
 impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> BobToAlice<AL, BL, AA, BA> {
-    pub fn new(response_future: Box<ResponseFuture<Bob<AL, BL, AA, BA>>>) -> Self {
-        Self { response_future }
+    pub fn new(
+        response_future: Box<ResponseFuture<Bob<AL, BL, AA, BA>>>,
+        request_aborted: Box<RequestAborted>,
+    ) -> Self {
+        Self {
+            response_future,
+            request_aborted,
+        }
     }
 }
 
@@ -31,4 +39,11 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> CommunicationEvents<Bob<AL, B
     ) -> &mut ResponseFuture<Bob<AL, BL, AA, BA>> {
         &mut self.response_future
     }
+
+    /// Bob only ever declines a request, he never aborts it
+    fn abort_request(&mut self, _request: &comit_client::rfc003::Request<AL, BL, AA, BA>) {}
+
+    fn request_aborted(&mut self) -> &mut RequestAborted {
+        &mut self.request_aborted
+    }
 }
@@ -1,9 +1,13 @@
+mod aborts;
 pub mod actions;
 mod communication_events;
+pub mod policy;
 mod spawner;
 mod swap_request;
 
-pub use self::{actions::*, communication_events::*, spawner::*, swap_request::SwapRequest};
+pub use self::{
+    aborts::Aborts, actions::*, communication_events::*, spawner::*, swap_request::SwapRequest,
+};
 
 use crate::{
     comit_client::SwapReject,
@@ -12,7 +16,7 @@ use crate::{
         asset::Asset,
         rfc003::{
             bob::actions::{Accept, Decline},
-            events::{LedgerEvents, ResponseFuture},
+            events::{LedgerEvents, RequestAborted, ResponseFuture},
             ledger::Ledger,
             role::Initiation,
             save_state::SaveState,
@@ -23,9 +27,11 @@ use crate::{
 };
 
 use futures::{sync::oneshot, Future};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     marker::PhantomData,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 #[derive(Debug, Clone)]
@@ -53,7 +59,13 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> Bob<AL, BL, AA, BA> {
         alpha_ledger_events: Box<dyn LedgerEvents<AL, AA>>,
         beta_ledger_events: Box<dyn LedgerEvents<BL, BA>>,
         save_state: Arc<dyn SaveState<Self>>,
-    ) -> (Box<FutureSwapOutcome<Self>>, Box<ResponseFuture<Self>>) {
+        request_aborted: Box<RequestAborted>,
+        lock_duration_margin: Duration,
+    ) -> (
+        Self,
+        Box<FutureSwapOutcome<Self>>,
+        Box<ResponseFuture<Self>>,
+    ) {
         let (bob, response_future) = Self::create();
 
         // We need to duplicate the future
@@ -69,7 +81,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> Bob<AL, BL, AA, BA> {
             beta_ledger_redeem_identity: initiation.beta_ledger_redeem_identity,
             alpha_ledger_lock_duration: initiation.alpha_ledger_lock_duration,
             secret: initiation.secret,
-            role: bob,
+            role: bob.clone(),
         };
 
         save_state.save(start_state.clone().into());
@@ -77,11 +89,16 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> Bob<AL, BL, AA, BA> {
         let context = Context {
             alpha_ledger_events,
             beta_ledger_events,
-            communication_events: Box::new(BobToAlice::new(Box::new(response_for_state_machine))),
+            communication_events: Box::new(BobToAlice::new(
+                Box::new(response_for_state_machine),
+                request_aborted,
+            )),
             state_repo: save_state,
+            lock_duration_margin,
         };
 
         (
+            bob,
             Box::new(Swap::start_in(start_state, context)),
             Box::new(response_for_caller),
         )
@@ -110,6 +127,32 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> Bob<AL, BL, AA, BA> {
     }
 }
 
+/// The response sender is only meaningful for the lifetime of the process
+/// that created it, hence Bob is persisted without it. A deserialized Bob can
+/// no longer accept or decline a swap request.
+impl<AL: Ledger, BL: Ledger, AA, BA> Serialize for Bob<AL, BL, AA, BA> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_unit()
+    }
+}
+
+impl<'de, AL: Ledger, BL: Ledger, AA, BA> Deserialize<'de> for Bob<AL, BL, AA, BA> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <()>::deserialize(deserializer)?;
+
+        Ok(Bob {
+            phantom_data: PhantomData,
+            response_sender: Arc::new(Mutex::new(None)),
+        })
+    }
+}
+
 impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> Role for Bob<AL, BL, AA, BA> {
     type AlphaLedger = AL;
     type BetaLedger = BL;
@@ -0,0 +1,776 @@
+use crate::{
+    comit_client::SwapDeclineReason,
+    swap_protocols::{
+        ledger::{Bitcoin, Ethereum},
+        metadata_store::AssetKind,
+        rfc003::{
+            bob::SwapRequest, ethereum::Seconds, state_machine::StateMachineResponse, Ledger,
+            SecretSource,
+        },
+        SwapId,
+    },
+};
+use bitcoin_support::{BitcoinQuantity, Blocks};
+use ethereum_support::{Address, Erc20Quantity, EtherQuantity, ToFloat};
+use futures::{future, Future};
+use reqwest::{r#async::Client, StatusCode};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::timer::Timeout;
+use url::Url;
+
+/// Why the policy declines a swap request
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeclineReason {
+    BadRate,
+    /// Bob does not trade the requested pair of assets
+    UnsupportedSwap,
+    BadQuantity,
+    BadLockDuration,
+    /// Bob is already busy with as many swaps as he handles
+    TooManySwaps,
+}
+
+impl DeclineReason {
+    /// The reason sent to the counterparty. RFC003 only defines `BadRate`,
+    /// every other request is declined without a reason.
+    pub fn to_swap_decline_reason(self) -> Option<SwapDeclineReason> {
+        match self {
+            DeclineReason::BadRate => Some(SwapDeclineReason::BadRate),
+            DeclineReason::UnsupportedSwap
+            | DeclineReason::BadQuantity
+            | DeclineReason::BadLockDuration
+            | DeclineReason::TooManySwaps => None,
+        }
+    }
+}
+
+/// An ERC20 token Bob trades, its quantities are converted to the nominal
+/// unit of the token with the given number of decimals
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Token {
+    pub token_contract: Address,
+    pub decimals: u16,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Tokens {
+    decimals: HashMap<Address, u16>,
+}
+
+impl Tokens {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Tokens {
+            decimals: tokens
+                .into_iter()
+                .map(|token| (token.token_contract, token.decimals))
+                .collect(),
+        }
+    }
+
+    pub fn decimals(&self, token_contract: &Address) -> Option<u16> {
+        self.decimals.get(token_contract).cloned()
+    }
+}
+
+/// An asset as seen by the policy, the quantity is expressed in the nominal
+/// unit of the asset, e.g. BTC instead of satoshi
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolicyAsset {
+    pub kind: AssetKind,
+    pub token_contract: Option<Address>,
+    pub quantity: f64,
+}
+
+pub trait ToPolicyAsset {
+    /// `None` for ERC20 tokens which are not configured
+    fn to_policy_asset(&self, tokens: &Tokens) -> Option<PolicyAsset>;
+}
+
+impl ToPolicyAsset for BitcoinQuantity {
+    fn to_policy_asset(&self, _: &Tokens) -> Option<PolicyAsset> {
+        Some(PolicyAsset {
+            kind: AssetKind::Bitcoin,
+            token_contract: None,
+            quantity: self.bitcoin(),
+        })
+    }
+}
+
+impl ToPolicyAsset for EtherQuantity {
+    fn to_policy_asset(&self, _: &Tokens) -> Option<PolicyAsset> {
+        Some(PolicyAsset {
+            kind: AssetKind::Ether,
+            token_contract: None,
+            quantity: self.ethereum(),
+        })
+    }
+}
+
+impl ToPolicyAsset for Erc20Quantity {
+    fn to_policy_asset(&self, tokens: &Tokens) -> Option<PolicyAsset> {
+        let token_contract = self.token_contract();
+        let decimals = tokens.decimals(&token_contract)?;
+
+        Some(PolicyAsset {
+            kind: AssetKind::Erc20,
+            token_contract: Some(token_contract),
+            quantity: self.quantity().to_float(i64::from(decimals)),
+        })
+    }
+}
+
+/// The parts of a swap request the policy decides upon
+#[derive(Clone, Debug, PartialEq)]
+pub struct Proposal {
+    pub alpha_asset: PolicyAsset,
+    pub beta_asset: PolicyAsset,
+    pub alpha_ledger_lock_duration: Duration,
+}
+
+impl Proposal {
+    /// The amount of the alpha asset Bob receives for one unit of the beta
+    /// asset he gives away. Proposals with a zero quantity are declined
+    /// before the rate is looked at.
+    pub fn rate(&self) -> f64 {
+        self.alpha_asset.quantity / self.beta_asset.quantity
+    }
+}
+
+/// What Bob answers with when the policy accepts a swap request
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResponseParameters {
+    pub ethereum_identity: Address,
+    pub bitcoin_lock_duration: Blocks,
+    pub ethereum_lock_duration: Seconds,
+}
+
+pub trait AutoRespond<AL: Ledger, BL: Ledger> {
+    /// `None` if one of the assets cannot be judged by the policy
+    fn proposal(&self, tokens: &Tokens) -> Option<Proposal>;
+
+    fn accept_response(
+        &self,
+        parameters: &ResponseParameters,
+        id: SwapId,
+        secret_source: &dyn SecretSource,
+    ) -> StateMachineResponse<AL::HtlcIdentity, BL::HtlcIdentity, BL::LockDuration>;
+}
+
+impl<AA: ToPolicyAsset, BA: ToPolicyAsset> AutoRespond<Bitcoin, Ethereum>
+    for SwapRequest<Bitcoin, Ethereum, AA, BA>
+{
+    fn proposal(&self, tokens: &Tokens) -> Option<Proposal> {
+        Some(Proposal {
+            alpha_asset: self.alpha_asset.to_policy_asset(tokens)?,
+            beta_asset: self.beta_asset.to_policy_asset(tokens)?,
+            alpha_ledger_lock_duration: self.alpha_ledger_lock_duration.into(),
+        })
+    }
+
+    fn accept_response(
+        &self,
+        parameters: &ResponseParameters,
+        id: SwapId,
+        secret_source: &dyn SecretSource,
+    ) -> StateMachineResponse<secp256k1_support::KeyPair, Address, Seconds> {
+        StateMachineResponse {
+            alpha_ledger_redeem_identity: secret_source.new_secp256k1_redeem(id),
+            beta_ledger_refund_identity: parameters.ethereum_identity,
+            beta_ledger_lock_duration: parameters.ethereum_lock_duration,
+        }
+    }
+}
+
+impl<AA: ToPolicyAsset, BA: ToPolicyAsset> AutoRespond<Ethereum, Bitcoin>
+    for SwapRequest<Ethereum, Bitcoin, AA, BA>
+{
+    fn proposal(&self, tokens: &Tokens) -> Option<Proposal> {
+        Some(Proposal {
+            alpha_asset: self.alpha_asset.to_policy_asset(tokens)?,
+            beta_asset: self.beta_asset.to_policy_asset(tokens)?,
+            alpha_ledger_lock_duration: self.alpha_ledger_lock_duration.into(),
+        })
+    }
+
+    fn accept_response(
+        &self,
+        parameters: &ResponseParameters,
+        id: SwapId,
+        secret_source: &dyn SecretSource,
+    ) -> StateMachineResponse<Address, secp256k1_support::KeyPair, Blocks> {
+        StateMachineResponse {
+            alpha_ledger_redeem_identity: parameters.ethereum_identity,
+            beta_ledger_refund_identity: secret_source.new_secp256k1_refund(id),
+            beta_ledger_lock_duration: parameters.bitcoin_lock_duration,
+        }
+    }
+}
+
+impl<AA: ToPolicyAsset, BA: ToPolicyAsset> AutoRespond<Ethereum, Ethereum>
+    for SwapRequest<Ethereum, Ethereum, AA, BA>
+{
+    fn proposal(&self, tokens: &Tokens) -> Option<Proposal> {
+        Some(Proposal {
+            alpha_asset: self.alpha_asset.to_policy_asset(tokens)?,
+            beta_asset: self.beta_asset.to_policy_asset(tokens)?,
+            alpha_ledger_lock_duration: self.alpha_ledger_lock_duration.into(),
+        })
+    }
+
+    fn accept_response(
+        &self,
+        parameters: &ResponseParameters,
+        _: SwapId,
+        _: &dyn SecretSource,
+    ) -> StateMachineResponse<Address, Address, Seconds> {
+        StateMachineResponse {
+            alpha_ledger_redeem_identity: parameters.ethereum_identity,
+            beta_ledger_refund_identity: parameters.ethereum_identity,
+            beta_ledger_lock_duration: parameters.ethereum_lock_duration,
+        }
+    }
+}
+
+/// Identifies an asset in the configuration, ERC20 tokens are only matched if
+/// the token contract is given
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct AssetSpec {
+    pub name: AssetKind,
+    #[serde(default)]
+    pub token_contract: Option<Address>,
+}
+
+impl AssetSpec {
+    pub fn matches(&self, asset: &PolicyAsset) -> bool {
+        self.name == asset.kind && self.token_contract == asset.token_contract
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Rule {
+    pub alpha_asset: AssetSpec,
+    pub beta_asset: AssetSpec,
+    #[serde(default)]
+    pub min_alpha_quantity: Option<f64>,
+    #[serde(default)]
+    pub max_alpha_quantity: Option<f64>,
+    #[serde(default)]
+    pub min_beta_quantity: Option<f64>,
+    #[serde(default)]
+    pub max_beta_quantity: Option<f64>,
+    /// Bitcoin lock durations are converted assuming 10 minutes per block
+    #[serde(default)]
+    pub min_alpha_ledger_lock_duration_secs: Option<u64>,
+}
+
+impl Rule {
+    fn matches(&self, proposal: &Proposal) -> bool {
+        self.alpha_asset.matches(&proposal.alpha_asset)
+            && self.beta_asset.matches(&proposal.beta_asset)
+    }
+
+    fn check(&self, proposal: &Proposal) -> Result<(), DeclineReason> {
+        let alpha_quantity = proposal.alpha_asset.quantity;
+        let beta_quantity = proposal.beta_asset.quantity;
+
+        if alpha_quantity <= 0.0 || beta_quantity <= 0.0 {
+            return Err(DeclineReason::BadQuantity);
+        }
+
+        if !within(
+            alpha_quantity,
+            self.min_alpha_quantity,
+            self.max_alpha_quantity,
+        ) || !within(
+            beta_quantity,
+            self.min_beta_quantity,
+            self.max_beta_quantity,
+        ) {
+            return Err(DeclineReason::BadQuantity);
+        }
+
+        if let Some(min) = self.min_alpha_ledger_lock_duration_secs {
+            if proposal.alpha_ledger_lock_duration < Duration::from_secs(min) {
+                return Err(DeclineReason::BadLockDuration);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn within(quantity: f64, min: Option<f64>, max: Option<f64>) -> bool {
+    min.map_or(true, |min| quantity >= min) && max.map_or(true, |max| quantity <= max)
+}
+
+#[derive(Debug, Fail)]
+pub enum RateSourceError {
+    #[fail(display = "Could not fetch the rate: {}", _0)]
+    Fetch(String),
+    #[fail(display = "The rate source did not answer in time")]
+    Timeout,
+}
+
+impl From<reqwest::Error> for RateSourceError {
+    fn from(e: reqwest::Error) -> Self {
+        RateSourceError::Fetch(e.to_string())
+    }
+}
+
+impl From<url::ParseError> for RateSourceError {
+    fn from(e: url::ParseError) -> Self {
+        RateSourceError::Fetch(e.to_string())
+    }
+}
+
+pub trait RateSource: Debug + Send + Sync + 'static {
+    /// The minimum rate (see `Proposal::rate`) Bob accepts for the given pair
+    /// or `None` if the pair is unknown to the rate source
+    fn min_rate(
+        &self,
+        alpha_asset: &PolicyAsset,
+        beta_asset: &PolicyAsset,
+    ) -> Box<dyn Future<Item = Option<f64>, Error = RateSourceError> + Send>;
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct StaticRate {
+    pub alpha_asset: AssetSpec,
+    pub beta_asset: AssetSpec,
+    pub min_rate: f64,
+}
+
+#[derive(Debug)]
+pub struct StaticRateSource {
+    rates: Vec<StaticRate>,
+}
+
+impl StaticRateSource {
+    pub fn new(rates: Vec<StaticRate>) -> Self {
+        StaticRateSource { rates }
+    }
+}
+
+impl RateSource for StaticRateSource {
+    fn min_rate(
+        &self,
+        alpha_asset: &PolicyAsset,
+        beta_asset: &PolicyAsset,
+    ) -> Box<dyn Future<Item = Option<f64>, Error = RateSourceError> + Send> {
+        Box::new(future::ok(
+            self.rates
+                .iter()
+                .find(|rate| {
+                    rate.alpha_asset.matches(alpha_asset) && rate.beta_asset.matches(beta_asset)
+                })
+                .map(|rate| rate.min_rate),
+        ))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct HttpRateResponse {
+    min_rate: f64,
+}
+
+/// Asks a local price endpoint for the rate, e.g.
+/// `GET <url>?alpha_asset=Bitcoin&beta_asset=Ether` which is expected to
+/// respond with `{ "min_rate": 0.03 }` or `404` for unknown pairs
+#[derive(Debug)]
+pub struct HttpRateSource {
+    client: Client,
+    url: Url,
+    timeout: Duration,
+}
+
+impl HttpRateSource {
+    pub fn new(url: Url, timeout: Duration) -> Self {
+        HttpRateSource {
+            client: Client::new(),
+            url,
+            timeout,
+        }
+    }
+}
+
+impl RateSource for HttpRateSource {
+    fn min_rate(
+        &self,
+        alpha_asset: &PolicyAsset,
+        beta_asset: &PolicyAsset,
+    ) -> Box<dyn Future<Item = Option<f64>, Error = RateSourceError> + Send> {
+        let mut url = self.url.clone();
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("alpha_asset", &alpha_asset.kind.to_string());
+            query.append_pair("beta_asset", &beta_asset.kind.to_string());
+            if let Some(token_contract) = alpha_asset.token_contract {
+                query.append_pair("alpha_token_contract", &format!("{:x}", token_contract));
+            }
+            if let Some(token_contract) = beta_asset.token_contract {
+                query.append_pair("beta_token_contract", &format!("{:x}", token_contract));
+            }
+        }
+
+        let min_rate = self
+            .client
+            .get(url)
+            .send()
+            .and_then(|response| -> Box<dyn Future<Item = _, Error = _> + Send> {
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Box::new(future::ok(None));
+                }
+
+                match response.error_for_status() {
+                    Ok(mut response) => Box::new(
+                        response
+                            .json::<HttpRateResponse>()
+                            .map(|response| Some(response.min_rate)),
+                    ),
+                    Err(e) => Box::new(future::err(e)),
+                }
+            })
+            .map_err(RateSourceError::from);
+
+        Box::new(Timeout::new(min_rate, self.timeout).map_err(|e| {
+            if e.is_elapsed() {
+                RateSourceError::Timeout
+            } else {
+                e.into_inner()
+                    .unwrap_or_else(|| RateSourceError::Fetch(String::from("timer failed")))
+            }
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub enum Decision {
+    Accept(ActiveSwap),
+    Decline(DeclineReason),
+    /// Leave the decision to the user of the HTTP API
+    Manual(ActiveSwap),
+}
+
+/// Counts towards the maximum number of concurrent swaps until dropped
+#[derive(Debug)]
+pub struct ActiveSwap {
+    active_swaps: Arc<AtomicUsize>,
+}
+
+impl Drop for ActiveSwap {
+    fn drop(&mut self) {
+        self.active_swaps.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Decides whether Bob accepts or declines an incoming swap request without
+/// waiting for the user to do so through the HTTP API.
+#[derive(Debug)]
+pub struct Policy {
+    rules: Vec<Rule>,
+    max_concurrent_swaps: Option<usize>,
+    rate_source: Arc<dyn RateSource>,
+    response_parameters: ResponseParameters,
+    tokens: Tokens,
+    active_swaps: Arc<AtomicUsize>,
+}
+
+impl Policy {
+    pub fn new(
+        rules: Vec<Rule>,
+        max_concurrent_swaps: Option<usize>,
+        rate_source: Arc<dyn RateSource>,
+        response_parameters: ResponseParameters,
+        tokens: Tokens,
+    ) -> Self {
+        Policy {
+            rules,
+            max_concurrent_swaps,
+            rate_source,
+            response_parameters,
+            tokens,
+            active_swaps: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn response_parameters(&self) -> &ResponseParameters {
+        &self.response_parameters
+    }
+
+    pub fn tokens(&self) -> &Tokens {
+        &self.tokens
+    }
+
+    /// Resolves once the rate source answered, a rate source which fails
+    /// leaves the decision to the user
+    pub fn decide(
+        &self,
+        proposal: &Proposal,
+    ) -> Box<dyn Future<Item = Decision, Error = ()> + Send> {
+        let active_swap = match self.reserve() {
+            Some(active_swap) => active_swap,
+            None => return decided(Decision::Decline(DeclineReason::TooManySwaps)),
+        };
+
+        let rule = match self.rules.iter().find(|rule| rule.matches(proposal)) {
+            Some(rule) => rule,
+            None => return decided(Decision::Decline(DeclineReason::UnsupportedSwap)),
+        };
+
+        if let Err(reason) = rule.check(proposal) {
+            return decided(Decision::Decline(reason));
+        }
+
+        let proposal = proposal.clone();
+        Box::new(
+            self.rate_source
+                .min_rate(&proposal.alpha_asset, &proposal.beta_asset)
+                .then(move |min_rate| {
+                    Ok::<_, ()>(match min_rate {
+                        Ok(Some(min_rate)) if proposal.rate() >= min_rate => {
+                            Decision::Accept(active_swap)
+                        }
+                        Ok(_) => Decision::Decline(DeclineReason::BadRate),
+                        Err(e) => {
+                            warn!(
+                                "Leaving the decision about {:?} to the user because the rate is unknown: {}",
+                                proposal, e
+                            );
+                            Decision::Manual(active_swap)
+                        }
+                    })
+                }),
+        )
+    }
+
+    fn reserve(&self) -> Option<ActiveSwap> {
+        let previously_active = self.active_swaps.fetch_add(1, Ordering::SeqCst);
+        let active_swap = ActiveSwap {
+            active_swaps: Arc::clone(&self.active_swaps),
+        };
+
+        match self.max_concurrent_swaps {
+            Some(max) if previously_active >= max => None,
+            _ => Some(active_swap),
+        }
+    }
+}
+
+fn decided(decision: Decision) -> Box<dyn Future<Item = Decision, Error = ()> + Send> {
+    Box::new(future::ok(decision))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_support::U256;
+    use spectral::prelude::*;
+    use std::net::TcpListener;
+
+    fn btc_eth_rule() -> Rule {
+        Rule {
+            alpha_asset: AssetSpec {
+                name: AssetKind::Bitcoin,
+                token_contract: None,
+            },
+            beta_asset: AssetSpec {
+                name: AssetKind::Ether,
+                token_contract: None,
+            },
+            min_alpha_quantity: Some(0.1),
+            max_alpha_quantity: Some(10.0),
+            min_beta_quantity: None,
+            max_beta_quantity: None,
+            min_alpha_ledger_lock_duration_secs: Some(24 * 60 * 60),
+        }
+    }
+
+    fn policy(max_concurrent_swaps: Option<usize>) -> Policy {
+        let rule = btc_eth_rule();
+
+        Policy::new(
+            vec![rule.clone()],
+            max_concurrent_swaps,
+            Arc::new(StaticRateSource::new(vec![StaticRate {
+                alpha_asset: rule.alpha_asset,
+                beta_asset: rule.beta_asset,
+                min_rate: 0.02,
+            }])),
+            ResponseParameters {
+                ethereum_identity: Address::from("0x00a329c0648769a73afac7f9381e08fb43dbea72"),
+                bitcoin_lock_duration: Blocks::new(72),
+                ethereum_lock_duration: Seconds(12 * 60 * 60),
+            },
+            Tokens::default(),
+        )
+    }
+
+    fn proposal(bitcoin: f64, ether: f64, lock_duration: Blocks) -> Proposal {
+        Proposal {
+            alpha_asset: BitcoinQuantity::from_bitcoin(bitcoin)
+                .to_policy_asset(&Tokens::default())
+                .unwrap(),
+            beta_asset: EtherQuantity::from_eth(ether)
+                .to_policy_asset(&Tokens::default())
+                .unwrap(),
+            alpha_ledger_lock_duration: lock_duration.into(),
+        }
+    }
+
+    fn declined_with(
+        decision: Box<dyn Future<Item = Decision, Error = ()> + Send>,
+    ) -> Option<DeclineReason> {
+        match decision.wait().unwrap() {
+            Decision::Decline(reason) => Some(reason),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn accepts_proposal_within_all_rules() {
+        let decision = policy(None).decide(&proposal(1.0, 40.0, Blocks::new(144)));
+
+        assert!(match decision.wait().unwrap() {
+            Decision::Accept(_) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn declines_unknown_pair() {
+        let proposal = Proposal {
+            alpha_asset: EtherQuantity::from_eth(1.0)
+                .to_policy_asset(&Tokens::default())
+                .unwrap(),
+            beta_asset: BitcoinQuantity::from_bitcoin(1.0)
+                .to_policy_asset(&Tokens::default())
+                .unwrap(),
+            alpha_ledger_lock_duration: Duration::from_secs(24 * 60 * 60),
+        };
+
+        assert_that(&declined_with(policy(None).decide(&proposal)))
+            .contains_value(DeclineReason::UnsupportedSwap);
+    }
+
+    #[test]
+    fn declines_bad_quantity_lock_duration_and_rate() {
+        let policy = policy(None);
+
+        assert_that(&declined_with(policy.decide(&proposal(
+            20.0,
+            1000.0,
+            Blocks::new(144),
+        ))))
+        .contains_value(DeclineReason::BadQuantity);
+        assert_that(&declined_with(policy.decide(&proposal(
+            1.0,
+            40.0,
+            Blocks::new(6),
+        ))))
+        .contains_value(DeclineReason::BadLockDuration);
+        assert_that(&declined_with(policy.decide(&proposal(
+            1.0,
+            60.0,
+            Blocks::new(144),
+        ))))
+        .contains_value(DeclineReason::BadRate);
+    }
+
+    #[test]
+    fn declines_zero_quantity_before_computing_the_rate() {
+        let policy = policy(None);
+
+        assert_that(&declined_with(policy.decide(&proposal(
+            1.0,
+            0.0,
+            Blocks::new(144),
+        ))))
+        .contains_value(DeclineReason::BadQuantity);
+    }
+
+    #[test]
+    fn declines_when_too_many_swaps_are_active() {
+        let policy = policy(Some(1));
+
+        let first = policy.decide(&proposal(1.0, 40.0, Blocks::new(144)));
+        assert_that(&declined_with(policy.decide(&proposal(
+            1.0,
+            40.0,
+            Blocks::new(144),
+        ))))
+        .contains_value(DeclineReason::TooManySwaps);
+
+        drop(first);
+        assert_that(&declined_with(policy.decide(&proposal(
+            1.0,
+            40.0,
+            Blocks::new(144),
+        ))))
+        .is_none();
+    }
+
+    #[test]
+    fn can_deserialize_rule() {
+        let rule = r#"{
+            "alpha_asset": { "name": "Bitcoin" },
+            "beta_asset": { "name": "Ether" },
+            "min_alpha_quantity": 0.1,
+            "max_alpha_quantity": 10.0,
+            "min_alpha_ledger_lock_duration_secs": 86400
+        }"#;
+
+        assert_that(&serde_json::from_str::<Rule>(rule)).is_ok_containing(btc_eth_rule());
+    }
+
+    #[test]
+    fn erc20_quantity_uses_configured_decimals() {
+        let token_contract = Address::from("0xb97048628db6b661d4c2aa833e95dbe1a905b280");
+        let tokens = Tokens::new(vec![Token {
+            token_contract,
+            decimals: 6,
+        }]);
+        let quantity = Erc20Quantity::new(token_contract, U256::from(2_500_000));
+
+        assert_that(
+            &quantity
+                .to_policy_asset(&tokens)
+                .map(|asset| asset.quantity),
+        )
+        .contains_value(2.5);
+        assert_that(&quantity.to_policy_asset(&Tokens::default())).is_none();
+    }
+
+    #[test]
+    fn only_rfc003_decline_reasons_are_sent() {
+        assert_that(&DeclineReason::BadRate.to_swap_decline_reason())
+            .contains_value(SwapDeclineReason::BadRate);
+        assert_that(&DeclineReason::BadQuantity.to_swap_decline_reason()).is_none();
+        assert_that(&DeclineReason::TooManySwaps.to_swap_decline_reason()).is_none();
+    }
+
+    #[test]
+    fn http_rate_source_gives_up_after_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/rate", listener.local_addr().unwrap())).unwrap();
+        let rate_source = HttpRateSource::new(url, Duration::from_millis(100));
+        let asset = BitcoinQuantity::from_bitcoin(1.0)
+            .to_policy_asset(&Tokens::default())
+            .unwrap();
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let result = runtime.block_on(rate_source.min_rate(&asset, &asset));
+
+        assert!(match result {
+            Err(RateSourceError::Timeout) => true,
+            _ => false,
+        });
+    }
+}
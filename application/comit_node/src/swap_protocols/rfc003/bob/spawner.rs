@@ -1,27 +1,36 @@
 use crate::{
     comit_client,
+    logging::{self, Instrument},
     swap_protocols::{
         asset::Asset,
         dependencies::{LedgerEventDependencies, ProtocolDependencies},
-        metadata_store::{self, Metadata, MetadataStore},
+        metadata_store::{self, IntoMetadata, MetadataStore, TransitionStore},
         rfc003::{
-            bob::SwapRequest,
+            self,
+            bob::{
+                actions::Decline,
+                policy::{ActiveSwap, AutoRespond, Decision, DeclineReason},
+                SwapRequest,
+            },
             create_ledger_events::CreateLedgerEvents,
             events::ResponseFuture,
+            save_failure,
             state_store::{self, StateStore},
-            Bob, Initiation, Ledger,
+            validate_lock_durations, Bob, Initiation, Ledger, SaveState, SecretHash,
         },
         SwapId,
     },
 };
 
-use futures::Future;
+use futures::{future, Future};
 use http_api_problem::HttpApiProblem;
+use std::{net::SocketAddr, sync::Arc};
 
 #[derive(Debug)]
 pub enum Error {
     Storage(state_store::Error),
     Metadata(metadata_store::Error),
+    UnsupportedNetwork,
 }
 
 impl From<Error> for HttpApiProblem {
@@ -30,6 +39,9 @@ impl From<Error> for HttpApiProblem {
         match e {
             Storage(e) => e.into(),
             Metadata(e) => e.into(),
+            UnsupportedNetwork => HttpApiProblem::new("unsupported-network")
+                .set_status(400)
+                .set_detail("The node is not configured for the network of one of the ledgers"),
         }
     }
 }
@@ -38,34 +50,78 @@ pub trait BobSpawner: Send + Sync + 'static {
     #[allow(clippy::type_complexity)]
     fn spawn<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset>(
         &self,
+        counterparty: SocketAddr,
         id: SwapId,
         swap_request: SwapRequest<AL, BL, AA, BA>,
     ) -> Result<Box<ResponseFuture<Bob<AL, BL, AA, BA>>>, Error>
     where
         LedgerEventDependencies: CreateLedgerEvents<AL, AA> + CreateLedgerEvents<BL, BA>,
-        SwapRequest<AL, BL, AA, BA>: Into<Metadata>;
+        SwapRequest<AL, BL, AA, BA>: IntoMetadata + AutoRespond<AL, BL>;
+
+    /// Ends a swap that was accepted but whose alpha HTLC was not deployed
+    /// yet, returns whether such a swap was found
+    fn abort(&self, counterparty: SocketAddr, secret_hash: SecretHash) -> bool;
 }
 
-impl<T: MetadataStore<SwapId>, S: StateStore<SwapId>, C: comit_client::Client> BobSpawner
-    for ProtocolDependencies<T, S, C>
+impl<
+        T: MetadataStore<SwapId> + TransitionStore<SwapId>,
+        S: StateStore<SwapId>,
+        C: comit_client::Client,
+    > BobSpawner for ProtocolDependencies<T, S, C>
 {
     #[allow(clippy::type_complexity)]
     fn spawn<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset>(
         &self,
+        counterparty: SocketAddr,
         id: SwapId,
         swap_request: SwapRequest<AL, BL, AA, BA>,
     ) -> Result<Box<ResponseFuture<Bob<AL, BL, AA, BA>>>, Error>
     where
         LedgerEventDependencies: CreateLedgerEvents<AL, AA> + CreateLedgerEvents<BL, BA>,
-        SwapRequest<AL, BL, AA, BA>: Into<Metadata>,
+        SwapRequest<AL, BL, AA, BA>: IntoMetadata + AutoRespond<AL, BL>,
     {
+        if !swap_request.alpha_ledger.is_on(&self.networks)
+            || !swap_request.beta_ledger.is_on(&self.networks)
+        {
+            return Err(Error::UnsupportedNetwork);
+        }
+
         let save_state = self
             .state_store
             .new_save_state(id)
             .map_err(Error::Storage)?;
         self.metadata_store
-            .insert(id, swap_request.clone())
+            .insert(id, swap_request.clone().into_metadata(counterparty))
             .map_err(Error::Metadata)?;
+        let save_state: Arc<dyn SaveState<Bob<AL, BL, AA, BA>>> =
+            self.observe_save_state(save_state, id);
+
+        // Decided before the request is consumed, the decision itself only
+        // resolves once the rate source answered
+        let decision = self.bob_policy.as_ref().map(|policy| {
+            let response =
+                swap_request.accept_response(policy.response_parameters(), id, &self.seed);
+            let lock_durations = validate_lock_durations(
+                swap_request.alpha_ledger_lock_duration.clone(),
+                response.beta_ledger_lock_duration.clone(),
+                self.lock_duration_margin,
+            );
+            let decision: Box<dyn Future<Item = Decision, Error = ()> + Send> =
+                match swap_request.proposal(policy.tokens()) {
+                    Some(proposal) => policy.decide(&proposal),
+                    None => Box::new(future::ok(Decision::Decline(
+                        DeclineReason::UnsupportedSwap,
+                    ))),
+                };
+
+            (decision, response, lock_durations)
+        });
+
+        let secret_hash = swap_request.secret_hash.clone();
+        let request_aborted = self
+            .aborts
+            .register(counterparty, secret_hash.clone())
+            .or_else(|_| future::empty::<(), rfc003::Error>());
 
         let initiation = Initiation {
             alpha_asset: swap_request.alpha_asset,
@@ -78,23 +134,166 @@ impl<T: MetadataStore<SwapId>, S: StateStore<SwapId>, C: comit_client::Client> B
             secret: swap_request.secret_hash,
         };
 
-        let (state_machine_future, response_future) = Bob::new_state_machine(
+        let (bob, state_machine_future, response_future) = Bob::new_state_machine(
             initiation,
             self.ledger_events.create_ledger_events(),
             self.ledger_events.create_ledger_events(),
-            save_state,
+            Arc::clone(&save_state),
+            Box::new(request_aborted),
+            self.lock_duration_margin,
         );
 
+        let respond: Box<dyn Future<Item = Option<ActiveSwap>, Error = ()> + Send> = match decision
+        {
+            Some((decision, response, lock_durations)) => {
+                let accept_action = bob.accept_action();
+                let decline_action = bob.decline_action();
+
+                Box::new(decision.map(move |decision| {
+                    let (responded, active_swap) = match decision {
+                        Decision::Accept(active_swap) => match lock_durations {
+                            Ok(()) => {
+                                info!("Accepting swap {} according to the policy", id);
+                                (accept_action.accept(response), Some(active_swap))
+                            }
+                            Err(e) => {
+                                warn!(
+                                    "Swap {} cannot be accepted according to the policy: {}",
+                                    id, e
+                                );
+                                (
+                                    decline(&decline_action, id, DeclineReason::BadLockDuration),
+                                    None,
+                                )
+                            }
+                        },
+                        Decision::Decline(reason) => (decline(&decline_action, id, reason), None),
+                        Decision::Manual(active_swap) => (Ok(()), Some(active_swap)),
+                    };
+                    if responded.is_err() {
+                        error!("Swap {} was already responded to", id);
+                    }
+
+                    active_swap
+                }))
+            }
+            None => Box::new(future::ok(None)),
+        };
+
+        let state_machine = save_failure(state_machine_future, save_state, id);
+
+        // The active swap counts towards the concurrent swaps of the policy
+        // until the swap finished
+        let aborts = Arc::clone(&self.aborts);
         tokio::spawn(
-            state_machine_future
-                .map(move |outcome| {
-                    info!("Swap {} finished with {:?}", id, outcome);
+            state_machine
+                .join(respond)
+                .then(move |result| {
+                    aborts.remove(counterparty, secret_hash);
+                    result.map(|((), active_swap)| drop(active_swap))
                 })
-                .map_err(move |e| {
-                    error!("Swap {} failed with {:?}", id, e);
-                }),
+                .instrument(logging::swap_span(id)),
         );
 
         Ok(response_future)
     }
+
+    fn abort(&self, counterparty: SocketAddr, secret_hash: SecretHash) -> bool {
+        self.aborts.abort(counterparty, secret_hash)
+    }
+}
+
+fn decline<AL: Ledger, BL: Ledger>(
+    decline_action: &Decline<AL, BL>,
+    id: SwapId,
+    reason: DeclineReason,
+) -> Result<(), ()> {
+    info!(
+        "Declining swap {} according to the policy: {:?}",
+        id, reason
+    );
+    decline_action.decline(reason.to_swap_decline_reason())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comit_client::fake::FakeClient,
+        swap_protocols::{
+            dependencies::test,
+            ledger::{Bitcoin, Ethereum},
+            metadata_store::InMemoryMetadataStore,
+            rfc003::{state_store::InMemoryStateStore, Secret},
+        },
+    };
+    use bitcoin_support::{BitcoinQuantity, Blocks, Network, PubkeyHash};
+    use ethereum_support::EtherQuantity;
+    use hex::FromHex;
+    use spectral::prelude::*;
+
+    fn dependencies(
+    ) -> ProtocolDependencies<InMemoryMetadataStore<SwapId>, InMemoryStateStore<SwapId>, FakeClient>
+    {
+        test::dependencies(
+            Arc::new(InMemoryMetadataStore::default()),
+            Arc::new(InMemoryStateStore::default()),
+        )
+    }
+
+    fn swap_request(
+        alpha_ledger: Bitcoin,
+        beta_ledger: Ethereum,
+    ) -> SwapRequest<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity> {
+        SwapRequest {
+            alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
+            beta_asset: EtherQuantity::from_eth(10.0),
+            alpha_ledger,
+            beta_ledger,
+            alpha_ledger_refund_identity: PubkeyHash::from_hex(
+                "875638cac0b0ae9f826575e190f2788918c354c2",
+            )
+            .unwrap(),
+            beta_ledger_redeem_identity: "8457037fcd80a8650c4692d7fcfc1d0a96b92867"
+                .parse()
+                .unwrap(),
+            alpha_ledger_lock_duration: Blocks::from(144),
+            secret_hash: Secret::from(*b"hello world, you are beautiful!!").hash(),
+        }
+    }
+
+    fn is_unsupported_network<T>(result: Result<T, Error>) -> bool {
+        match result {
+            Err(Error::UnsupportedNetwork) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn rejects_swap_request_on_other_bitcoin_network() {
+        let dependencies = dependencies();
+
+        let result = dependencies.spawn(
+            "127.0.0.1:8011".parse().unwrap(),
+            SwapId::default(),
+            swap_request(Bitcoin::new(Network::Testnet), Ethereum::new(17)),
+        );
+
+        assert!(is_unsupported_network(result));
+        assert_that(&dependencies.metadata_store.all().unwrap()).is_empty();
+    }
+
+    #[test]
+    fn rejects_swap_request_on_other_ethereum_chain() {
+        let dependencies = dependencies();
+
+        let result = dependencies.spawn(
+            "127.0.0.1:8011".parse().unwrap(),
+            SwapId::default(),
+            swap_request(Bitcoin::regtest(), Ethereum::new(3)),
+        );
+
+        assert!(is_unsupported_network(result));
+        assert_that(&dependencies.metadata_store.all().unwrap()).is_empty();
+    }
 }
@@ -5,7 +5,10 @@ use crate::{
         dependencies::LedgerEventDependencies,
         ledger::{Bitcoin, Ethereum},
         rfc003::{
-            events::{LedgerEvents, LqsEvents, LqsEventsForErc20},
+            events::{
+                LedgerEvents, LqsBitcoinHtlcExpiryEvents, LqsEthereumHtlcExpiryEvents, LqsEvents,
+                LqsEventsForErc20,
+            },
             Ledger,
         },
     },
@@ -23,6 +26,11 @@ impl CreateLedgerEvents<Bitcoin, BitcoinQuantity> for LedgerEventDependencies {
         Box::new(LqsEvents::new(
             QueryIdCache::wrap(Arc::clone(&self.lqs_client)),
             FirstMatch::new(Arc::clone(&self.lqs_client), self.lqs_bitcoin_poll_interval),
+            Box::new(LqsBitcoinHtlcExpiryEvents::new(
+                QueryIdCache::wrap(Arc::clone(&self.lqs_client)),
+                Arc::clone(&self.lqs_client),
+                self.lqs_bitcoin_poll_interval,
+            )),
         ))
     }
 }
@@ -35,6 +43,11 @@ impl CreateLedgerEvents<Ethereum, EtherQuantity> for LedgerEventDependencies {
                 Arc::clone(&self.lqs_client),
                 self.lqs_ethereum_poll_interval,
             ),
+            Box::new(LqsEthereumHtlcExpiryEvents::new(
+                QueryIdCache::wrap(Arc::clone(&self.lqs_client)),
+                Arc::clone(&self.lqs_client),
+                self.lqs_ethereum_poll_interval,
+            )),
         ))
     }
 }
@@ -47,6 +60,11 @@ impl CreateLedgerEvents<Ethereum, Erc20Quantity> for LedgerEventDependencies {
                 Arc::clone(&self.lqs_client),
                 self.lqs_ethereum_poll_interval,
             ),
+            Box::new(LqsEthereumHtlcExpiryEvents::new(
+                QueryIdCache::wrap(Arc::clone(&self.lqs_client)),
+                Arc::clone(&self.lqs_client),
+                self.lqs_ethereum_poll_interval,
+            )),
         ))
     }
 }
@@ -1,6 +1,6 @@
 use crate::{comit_client::SwapResponseError, ledger_query_service};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Error {
     SwapResponse(SwapResponseError),
     LedgerQueryService(ledger_query_service::Error),
@@ -2,15 +2,21 @@ use ethereum_support::{web3::types::U256, Address, Bytes, EtherQuantity};
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ContractDeploy {
+    /// The identity of the party which sends the transaction
+    pub from: Address,
     pub data: Bytes,
     pub value: EtherQuantity,
     pub gas_limit: U256,
+    pub chain_id: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SendTransaction {
+    /// The identity of the party which sends the transaction
+    pub from: Address,
     pub to: Address,
     pub data: Bytes,
     pub gas_limit: U256,
     pub value: EtherQuantity,
+    pub chain_id: u64,
 }
@@ -3,7 +3,7 @@ use crate::swap_protocols::{
     rfc003::{state_machine::HtlcParams, Ledger},
 };
 use ethereum_support::{web3::types::Address, Bytes, Erc20Quantity, EtherQuantity};
-use std::time::Duration;
+use std::{ops::Add, time::Duration};
 
 mod actions;
 mod erc20_htlc;
@@ -42,8 +42,27 @@ impl From<Seconds> for Duration {
     }
 }
 
+/// Seconds since the UNIX epoch, the unit of Ethereum block timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Timestamp(pub u64);
+
+impl Add<Seconds> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, seconds: Seconds) -> Timestamp {
+        Timestamp(self.0.saturating_add(seconds.0))
+    }
+}
+
+impl From<Timestamp> for u64 {
+    fn from(timestamp: Timestamp) -> u64 {
+        timestamp.0
+    }
+}
+
 impl Ledger for Ethereum {
     type LockDuration = Seconds;
+    type Expiry = Timestamp;
     type HtlcLocation = Address;
     type HtlcIdentity = Address;
 }
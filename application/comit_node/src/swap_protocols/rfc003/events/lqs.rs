@@ -8,8 +8,9 @@ use crate::{
             self,
             ethereum::erc20,
             events::{
-                Deployed, Funded, LedgerEvents, NewHtlcFundedQuery, NewHtlcRedeemedQuery,
-                NewHtlcRefundedQuery, RedeemedOrRefunded,
+                Deployed, Funded, HtlcExpiryEvents, LedgerEvents, LqsHtlcExpiryEvents,
+                NewHtlcFundedQuery, NewHtlcRedeemedQuery, NewHtlcRefundedQuery, RedeemedOrRefunded,
+                Refundable, RefundableAt,
             },
             find_htlc_location::FindHtlcLocation,
             secret::SecretHash,
@@ -23,11 +24,16 @@ use futures::{
     future::{self, Either},
     Future,
 };
+use std::sync::{Arc, Mutex};
 
 #[allow(missing_debug_implementations)]
 pub struct LqsEvents<L: Ledger, Q: Query> {
     create_ledger_query: QueryIdCache<L, Q>,
     ledger_first_match: FirstMatch<L>,
+    htlc_expiry_events: Box<dyn LqsHtlcExpiryEvents<L, Q>>,
+    /// For HTLCs which are funded by deploying them, the deploying
+    /// transaction is also the funding one
+    deploying_transaction: Arc<Mutex<Option<L::Transaction>>>,
 
     htlc_deployed_and_funded: Option<Box<Deployed<L>>>,
     htlc_funded: Option<Box<Funded<L>>>,
@@ -38,10 +44,16 @@ impl<L: Ledger, Q: Query> LqsEvents<L, Q>
 where
     L::Transaction: ExtractSecret,
 {
-    pub fn new(create_ledger_query: QueryIdCache<L, Q>, ledger_first_match: FirstMatch<L>) -> Self {
+    pub fn new(
+        create_ledger_query: QueryIdCache<L, Q>,
+        ledger_first_match: FirstMatch<L>,
+        htlc_expiry_events: Box<dyn LqsHtlcExpiryEvents<L, Q>>,
+    ) -> Self {
         Self {
             create_ledger_query,
             ledger_first_match,
+            htlc_expiry_events,
+            deploying_transaction: Arc::new(Mutex::new(None)),
             htlc_deployed_and_funded: None,
             htlc_funded: None,
             htlc_redeemed_or_refunded: None,
@@ -53,19 +65,35 @@ where
         A: Asset,
         <L as swap_protocols::Ledger>::Transaction: FindHtlcLocation<L, A>,
     {
-        let ledger_first_match = self.ledger_first_match.clone();
-        let query_id = self.create_ledger_query.create_query(query);
+        let Self {
+            create_ledger_query,
+            ledger_first_match,
+            htlc_expiry_events,
+            deploying_transaction,
+            htlc_deployed_and_funded,
+            ..
+        } = self;
+
+        htlc_deployed_and_funded.get_or_insert_with(|| {
+            let ledger_first_match = ledger_first_match.clone();
+            let deploying_transaction = Arc::clone(deploying_transaction);
+            let deployment_block_watched = htlc_expiry_events.watch_htlc_deployment(&query);
+            let query_id = create_ledger_query.create_query(query);
 
-        self.htlc_deployed_and_funded.get_or_insert_with(move || {
             let funded_future = query_id
+                .join(deployment_block_watched)
                 .map_err(rfc003::Error::LedgerQueryService)
-                .and_then(move |query_id| {
-                    ledger_first_match
-                        .first_match_of(query_id)
-                        .and_then(move |tx| {
-                            tx.find_htlc_location(&htlc_params)
-                                .map_err(|_| rfc003::Error::InsufficientFunding)
-                        })
+                .and_then(move |(query_id, ())| {
+                    ledger_first_match.first_match_of(query_id).and_then(
+                        move |tx| -> Result<_, rfc003::Error> {
+                            let htlc_location = tx
+                                .find_htlc_location(&htlc_params)
+                                .map_err(|_| rfc003::Error::InsufficientFunding)?;
+                            *deploying_transaction.lock().unwrap() = Some(tx);
+
+                            Ok(htlc_location)
+                        },
+                    )
                 });
 
             Box::new(funded_future)
@@ -123,6 +151,17 @@ where
         })
     }
 }
+
+impl<L: Ledger, Q: Query> HtlcExpiryEvents<L> for LqsEvents<L, Q> {
+    fn htlc_refundable_at(&mut self, lock_duration: L::LockDuration) -> &mut RefundableAt<L> {
+        self.htlc_expiry_events.htlc_refundable_at(lock_duration)
+    }
+
+    fn htlc_refundable(&mut self, refundable_at: L::Expiry) -> &mut Refundable {
+        self.htlc_expiry_events.htlc_refundable(refundable_at)
+    }
+}
+
 impl<L, A, Q> LedgerEvents<L, A> for LqsEvents<L, Q>
 where
     L: Ledger,
@@ -141,7 +180,17 @@ where
         _htlc_params: HtlcParams<L, A>,
         _htlc_location: &L::HtlcLocation,
     ) -> &mut Funded<L> {
-        self.htlc_funded.get_or_insert(Box::new(future::ok(None)))
+        let deploying_transaction = &self.deploying_transaction;
+
+        self.htlc_funded.get_or_insert_with(|| {
+            let fund_transaction = deploying_transaction
+                .lock()
+                .unwrap()
+                .take()
+                .map(FundTransaction);
+
+            Box::new(future::ok(fund_transaction))
+        })
     }
 
     fn htlc_redeemed_or_refunded(
@@ -165,11 +214,14 @@ impl LqsEventsForErc20 {
     pub fn new(
         create_ledger_query: QueryIdCache<Ethereum, EthereumQuery>,
         ledger_first_match: FirstMatch<Ethereum>,
+        htlc_expiry_events: Box<dyn LqsHtlcExpiryEvents<Ethereum, EthereumQuery>>,
     ) -> Self {
         Self {
             lqs_events: LqsEvents {
                 create_ledger_query,
                 ledger_first_match,
+                htlc_expiry_events,
+                deploying_transaction: Arc::new(Mutex::new(None)),
                 htlc_deployed_and_funded: None,
                 htlc_funded: None,
                 htlc_redeemed_or_refunded: None,
@@ -178,6 +230,19 @@ impl LqsEventsForErc20 {
     }
 }
 
+impl HtlcExpiryEvents<Ethereum> for LqsEventsForErc20 {
+    fn htlc_refundable_at(
+        &mut self,
+        lock_duration: <Ethereum as Ledger>::LockDuration,
+    ) -> &mut RefundableAt<Ethereum> {
+        self.lqs_events.htlc_refundable_at(lock_duration)
+    }
+
+    fn htlc_refundable(&mut self, refundable_at: <Ethereum as Ledger>::Expiry) -> &mut Refundable {
+        self.lqs_events.htlc_refundable(refundable_at)
+    }
+}
+
 impl LedgerEvents<Ethereum, Erc20Quantity> for LqsEventsForErc20 {
     fn htlc_deployed(
         &mut self,
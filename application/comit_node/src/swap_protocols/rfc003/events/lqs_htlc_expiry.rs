@@ -0,0 +1,429 @@
+use crate::{
+    ledger_query_service::{
+        self, BitcoinQuery, CreateQuery, EthereumQuery, FetchBlockHeights, FetchBlockTimestamps,
+        Query, QueryIdCache,
+    },
+    swap_protocols::{
+        ledger::{Bitcoin, Ethereum},
+        rfc003::{
+            self,
+            ethereum::{Seconds, Timestamp},
+            events::{HtlcExpiryEvents, Refundable, RefundableAt},
+            Ledger,
+        },
+    },
+};
+use bitcoin_support::{BlockHeight, Blocks};
+use futures::{stream::Stream, Future};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::timer::Interval;
+
+/// HTLC expiry events based on LQS block queries. The lock duration of an HTLC
+/// starts to run in the block which includes the transaction deploying it.
+pub trait LqsHtlcExpiryEvents<L: Ledger, Q: Query>: HtlcExpiryEvents<L> {
+    /// Creates a query for the block which includes the first transaction
+    /// matching `deployed_query`. The LQS only matches new blocks, hence the
+    /// returned future has to resolve before that transaction is mined.
+    fn watch_htlc_deployment(
+        &mut self,
+        deployed_query: &Q,
+    ) -> Box<dyn Future<Item = (), Error = ledger_query_service::Error> + Send>;
+}
+
+#[allow(missing_debug_implementations)]
+pub struct LqsBitcoinHtlcExpiryEvents {
+    create_ledger_query: QueryIdCache<Bitcoin, BitcoinQuery>,
+    fetch_block_heights: Arc<dyn FetchBlockHeights>,
+    poll_interval: Duration,
+    deployment_block_query: Option<BitcoinQuery>,
+
+    htlc_refundable_at: Option<Box<RefundableAt<Bitcoin>>>,
+    htlc_refundable: Option<Box<Refundable>>,
+}
+
+impl LqsBitcoinHtlcExpiryEvents {
+    pub fn new<F: FetchBlockHeights>(
+        create_ledger_query: QueryIdCache<Bitcoin, BitcoinQuery>,
+        fetch_block_heights: Arc<F>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            create_ledger_query,
+            fetch_block_heights: fetch_block_heights as Arc<dyn FetchBlockHeights>,
+            poll_interval,
+            deployment_block_query: None,
+            htlc_refundable_at: None,
+            htlc_refundable: None,
+        }
+    }
+}
+
+impl LqsHtlcExpiryEvents<Bitcoin, BitcoinQuery> for LqsBitcoinHtlcExpiryEvents {
+    fn watch_htlc_deployment(
+        &mut self,
+        deployed_query: &BitcoinQuery,
+    ) -> Box<dyn Future<Item = (), Error = ledger_query_service::Error> + Send> {
+        let query = self
+            .deployment_block_query
+            .get_or_insert_with(|| BitcoinQuery::Block {
+                min_height: None,
+                containing_transaction: Some(Box::new(deployed_query.clone())),
+            })
+            .clone();
+
+        Box::new(self.create_ledger_query.create_query(query).map(|_| ()))
+    }
+}
+
+impl HtlcExpiryEvents<Bitcoin> for LqsBitcoinHtlcExpiryEvents {
+    /// A relative timelock counts the blocks since the one which includes the
+    /// funding transaction. If the deployment was not watched because the
+    /// swap was resumed after it, the first new block is used instead. It is
+    /// newer than the funding block, hence this never makes the HTLC
+    /// refundable too early.
+    fn htlc_refundable_at(&mut self, lock_duration: Blocks) -> &mut RefundableAt<Bitcoin> {
+        let Self {
+            create_ledger_query,
+            fetch_block_heights,
+            poll_interval,
+            deployment_block_query,
+            htlc_refundable_at,
+            ..
+        } = self;
+
+        htlc_refundable_at.get_or_insert_with(|| {
+            let query = deployment_block_query.clone().unwrap_or_else(|| {
+                warn!("HTLC deployment was not watched, counting from the first new block");
+                min_height_query(0)
+            });
+
+            Box::new(
+                first_block_height(
+                    create_ledger_query,
+                    fetch_block_heights,
+                    *poll_interval,
+                    query,
+                )
+                .map(move |height| height + lock_duration),
+            )
+        })
+    }
+
+    fn htlc_refundable(&mut self, refundable_at: BlockHeight) -> &mut Refundable {
+        let Self {
+            create_ledger_query,
+            fetch_block_heights,
+            poll_interval,
+            htlc_refundable,
+            ..
+        } = self;
+
+        htlc_refundable.get_or_insert_with(|| {
+            Box::new(
+                first_block_height(
+                    create_ledger_query,
+                    fetch_block_heights,
+                    *poll_interval,
+                    min_height_query(refundable_at.into()),
+                )
+                .map(|_| ()),
+            )
+        })
+    }
+}
+
+fn min_height_query(min_height: u32) -> BitcoinQuery {
+    BitcoinQuery::Block {
+        min_height: Some(min_height),
+        containing_transaction: None,
+    }
+}
+
+fn first_block_height(
+    create_ledger_query: &QueryIdCache<Bitcoin, BitcoinQuery>,
+    fetch_block_heights: &Arc<dyn FetchBlockHeights>,
+    poll_interval: Duration,
+    query: BitcoinQuery,
+) -> impl Future<Item = BlockHeight, Error = rfc003::Error> {
+    let query_id = create_ledger_query.create_query(query);
+    let fetch_block_heights = Arc::clone(fetch_block_heights);
+
+    query_id
+        .map_err(rfc003::Error::LedgerQueryService)
+        .and_then(move |query_id| {
+            first_non_empty_results(poll_interval, move || {
+                fetch_block_heights.fetch_block_heights(&query_id)
+            })
+        })
+        .and_then(|heights| {
+            heights
+                .into_iter()
+                .min()
+                .ok_or_else(|| rfc003::Error::Internal(String::from("no block heights were found")))
+        })
+}
+
+#[allow(missing_debug_implementations)]
+pub struct LqsEthereumHtlcExpiryEvents {
+    create_ledger_query: QueryIdCache<Ethereum, EthereumQuery>,
+    fetch_block_timestamps: Arc<dyn FetchBlockTimestamps>,
+    poll_interval: Duration,
+    deployment_block_query: Option<EthereumQuery>,
+
+    htlc_refundable_at: Option<Box<RefundableAt<Ethereum>>>,
+    htlc_refundable: Option<Box<Refundable>>,
+}
+
+impl LqsEthereumHtlcExpiryEvents {
+    pub fn new<F: FetchBlockTimestamps>(
+        create_ledger_query: QueryIdCache<Ethereum, EthereumQuery>,
+        fetch_block_timestamps: Arc<F>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            create_ledger_query,
+            fetch_block_timestamps: fetch_block_timestamps as Arc<dyn FetchBlockTimestamps>,
+            poll_interval,
+            deployment_block_query: None,
+            htlc_refundable_at: None,
+            htlc_refundable: None,
+        }
+    }
+}
+
+impl LqsHtlcExpiryEvents<Ethereum, EthereumQuery> for LqsEthereumHtlcExpiryEvents {
+    fn watch_htlc_deployment(
+        &mut self,
+        deployed_query: &EthereumQuery,
+    ) -> Box<dyn Future<Item = (), Error = ledger_query_service::Error> + Send> {
+        let query = self
+            .deployment_block_query
+            .get_or_insert_with(|| EthereumQuery::Block {
+                min_timestamp_secs: None,
+                containing_transaction: Some(Box::new(deployed_query.clone())),
+            })
+            .clone();
+
+        Box::new(self.create_ledger_query.create_query(query).map(|_| ()))
+    }
+}
+
+impl HtlcExpiryEvents<Ethereum> for LqsEthereumHtlcExpiryEvents {
+    /// The contract stores the timestamp of the block it was deployed in and
+    /// only allows a refund once a block is more than the lock duration newer.
+    /// If the deployment was not watched because the swap was resumed after
+    /// it, the first new block is used instead. It is not older than the
+    /// deployment block, hence this never makes the HTLC refundable too early.
+    fn htlc_refundable_at(&mut self, lock_duration: Seconds) -> &mut RefundableAt<Ethereum> {
+        let Self {
+            create_ledger_query,
+            fetch_block_timestamps,
+            poll_interval,
+            deployment_block_query,
+            htlc_refundable_at,
+            ..
+        } = self;
+
+        htlc_refundable_at.get_or_insert_with(|| {
+            let query = deployment_block_query.clone().unwrap_or_else(|| {
+                warn!("HTLC deployment was not watched, counting from the first new block");
+                min_timestamp_query(Timestamp(0))
+            });
+
+            Box::new(
+                first_block_timestamp(
+                    create_ledger_query,
+                    fetch_block_timestamps,
+                    *poll_interval,
+                    query,
+                )
+                .map(move |timestamp| timestamp + lock_duration + Seconds(1)),
+            )
+        })
+    }
+
+    /// Refundable once the latest block is at least as new as `refundable_at`
+    fn htlc_refundable(&mut self, refundable_at: Timestamp) -> &mut Refundable {
+        let Self {
+            create_ledger_query,
+            fetch_block_timestamps,
+            poll_interval,
+            htlc_refundable,
+            ..
+        } = self;
+
+        htlc_refundable.get_or_insert_with(|| {
+            Box::new(
+                first_block_timestamp(
+                    create_ledger_query,
+                    fetch_block_timestamps,
+                    *poll_interval,
+                    min_timestamp_query(refundable_at),
+                )
+                .map(|_| ()),
+            )
+        })
+    }
+}
+
+fn min_timestamp_query(min_timestamp: Timestamp) -> EthereumQuery {
+    EthereumQuery::Block {
+        min_timestamp_secs: Some(min_timestamp.into()),
+        containing_transaction: None,
+    }
+}
+
+fn first_block_timestamp(
+    create_ledger_query: &QueryIdCache<Ethereum, EthereumQuery>,
+    fetch_block_timestamps: &Arc<dyn FetchBlockTimestamps>,
+    poll_interval: Duration,
+    query: EthereumQuery,
+) -> impl Future<Item = Timestamp, Error = rfc003::Error> {
+    let query_id = create_ledger_query.create_query(query);
+    let fetch_block_timestamps = Arc::clone(fetch_block_timestamps);
+
+    query_id
+        .map_err(rfc003::Error::LedgerQueryService)
+        .and_then(move |query_id| {
+            first_non_empty_results(poll_interval, move || {
+                fetch_block_timestamps.fetch_block_timestamps(&query_id)
+            })
+        })
+        .and_then(|timestamps| {
+            timestamps.into_iter().min().ok_or_else(|| {
+                rfc003::Error::Internal(String::from("no block timestamps were found"))
+            })
+        })
+}
+
+fn first_non_empty_results<T, F>(
+    poll_interval: Duration,
+    fetch_results: F,
+) -> impl Future<Item = Vec<T>, Error = rfc003::Error>
+where
+    T: Send + 'static,
+    F: Fn() -> Box<dyn Future<Item = Vec<T>, Error = ledger_query_service::Error> + Send>
+        + Send
+        + 'static,
+{
+    Interval::new(Instant::now(), poll_interval)
+        .and_then(move |_| {
+            fetch_results().or_else(|e| {
+                warn!("Failed to fetch query results, retrying: {:?}", e);
+                Ok(Vec::new())
+            })
+        })
+        .filter(|results| !results.is_empty())
+        .into_future()
+        .map_err(|(e, _)| {
+            error!("Timer failed while waiting for query results: {:?}", e);
+            rfc003::Error::TimerError
+        })
+        .and_then(|(results, _)| {
+            results.ok_or_else(|| {
+                rfc003::Error::Internal(String::from("the timer stopped unexpectedly"))
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger_query_service::QueryId;
+    use futures::future;
+    use spectral::prelude::*;
+
+    /// Returns `deployment_block` for queries of the block which includes a
+    /// transaction and `new_blocks` for any other block query
+    #[derive(Debug)]
+    struct FakeBlockTimestamps {
+        deployment_block: Timestamp,
+        new_blocks: Vec<Timestamp>,
+    }
+
+    impl CreateQuery<Ethereum, EthereumQuery> for FakeBlockTimestamps {
+        fn create_query(
+            &self,
+            query: EthereumQuery,
+        ) -> Box<dyn Future<Item = QueryId<Ethereum>, Error = ledger_query_service::Error> + Send>
+        {
+            let location = match query {
+                EthereumQuery::Block {
+                    containing_transaction: Some(_),
+                    ..
+                } => "http://localhost/queries/ethereum/blocks/1",
+                _ => "http://localhost/queries/ethereum/blocks/2",
+            };
+
+            Box::new(future::ok(QueryId::new(location.parse().unwrap())))
+        }
+    }
+
+    impl FetchBlockTimestamps for FakeBlockTimestamps {
+        fn fetch_block_timestamps(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = Vec<Timestamp>, Error = ledger_query_service::Error> + Send>
+        {
+            if query.as_ref().path().ends_with("/1") {
+                Box::new(future::ok(vec![self.deployment_block]))
+            } else {
+                Box::new(future::ok(self.new_blocks.clone()))
+            }
+        }
+    }
+
+    fn ethereum_expiry_events() -> LqsEthereumHtlcExpiryEvents {
+        let lqs = Arc::new(FakeBlockTimestamps {
+            deployment_block: Timestamp(900),
+            new_blocks: vec![Timestamp(1_000), Timestamp(990)],
+        });
+
+        LqsEthereumHtlcExpiryEvents::new(
+            QueryIdCache::wrap(Arc::clone(&lqs)),
+            lqs,
+            Duration::from_millis(10),
+        )
+    }
+
+    fn deployed_query() -> EthereumQuery {
+        EthereumQuery::Transaction {
+            from_address: None,
+            to_address: None,
+            is_contract_creation: Some(true),
+            transaction_data: None,
+            transaction_data_length: None,
+        }
+    }
+
+    #[test]
+    fn ethereum_htlc_expires_lock_duration_after_the_deployment_block() {
+        let mut events = ethereum_expiry_events();
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime
+            .block_on(events.watch_htlc_deployment(&deployed_query()))
+            .unwrap();
+        let refundable_at = runtime.block_on(future::poll_fn(move || {
+            events.htlc_refundable_at(Seconds(60)).poll()
+        }));
+
+        assert_that(&refundable_at).is_ok_containing(Timestamp(961));
+    }
+
+    #[test]
+    fn ethereum_htlc_expires_lock_duration_after_the_first_new_block_if_deployment_was_not_watched()
+    {
+        let mut events = ethereum_expiry_events();
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let refundable_at = runtime.block_on(future::poll_fn(move || {
+            events.htlc_refundable_at(Seconds(60)).poll()
+        }));
+
+        assert_that(&refundable_at).is_ok_containing(Timestamp(1_051));
+    }
+}
@@ -18,8 +18,14 @@ use crate::{
 use tokio::{self, prelude::future::Either};
 
 mod lqs;
+mod lqs_htlc_expiry;
 
-pub use self::lqs::{LqsEvents, LqsEventsForErc20};
+pub use self::{
+    lqs::{LqsEvents, LqsEventsForErc20},
+    lqs_htlc_expiry::{
+        LqsBitcoinHtlcExpiryEvents, LqsEthereumHtlcExpiryEvents, LqsHtlcExpiryEvents,
+    },
+};
 
 type Future<I> = dyn tokio::prelude::Future<Item = I, Error = rfc003::Error> + Send;
 
@@ -33,6 +39,8 @@ pub type ResponseFuture<R: Role> = StateMachineResponseFuture<
     <R::BetaLedger as Ledger>::LockDuration,
 >;
 
+pub type RequestAborted = Future<()>;
+
 pub type Deployed<L: Ledger> = Future<L::HtlcLocation>;
 pub type Funded<L: Ledger> = Future<Option<FundTransaction<L>>>;
 pub type Refunded<L: Ledger> = Future<L::TxId>;
@@ -40,8 +48,10 @@ pub type Redeemed<L: Ledger> = Future<L::TxId>;
 pub type AlphaRefundedOrBetaFunded<AL: Ledger, BL: Ledger> =
     Future<Either<AL::Transaction, BL::HtlcLocation>>;
 pub type RedeemedOrRefunded<L: Ledger> = Future<Either<RedeemTransaction<L>, RefundTransaction<L>>>;
+pub type RefundableAt<L: Ledger> = Future<L::Expiry>;
+pub type Refundable = Future<()>;
 
-pub trait LedgerEvents<L: Ledger, A: Asset>: Send {
+pub trait LedgerEvents<L: Ledger, A: Asset>: HtlcExpiryEvents<L> + Send {
     fn htlc_deployed(&mut self, htlc_params: HtlcParams<L, A>) -> &mut Deployed<L>;
 
     fn htlc_funded(
@@ -57,6 +67,14 @@ pub trait LedgerEvents<L: Ledger, A: Asset>: Send {
     ) -> &mut RedeemedOrRefunded<L>;
 }
 
+/// The lock duration of an HTLC starts to run once it is deployed. Both events
+/// are only asked for after the HTLC has been deployed.
+pub trait HtlcExpiryEvents<L: Ledger>: Send {
+    fn htlc_refundable_at(&mut self, lock_duration: L::LockDuration) -> &mut RefundableAt<L>;
+
+    fn htlc_refundable(&mut self, refundable_at: L::Expiry) -> &mut Refundable;
+}
+
 pub trait CommunicationEvents<R: Role>: Send {
     fn request_responded(
         &mut self,
@@ -67,6 +85,20 @@ pub trait CommunicationEvents<R: Role>: Send {
             R::BetaAsset,
         >,
     ) -> &mut ResponseFuture<R>;
+
+    /// Tells the counterparty that an accepted request will not go ahead
+    fn abort_request(
+        &mut self,
+        request: &comit_client::rfc003::Request<
+            R::AlphaLedger,
+            R::BetaLedger,
+            R::AlphaAsset,
+            R::BetaAsset,
+        >,
+    );
+
+    /// Resolves once the counterparty aborted the request it made
+    fn request_aborted(&mut self) -> &mut RequestAborted;
 }
 
 pub trait NewHtlcDeployedQuery<L: Ledger, A: Asset>: Send + Sync
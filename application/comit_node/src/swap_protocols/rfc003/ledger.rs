@@ -1,9 +1,12 @@
 use crate::swap_protocols::{
     self,
-    rfc003::secret::{Secret, SecretHash},
+    rfc003::{
+        secret::{Secret, SecretHash},
+        state_store::Persist,
+    },
 };
 use serde::{de::DeserializeOwned, Serialize};
-use std::{fmt::Debug, hash::Hash};
+use std::{fmt::Debug, hash::Hash, time::Duration};
 
 pub trait Ledger: swap_protocols::Ledger {
     type LockDuration: PartialEq
@@ -15,6 +18,17 @@ pub trait Ledger: swap_protocols::Ledger {
         + Sync
         + Serialize
         + DeserializeOwned
+        + Into<Duration>
+        + 'static;
+    /// The point on the ledger from which on an HTLC can be refunded
+    type Expiry: PartialEq
+        + Debug
+        + Clone
+        + Send
+        + Sync
+        + Serialize
+        + DeserializeOwned
+        + Into<u64>
         + 'static;
     type HtlcLocation: PartialEq + Debug + Clone + DeserializeOwned + Serialize + Send + Sync;
     type HtlcIdentity: Clone
@@ -22,6 +36,7 @@ pub trait Ledger: swap_protocols::Ledger {
         + Sync
         + PartialEq
         + Debug
+        + Persist
         + Into<<Self as swap_protocols::ledger::Ledger>::Identity>;
 }
 
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+/// The alpha and beta lock durations of a swap do not leave enough time
+/// between the expiry of the beta HTLC and the expiry of the alpha HTLC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Fail)]
+#[fail(
+    display = "The beta HTLC expires after {:?} which is less than {:?} before the alpha HTLC expires after {:?}",
+    beta_ledger_lock_duration, margin, alpha_ledger_lock_duration
+)]
+pub struct UnsafeLockDurations {
+    pub alpha_ledger_lock_duration: Duration,
+    pub beta_ledger_lock_duration: Duration,
+    pub margin: Duration,
+}
+
+/// Bob learns the secret only once Alice redeems the beta HTLC. If the beta
+/// HTLC does not expire well before the alpha HTLC, Alice can redeem beta at
+/// the last moment and refund alpha before Bob gets to redeem it.
+///
+/// Lock durations of different ledgers are compared in time, Bitcoin blocks
+/// are assumed to be mined every 10 minutes.
+pub fn validate_lock_durations<A: Into<Duration>, B: Into<Duration>>(
+    alpha_ledger_lock_duration: A,
+    beta_ledger_lock_duration: B,
+    margin: Duration,
+) -> Result<(), UnsafeLockDurations> {
+    let alpha_ledger_lock_duration = alpha_ledger_lock_duration.into();
+    let beta_ledger_lock_duration = beta_ledger_lock_duration.into();
+
+    if beta_ledger_lock_duration + margin <= alpha_ledger_lock_duration {
+        Ok(())
+    } else {
+        Err(UnsafeLockDurations {
+            alpha_ledger_lock_duration,
+            beta_ledger_lock_duration,
+            margin,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::rfc003::ethereum::Seconds;
+    use bitcoin_support::Blocks;
+    use spectral::prelude::*;
+
+    fn one_hour() -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
+    #[test]
+    fn accepts_beta_expiring_before_alpha_with_margin() {
+        let result = validate_lock_durations(Blocks::new(144), Seconds(12 * 60 * 60), one_hour());
+
+        assert_that(&result).is_ok();
+    }
+
+    #[test]
+    fn rejects_beta_expiring_within_margin_of_alpha() {
+        let result = validate_lock_durations(Seconds(2 * 60 * 60), Blocks::new(8), one_hour());
+
+        assert_that(&result).is_err_containing(UnsafeLockDurations {
+            alpha_ledger_lock_duration: Duration::from_secs(2 * 60 * 60),
+            beta_ledger_lock_duration: Duration::from_secs(80 * 60),
+            margin: one_hour(),
+        });
+    }
+
+    #[test]
+    fn rejects_beta_expiring_after_alpha() {
+        let result =
+            validate_lock_durations(Seconds(144), Blocks::new(43200), Duration::from_secs(0));
+
+        assert_that(&result).is_err();
+    }
+}
@@ -1,5 +1,7 @@
 #[macro_use]
 mod transition_save;
+#[macro_use]
+mod swap_combinations;
 
 pub mod alice;
 pub mod bitcoin;
@@ -7,6 +9,7 @@ pub mod bob;
 pub mod ethereum;
 pub mod events;
 pub mod find_htlc_location;
+pub mod resumer;
 
 pub mod state_machine;
 pub mod state_store;
@@ -14,6 +17,7 @@ pub mod state_store;
 mod error;
 
 mod ledger;
+mod lock_duration_safety;
 mod role;
 mod save_state;
 mod secret;
@@ -29,8 +33,13 @@ pub use self::{
     create_ledger_events::CreateLedgerEvents,
     error::Error,
     ledger::{ExtractSecret, FundTransaction, Ledger, RedeemTransaction, RefundTransaction},
+    lock_duration_safety::{validate_lock_durations, UnsafeLockDurations},
     role::*,
-    save_state::SaveState,
+    save_state::{
+        save_failure, NotifyWebhooks, PublishStateChanges, RecordMetrics, RecordTransitions,
+        SaveState, TouchMetadata,
+    },
     secret::{FromErr, RandomnessSource, Secret, SecretHash},
     secret_source::*,
+    swap_combinations::SwapCombination,
 };
@@ -0,0 +1,241 @@
+use crate::{
+    comit_client,
+    logging::{self, Instrument},
+    swap_protocols::{
+        dependencies::{LedgerEventDependencies, ProtocolDependencies},
+        metadata_store::{self, MetadataStore, RoleKind, TransitionStore},
+        rfc003::{
+            self,
+            events::{CommunicationEvents, RequestAborted, ResponseFuture},
+            save_failure,
+            state_machine::{Context, Swap, SwapStates},
+            state_store::{self, StateStore},
+            Alice, Bob, CreateLedgerEvents, Role, SwapCombination,
+        },
+        SwapId,
+    },
+};
+use futures::future;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum Error {
+    Storage(state_store::Error),
+    Metadata(metadata_store::Error),
+}
+
+pub trait SwapResumer: Send + Sync + 'static {
+    /// Re-enters the state machine of every swap that was accepted but did
+    /// not reach a final state before the node was stopped. Swaps which
+    /// cannot be resumed are logged and skipped.
+    fn resume_swaps(&self) -> Result<(), Error>;
+}
+
+impl<
+        T: MetadataStore<SwapId> + TransitionStore<SwapId>,
+        S: StateStore<SwapId>,
+        C: comit_client::Client,
+    > SwapResumer for ProtocolDependencies<T, S, C>
+{
+    fn resume_swaps(&self) -> Result<(), Error> {
+        let swaps = self.metadata_store.all().map_err(Error::Metadata)?;
+
+        for (id, metadata) in swaps {
+            // A swap which cannot be resumed must not keep the others from
+            // resuming
+            if let Err(e) = with_swap_combination!(
+                SwapCombination::from(&metadata),
+                match metadata.role {
+                    RoleKind::Alice => resume::<Alice<AL, BL, AA, BA>, _, _, _>(self, id),
+                    RoleKind::Bob => resume::<Bob<AL, BL, AA, BA>, _, _, _>(self, id),
+                },
+                {
+                    warn!("Swap {} has unsupported metadata {:?}", id, metadata);
+                    Ok(())
+                }
+            ) {
+                error!("Failed to resume swap {}: {:?}", id, e);
+                continue;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn resume<R: Role, T: MetadataStore<SwapId> + TransitionStore<SwapId>, S: StateStore<SwapId>, C>(
+    dependencies: &ProtocolDependencies<T, S, C>,
+    id: SwapId,
+) -> Result<(), Error>
+where
+    LedgerEventDependencies: CreateLedgerEvents<R::AlphaLedger, R::AlphaAsset>
+        + CreateLedgerEvents<R::BetaLedger, R::BetaAsset>,
+{
+    let state = match dependencies
+        .state_store
+        .get::<R>(&id)
+        .map_err(Error::Storage)?
+    {
+        Some(state) => state,
+        None => return Ok(()),
+    };
+
+    match state {
+        SwapStates::Start(_) => {
+            warn!(
+                "Swap {} was not accepted before the node stopped and cannot be resumed",
+                id
+            );
+            return Ok(());
+        }
+        SwapStates::Final(_) | SwapStates::Error(_) => return Ok(()),
+        _ => {}
+    }
+
+    let save_state = match dependencies
+        .state_store
+        .get_save_state::<R>(&id)
+        .map_err(Error::Storage)?
+    {
+        Some(save_state) => save_state,
+        None => return Ok(()),
+    };
+
+    info!("Resuming swap {} in state {}", id, state.name());
+
+    let save_state = dependencies.observe_save_state(save_state, id);
+    let context = Context {
+        alpha_ledger_events: dependencies.ledger_events.create_ledger_events(),
+        beta_ledger_events: dependencies.ledger_events.create_ledger_events(),
+        state_repo: Arc::clone(&save_state),
+        communication_events: Box::new(ResponseAlreadyReceived::<R>::new()),
+        lock_duration_margin: dependencies.lock_duration_margin,
+    };
+
+    tokio::spawn(
+        save_failure(Swap::start_in(state, context), save_state, id)
+            .instrument(logging::swap_span(id)),
+    );
+
+    Ok(())
+}
+
+/// A resumed swap has already been accepted, hence the state machine never
+/// asks for the counterparty's response again.
+struct ResponseAlreadyReceived<R: Role> {
+    response_future: Box<ResponseFuture<R>>,
+    request_aborted: Box<RequestAborted>,
+}
+
+impl<R: Role> ResponseAlreadyReceived<R> {
+    fn new() -> Self {
+        ResponseAlreadyReceived {
+            response_future: Box::new(future::err(rfc003::Error::Internal(String::from(
+                "the response to a resumed swap is no longer available",
+            )))),
+            request_aborted: Box::new(future::empty()),
+        }
+    }
+}
+
+impl<R: Role> CommunicationEvents<R> for ResponseAlreadyReceived<R> {
+    fn request_responded(
+        &mut self,
+        _request: &comit_client::rfc003::Request<
+            R::AlphaLedger,
+            R::BetaLedger,
+            R::AlphaAsset,
+            R::BetaAsset,
+        >,
+    ) -> &mut ResponseFuture<R> {
+        &mut self.response_future
+    }
+
+    fn abort_request(
+        &mut self,
+        _request: &comit_client::rfc003::Request<
+            R::AlphaLedger,
+            R::BetaLedger,
+            R::AlphaAsset,
+            R::BetaAsset,
+        >,
+    ) {
+    }
+
+    /// Aborts are only ever delivered to the process that accepted the request
+    fn request_aborted(&mut self) -> &mut RequestAborted {
+        &mut self.request_aborted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::{
+        dependencies::test,
+        metadata_store::{AssetKind, InMemoryMetadataStore, LedgerKind, Metadata},
+        rfc003::{state_store::InMemoryStateStore, SaveState},
+    };
+    use bitcoin_support::Network;
+    use spectral::prelude::*;
+    use std::sync::Mutex;
+
+    /// Fails to load the first swap it is asked for
+    #[derive(Default)]
+    struct FirstSwapBroken {
+        inner: InMemoryStateStore<SwapId>,
+        requested: Mutex<Vec<SwapId>>,
+    }
+
+    impl StateStore<SwapId> for FirstSwapBroken {
+        fn new_save_state<R: Role>(
+            &self,
+            key: SwapId,
+        ) -> Result<Arc<dyn SaveState<R>>, state_store::Error> {
+            self.inner.new_save_state(key)
+        }
+
+        fn get<R: Role>(&self, key: &SwapId) -> Result<Option<SwapStates<R>>, state_store::Error> {
+            let mut requested = self.requested.lock().unwrap();
+            requested.push(*key);
+
+            if requested.len() == 1 {
+                return Err(state_store::Error::Backend(String::from("broken")));
+            }
+            self.inner.get(key)
+        }
+
+        fn get_save_state<R: Role>(
+            &self,
+            key: &SwapId,
+        ) -> Result<Option<Arc<dyn SaveState<R>>>, state_store::Error> {
+            self.inner.get_save_state(key)
+        }
+    }
+
+    #[test]
+    fn broken_swap_does_not_keep_others_from_resuming() {
+        let metadata_store = Arc::new(InMemoryMetadataStore::default());
+        for _ in 0..3 {
+            metadata_store
+                .insert(
+                    SwapId::default(),
+                    Metadata::new(
+                        LedgerKind::Bitcoin,
+                        LedgerKind::Ethereum,
+                        AssetKind::Bitcoin,
+                        AssetKind::Ether,
+                        RoleKind::Alice,
+                        "127.0.0.1:8011".parse().unwrap(),
+                        Some(Network::Regtest),
+                    ),
+                )
+                .unwrap();
+        }
+        let state_store = Arc::new(FirstSwapBroken::default());
+        let dependencies = test::dependencies(metadata_store, Arc::clone(&state_store));
+
+        assert_that(&dependencies.resume_swaps()).is_ok();
+        assert_that(&state_store.requested.lock().unwrap().len()).is_equal_to(3);
+    }
+}
@@ -1,11 +1,12 @@
 use crate::swap_protocols::{
     self,
     asset::Asset,
-    rfc003::{ledger::Ledger, SecretHash},
+    rfc003::{ledger::Ledger, state_store::Persist, SecretHash},
 };
+use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Debug;
 
-pub trait Role: Send + Sync + Debug + Clone + 'static {
+pub trait Role: Send + Sync + Debug + Clone + Serialize + DeserializeOwned + 'static {
     type AlphaLedger: Ledger;
     type BetaLedger: Ledger;
     type AlphaAsset: Asset;
@@ -15,26 +16,30 @@ pub trait Role: Send + Sync + Debug + Clone + 'static {
         + Clone
         + Debug
         + PartialEq
+        + Persist
         + Into<<Self::AlphaLedger as swap_protocols::Ledger>::Identity>;
     type AlphaRefundHtlcIdentity: Send
         + Sync
         + Clone
         + Debug
         + PartialEq
+        + Persist
         + Into<<Self::AlphaLedger as swap_protocols::Ledger>::Identity>;
     type BetaRedeemHtlcIdentity: Send
         + Sync
         + Clone
         + Debug
         + PartialEq
+        + Persist
         + Into<<Self::BetaLedger as swap_protocols::Ledger>::Identity>;
     type BetaRefundHtlcIdentity: Send
         + Sync
         + Clone
         + Debug
         + PartialEq
+        + Persist
         + Into<<Self::BetaLedger as swap_protocols::Ledger>::Identity>;
-    type Secret: Send + Sync + Clone + Into<SecretHash> + Debug + PartialEq;
+    type Secret: Send + Sync + Clone + Into<SecretHash> + Debug + PartialEq + Persist;
 }
 
 #[derive(Debug)]
@@ -63,13 +68,14 @@ pub mod test {
         swap_protocols::{
             ledger::{Bitcoin, Ethereum},
             rfc003::{
-                events::{CommunicationEvents, ResponseFuture},
+                events::{CommunicationEvents, RequestAborted, ResponseFuture},
                 Alice, Bob,
             },
         },
     };
     use bitcoin_support::BitcoinQuantity;
     use ethereum_support::EtherQuantity;
+    use futures::future;
 
     pub type Alisha = Alice<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>;
     pub type Bobisha = Bob<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>;
@@ -89,6 +95,7 @@ pub mod test {
     #[allow(missing_debug_implementations)]
     pub struct FakeCommunicationEvents<R: Role> {
         pub response: Option<Box<ResponseFuture<R>>>,
+        pub aborted: Option<Box<RequestAborted>>,
     }
 
     impl<R: Role> CommunicationEvents<R> for FakeCommunicationEvents<R> {
@@ -103,5 +110,21 @@ pub mod test {
         ) -> &mut ResponseFuture<R> {
             self.response.as_mut().unwrap()
         }
+
+        fn abort_request(
+            &mut self,
+            _request: &comit_client::rfc003::Request<
+                R::AlphaLedger,
+                R::BetaLedger,
+                R::AlphaAsset,
+                R::BetaAsset,
+            >,
+        ) {
+        }
+
+        fn request_aborted(&mut self) -> &mut RequestAborted {
+            self.aborted
+                .get_or_insert_with(|| Box::new(future::empty()))
+        }
     }
 }
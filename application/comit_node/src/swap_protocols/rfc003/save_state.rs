@@ -1,9 +1,30 @@
-use crate::swap_protocols::rfc003::{state_machine::SwapStates, Role};
-use futures::sync::mpsc;
-use std::sync::RwLock;
+use crate::{
+    metrics,
+    swap_protocols::{
+        metadata_store::{HtlcEvent, Metadata, MetadataStore, Transition, TransitionStore},
+        rfc003::{
+            self,
+            state_machine::{Final, SwapOutcome, SwapStates},
+            Role,
+        },
+        StateChange, StateChanges, SwapId,
+    },
+    webhooks::{SwapEvent, Webhooks},
+};
+use futures::{sync::mpsc, Future};
+use std::{
+    sync::{Arc, Mutex, RwLock},
+    time::Instant,
+};
 
 pub trait SaveState<R: Role>: Send + Sync {
     fn save(&self, state: SwapStates<R>);
+
+    /// Saves a state the swap transitioned to, `htlc_event` being what
+    /// happened on a ledger to trigger the transition
+    fn save_transition(&self, state: SwapStates<R>, _htlc_event: Option<HtlcEvent>) {
+        self.save(state)
+    }
 }
 
 impl<R: Role + Sync> SaveState<R> for RwLock<Option<SwapStates<R>>> {
@@ -19,3 +40,409 @@ impl<R: Role> SaveState<R> for mpsc::UnboundedSender<SwapStates<R>> {
         let _ = self.unbounded_send(state);
     }
 }
+
+/// Keeps the current state and the last-update timestamp in the metadata of a
+/// swap in sync with its state transitions
+#[allow(missing_debug_implementations)]
+pub struct TouchMetadata<T, R: Role> {
+    save_state: Arc<dyn SaveState<R>>,
+    metadata_store: Arc<T>,
+    id: SwapId,
+}
+
+impl<T: MetadataStore<SwapId>, R: Role> TouchMetadata<T, R> {
+    pub fn new(save_state: Arc<dyn SaveState<R>>, metadata_store: Arc<T>, id: SwapId) -> Self {
+        TouchMetadata {
+            save_state,
+            metadata_store,
+            id,
+        }
+    }
+}
+
+impl<T: MetadataStore<SwapId>, R: Role> SaveState<R> for TouchMetadata<T, R> {
+    fn save(&self, state: SwapStates<R>) {
+        let name = state.name();
+        self.save_state.save(state);
+
+        if let Err(e) = self.metadata_store.touch(&self.id, &name) {
+            error!("Failed to update metadata of swap {}: {:?}", self.id, e);
+        }
+    }
+}
+
+/// Records the state transitions of a swap in its history. Only transitions
+/// reach the history, states saved while staying in a state do not.
+#[allow(missing_debug_implementations)]
+pub struct RecordTransitions<T, R: Role> {
+    save_state: Arc<dyn SaveState<R>>,
+    transition_store: Arc<T>,
+    id: SwapId,
+}
+
+impl<T: TransitionStore<SwapId>, R: Role> RecordTransitions<T, R> {
+    pub fn new(save_state: Arc<dyn SaveState<R>>, transition_store: Arc<T>, id: SwapId) -> Self {
+        RecordTransitions {
+            save_state,
+            transition_store,
+            id,
+        }
+    }
+}
+
+impl<T: TransitionStore<SwapId>, R: Role> SaveState<R> for RecordTransitions<T, R> {
+    fn save(&self, state: SwapStates<R>) {
+        self.save_state.save(state);
+    }
+
+    fn save_transition(&self, state: SwapStates<R>, htlc_event: Option<HtlcEvent>) {
+        let transition = Transition::new(state.name(), htlc_event.clone());
+
+        self.save_state.save_transition(state, htlc_event);
+
+        if let Err(e) = self
+            .transition_store
+            .record_transition(&self.id, transition)
+        {
+            metrics::persistence_failed(metrics::RECORD_TRANSITION);
+            error!("Failed to record transition of swap {}: {:?}", self.id, e);
+        }
+    }
+}
+
+/// Publishes each state of a swap, and what triggered it, once it has been
+/// saved
+#[allow(missing_debug_implementations)]
+pub struct PublishStateChanges<R: Role> {
+    save_state: Arc<dyn SaveState<R>>,
+    state_changes: Arc<StateChanges>,
+    id: SwapId,
+}
+
+impl<R: Role> PublishStateChanges<R> {
+    pub fn new(
+        save_state: Arc<dyn SaveState<R>>,
+        state_changes: Arc<StateChanges>,
+        id: SwapId,
+    ) -> Self {
+        PublishStateChanges {
+            save_state,
+            state_changes,
+            id,
+        }
+    }
+}
+
+impl<R: Role> SaveState<R> for PublishStateChanges<R> {
+    fn save(&self, state: SwapStates<R>) {
+        self.save_transition(state, None);
+    }
+
+    fn save_transition(&self, state: SwapStates<R>, htlc_event: Option<HtlcEvent>) {
+        let transition = Transition::new(state.name(), htlc_event.clone());
+
+        self.save_state.save_transition(state, htlc_event);
+        self.state_changes.publish(StateChange {
+            id: self.id,
+            transition,
+        });
+    }
+}
+
+/// Notifies the webhooks of the lifecycle events of a swap, once per state
+/// the swap enters
+#[allow(missing_debug_implementations)]
+pub struct NotifyWebhooks<R: Role> {
+    save_state: Arc<dyn SaveState<R>>,
+    webhooks: Arc<Webhooks>,
+    id: SwapId,
+    last_state: Mutex<Option<String>>,
+}
+
+impl<R: Role> NotifyWebhooks<R> {
+    pub fn new(save_state: Arc<dyn SaveState<R>>, webhooks: Arc<Webhooks>, id: SwapId) -> Self {
+        NotifyWebhooks {
+            save_state,
+            webhooks,
+            id,
+            last_state: Mutex::new(None),
+        }
+    }
+}
+
+impl<R: Role> SaveState<R> for NotifyWebhooks<R> {
+    fn save(&self, state: SwapStates<R>) {
+        let event = swap_event(&state);
+        let name = state.name();
+
+        self.save_state.save(state);
+
+        let mut last_state = self.last_state.lock().unwrap();
+        if last_state.as_ref() == Some(&name) {
+            return;
+        }
+        *last_state = Some(name.clone());
+
+        if let Some(event) = event {
+            self.webhooks.notify(self.id, event, name);
+        }
+    }
+}
+
+/// Keeps the swap metrics in sync with the state transitions of a swap
+#[allow(missing_debug_implementations)]
+pub struct RecordMetrics<R: Role> {
+    save_state: Arc<dyn SaveState<R>>,
+    metadata: Metadata,
+    current_state: Mutex<Option<(String, Instant)>>,
+}
+
+impl<R: Role> RecordMetrics<R> {
+    pub fn new(save_state: Arc<dyn SaveState<R>>, metadata: Metadata) -> Self {
+        RecordMetrics {
+            save_state,
+            metadata,
+            current_state: Mutex::new(None),
+        }
+    }
+}
+
+impl<R: Role> SaveState<R> for RecordMetrics<R> {
+    fn save(&self, state: SwapStates<R>) {
+        let name = state.name();
+        let is_final = match state {
+            SwapStates::Final(_) => true,
+            _ => false,
+        };
+
+        self.save_state.save(state);
+
+        let mut current_state = self.current_state.lock().unwrap();
+        match *current_state {
+            Some((ref previous, _)) if *previous == name => return,
+            Some((ref previous, entered_at)) => {
+                metrics::swap_left_state(&self.metadata, previous, entered_at.elapsed())
+            }
+            None => {}
+        }
+
+        metrics::swap_entered_state(&self.metadata, &name);
+        if is_final {
+            metrics::swap_finished(&self.metadata, &name);
+        }
+        *current_state = Some((name, Instant::now()));
+    }
+}
+
+/// Logs how the state machine of a swap ended. The error it fails with is saved
+/// as the last state of the swap, so the failure is recorded and published
+/// like any other state.
+pub fn save_failure<R: Role, F: Future<Item = SwapOutcome<R>, Error = rfc003::Error>>(
+    state_machine: F,
+    save_state: Arc<dyn SaveState<R>>,
+    id: SwapId,
+) -> impl Future<Item = (), Error = ()> {
+    state_machine
+        .map(move |outcome| {
+            info!("Swap {} finished with {:?}", id, outcome);
+        })
+        .map_err(move |e| {
+            error!("Swap {} failed with {:?}", id, e);
+            save_state.save(SwapStates::Error(e));
+        })
+}
+
+fn swap_event<R: Role>(state: &SwapStates<R>) -> Option<SwapEvent> {
+    use self::{SwapOutcome as SO, SwapStates as SS};
+    match *state {
+        SS::Start { .. } => Some(SwapEvent::Requested),
+        SS::Accepted { .. } => Some(SwapEvent::Accepted),
+        SS::AlphaDeployed { .. } | SS::AlphaFundedBetaDeployed { .. } => None,
+        SS::AlphaFunded { .. } | SS::BothFunded { .. } => Some(SwapEvent::Funded),
+        SS::AlphaFundedBetaRedeemed { .. }
+        | SS::AlphaRedeemedBetaFunded { .. }
+        | SS::Final(Final(SO::BothRedeemed { .. }))
+        | SS::Final(Final(SO::AlphaRedeemedBetaRefunded { .. })) => Some(SwapEvent::Redeemed),
+        SS::AlphaFundedBetaRefunded { .. }
+        | SS::AlphaRefundedBetaFunded { .. }
+        | SS::Final(Final(SO::AlphaRefunded { .. }))
+        | SS::Final(Final(SO::BothRefunded { .. }))
+        | SS::Final(Final(SO::AlphaRefundedBetaRedeemed { .. })) => Some(SwapEvent::Refunded),
+        SS::Final(Final(SO::Rejected { .. })) => Some(SwapEvent::Rejected),
+        SS::Error(_) => Some(SwapEvent::Failed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        swap_protocols::{
+            dependencies::{test, ProtocolDependencies},
+            ledger::{Bitcoin, Ethereum},
+            metadata_store::{self, HtlcEventKind, InMemoryMetadataStore, SwapLedger},
+            rfc003::{
+                self, role::test::Alisha, state_machine::Start, state_store::InMemoryStateStore,
+                Secret,
+            },
+        },
+        webhooks::{InMemoryPendingDeliveries, PendingDeliveries, Webhook},
+    };
+    use bitcoin_support::{BitcoinQuantity, Blocks};
+    use ethereum_support::EtherQuantity;
+    use futures::future;
+    use spectral::prelude::*;
+
+    /// Only keeps transitions, or fails to if `failing` is set
+    #[derive(Debug, Default)]
+    struct TransitionsStore {
+        transitions: Mutex<Vec<Transition>>,
+        failing: bool,
+    }
+
+    impl TransitionStore<SwapId> for TransitionsStore {
+        fn record_transition(
+            &self,
+            _: &SwapId,
+            transition: Transition,
+        ) -> Result<(), metadata_store::Error> {
+            if self.failing {
+                return Err(metadata_store::Error::Backend(String::from("disk full")));
+            }
+
+            self.transitions.lock().unwrap().push(transition);
+            Ok(())
+        }
+
+        fn transitions(&self, _: &SwapId) -> Result<Vec<Transition>, metadata_store::Error> {
+            Ok(self.transitions.lock().unwrap().clone())
+        }
+    }
+
+    fn start_state() -> SwapStates<Alisha> {
+        SwapStates::from(Start::<Alisha> {
+            alpha_ledger_refund_identity: secp256k1_support::KeyPair::from_secret_key_slice(
+                &[1u8; 32],
+            )
+            .unwrap(),
+            beta_ledger_redeem_identity: "8457037fcd80a8650c4692d7fcfc1d0a96b92867"
+                .parse()
+                .unwrap(),
+            alpha_ledger: Bitcoin::regtest(),
+            beta_ledger: Ethereum::dev(),
+            alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
+            beta_asset: EtherQuantity::from_eth(10.0),
+            alpha_ledger_lock_duration: Blocks::from(144),
+            secret: Secret::from(*b"hello world, you are beautiful!!"),
+            role: Alisha::default(),
+        })
+    }
+
+    fn record_transitions(
+        store: &Arc<TransitionsStore>,
+    ) -> (
+        RecordTransitions<TransitionsStore, Alisha>,
+        Arc<RwLock<Option<SwapStates<Alisha>>>>,
+    ) {
+        let inner = Arc::new(RwLock::new(None));
+        let record_transitions = RecordTransitions::new(
+            Arc::clone(&inner) as Arc<dyn SaveState<Alisha>>,
+            Arc::clone(store),
+            SwapId::default(),
+        );
+
+        (record_transitions, inner)
+    }
+
+    #[test]
+    fn error_state_is_a_failed_swap() {
+        let state = SwapStates::<Alisha>::Error(rfc003::Error::Internal(String::from("boom")));
+
+        assert_that(&swap_event(&state)).is_equal_to(Some(SwapEvent::Failed));
+    }
+
+    #[test]
+    fn saved_states_are_kept_out_of_the_history() {
+        let store = Arc::new(TransitionsStore::default());
+        let (record_transitions, inner) = record_transitions(&store);
+
+        record_transitions.save(start_state());
+
+        assert_that(&*inner.read().unwrap()).contains_value(start_state());
+        assert_that(&*store.transitions.lock().unwrap()).is_empty();
+    }
+
+    #[test]
+    fn transitions_are_recorded_with_their_transaction_id() {
+        let store = Arc::new(TransitionsStore::default());
+        let (record_transitions, inner) = record_transitions(&store);
+        let htlc_event = HtlcEvent {
+            ledger: SwapLedger::Alpha,
+            event: HtlcEventKind::Funded,
+            transaction_id: Some(String::from("abcd")),
+            htlc_location: None,
+            secret: None,
+        };
+
+        record_transitions.save_transition(start_state(), Some(htlc_event.clone()));
+
+        let transitions = store.transitions.lock().unwrap();
+        assert_that(&*inner.read().unwrap()).contains_value(start_state());
+        assert_that(&transitions.len()).is_equal_to(1);
+        assert_that(&transitions[0].state).is_equal_to(start_state().name());
+        assert_that(&transitions[0].htlc_event).contains_value(htlc_event);
+    }
+
+    #[test]
+    fn failing_to_record_a_transition_is_counted() {
+        let store = Arc::new(TransitionsStore {
+            failing: true,
+            ..TransitionsStore::default()
+        });
+        let (record_transitions, inner) = record_transitions(&store);
+        let failures = metrics::PERSISTENCE_ERRORS.with_label_values(&[metrics::RECORD_TRANSITION]);
+        let before = failures.get();
+
+        record_transitions.save_transition(start_state(), None);
+
+        assert_that(&*inner.read().unwrap()).contains_value(start_state());
+        assert_that(&failures.get()).is_equal_to(before + 1);
+    }
+
+    #[test]
+    fn failing_state_machine_notifies_webhooks_of_the_failure() {
+        let pending_deliveries = Arc::new(InMemoryPendingDeliveries::default());
+        let dependencies = ProtocolDependencies {
+            webhooks: Arc::new(Webhooks::with_pending_deliveries(
+                vec![Webhook {
+                    url: "http://localhost:1/".parse().unwrap(),
+                    secret: String::from("secret"),
+                }],
+                Arc::clone(&pending_deliveries) as Arc<dyn PendingDeliveries>,
+            )),
+            ..test::dependencies(
+                Arc::new(InMemoryMetadataStore::default()),
+                Arc::new(InMemoryStateStore::default()),
+            )
+        };
+        let id = SwapId::default();
+        let inner: Arc<RwLock<Option<SwapStates<Alisha>>>> = Arc::new(RwLock::new(None));
+        let save_state =
+            dependencies.observe_save_state(Arc::clone(&inner) as Arc<dyn SaveState<Alisha>>, id);
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let result = runtime.block_on(save_failure(
+            future::err::<SwapOutcome<Alisha>, _>(rfc003::Error::TimerError),
+            save_state,
+            id,
+        ));
+
+        assert_that(&result).is_err();
+        assert_that(&*inner.read().unwrap())
+            .contains_value(SwapStates::Error(rfc003::Error::TimerError));
+        let deliveries = pending_deliveries.all().unwrap();
+        assert_that(&deliveries).has_length(1);
+        assert_that(&String::from_utf8(deliveries[0].body.clone()).unwrap())
+            .contains("\"event\":\"failed\"");
+    }
+}
@@ -4,12 +4,16 @@ use crate::{
     comit_client::{self, SwapReject},
     swap_protocols::{
         asset::Asset,
-        rfc003::{self, events, ledger::Ledger, RedeemTransaction, Role, SaveState, SecretHash},
+        rfc003::{
+            self, events, ledger::Ledger, validate_lock_durations, FundTransaction,
+            RedeemTransaction, RefundTransaction, Role, SaveState, SecretHash,
+        },
+        HtlcEvent, HtlcEventKind, SwapLedger,
     },
 };
 use futures::{future::Either, Async, Future};
 use state_machine_future::{RentToOwn, StateMachineFuture};
-use std::{fmt, sync::Arc};
+use std::{fmt, sync::Arc, time::Duration};
 
 #[derive(Debug, Clone, PartialEq, Eq, LabelledGeneric)]
 pub struct StateMachineResponse<ALSI, BLRI, BLLD> {
@@ -104,6 +108,39 @@ impl<R: Role> OngoingSwap<R> {
     }
 }
 
+impl<R: Role> From<OngoingSwap<R>> for Start<R> {
+    fn from(swap: OngoingSwap<R>) -> Self {
+        Start {
+            alpha_ledger_refund_identity: swap.alpha_ledger_refund_identity,
+            beta_ledger_redeem_identity: swap.beta_ledger_redeem_identity,
+            alpha_ledger: swap.alpha_ledger,
+            beta_ledger: swap.beta_ledger,
+            alpha_asset: swap.alpha_asset,
+            beta_asset: swap.beta_asset,
+            alpha_ledger_lock_duration: swap.alpha_ledger_lock_duration,
+            secret: swap.secret,
+            role: swap.role,
+        }
+    }
+}
+
+/// Whether and from when on an HTLC can be refunded. `refundable_at` is
+/// only known once the HTLC has been deployed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HtlcExpiry<L: Ledger> {
+    pub refundable_at: Option<L::Expiry>,
+    pub refundable_now: bool,
+}
+
+impl<L: Ledger> Default for HtlcExpiry<L> {
+    fn default() -> Self {
+        HtlcExpiry {
+            refundable_at: None,
+            refundable_now: false,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum SwapOutcome<R: Role> {
     Rejected {
@@ -137,6 +174,8 @@ pub struct Context<R: Role> {
     pub beta_ledger_events: Box<dyn events::LedgerEvents<R::BetaLedger, R::BetaAsset>>,
     pub state_repo: Arc<dyn SaveState<R>>,
     pub communication_events: Box<dyn events::CommunicationEvents<R>>,
+    /// The minimum time between the expiry of the beta and the alpha HTLC
+    pub lock_duration_margin: Duration,
 }
 
 #[derive(StateMachineFuture)]
@@ -156,19 +195,21 @@ pub enum Swap<R: Role> {
         role: R,
     },
 
-    #[state_machine_future(transitions(AlphaDeployed))]
+    #[state_machine_future(transitions(AlphaDeployed, Final))]
     Accepted { swap: OngoingSwap<R> },
 
     #[state_machine_future(transitions(AlphaFunded, Final))]
     AlphaDeployed {
         swap: OngoingSwap<R>,
         alpha_htlc_location: <R::AlphaLedger as Ledger>::HtlcLocation,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
     },
 
     #[state_machine_future(transitions(AlphaFundedBetaDeployed, Final))]
     AlphaFunded {
         swap: OngoingSwap<R>,
         alpha_htlc_location: <R::AlphaLedger as Ledger>::HtlcLocation,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
     },
 
     #[state_machine_future(transitions(BothFunded, Final))]
@@ -176,6 +217,8 @@ pub enum Swap<R: Role> {
         swap: OngoingSwap<R>,
         alpha_htlc_location: <R::AlphaLedger as Ledger>::HtlcLocation,
         beta_htlc_location: <R::BetaLedger as Ledger>::HtlcLocation,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
+        beta_htlc_expiry: HtlcExpiry<R::BetaLedger>,
     },
 
     #[state_machine_future(transitions(
@@ -188,24 +231,29 @@ pub enum Swap<R: Role> {
         swap: OngoingSwap<R>,
         alpha_htlc_location: <R::AlphaLedger as Ledger>::HtlcLocation,
         beta_htlc_location: <R::BetaLedger as Ledger>::HtlcLocation,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
+        beta_htlc_expiry: HtlcExpiry<R::BetaLedger>,
     },
 
     #[state_machine_future(transitions(Final))]
     AlphaFundedBetaRefunded {
         swap: OngoingSwap<R>,
         alpha_htlc_location: <R::AlphaLedger as Ledger>::HtlcLocation,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
     },
 
     #[state_machine_future(transitions(Final))]
     AlphaRefundedBetaFunded {
         swap: OngoingSwap<R>,
         beta_htlc_location: <R::BetaLedger as Ledger>::HtlcLocation,
+        beta_htlc_expiry: HtlcExpiry<R::BetaLedger>,
     },
 
     #[state_machine_future(transitions(Final))]
     AlphaRedeemedBetaFunded {
         swap: OngoingSwap<R>,
         beta_htlc_location: <R::BetaLedger as Ledger>::HtlcLocation,
+        beta_htlc_expiry: HtlcExpiry<R::BetaLedger>,
     },
 
     #[state_machine_future(transitions(Final))]
@@ -213,6 +261,7 @@ pub enum Swap<R: Role> {
         swap: OngoingSwap<R>,
         beta_redeemed_tx: RedeemTransaction<R::BetaLedger>,
         alpha_htlc_location: <R::AlphaLedger as Ledger>::HtlcLocation,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
     },
 
     #[state_machine_future(ready)]
@@ -246,12 +295,29 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         let state = state.take();
 
         match response {
-            Ok(swap_accepted) => transition_save!(
-                context.state_repo,
-                Accepted {
-                    swap: OngoingSwap::new(state, swap_accepted),
+            Ok(swap_accepted) => match validate_lock_durations(
+                state.alpha_ledger_lock_duration.clone(),
+                swap_accepted.beta_ledger_lock_duration.clone(),
+                context.lock_duration_margin,
+            ) {
+                Ok(()) => transition_save!(
+                    context.state_repo,
+                    Accepted {
+                        swap: OngoingSwap::new(state, swap_accepted),
+                    }
+                ),
+                Err(e) => {
+                    warn!("Rejecting response to swap request: {}", e);
+                    context.communication_events.abort_request(&request);
+                    transition_save!(
+                        context.state_repo,
+                        Final(SwapOutcome::Rejected {
+                            start: state,
+                            rejection_type: SwapReject::UnsafeLockDurations,
+                        })
+                    )
                 }
-            ),
+            },
             Err(rejection_type) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::Rejected {
@@ -266,17 +332,32 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         state: &'s mut RentToOwn<'s, Accepted<R>>,
         context: &'c mut RentToOwn<'c, Context<R>>,
     ) -> Result<Async<AfterAccepted<R>>, rfc003::Error> {
+        if let Async::Ready(()) = context.communication_events.request_aborted().poll()? {
+            let state = state.take();
+            warn!("Counterparty aborted the swap before deploying the alpha HTLC");
+            transition_save!(
+                context.state_repo,
+                Final(SwapOutcome::Rejected {
+                    start: state.swap.into(),
+                    rejection_type: SwapReject::Aborted,
+                })
+            )
+        }
+
         let alpha_htlc_location = try_ready!(context
             .alpha_ledger_events
             .htlc_deployed(state.swap.alpha_htlc_params())
             .poll());
         let state = state.take();
+        let htlc_event = htlc_deployed::<R::AlphaLedger>(SwapLedger::Alpha, &alpha_htlc_location);
         transition_save!(
             context.state_repo,
             AlphaDeployed {
                 swap: state.swap,
                 alpha_htlc_location,
-            }
+                alpha_htlc_expiry: HtlcExpiry::default(),
+            },
+            htlc_event
         )
     }
 
@@ -284,7 +365,15 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         state: &'s mut RentToOwn<'s, AlphaDeployed<R>>,
         context: &'c mut RentToOwn<'c, Context<R>>,
     ) -> Result<Async<AfterAlphaDeployed<R>>, rfc003::Error> {
-        let _alpha_funding_transaction = try_ready!(context
+        if poll_htlc_expiry(
+            &mut *context.alpha_ledger_events,
+            state.swap.alpha_ledger_lock_duration.clone(),
+            &mut state.alpha_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+
+        let alpha_funding_transaction = try_ready!(context
             .alpha_ledger_events
             .htlc_funded(state.swap.alpha_htlc_params(), &state.alpha_htlc_location)
             .poll());
@@ -294,7 +383,9 @@ impl<R: Role> PollSwap<R> for Swap<R> {
             AlphaFunded {
                 swap: state.swap,
                 alpha_htlc_location: state.alpha_htlc_location,
-            }
+                alpha_htlc_expiry: state.alpha_htlc_expiry,
+            },
+            htlc_funded(SwapLedger::Alpha, &alpha_funding_transaction)
         )
     }
 
@@ -302,7 +393,15 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         state: &'s mut RentToOwn<'s, AlphaFunded<R>>,
         context: &'c mut RentToOwn<'c, Context<R>>,
     ) -> Result<Async<AfterAlphaFunded<R>>, rfc003::Error> {
-        if let Async::Ready(_alpha_redeemed_or_refunded) = context
+        if poll_htlc_expiry(
+            &mut *context.alpha_ledger_events,
+            state.swap.alpha_ledger_lock_duration.clone(),
+            &mut state.alpha_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+
+        if let Async::Ready(alpha_redeemed_or_refunded) = context
             .alpha_ledger_events
             .htlc_redeemed_or_refunded(state.swap.alpha_htlc_params(), &state.alpha_htlc_location)
             .poll()?
@@ -311,7 +410,8 @@ impl<R: Role> PollSwap<R> for Swap<R> {
                 context.state_repo,
                 Final(SwapOutcome::AlphaRefunded {
                     swap: state.take().swap
-                })
+                }),
+                htlc_redeemed_or_refunded(SwapLedger::Alpha, &alpha_redeemed_or_refunded)
             )
         }
 
@@ -320,13 +420,17 @@ impl<R: Role> PollSwap<R> for Swap<R> {
             .htlc_deployed(state.swap.beta_htlc_params())
             .poll());
         let state = state.take();
+        let htlc_event = htlc_deployed::<R::BetaLedger>(SwapLedger::Beta, &beta_htlc_location);
         transition_save!(
             context.state_repo,
             AlphaFundedBetaDeployed {
                 swap: state.swap,
                 alpha_htlc_location: state.alpha_htlc_location,
                 beta_htlc_location,
-            }
+                alpha_htlc_expiry: state.alpha_htlc_expiry,
+                beta_htlc_expiry: HtlcExpiry::default(),
+            },
+            htlc_event
         )
     }
 
@@ -334,7 +438,22 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         state: &'s mut RentToOwn<'s, AlphaFundedBetaDeployed<R>>,
         context: &'c mut RentToOwn<'c, Context<R>>,
     ) -> Result<Async<AfterAlphaFundedBetaDeployed<R>>, rfc003::Error> {
-        if let Async::Ready(_alpha_redeemed_or_refunded) = context
+        if poll_htlc_expiry(
+            &mut *context.alpha_ledger_events,
+            state.swap.alpha_ledger_lock_duration.clone(),
+            &mut state.alpha_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+        if poll_htlc_expiry(
+            &mut *context.beta_ledger_events,
+            state.swap.beta_ledger_lock_duration.clone(),
+            &mut state.beta_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+
+        if let Async::Ready(alpha_redeemed_or_refunded) = context
             .alpha_ledger_events
             .htlc_redeemed_or_refunded(state.swap.alpha_htlc_params(), &state.alpha_htlc_location)
             .poll()?
@@ -343,11 +462,12 @@ impl<R: Role> PollSwap<R> for Swap<R> {
                 context.state_repo,
                 Final(SwapOutcome::AlphaRefunded {
                     swap: state.take().swap
-                })
+                }),
+                htlc_redeemed_or_refunded(SwapLedger::Alpha, &alpha_redeemed_or_refunded)
             )
         }
 
-        let _ = try_ready!(context
+        let beta_funding_transaction = try_ready!(context
             .beta_ledger_events
             .htlc_funded(state.swap.beta_htlc_params(), &state.beta_htlc_location)
             .poll());
@@ -358,7 +478,10 @@ impl<R: Role> PollSwap<R> for Swap<R> {
                 swap: state.swap,
                 alpha_htlc_location: state.alpha_htlc_location,
                 beta_htlc_location: state.beta_htlc_location,
-            }
+                alpha_htlc_expiry: state.alpha_htlc_expiry,
+                beta_htlc_expiry: state.beta_htlc_expiry,
+            },
+            htlc_funded(SwapLedger::Beta, &beta_funding_transaction)
         )
     }
 
@@ -366,12 +489,28 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         state: &'s mut RentToOwn<'s, BothFunded<R>>,
         context: &'c mut RentToOwn<'c, Context<R>>,
     ) -> Result<Async<AfterBothFunded<R>>, rfc003::Error> {
+        if poll_htlc_expiry(
+            &mut *context.alpha_ledger_events,
+            state.swap.alpha_ledger_lock_duration.clone(),
+            &mut state.alpha_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+        if poll_htlc_expiry(
+            &mut *context.beta_ledger_events,
+            state.swap.beta_ledger_lock_duration.clone(),
+            &mut state.beta_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+
         if let Async::Ready(redeemed_or_refunded) = context
             .beta_ledger_events
             .htlc_redeemed_or_refunded(state.swap.beta_htlc_params(), &state.beta_htlc_location)
             .poll()?
         {
             let state = state.take();
+            let htlc_event = htlc_redeemed_or_refunded(SwapLedger::Beta, &redeemed_or_refunded);
             match redeemed_or_refunded {
                 Either::A(beta_redeemed_tx) => transition_save!(
                     context.state_repo,
@@ -379,41 +518,51 @@ impl<R: Role> PollSwap<R> for Swap<R> {
                         swap: state.swap,
                         beta_redeemed_tx,
                         alpha_htlc_location: state.alpha_htlc_location,
-                    }
+                        alpha_htlc_expiry: state.alpha_htlc_expiry,
+                    },
+                    htlc_event
                 ),
-                Either::B(_beta_refunded_txid) => transition_save!(
+                Either::B(_) => transition_save!(
                     context.state_repo,
                     AlphaFundedBetaRefunded {
                         swap: state.swap,
                         alpha_htlc_location: state.alpha_htlc_location,
-                    }
+                        alpha_htlc_expiry: state.alpha_htlc_expiry,
+                    },
+                    htlc_event
                 ),
             }
         }
 
-        match try_ready!(context
+        let alpha_redeemed_or_refunded = try_ready!(context
             .alpha_ledger_events
             .htlc_redeemed_or_refunded(state.swap.alpha_htlc_params(), &state.alpha_htlc_location)
-            .poll())
-        {
-            Either::A(_alpha_redeemed_tx) => {
+            .poll());
+        let htlc_event = htlc_redeemed_or_refunded(SwapLedger::Alpha, &alpha_redeemed_or_refunded);
+
+        match alpha_redeemed_or_refunded {
+            Either::A(_) => {
                 let state = state.take();
                 transition_save!(
                     context.state_repo,
                     AlphaRedeemedBetaFunded {
                         swap: state.swap,
                         beta_htlc_location: state.beta_htlc_location,
-                    }
+                        beta_htlc_expiry: state.beta_htlc_expiry,
+                    },
+                    htlc_event
                 )
             }
-            Either::B(_alpha_refunded_txid) => {
+            Either::B(_) => {
                 let state = state.take();
                 transition_save!(
                     context.state_repo,
                     AlphaRefundedBetaFunded {
                         swap: state.swap,
                         beta_htlc_location: state.beta_htlc_location,
-                    }
+                        beta_htlc_expiry: state.beta_htlc_expiry,
+                    },
+                    htlc_event
                 )
             }
         }
@@ -423,22 +572,34 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         state: &'s mut RentToOwn<'s, AlphaFundedBetaRefunded<R>>,
         context: &'c mut RentToOwn<'c, Context<R>>,
     ) -> Result<Async<AfterAlphaFundedBetaRefunded<R>>, rfc003::Error> {
-        match try_ready!(context
+        if poll_htlc_expiry(
+            &mut *context.alpha_ledger_events,
+            state.swap.alpha_ledger_lock_duration.clone(),
+            &mut state.alpha_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+
+        let alpha_redeemed_or_refunded = try_ready!(context
             .alpha_ledger_events
             .htlc_redeemed_or_refunded(state.swap.alpha_htlc_params(), &state.alpha_htlc_location)
-            .poll())
-        {
-            Either::A(_alpha_redeemed_txid) => transition_save!(
+            .poll());
+        let htlc_event = htlc_redeemed_or_refunded(SwapLedger::Alpha, &alpha_redeemed_or_refunded);
+
+        match alpha_redeemed_or_refunded {
+            Either::A(_) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::AlphaRedeemedBetaRefunded {
                     swap: state.take().swap
-                })
+                }),
+                htlc_event
             ),
-            Either::B(_alpha_refunded_txid) => transition_save!(
+            Either::B(_) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::BothRefunded {
                     swap: state.take().swap
-                })
+                }),
+                htlc_event
             ),
         }
     }
@@ -447,22 +608,34 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         state: &'s mut RentToOwn<'s, AlphaRefundedBetaFunded<R>>,
         context: &'c mut RentToOwn<'c, Context<R>>,
     ) -> Result<Async<AfterAlphaRefundedBetaFunded<R>>, rfc003::Error> {
-        match try_ready!(context
+        if poll_htlc_expiry(
+            &mut *context.beta_ledger_events,
+            state.swap.beta_ledger_lock_duration.clone(),
+            &mut state.beta_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+
+        let beta_redeemed_or_refunded = try_ready!(context
             .beta_ledger_events
             .htlc_redeemed_or_refunded(state.swap.beta_htlc_params(), &state.beta_htlc_location)
-            .poll())
-        {
-            Either::A(_beta_redeemed_txid) => transition_save!(
+            .poll());
+        let htlc_event = htlc_redeemed_or_refunded(SwapLedger::Beta, &beta_redeemed_or_refunded);
+
+        match beta_redeemed_or_refunded {
+            Either::A(_) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::AlphaRefundedBetaRedeemed {
                     swap: state.take().swap
-                })
+                }),
+                htlc_event
             ),
-            Either::B(_beta_refunded_txid) => transition_save!(
+            Either::B(_) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::BothRefunded {
                     swap: state.take().swap
-                })
+                }),
+                htlc_event
             ),
         }
     }
@@ -471,22 +644,34 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         state: &'s mut RentToOwn<'s, AlphaRedeemedBetaFunded<R>>,
         context: &'c mut RentToOwn<'c, Context<R>>,
     ) -> Result<Async<AfterAlphaRedeemedBetaFunded<R>>, rfc003::Error> {
-        match try_ready!(context
+        if poll_htlc_expiry(
+            &mut *context.beta_ledger_events,
+            state.swap.beta_ledger_lock_duration.clone(),
+            &mut state.beta_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+
+        let beta_redeemed_or_refunded = try_ready!(context
             .beta_ledger_events
             .htlc_redeemed_or_refunded(state.swap.beta_htlc_params(), &state.beta_htlc_location)
-            .poll())
-        {
-            Either::A(_beta_redeemed_txid) => transition_save!(
+            .poll());
+        let htlc_event = htlc_redeemed_or_refunded(SwapLedger::Beta, &beta_redeemed_or_refunded);
+
+        match beta_redeemed_or_refunded {
+            Either::A(_) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::BothRedeemed {
                     swap: state.take().swap
-                })
+                }),
+                htlc_event
             ),
-            Either::B(_beta_refunded_txid) => transition_save!(
+            Either::B(_) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::AlphaRedeemedBetaRefunded {
                     swap: state.take().swap
-                })
+                }),
+                htlc_event
             ),
         }
     }
@@ -495,27 +680,123 @@ impl<R: Role> PollSwap<R> for Swap<R> {
         state: &'s mut RentToOwn<'s, AlphaFundedBetaRedeemed<R>>,
         context: &'c mut RentToOwn<'c, Context<R>>,
     ) -> Result<Async<AfterAlphaFundedBetaRedeemed<R>>, rfc003::Error> {
-        match try_ready!(context
+        if poll_htlc_expiry(
+            &mut *context.alpha_ledger_events,
+            state.swap.alpha_ledger_lock_duration.clone(),
+            &mut state.alpha_htlc_expiry,
+        )? {
+            context.state_repo.save((**state).clone().into());
+        }
+
+        let alpha_redeemed_or_refunded = try_ready!(context
             .alpha_ledger_events
             .htlc_redeemed_or_refunded(state.swap.alpha_htlc_params(), &state.alpha_htlc_location)
-            .poll())
-        {
-            Either::A(_beta_redeemed_txid) => transition_save!(
+            .poll());
+        let htlc_event = htlc_redeemed_or_refunded(SwapLedger::Alpha, &alpha_redeemed_or_refunded);
+
+        match alpha_redeemed_or_refunded {
+            Either::A(_) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::BothRedeemed {
                     swap: state.take().swap
-                })
+                }),
+                htlc_event
             ),
-            Either::B(_beta_refunded_txid) => transition_save!(
+            Either::B(_) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::AlphaRefundedBetaRedeemed {
                     swap: state.take().swap
-                })
+                }),
+                htlc_event
             ),
         }
     }
 }
 
+/// Returns whether `htlc_expiry` changed
+fn poll_htlc_expiry<L: Ledger, E: events::HtlcExpiryEvents<L> + ?Sized>(
+    htlc_expiry_events: &mut E,
+    lock_duration: L::LockDuration,
+    htlc_expiry: &mut HtlcExpiry<L>,
+) -> Result<bool, rfc003::Error> {
+    let mut changed = false;
+
+    if htlc_expiry.refundable_at.is_none() {
+        if let Async::Ready(refundable_at) = htlc_expiry_events
+            .htlc_refundable_at(lock_duration)
+            .poll()?
+        {
+            htlc_expiry.refundable_at = Some(refundable_at);
+            changed = true;
+        }
+    }
+
+    if let (Some(refundable_at), false) = (
+        htlc_expiry.refundable_at.clone(),
+        htlc_expiry.refundable_now,
+    ) {
+        if let Async::Ready(()) = htlc_expiry_events.htlc_refundable(refundable_at).poll()? {
+            htlc_expiry.refundable_now = true;
+            changed = true;
+        }
+    }
+
+    Ok(changed)
+}
+
+fn htlc_deployed<L: Ledger>(
+    ledger: SwapLedger,
+    htlc_location: &L::HtlcLocation,
+) -> Option<HtlcEvent> {
+    Some(HtlcEvent {
+        ledger,
+        event: HtlcEventKind::Deployed,
+        transaction_id: None,
+        htlc_location: serde_json::to_value(htlc_location).ok(),
+        secret: None,
+    })
+}
+
+fn htlc_funded<L: Ledger>(
+    ledger: SwapLedger,
+    fund_transaction: &Option<FundTransaction<L>>,
+) -> Option<HtlcEvent> {
+    Some(HtlcEvent {
+        ledger,
+        event: HtlcEventKind::Funded,
+        transaction_id: fund_transaction
+            .as_ref()
+            .map(|transaction| L::transaction_id(transaction.as_ref())),
+        htlc_location: None,
+        secret: None,
+    })
+}
+
+fn htlc_redeemed_or_refunded<L: Ledger>(
+    ledger: SwapLedger,
+    redeemed_or_refunded: &Either<RedeemTransaction<L>, RefundTransaction<L>>,
+) -> Option<HtlcEvent> {
+    let (event, transaction, secret) = match redeemed_or_refunded {
+        Either::A(RedeemTransaction {
+            transaction,
+            secret,
+        }) => (
+            HtlcEventKind::Redeemed,
+            transaction,
+            Some(format!("{:x}", secret)),
+        ),
+        Either::B(RefundTransaction(transaction)) => (HtlcEventKind::Refunded, transaction, None),
+    };
+
+    Some(HtlcEvent {
+        ledger,
+        event,
+        transaction_id: Some(L::transaction_id(transaction)),
+        htlc_location: None,
+        secret,
+    })
+}
+
 macro_rules! impl_display {
     ($state:ident) => {
         impl<R: Role> fmt::Display for $state<R> {
@@ -622,4 +903,112 @@ impl<R: Role> SwapStates<R> {
             SS::Start(_) | SS::Final(_) | SS::Error(_) => None,
         }
     }
+
+    pub fn alpha_htlc_expiry(&self) -> Option<&HtlcExpiry<R::AlphaLedger>> {
+        use self::SwapStates as SS;
+        match *self {
+            SS::AlphaDeployed(AlphaDeployed {
+                ref alpha_htlc_expiry,
+                ..
+            })
+            | SS::AlphaFunded(AlphaFunded {
+                ref alpha_htlc_expiry,
+                ..
+            })
+            | SS::AlphaFundedBetaDeployed(AlphaFundedBetaDeployed {
+                ref alpha_htlc_expiry,
+                ..
+            })
+            | SS::BothFunded(BothFunded {
+                ref alpha_htlc_expiry,
+                ..
+            })
+            | SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
+                ref alpha_htlc_expiry,
+                ..
+            })
+            | SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
+                ref alpha_htlc_expiry,
+                ..
+            }) => Some(alpha_htlc_expiry),
+            _ => None,
+        }
+    }
+
+    pub fn beta_htlc_expiry(&self) -> Option<&HtlcExpiry<R::BetaLedger>> {
+        use self::SwapStates as SS;
+        match *self {
+            SS::AlphaFundedBetaDeployed(AlphaFundedBetaDeployed {
+                ref beta_htlc_expiry,
+                ..
+            })
+            | SS::BothFunded(BothFunded {
+                ref beta_htlc_expiry,
+                ..
+            })
+            | SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
+                ref beta_htlc_expiry,
+                ..
+            })
+            | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
+                ref beta_htlc_expiry,
+                ..
+            }) => Some(beta_htlc_expiry),
+            _ => None,
+        }
+    }
+
+    pub fn alpha_htlc_location(&self) -> Option<&<R::AlphaLedger as Ledger>::HtlcLocation> {
+        use self::SwapStates as SS;
+        match *self {
+            SS::AlphaDeployed(AlphaDeployed {
+                ref alpha_htlc_location,
+                ..
+            })
+            | SS::AlphaFunded(AlphaFunded {
+                ref alpha_htlc_location,
+                ..
+            })
+            | SS::AlphaFundedBetaDeployed(AlphaFundedBetaDeployed {
+                ref alpha_htlc_location,
+                ..
+            })
+            | SS::BothFunded(BothFunded {
+                ref alpha_htlc_location,
+                ..
+            })
+            | SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
+                ref alpha_htlc_location,
+                ..
+            })
+            | SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
+                ref alpha_htlc_location,
+                ..
+            }) => Some(alpha_htlc_location),
+            _ => None,
+        }
+    }
+
+    pub fn beta_htlc_location(&self) -> Option<&<R::BetaLedger as Ledger>::HtlcLocation> {
+        use self::SwapStates as SS;
+        match *self {
+            SS::AlphaFundedBetaDeployed(AlphaFundedBetaDeployed {
+                ref beta_htlc_location,
+                ..
+            })
+            | SS::BothFunded(BothFunded {
+                ref beta_htlc_location,
+                ..
+            })
+            | SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
+                ref beta_htlc_location,
+                ..
+            })
+            | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
+                ref beta_htlc_location,
+                ..
+            }) => Some(beta_htlc_location),
+            _ => None,
+        }
+    }
 }
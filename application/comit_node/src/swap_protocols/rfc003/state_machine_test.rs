@@ -4,14 +4,14 @@ use crate::{
         ledger::{Bitcoin, Ethereum},
         rfc003::{
             ethereum::Seconds,
-            events::{self, LedgerEvents},
+            events::{self, HtlcExpiryEvents, LedgerEvents},
             role::test::{Alisha, Bobisha, FakeCommunicationEvents},
             state_machine::*,
             Ledger, RedeemTransaction, Secret,
         },
     },
 };
-use bitcoin_support::{BitcoinQuantity, Blocks, OutPoint, Sha256dHash};
+use bitcoin_support::{BitcoinQuantity, BlockHeight, Blocks, OutPoint, Sha256dHash};
 use ethereum_support::EtherQuantity;
 use futures::{
     future::{self, Either},
@@ -19,13 +19,41 @@ use futures::{
     Stream,
 };
 use hex::FromHex;
-use std::{str::FromStr, sync::Arc};
+use std::{str::FromStr, sync::Arc, time::Duration};
 
-#[derive(Default)]
 struct FakeLedgerEvents<L: Ledger> {
     pub htlc_deployed: Option<Box<events::Deployed<L>>>,
     pub htlc_funded: Option<Box<events::Funded<L>>>,
     pub htlc_redeemed_or_refunded: Option<Box<events::RedeemedOrRefunded<L>>>,
+    pub htlc_refundable_at: Option<Box<events::RefundableAt<L>>>,
+    pub htlc_refundable: Option<Box<events::Refundable>>,
+}
+
+impl<L: Ledger> Default for FakeLedgerEvents<L> {
+    fn default() -> Self {
+        FakeLedgerEvents {
+            htlc_deployed: None,
+            htlc_funded: None,
+            htlc_redeemed_or_refunded: None,
+            htlc_refundable_at: None,
+            htlc_refundable: None,
+        }
+    }
+}
+
+impl<L: Ledger> HtlcExpiryEvents<L> for FakeLedgerEvents<L> {
+    fn htlc_refundable_at(
+        &mut self,
+        _lock_duration: L::LockDuration,
+    ) -> &mut events::RefundableAt<L> {
+        self.htlc_refundable_at
+            .get_or_insert_with(|| Box::new(future::empty()))
+    }
+
+    fn htlc_refundable(&mut self, _refundable_at: L::Expiry) -> &mut events::Refundable {
+        self.htlc_refundable
+            .get_or_insert_with(|| Box::new(future::empty()))
+    }
 }
 
 impl LedgerEvents<Bitcoin, BitcoinQuantity> for FakeLedgerEvents<Bitcoin> {
@@ -90,7 +118,7 @@ fn gen_start_state() -> Start<Alisha> {
         )
         .unwrap(),
         alpha_ledger: Bitcoin::regtest(),
-        beta_ledger: Ethereum::default(),
+        beta_ledger: Ethereum::dev(),
         alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
         beta_asset: EtherQuantity::from_eth(10.0),
         alpha_ledger_lock_duration: Blocks::from(144),
@@ -107,6 +135,7 @@ macro_rules! init {
             beta_ledger_events: Box::new($beta_events),
             state_repo: Arc::new(state_sender),
             communication_events: Box::new($response_event),
+            lock_duration_margin: Duration::from_secs(60 * 60),
         };
         let state: SwapStates<$role> = $state;
         let final_state_future = Swap::start_in(state, context);
@@ -151,6 +180,41 @@ fn when_swap_is_rejected_go_to_final_reject() {
         Alisha,
         FakeCommunicationEvents::<Alisha> {
             response: Some(Box::new(future::ok(Err(SwapReject::Rejected)))),
+            aborted: None,
+        },
+        start.clone().into(),
+        FakeLedgerEvents {
+            ..Default::default()
+        },
+        FakeLedgerEvents {
+            ..Default::default()
+        }
+    );
+
+    run_state_machine!(state_machine, states);
+}
+
+#[test]
+fn when_lock_durations_are_unsafe_go_to_final_reject() {
+    let bob_response = StateMachineResponse {
+        beta_ledger_refund_identity: ethereum_support::Address::from_str(
+            "71b9f69dcabb340a3fe229c3f94f1662ad85e5e8",
+        )
+        .unwrap(),
+        alpha_ledger_redeem_identity: bitcoin_support::PubkeyHash::from_hex(
+            "d38e554430c4035f2877a579a07a99886153f071",
+        )
+        .unwrap(),
+        beta_ledger_lock_duration: Seconds(144 * 600),
+    };
+
+    let start = gen_start_state();
+
+    let (state_machine, states) = init!(
+        Alisha,
+        FakeCommunicationEvents::<Alisha> {
+            response: Some(Box::new(future::ok(Ok(bob_response)))),
+            aborted: None,
         },
         start.clone().into(),
         FakeLedgerEvents {
@@ -164,6 +228,77 @@ fn when_swap_is_rejected_go_to_final_reject() {
     run_state_machine!(state_machine, states);
 }
 
+#[test]
+fn when_accepted_swap_is_aborted_go_to_final_reject() {
+    let (bobisha, _) = Bobisha::create();
+    let start = Start {
+        alpha_ledger_refund_identity: bitcoin_support::PubkeyHash::from_hex(
+            "d38e554430c4035f2877a579a07a99886153f071",
+        )
+        .unwrap(),
+        beta_ledger_redeem_identity: ethereum_support::Address::from_str(
+            "8457037fcd80a8650c4692d7fcfc1d0a96b92867",
+        )
+        .unwrap(),
+        alpha_ledger: Bitcoin::regtest(),
+        beta_ledger: Ethereum::dev(),
+        alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
+        beta_asset: EtherQuantity::from_eth(10.0),
+        alpha_ledger_lock_duration: Blocks::from(144),
+        secret: Secret::from(*b"hello world, you are beautiful!!").hash(),
+        role: bobisha,
+    };
+
+    let response = StateMachineResponse {
+        alpha_ledger_redeem_identity: secp256k1_support::KeyPair::from_secret_key_slice(
+            &hex::decode("18e14a7b6a307f426a94f8114701e7c8e774e7f9a47e2c2035db29a206321725")
+                .unwrap(),
+        )
+        .unwrap(),
+        beta_ledger_refund_identity: ethereum_support::Address::from_str(
+            "8457037fcd80a8650c4692d7fcfc1d0a96b92867",
+        )
+        .unwrap(),
+        beta_ledger_lock_duration: Seconds(42),
+    };
+
+    let (state_machine, states) = init!(
+        Bobisha,
+        FakeCommunicationEvents::<Bobisha> {
+            response: Some(Box::new(future::ok(Ok(response.clone())))),
+            aborted: Some(Box::new(future::ok(()))),
+        },
+        start.clone().into(),
+        FakeLedgerEvents::<Bitcoin> {
+            ..Default::default()
+        },
+        FakeLedgerEvents::<Ethereum> {
+            ..Default::default()
+        }
+    );
+
+    let mut runtime = tokio::runtime::Runtime::new().unwrap();
+    let outcome = runtime.block_on(state_machine).unwrap();
+    let states: Vec<SwapStates<Bobisha>> = runtime.block_on(states.take(2).collect()).unwrap();
+
+    assert_eq!(
+        outcome,
+        SwapOutcome::Rejected {
+            start: start.clone(),
+            rejection_type: SwapReject::Aborted,
+        }
+    );
+    assert_eq!(
+        states,
+        vec![
+            SwapStates::from(Accepted {
+                swap: OngoingSwap::new(start, response.into())
+            }),
+            SwapStates::from(Final(outcome)),
+        ]
+    );
+}
+
 #[test]
 fn alpha_refunded() {
     let bob_response = StateMachineResponse {
@@ -184,6 +319,7 @@ fn alpha_refunded() {
         Alisha,
         FakeCommunicationEvents::<Alisha> {
             response: Some(Box::new(future::ok(Ok(bob_response.clone())))),
+            aborted: None,
         },
         start.clone().into(),
         FakeLedgerEvents::<Bitcoin> {
@@ -219,14 +355,16 @@ fn alpha_refunded() {
             alpha_htlc_location: OutPoint {
                 txid: Sha256dHash::from_data(b"funding"),
                 vout: 0
-            }
+            },
+            alpha_htlc_expiry: HtlcExpiry::default(),
         },
         AlphaFunded {
             swap: OngoingSwap::new(start.clone(), bob_response.clone().into()),
             alpha_htlc_location: OutPoint {
                 txid: Sha256dHash::from_data(b"funding"),
                 vout: 0
-            }
+            },
+            alpha_htlc_expiry: HtlcExpiry::default(),
         }
     );
 }
@@ -244,7 +382,7 @@ fn bob_transition_alpha_refunded() {
         )
         .unwrap(),
         alpha_ledger: Bitcoin::regtest(),
-        beta_ledger: Ethereum::default(),
+        beta_ledger: Ethereum::dev(),
         alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
         beta_asset: EtherQuantity::from_eth(10.0),
         alpha_ledger_lock_duration: Blocks::from(144),
@@ -268,7 +406,8 @@ fn bob_transition_alpha_refunded() {
     let (state_machine, states) = init!(
         Bobisha,
         FakeCommunicationEvents::<Bobisha> {
-            response: Some(Box::new(future::ok(Ok(response.clone()))))
+            response: Some(Box::new(future::ok(Ok(response.clone())))),
+            aborted: None,
         },
         start.clone().into(),
         FakeLedgerEvents::<Bitcoin> {
@@ -304,14 +443,131 @@ fn bob_transition_alpha_refunded() {
             alpha_htlc_location: OutPoint {
                 txid: Sha256dHash::from_data(b"funding"),
                 vout: 0
-            }
+            },
+            alpha_htlc_expiry: HtlcExpiry::default(),
         },
         AlphaFunded {
             swap: OngoingSwap::new(start.clone(), response.clone().into()),
             alpha_htlc_location: OutPoint {
                 txid: Sha256dHash::from_data(b"funding"),
                 vout: 0
-            }
+            },
+            alpha_htlc_expiry: HtlcExpiry::default(),
         }
     );
 }
+
+#[test]
+fn refund_becomes_available_once_alpha_htlc_expires() {
+    let bob_response = StateMachineResponse {
+        beta_ledger_refund_identity: ethereum_support::Address::from_str(
+            "71b9f69dcabb340a3fe229c3f94f1662ad85e5e8",
+        )
+        .unwrap(),
+        alpha_ledger_redeem_identity: bitcoin_support::PubkeyHash::from_hex(
+            "d38e554430c4035f2877a579a07a99886153f071",
+        )
+        .unwrap(),
+        beta_ledger_lock_duration: Seconds(42),
+    };
+
+    let start = gen_start_state();
+
+    let (state_machine, states) = init!(
+        Alisha,
+        FakeCommunicationEvents::<Alisha> {
+            response: Some(Box::new(future::ok(Ok(bob_response.clone())))),
+            aborted: None,
+        },
+        start.clone().into(),
+        FakeLedgerEvents::<Bitcoin> {
+            htlc_deployed: Some(Box::new(future::ok(OutPoint {
+                txid: Sha256dHash::from_data(b"funding"),
+                vout: 0,
+            }))),
+            htlc_funded: Some(Box::new(future::ok(None))),
+            htlc_redeemed_or_refunded: Some(Box::new(future::ok(Either::A(RedeemTransaction {
+                transaction: bitcoin_support::Transaction {
+                    version: 1,
+                    lock_time: 42,
+                    input: vec![],
+                    output: vec![],
+                },
+                secret: start.secret,
+            })))),
+            htlc_refundable_at: Some(Box::new(future::ok(BlockHeight::new(300)))),
+            htlc_refundable: Some(Box::new(future::ok(()))),
+        },
+        FakeLedgerEvents::<Ethereum> {
+            ..Default::default()
+        }
+    );
+
+    let alpha_htlc_location = OutPoint {
+        txid: Sha256dHash::from_data(b"funding"),
+        vout: 0,
+    };
+    let alpha_htlc_expiry = HtlcExpiry {
+        refundable_at: Some(BlockHeight::new(300)),
+        refundable_now: true,
+    };
+
+    run_state_machine!(
+        state_machine,
+        states,
+        Accepted {
+            swap: OngoingSwap::new(start.clone(), bob_response.clone().into()),
+        },
+        AlphaDeployed {
+            swap: OngoingSwap::new(start.clone(), bob_response.clone().into()),
+            alpha_htlc_location,
+            alpha_htlc_expiry: HtlcExpiry::default(),
+        },
+        AlphaDeployed {
+            swap: OngoingSwap::new(start.clone(), bob_response.clone().into()),
+            alpha_htlc_location,
+            alpha_htlc_expiry: alpha_htlc_expiry.clone(),
+        },
+        AlphaFunded {
+            swap: OngoingSwap::new(start.clone(), bob_response.clone().into()),
+            alpha_htlc_location,
+            alpha_htlc_expiry,
+        }
+    );
+}
+
+#[test]
+fn htlc_locations_are_known_once_deployed() {
+    let bob_response = StateMachineResponse {
+        beta_ledger_refund_identity: ethereum_support::Address::from_str(
+            "71b9f69dcabb340a3fe229c3f94f1662ad85e5e8",
+        )
+        .unwrap(),
+        alpha_ledger_redeem_identity: bitcoin_support::PubkeyHash::from_hex(
+            "d38e554430c4035f2877a579a07a99886153f071",
+        )
+        .unwrap(),
+        beta_ledger_lock_duration: Seconds(42),
+    };
+    let start = gen_start_state();
+    let alpha_htlc_location = OutPoint {
+        txid: Sha256dHash::from_data(b"funding"),
+        vout: 0,
+    };
+
+    let accepted: SwapStates<Alisha> = SwapStates::Accepted(Accepted {
+        swap: OngoingSwap::new(start.clone(), bob_response.clone().into()),
+    });
+    let alpha_funded: SwapStates<Alisha> = SwapStates::AlphaFunded(AlphaFunded {
+        swap: OngoingSwap::new(start, bob_response.into()),
+        alpha_htlc_location,
+        alpha_htlc_expiry: HtlcExpiry::default(),
+    });
+
+    assert_eq!(accepted.alpha_htlc_location(), None);
+    assert_eq!(
+        alpha_funded.alpha_htlc_location(),
+        Some(&alpha_htlc_location)
+    );
+    assert_eq!(alpha_funded.beta_htlc_location(), None);
+}
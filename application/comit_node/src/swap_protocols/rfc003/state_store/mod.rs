@@ -1,3 +1,11 @@
+mod sqlite;
+mod stored;
+
+pub use self::{
+    sqlite::SqliteStateStore,
+    stored::{DerivedSecrets, Persist},
+};
+
 use crate::swap_protocols::rfc003::{state_machine::SwapStates, Role, SaveState};
 use std::{
     any::Any,
@@ -10,6 +18,10 @@ use std::{
 pub enum Error {
     #[fail(display = "State already exists for given key")]
     DuplicateKey,
+    #[fail(display = "The storage backend failed: {}", _0)]
+    Backend(String),
+    #[fail(display = "The state could not be (de)serialized: {}", _0)]
+    Serialization(String),
 }
 
 pub trait StateStore<K>: Send + Sync + 'static {
@@ -88,8 +100,8 @@ mod tests {
             beta_ledger_redeem_identity: "8457037fcd80a8650c4692d7fcfc1d0a96b92867"
                 .parse()
                 .unwrap(),
-            alpha_ledger: Bitcoin::default(),
-            beta_ledger: Ethereum::default(),
+            alpha_ledger: Bitcoin::regtest(),
+            beta_ledger: Ethereum::dev(),
             alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
             beta_asset: EtherQuantity::from_eth(10.0),
             alpha_ledger_lock_duration: Blocks::from(144),
@@ -0,0 +1,316 @@
+use crate::{
+    metrics,
+    swap_protocols::{
+        rfc003::{
+            state_machine::SwapStates,
+            state_store::{DerivedSecrets, Error, Persist, StateStore},
+            Role, SaveState, SecretSource,
+        },
+        SwapId,
+    },
+};
+use rusqlite::{types::ToSql, Connection, OptionalExtension, NO_PARAMS};
+use std::{
+    marker::PhantomData,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+/// Persists the state of every swap as JSON in a SQLite database so that
+/// swaps survive a restart of the node.
+///
+/// The keys and the secret of a swap are derived from the seed, they are
+/// left out of the database and derived again when the state is loaded.
+#[derive(Clone, DebugStub)]
+pub struct SqliteStateStore {
+    #[debug_stub = "Connection"]
+    connection: Arc<Mutex<Connection>>,
+    #[debug_stub = "SecretSource"]
+    secret_source: Arc<dyn SecretSource>,
+}
+
+impl SqliteStateStore {
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        secret_source: Arc<dyn SecretSource>,
+    ) -> Result<Self, Error> {
+        Self::init(Connection::open(path)?, secret_source)
+    }
+
+    pub fn open_in_memory(secret_source: Arc<dyn SecretSource>) -> Result<Self, Error> {
+        Self::init(Connection::open_in_memory()?, secret_source)
+    }
+
+    fn init(connection: Connection, secret_source: Arc<dyn SecretSource>) -> Result<Self, Error> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS swap_states (
+                id TEXT PRIMARY KEY NOT NULL,
+                state TEXT
+            )",
+            NO_PARAMS,
+        )?;
+
+        Ok(SqliteStateStore {
+            connection: Arc::new(Mutex::new(connection)),
+            secret_source,
+        })
+    }
+
+    fn save_state<R: Role>(&self, id: SwapId) -> Arc<dyn SaveState<R>> {
+        Arc::new(SqliteSaveState {
+            connection: self.connection.clone(),
+            derived_secrets: DerivedSecrets::new(&*self.secret_source, id),
+            id,
+            role: PhantomData,
+        })
+    }
+}
+
+impl StateStore<SwapId> for SqliteStateStore {
+    fn new_save_state<R: Role>(&self, id: SwapId) -> Result<Arc<dyn SaveState<R>>, Error> {
+        let connection = self.connection.lock().unwrap();
+
+        let existing = connection
+            .query_row(
+                "SELECT id FROM swap_states WHERE id = ?1",
+                &[&id.to_string() as &dyn ToSql],
+                |_| (),
+            )
+            .optional()?;
+
+        if existing.is_some() {
+            return Err(Error::DuplicateKey);
+        }
+
+        connection.execute(
+            "INSERT INTO swap_states (id, state) VALUES (?1, NULL)",
+            &[&id.to_string() as &dyn ToSql],
+        )?;
+
+        Ok(self.save_state(id))
+    }
+
+    fn get<R: Role>(&self, id: &SwapId) -> Result<Option<SwapStates<R>>, Error> {
+        let connection = self.connection.lock().unwrap();
+
+        let state: Option<Option<String>> = connection
+            .query_row(
+                "SELECT state FROM swap_states WHERE id = ?1",
+                &[&id.to_string() as &dyn ToSql],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match state {
+            Some(Some(state)) => {
+                let derived_secrets = DerivedSecrets::new(&*self.secret_source, *id);
+
+                Ok(Some(SwapStates::from_stored(
+                    serde_json::from_str(&state)?,
+                    &derived_secrets,
+                )?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn get_save_state<R: Role>(&self, id: &SwapId) -> Result<Option<Arc<dyn SaveState<R>>>, Error> {
+        let connection = self.connection.lock().unwrap();
+
+        let existing = connection
+            .query_row(
+                "SELECT id FROM swap_states WHERE id = ?1",
+                &[&id.to_string() as &dyn ToSql],
+                |_| (),
+            )
+            .optional()?;
+
+        Ok(existing.map(|_| self.save_state(*id)))
+    }
+}
+
+#[derive(DebugStub)]
+struct SqliteSaveState<R> {
+    #[debug_stub = "Connection"]
+    connection: Arc<Mutex<Connection>>,
+    derived_secrets: DerivedSecrets,
+    id: SwapId,
+    role: PhantomData<R>,
+}
+
+impl<R: Role> SqliteSaveState<R> {
+    fn update(&self, state: &SwapStates<R>) -> Result<(), Error> {
+        let state = serde_json::to_string(&state.to_stored(&self.derived_secrets)?)?;
+        let connection = self.connection.lock().unwrap();
+
+        connection.execute(
+            "UPDATE swap_states SET state = ?1 WHERE id = ?2",
+            &[&state as &dyn ToSql, &self.id.to_string()],
+        )?;
+
+        Ok(())
+    }
+}
+
+impl<R: Role> SaveState<R> for SqliteSaveState<R> {
+    fn save(&self, state: SwapStates<R>) {
+        if let Err(e) = self.update(&state) {
+            metrics::persistence_failed(metrics::SAVE_STATE);
+            error!(
+                "Failed to persist state {} of swap {}: {:?}",
+                state.name(),
+                self.id,
+                e
+            );
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Backend(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Serialization(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        seed::Seed,
+        swap_protocols::{
+            ledger::{Bitcoin, Ethereum},
+            rfc003::{
+                ethereum::Seconds,
+                role::test::{Alisha, Bobisha},
+                state_machine::{Accepted, OngoingSwap, Start},
+            },
+        },
+    };
+    use bitcoin_support::{BitcoinQuantity, Blocks, PubkeyHash};
+    use ethereum_support::EtherQuantity;
+    use hex::FromHex;
+    use spectral::prelude::*;
+
+    fn seed() -> Seed {
+        Seed::from(*b"hello world, you are beautiful!!")
+    }
+
+    fn alice_start_state(id: SwapId) -> Start<Alisha> {
+        Start {
+            alpha_ledger_refund_identity: seed().new_secp256k1_refund(id),
+            beta_ledger_redeem_identity: "8457037fcd80a8650c4692d7fcfc1d0a96b92867"
+                .parse()
+                .unwrap(),
+            alpha_ledger: Bitcoin::regtest(),
+            beta_ledger: Ethereum::dev(),
+            alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
+            beta_asset: EtherQuantity::from_eth(10.0),
+            alpha_ledger_lock_duration: Blocks::from(144),
+            secret: seed().new_secret(id),
+            role: Alisha::default(),
+        }
+    }
+
+    #[test]
+    fn store_get_and_save_state() {
+        let state_store = SqliteStateStore::open_in_memory(Arc::new(seed())).unwrap();
+        let id = SwapId::default();
+        let start_state = alice_start_state(id);
+
+        let save_state = state_store.new_save_state::<Alisha>(id).unwrap();
+        assert_that(&state_store.get::<Alisha>(&id))
+            .is_ok()
+            .is_none();
+
+        save_state.save(start_state.clone().into());
+
+        let res = state_store.get(&id).unwrap();
+        assert_that(&res).contains_value(SwapStates::from(start_state.clone()));
+
+        let save_state = state_store.get_save_state(&id).unwrap().unwrap();
+
+        let accepted_state = SwapStates::from(Accepted {
+            swap: OngoingSwap {
+                alpha_ledger: start_state.alpha_ledger,
+                beta_ledger: start_state.beta_ledger,
+                alpha_asset: start_state.alpha_asset,
+                beta_asset: start_state.beta_asset,
+                alpha_ledger_redeem_identity: PubkeyHash::from_hex(
+                    "875638cac0b0ae9f826575e190f2788918c354c2",
+                )
+                .unwrap(),
+                alpha_ledger_refund_identity: start_state.alpha_ledger_refund_identity,
+                beta_ledger_redeem_identity: start_state.beta_ledger_redeem_identity,
+                beta_ledger_refund_identity: "0f59e9e105ca8ba6bc8e0af4ddc6af6d9a31c0ad"
+                    .parse()
+                    .unwrap(),
+                alpha_ledger_lock_duration: start_state.alpha_ledger_lock_duration,
+                beta_ledger_lock_duration: Seconds(86400),
+                secret: start_state.secret,
+                role: start_state.role,
+            },
+        });
+
+        save_state.save(accepted_state.clone());
+
+        let res = state_store.get(&id).unwrap();
+        assert_that(&res).contains_value(accepted_state)
+    }
+
+    #[test]
+    fn keys_and_secret_are_not_stored() {
+        let state_store = SqliteStateStore::open_in_memory(Arc::new(seed())).unwrap();
+        let id = SwapId::default();
+        let start_state = alice_start_state(id);
+
+        let save_state = state_store.new_save_state::<Alisha>(id).unwrap();
+        save_state.save(start_state.clone().into());
+
+        let stored: String = state_store
+            .connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT state FROM swap_states WHERE id = ?1",
+                &[&id.to_string() as &dyn ToSql],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let refund_key = hex::encode(&start_state.alpha_ledger_refund_identity.secret_key()[..]);
+        let secret = format!("{:x}", start_state.secret);
+
+        assert_that(&stored.contains(&refund_key)).is_false();
+        assert_that(&stored.contains(&secret)).is_false();
+        assert_that(&state_store.get(&id).unwrap()).contains_value(SwapStates::from(start_state));
+    }
+
+    #[test]
+    fn new_save_state_rejects_duplicate_key() {
+        let state_store = SqliteStateStore::open_in_memory(Arc::new(seed())).unwrap();
+        let id = SwapId::default();
+
+        let _ = state_store.new_save_state::<Alisha>(id).unwrap();
+        let res = state_store.new_save_state::<Alisha>(id);
+
+        assert!(match res {
+            Err(Error::DuplicateKey) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn restored_bob_can_no_longer_respond() {
+        let (bob, _response_future) = Bobisha::create();
+
+        let json = serde_json::to_string(&bob).unwrap();
+        let restored_bob = serde_json::from_str::<Bobisha>(&json).unwrap();
+
+        assert_that(&restored_bob.decline_action().decline(None)).is_err();
+    }
+}
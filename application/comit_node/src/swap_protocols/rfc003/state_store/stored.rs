@@ -0,0 +1,617 @@
+use crate::{
+    comit_client::SwapReject,
+    swap_protocols::{
+        rfc003::{
+            self,
+            state_machine::{
+                Accepted, AlphaDeployed, AlphaFunded, AlphaFundedBetaDeployed,
+                AlphaFundedBetaRedeemed, AlphaFundedBetaRefunded, AlphaRedeemedBetaFunded,
+                AlphaRefundedBetaFunded, BothFunded, Final, HtlcExpiry, OngoingSwap, Start,
+                SwapOutcome, SwapStates,
+            },
+            state_store::Error,
+            Ledger, RedeemTransaction, Role, Secret, SecretHash, SecretSource,
+        },
+        SwapId,
+    },
+};
+use bitcoin_support::PubkeyHash;
+use ethereum_support::Address;
+use secp256k1_support::KeyPair;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Converts a value to and from the form in which it is stored.
+///
+/// Keys and secrets which are derived from the seed are not stored, only
+/// which of the derived values they are. They are derived again on load.
+pub trait Persist: Sized {
+    type Stored: Serialize + DeserializeOwned;
+
+    fn to_stored(&self, derived: &DerivedSecrets) -> Result<Self::Stored, Error>;
+
+    fn from_stored(stored: Self::Stored, derived: &DerivedSecrets) -> Result<Self, Error>;
+}
+
+type StoredAs<T> = <T as Persist>::Stored;
+
+/// The keys and the secret which are derived from the seed for a swap
+#[derive(DebugStub)]
+pub struct DerivedSecrets {
+    #[debug_stub = "Secret"]
+    secret: Secret,
+    #[debug_stub = "KeyPair"]
+    redeem: KeyPair,
+    #[debug_stub = "KeyPair"]
+    refund: KeyPair,
+}
+
+impl DerivedSecrets {
+    pub fn new(secret_source: &dyn SecretSource, id: SwapId) -> Self {
+        DerivedSecrets {
+            secret: secret_source.new_secret(id),
+            redeem: secret_source.new_secp256k1_redeem(id),
+            refund: secret_source.new_secp256k1_refund(id),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DerivedKey {
+    Redeem,
+    Refund,
+}
+
+impl Persist for KeyPair {
+    type Stored = DerivedKey;
+
+    fn to_stored(&self, derived: &DerivedSecrets) -> Result<DerivedKey, Error> {
+        if *self == derived.redeem {
+            Ok(DerivedKey::Redeem)
+        } else if *self == derived.refund {
+            Ok(DerivedKey::Refund)
+        } else {
+            Err(Error::Serialization(String::from(
+                "key is not derived from the seed",
+            )))
+        }
+    }
+
+    fn from_stored(stored: DerivedKey, derived: &DerivedSecrets) -> Result<Self, Error> {
+        match stored {
+            DerivedKey::Redeem => Ok(derived.redeem),
+            DerivedKey::Refund => Ok(derived.refund),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DerivedSecret;
+
+impl Persist for Secret {
+    type Stored = DerivedSecret;
+
+    fn to_stored(&self, derived: &DerivedSecrets) -> Result<DerivedSecret, Error> {
+        if *self == derived.secret {
+            Ok(DerivedSecret)
+        } else {
+            Err(Error::Serialization(String::from(
+                "secret is not derived from the seed",
+            )))
+        }
+    }
+
+    fn from_stored(_: DerivedSecret, derived: &DerivedSecrets) -> Result<Self, Error> {
+        Ok(derived.secret)
+    }
+}
+
+macro_rules! impl_persist_as_is {
+    ($type:ty) => {
+        impl Persist for $type {
+            type Stored = $type;
+
+            fn to_stored(&self, _: &DerivedSecrets) -> Result<$type, Error> {
+                Ok(self.clone())
+            }
+
+            fn from_stored(stored: $type, _: &DerivedSecrets) -> Result<Self, Error> {
+                Ok(stored)
+            }
+        }
+    };
+}
+
+impl_persist_as_is!(PubkeyHash);
+impl_persist_as_is!(Address);
+impl_persist_as_is!(SecretHash);
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct StoredStart<R: Role> {
+    alpha_ledger_refund_identity: StoredAs<R::AlphaRefundHtlcIdentity>,
+    beta_ledger_redeem_identity: StoredAs<R::BetaRedeemHtlcIdentity>,
+    alpha_ledger: R::AlphaLedger,
+    beta_ledger: R::BetaLedger,
+    alpha_asset: R::AlphaAsset,
+    beta_asset: R::BetaAsset,
+    alpha_ledger_lock_duration: <R::AlphaLedger as Ledger>::LockDuration,
+    secret: StoredAs<R::Secret>,
+    role: R,
+}
+
+impl<R: Role> Persist for Start<R> {
+    type Stored = StoredStart<R>;
+
+    fn to_stored(&self, derived: &DerivedSecrets) -> Result<StoredStart<R>, Error> {
+        Ok(StoredStart {
+            alpha_ledger_refund_identity: self.alpha_ledger_refund_identity.to_stored(derived)?,
+            beta_ledger_redeem_identity: self.beta_ledger_redeem_identity.to_stored(derived)?,
+            alpha_ledger: self.alpha_ledger.clone(),
+            beta_ledger: self.beta_ledger.clone(),
+            alpha_asset: self.alpha_asset.clone(),
+            beta_asset: self.beta_asset.clone(),
+            alpha_ledger_lock_duration: self.alpha_ledger_lock_duration.clone(),
+            secret: self.secret.to_stored(derived)?,
+            role: self.role.clone(),
+        })
+    }
+
+    fn from_stored(stored: StoredStart<R>, derived: &DerivedSecrets) -> Result<Self, Error> {
+        Ok(Start {
+            alpha_ledger_refund_identity: R::AlphaRefundHtlcIdentity::from_stored(
+                stored.alpha_ledger_refund_identity,
+                derived,
+            )?,
+            beta_ledger_redeem_identity: R::BetaRedeemHtlcIdentity::from_stored(
+                stored.beta_ledger_redeem_identity,
+                derived,
+            )?,
+            alpha_ledger: stored.alpha_ledger,
+            beta_ledger: stored.beta_ledger,
+            alpha_asset: stored.alpha_asset,
+            beta_asset: stored.beta_asset,
+            alpha_ledger_lock_duration: stored.alpha_ledger_lock_duration,
+            secret: R::Secret::from_stored(stored.secret, derived)?,
+            role: stored.role,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct StoredSwap<R: Role> {
+    alpha_ledger: R::AlphaLedger,
+    beta_ledger: R::BetaLedger,
+    alpha_asset: R::AlphaAsset,
+    beta_asset: R::BetaAsset,
+    alpha_ledger_redeem_identity: StoredAs<R::AlphaRedeemHtlcIdentity>,
+    alpha_ledger_refund_identity: StoredAs<R::AlphaRefundHtlcIdentity>,
+    beta_ledger_redeem_identity: StoredAs<R::BetaRedeemHtlcIdentity>,
+    beta_ledger_refund_identity: StoredAs<R::BetaRefundHtlcIdentity>,
+    alpha_ledger_lock_duration: <R::AlphaLedger as Ledger>::LockDuration,
+    beta_ledger_lock_duration: <R::BetaLedger as Ledger>::LockDuration,
+    secret: StoredAs<R::Secret>,
+    role: R,
+}
+
+impl<R: Role> Persist for OngoingSwap<R> {
+    type Stored = StoredSwap<R>;
+
+    fn to_stored(&self, derived: &DerivedSecrets) -> Result<StoredSwap<R>, Error> {
+        Ok(StoredSwap {
+            alpha_ledger: self.alpha_ledger.clone(),
+            beta_ledger: self.beta_ledger.clone(),
+            alpha_asset: self.alpha_asset.clone(),
+            beta_asset: self.beta_asset.clone(),
+            alpha_ledger_redeem_identity: self.alpha_ledger_redeem_identity.to_stored(derived)?,
+            alpha_ledger_refund_identity: self.alpha_ledger_refund_identity.to_stored(derived)?,
+            beta_ledger_redeem_identity: self.beta_ledger_redeem_identity.to_stored(derived)?,
+            beta_ledger_refund_identity: self.beta_ledger_refund_identity.to_stored(derived)?,
+            alpha_ledger_lock_duration: self.alpha_ledger_lock_duration.clone(),
+            beta_ledger_lock_duration: self.beta_ledger_lock_duration.clone(),
+            secret: self.secret.to_stored(derived)?,
+            role: self.role.clone(),
+        })
+    }
+
+    fn from_stored(stored: StoredSwap<R>, derived: &DerivedSecrets) -> Result<Self, Error> {
+        Ok(OngoingSwap {
+            alpha_ledger: stored.alpha_ledger,
+            beta_ledger: stored.beta_ledger,
+            alpha_asset: stored.alpha_asset,
+            beta_asset: stored.beta_asset,
+            alpha_ledger_redeem_identity: R::AlphaRedeemHtlcIdentity::from_stored(
+                stored.alpha_ledger_redeem_identity,
+                derived,
+            )?,
+            alpha_ledger_refund_identity: R::AlphaRefundHtlcIdentity::from_stored(
+                stored.alpha_ledger_refund_identity,
+                derived,
+            )?,
+            beta_ledger_redeem_identity: R::BetaRedeemHtlcIdentity::from_stored(
+                stored.beta_ledger_redeem_identity,
+                derived,
+            )?,
+            beta_ledger_refund_identity: R::BetaRefundHtlcIdentity::from_stored(
+                stored.beta_ledger_refund_identity,
+                derived,
+            )?,
+            alpha_ledger_lock_duration: stored.alpha_ledger_lock_duration,
+            beta_ledger_lock_duration: stored.beta_ledger_lock_duration,
+            secret: R::Secret::from_stored(stored.secret, derived)?,
+            role: stored.role,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum StoredOutcome<R: Role> {
+    Rejected {
+        start: StoredStart<R>,
+        rejection_type: SwapReject,
+    },
+    AlphaRefunded {
+        swap: StoredSwap<R>,
+    },
+    BothRefunded {
+        swap: StoredSwap<R>,
+    },
+    BothRedeemed {
+        swap: StoredSwap<R>,
+    },
+    AlphaRedeemedBetaRefunded {
+        swap: StoredSwap<R>,
+    },
+    AlphaRefundedBetaRedeemed {
+        swap: StoredSwap<R>,
+    },
+}
+
+impl<R: Role> Persist for SwapOutcome<R> {
+    type Stored = StoredOutcome<R>;
+
+    fn to_stored(&self, derived: &DerivedSecrets) -> Result<StoredOutcome<R>, Error> {
+        use self::{StoredOutcome as Stored, SwapOutcome as SO};
+
+        Ok(match self {
+            SO::Rejected {
+                start,
+                rejection_type,
+            } => Stored::Rejected {
+                start: start.to_stored(derived)?,
+                rejection_type: rejection_type.clone(),
+            },
+            SO::AlphaRefunded { swap } => Stored::AlphaRefunded {
+                swap: swap.to_stored(derived)?,
+            },
+            SO::BothRefunded { swap } => Stored::BothRefunded {
+                swap: swap.to_stored(derived)?,
+            },
+            SO::BothRedeemed { swap } => Stored::BothRedeemed {
+                swap: swap.to_stored(derived)?,
+            },
+            SO::AlphaRedeemedBetaRefunded { swap } => Stored::AlphaRedeemedBetaRefunded {
+                swap: swap.to_stored(derived)?,
+            },
+            SO::AlphaRefundedBetaRedeemed { swap } => Stored::AlphaRefundedBetaRedeemed {
+                swap: swap.to_stored(derived)?,
+            },
+        })
+    }
+
+    fn from_stored(stored: StoredOutcome<R>, derived: &DerivedSecrets) -> Result<Self, Error> {
+        use self::{StoredOutcome as Stored, SwapOutcome as SO};
+
+        Ok(match stored {
+            Stored::Rejected {
+                start,
+                rejection_type,
+            } => SO::Rejected {
+                start: Start::from_stored(start, derived)?,
+                rejection_type,
+            },
+            Stored::AlphaRefunded { swap } => SO::AlphaRefunded {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+            },
+            Stored::BothRefunded { swap } => SO::BothRefunded {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+            },
+            Stored::BothRedeemed { swap } => SO::BothRedeemed {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+            },
+            Stored::AlphaRedeemedBetaRefunded { swap } => SO::AlphaRedeemedBetaRefunded {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+            },
+            Stored::AlphaRefundedBetaRedeemed { swap } => SO::AlphaRefundedBetaRedeemed {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+            },
+        })
+    }
+}
+
+#[allow(type_alias_bounds)]
+type AlphaHtlcLocation<R: Role> = <R::AlphaLedger as Ledger>::HtlcLocation;
+#[allow(type_alias_bounds)]
+type BetaHtlcLocation<R: Role> = <R::BetaLedger as Ledger>::HtlcLocation;
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum StoredState<R: Role> {
+    Start(StoredStart<R>),
+    Accepted {
+        swap: StoredSwap<R>,
+    },
+    AlphaDeployed {
+        swap: StoredSwap<R>,
+        alpha_htlc_location: AlphaHtlcLocation<R>,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
+    },
+    AlphaFunded {
+        swap: StoredSwap<R>,
+        alpha_htlc_location: AlphaHtlcLocation<R>,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
+    },
+    AlphaFundedBetaDeployed {
+        swap: StoredSwap<R>,
+        alpha_htlc_location: AlphaHtlcLocation<R>,
+        beta_htlc_location: BetaHtlcLocation<R>,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
+        beta_htlc_expiry: HtlcExpiry<R::BetaLedger>,
+    },
+    BothFunded {
+        swap: StoredSwap<R>,
+        alpha_htlc_location: AlphaHtlcLocation<R>,
+        beta_htlc_location: BetaHtlcLocation<R>,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
+        beta_htlc_expiry: HtlcExpiry<R::BetaLedger>,
+    },
+    AlphaFundedBetaRefunded {
+        swap: StoredSwap<R>,
+        alpha_htlc_location: AlphaHtlcLocation<R>,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
+    },
+    AlphaRefundedBetaFunded {
+        swap: StoredSwap<R>,
+        beta_htlc_location: BetaHtlcLocation<R>,
+        beta_htlc_expiry: HtlcExpiry<R::BetaLedger>,
+    },
+    AlphaRedeemedBetaFunded {
+        swap: StoredSwap<R>,
+        beta_htlc_location: BetaHtlcLocation<R>,
+        beta_htlc_expiry: HtlcExpiry<R::BetaLedger>,
+    },
+    AlphaFundedBetaRedeemed {
+        swap: StoredSwap<R>,
+        beta_redeemed_tx: RedeemTransaction<R::BetaLedger>,
+        alpha_htlc_location: AlphaHtlcLocation<R>,
+        alpha_htlc_expiry: HtlcExpiry<R::AlphaLedger>,
+    },
+    Final(StoredOutcome<R>),
+    Error(rfc003::Error),
+}
+
+impl<R: Role> Persist for SwapStates<R> {
+    type Stored = StoredState<R>;
+
+    fn to_stored(&self, derived: &DerivedSecrets) -> Result<StoredState<R>, Error> {
+        use self::{StoredState as Stored, SwapStates as SS};
+
+        Ok(match self {
+            SS::Start(start) => Stored::Start(start.to_stored(derived)?),
+            SS::Accepted(Accepted { swap }) => Stored::Accepted {
+                swap: swap.to_stored(derived)?,
+            },
+            SS::AlphaDeployed(AlphaDeployed {
+                swap,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            }) => Stored::AlphaDeployed {
+                swap: swap.to_stored(derived)?,
+                alpha_htlc_location: alpha_htlc_location.clone(),
+                alpha_htlc_expiry: alpha_htlc_expiry.clone(),
+            },
+            SS::AlphaFunded(AlphaFunded {
+                swap,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            }) => Stored::AlphaFunded {
+                swap: swap.to_stored(derived)?,
+                alpha_htlc_location: alpha_htlc_location.clone(),
+                alpha_htlc_expiry: alpha_htlc_expiry.clone(),
+            },
+            SS::AlphaFundedBetaDeployed(AlphaFundedBetaDeployed {
+                swap,
+                alpha_htlc_location,
+                beta_htlc_location,
+                alpha_htlc_expiry,
+                beta_htlc_expiry,
+            }) => Stored::AlphaFundedBetaDeployed {
+                swap: swap.to_stored(derived)?,
+                alpha_htlc_location: alpha_htlc_location.clone(),
+                beta_htlc_location: beta_htlc_location.clone(),
+                alpha_htlc_expiry: alpha_htlc_expiry.clone(),
+                beta_htlc_expiry: beta_htlc_expiry.clone(),
+            },
+            SS::BothFunded(BothFunded {
+                swap,
+                alpha_htlc_location,
+                beta_htlc_location,
+                alpha_htlc_expiry,
+                beta_htlc_expiry,
+            }) => Stored::BothFunded {
+                swap: swap.to_stored(derived)?,
+                alpha_htlc_location: alpha_htlc_location.clone(),
+                beta_htlc_location: beta_htlc_location.clone(),
+                alpha_htlc_expiry: alpha_htlc_expiry.clone(),
+                beta_htlc_expiry: beta_htlc_expiry.clone(),
+            },
+            SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
+                swap,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            }) => Stored::AlphaFundedBetaRefunded {
+                swap: swap.to_stored(derived)?,
+                alpha_htlc_location: alpha_htlc_location.clone(),
+                alpha_htlc_expiry: alpha_htlc_expiry.clone(),
+            },
+            SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
+                swap,
+                beta_htlc_location,
+                beta_htlc_expiry,
+            }) => Stored::AlphaRefundedBetaFunded {
+                swap: swap.to_stored(derived)?,
+                beta_htlc_location: beta_htlc_location.clone(),
+                beta_htlc_expiry: beta_htlc_expiry.clone(),
+            },
+            SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
+                swap,
+                beta_htlc_location,
+                beta_htlc_expiry,
+            }) => Stored::AlphaRedeemedBetaFunded {
+                swap: swap.to_stored(derived)?,
+                beta_htlc_location: beta_htlc_location.clone(),
+                beta_htlc_expiry: beta_htlc_expiry.clone(),
+            },
+            SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
+                swap,
+                beta_redeemed_tx,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            }) => Stored::AlphaFundedBetaRedeemed {
+                swap: swap.to_stored(derived)?,
+                beta_redeemed_tx: beta_redeemed_tx.clone(),
+                alpha_htlc_location: alpha_htlc_location.clone(),
+                alpha_htlc_expiry: alpha_htlc_expiry.clone(),
+            },
+            SS::Final(Final(outcome)) => Stored::Final(outcome.to_stored(derived)?),
+            SS::Error(e) => Stored::Error(e.clone()),
+        })
+    }
+
+    fn from_stored(stored: StoredState<R>, derived: &DerivedSecrets) -> Result<Self, Error> {
+        use self::{StoredState as Stored, SwapStates as SS};
+
+        Ok(match stored {
+            Stored::Start(start) => SS::Start(Start::from_stored(start, derived)?),
+            Stored::Accepted { swap } => SS::Accepted(Accepted {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+            }),
+            Stored::AlphaDeployed {
+                swap,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            } => SS::AlphaDeployed(AlphaDeployed {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            }),
+            Stored::AlphaFunded {
+                swap,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            } => SS::AlphaFunded(AlphaFunded {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            }),
+            Stored::AlphaFundedBetaDeployed {
+                swap,
+                alpha_htlc_location,
+                beta_htlc_location,
+                alpha_htlc_expiry,
+                beta_htlc_expiry,
+            } => SS::AlphaFundedBetaDeployed(AlphaFundedBetaDeployed {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+                alpha_htlc_location,
+                beta_htlc_location,
+                alpha_htlc_expiry,
+                beta_htlc_expiry,
+            }),
+            Stored::BothFunded {
+                swap,
+                alpha_htlc_location,
+                beta_htlc_location,
+                alpha_htlc_expiry,
+                beta_htlc_expiry,
+            } => SS::BothFunded(BothFunded {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+                alpha_htlc_location,
+                beta_htlc_location,
+                alpha_htlc_expiry,
+                beta_htlc_expiry,
+            }),
+            Stored::AlphaFundedBetaRefunded {
+                swap,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            } => SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            }),
+            Stored::AlphaRefundedBetaFunded {
+                swap,
+                beta_htlc_location,
+                beta_htlc_expiry,
+            } => SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+                beta_htlc_location,
+                beta_htlc_expiry,
+            }),
+            Stored::AlphaRedeemedBetaFunded {
+                swap,
+                beta_htlc_location,
+                beta_htlc_expiry,
+            } => SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+                beta_htlc_location,
+                beta_htlc_expiry,
+            }),
+            Stored::AlphaFundedBetaRedeemed {
+                swap,
+                beta_redeemed_tx,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            } => SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
+                swap: OngoingSwap::from_stored(swap, derived)?,
+                beta_redeemed_tx,
+                alpha_htlc_location,
+                alpha_htlc_expiry,
+            }),
+            Stored::Final(outcome) => SS::Final(Final(SwapOutcome::from_stored(outcome, derived)?)),
+            Stored::Error(e) => SS::Error(e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::Seed;
+    use spectral::prelude::*;
+
+    #[test]
+    fn derived_keys_are_stored_as_which_key_they_are() {
+        let seed = Seed::from(*b"hello world, you are beautiful!!");
+        let id = SwapId::default();
+        let derived = DerivedSecrets::new(&seed, id);
+
+        let refund_key = seed.new_secp256k1_refund(id);
+        let stored = refund_key.to_stored(&derived).unwrap();
+
+        assert_that(&serde_json::to_string(&stored).unwrap())
+            .is_equal_to(r#""Refund""#.to_string());
+        assert_that(&KeyPair::from_stored(stored, &derived)).is_ok_containing(refund_key);
+    }
+
+    #[test]
+    fn keys_which_are_not_derived_are_not_stored() {
+        let seed = Seed::from(*b"hello world, you are beautiful!!");
+        let derived = DerivedSecrets::new(&seed, SwapId::default());
+
+        let key = KeyPair::from_secret_key_slice(&[1u8; 32]).unwrap();
+
+        assert_that(&key.to_stored(&derived)).is_err();
+    }
+}
@@ -0,0 +1,262 @@
+use crate::swap_protocols::{
+    ledger::{Bitcoin, Ethereum},
+    metadata_store::{AssetKind, IntoMetadata, LedgerKind, Metadata, RoleKind},
+    rfc003::{alice, bob},
+};
+use bitcoin_support::Network;
+use std::net::SocketAddr;
+
+/// The registry of all `(AL, BL, AA, BA)` tuples the node can swap.
+///
+/// Invokes `$crate::$callback!` with the given arguments in parentheses,
+/// followed by one `(AlphaLedger, BetaLedger, AlphaAssetKind: AlphaAsset,
+/// BetaAssetKind: BetaAsset)` entry per combination. Ledgers are named after
+/// both their `LedgerKind` variant and their type in
+/// `swap_protocols::ledger`. HTTP and BAM decoding, the metadata mapping,
+/// action dispatch and resuming swaps are all generated from this list.
+#[macro_export]
+macro_rules! swap_combinations {
+    ($callback:ident!($($args:tt)*)) => {
+        $crate::$callback!(
+            ($($args)*)
+            (Bitcoin, Ethereum, Bitcoin: bitcoin_support::BitcoinQuantity, Ether: ethereum_support::EtherQuantity)
+            (Bitcoin, Ethereum, Bitcoin: bitcoin_support::BitcoinQuantity, Erc20: ethereum_support::Erc20Quantity)
+            (Ethereum, Bitcoin, Ether: ethereum_support::EtherQuantity, Bitcoin: bitcoin_support::BitcoinQuantity)
+            (Ethereum, Bitcoin, Erc20: ethereum_support::Erc20Quantity, Bitcoin: bitcoin_support::BitcoinQuantity)
+            (Ethereum, Ethereum, Ether: ethereum_support::EtherQuantity, Erc20: ethereum_support::Erc20Quantity)
+            (Ethereum, Ethereum, Erc20: ethereum_support::Erc20Quantity, Ether: ethereum_support::EtherQuantity)
+            (Ethereum, Ethereum, Erc20: ethereum_support::Erc20Quantity, Erc20: ethereum_support::Erc20Quantity)
+        )
+    };
+}
+
+/// Evaluates `$body` with the type aliases `AL`, `BL`, `AA` and `BA` set to
+/// the types of the given `SwapCombination`, or `$unsupported` if the
+/// combination is not in the registry.
+#[macro_export]
+macro_rules! with_swap_combination {
+    ($combination:expr, $body:expr, $unsupported:expr) => {
+        $crate::swap_combinations!(_with_swap_combination!($combination, $body, $unsupported))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _with_swap_combination {
+    (
+        ($combination:expr, $body:expr, $unsupported:expr)
+        $(($al:ident, $bl:ident, $ak:ident: $aa:ty, $bk:ident: $ba:ty))*
+    ) => {
+        match $combination {
+            $(
+                $crate::swap_protocols::rfc003::SwapCombination {
+                    alpha_ledger: $crate::swap_protocols::LedgerKind::$al,
+                    beta_ledger: $crate::swap_protocols::LedgerKind::$bl,
+                    alpha_asset: $crate::swap_protocols::AssetKind::$ak,
+                    beta_asset: $crate::swap_protocols::AssetKind::$bk,
+                } => {
+                    #[allow(dead_code)]
+                    type AL = $crate::swap_protocols::ledger::$al;
+                    #[allow(dead_code)]
+                    type BL = $crate::swap_protocols::ledger::$bl;
+                    #[allow(dead_code)]
+                    type AA = $aa;
+                    #[allow(dead_code)]
+                    type BA = $ba;
+
+                    $body
+                }
+            )*
+            _ => $unsupported,
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _supported_swap_combinations {
+    (() $(($al:ident, $bl:ident, $ak:ident: $aa:ty, $bk:ident: $ba:ty))*) => {
+        vec![
+            $(
+                $crate::swap_protocols::rfc003::SwapCombination {
+                    alpha_ledger: $crate::swap_protocols::LedgerKind::$al,
+                    beta_ledger: $crate::swap_protocols::LedgerKind::$bl,
+                    alpha_asset: $crate::swap_protocols::AssetKind::$ak,
+                    beta_asset: $crate::swap_protocols::AssetKind::$bk,
+                }
+            ),*
+        ]
+    };
+}
+
+/// Only meant to be expanded in this module, where the ledger types and the
+/// swap requests are in scope.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_into_metadata {
+    (() $(($al:ident, $bl:ident, $ak:ident: $aa:ty, $bk:ident: $ba:ty))*) => {
+        $(
+            impl IntoMetadata for alice::SwapRequest<$al, $bl, $aa, $ba> {
+                fn into_metadata(self, counterparty: SocketAddr) -> Metadata {
+                    Metadata::new(
+                        LedgerKind::$al,
+                        LedgerKind::$bl,
+                        AssetKind::$ak,
+                        AssetKind::$bk,
+                        RoleKind::Alice,
+                        counterparty,
+                        bitcoin_network(&self.alpha_ledger, &self.beta_ledger),
+                    )
+                }
+            }
+
+            impl IntoMetadata for bob::SwapRequest<$al, $bl, $aa, $ba> {
+                fn into_metadata(self, counterparty: SocketAddr) -> Metadata {
+                    Metadata::new(
+                        LedgerKind::$al,
+                        LedgerKind::$bl,
+                        AssetKind::$ak,
+                        AssetKind::$bk,
+                        RoleKind::Bob,
+                        counterparty,
+                        bitcoin_network(&self.alpha_ledger, &self.beta_ledger),
+                    )
+                }
+            }
+        )*
+    };
+}
+
+/// The ledgers and assets of a swap, without any of their parameters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SwapCombination {
+    pub alpha_ledger: LedgerKind,
+    pub beta_ledger: LedgerKind,
+    pub alpha_asset: AssetKind,
+    pub beta_asset: AssetKind,
+}
+
+impl SwapCombination {
+    pub fn supported() -> Vec<SwapCombination> {
+        swap_combinations!(_supported_swap_combinations!())
+    }
+}
+
+impl From<&Metadata> for SwapCombination {
+    fn from(metadata: &Metadata) -> Self {
+        SwapCombination {
+            alpha_ledger: metadata.alpha_ledger,
+            beta_ledger: metadata.beta_ledger,
+            alpha_asset: metadata.alpha_asset,
+            beta_asset: metadata.beta_asset,
+        }
+    }
+}
+
+trait BitcoinNetwork {
+    fn bitcoin_network(&self) -> Option<Network>;
+}
+
+impl BitcoinNetwork for Bitcoin {
+    fn bitcoin_network(&self) -> Option<Network> {
+        Some(self.network)
+    }
+}
+
+impl BitcoinNetwork for Ethereum {
+    fn bitcoin_network(&self) -> Option<Network> {
+        None
+    }
+}
+
+fn bitcoin_network<AL: BitcoinNetwork, BL: BitcoinNetwork>(
+    alpha_ledger: &AL,
+    beta_ledger: &BL,
+) -> Option<Network> {
+    alpha_ledger
+        .bitcoin_network()
+        .or_else(|| beta_ledger.bitcoin_network())
+}
+
+swap_combinations!(_impl_into_metadata!());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::rfc003::ethereum::Seconds;
+    use bitcoin_support::BitcoinQuantity;
+    use ethereum_support::{Erc20Quantity, EtherQuantity};
+    use spectral::prelude::*;
+    use std::any::TypeId;
+
+    #[test]
+    fn supported_combinations_are_distinct() {
+        let combinations = SwapCombination::supported();
+
+        for (i, combination) in combinations.iter().enumerate() {
+            assert_that(&combinations[i + 1..].contains(combination)).is_false();
+        }
+    }
+
+    #[test]
+    fn dispatches_on_the_types_of_the_combination() {
+        let combination = SwapCombination {
+            alpha_ledger: LedgerKind::Ethereum,
+            beta_ledger: LedgerKind::Bitcoin,
+            alpha_asset: AssetKind::Erc20,
+            beta_asset: AssetKind::Bitcoin,
+        };
+
+        let types =
+            with_swap_combination!(combination, Some(TypeId::of::<(AL, BL, AA, BA)>()), None);
+
+        assert_that(&types).is_equal_to(Some(TypeId::of::<(
+            Ethereum,
+            Bitcoin,
+            Erc20Quantity,
+            BitcoinQuantity,
+        )>()));
+    }
+
+    #[test]
+    fn unsupported_combination_is_not_dispatched() {
+        let combination = SwapCombination {
+            alpha_ledger: LedgerKind::Bitcoin,
+            beta_ledger: LedgerKind::Bitcoin,
+            alpha_asset: AssetKind::Bitcoin,
+            beta_asset: AssetKind::Bitcoin,
+        };
+
+        let dispatched = with_swap_combination!(combination, true, false);
+
+        assert_that(&dispatched).is_false();
+    }
+
+    #[test]
+    fn metadata_takes_bitcoin_network_from_beta_ledger() {
+        let swap_request = bob::SwapRequest {
+            alpha_asset: EtherQuantity::from_eth(10.0),
+            beta_asset: BitcoinQuantity::from_bitcoin(1.0),
+            alpha_ledger: Ethereum::dev(),
+            beta_ledger: Bitcoin::regtest(),
+            alpha_ledger_refund_identity: ethereum_support::Address::from(
+                "0x00a329c0648769a73afac7f9381e08fb43dbea72",
+            ),
+            beta_ledger_redeem_identity: bitcoin_support::PubkeyHash::from(&[0u8; 20][..]),
+            alpha_ledger_lock_duration: Seconds(86400),
+            secret_hash: "f6fc84c9f21c24907d6bee6eec38cabab5fa9a7be8c4a7827fe9e56f245bd2d5"
+                .parse()
+                .unwrap(),
+        };
+
+        let metadata = swap_request.into_metadata("127.0.0.1:8011".parse().unwrap());
+
+        assert_that(&SwapCombination::from(&metadata)).is_equal_to(SwapCombination {
+            alpha_ledger: LedgerKind::Ethereum,
+            beta_ledger: LedgerKind::Bitcoin,
+            alpha_asset: AssetKind::Ether,
+            beta_asset: AssetKind::Bitcoin,
+        });
+        assert_that(&metadata.bitcoin_network).is_equal_to(Some(Network::Regtest));
+    }
+}
@@ -1,8 +1,11 @@
 #[macro_export]
 macro_rules! transition_save {
     ($repo:expr, $new_state:expr) => {{
+        transition_save!($repo, $new_state, None)
+    }};
+    ($repo:expr, $new_state:expr, $htlc_event:expr) => {{
         let save_state = $new_state;
-        $repo.save(save_state.clone().into());
+        $repo.save_transition(save_state.clone().into(), $htlc_event);
 
         debug!("Transitioning to {}", save_state);
 
@@ -0,0 +1,98 @@
+use crate::swap_protocols::{metadata_store::Transition, SwapId};
+use futures::sync::mpsc;
+use std::sync::Mutex;
+
+/// How many changes a subscriber may fall behind before it is disconnected
+const SUBSCRIBER_BUFFER: usize = 64;
+
+/// A state a swap was saved in, together with what triggered it
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateChange {
+    pub id: SwapId,
+    pub transition: Transition,
+}
+
+/// Tells subscribers about the states swaps are saved in, e.g. to stream the
+/// changes to HTTP clients instead of having them poll
+#[derive(Debug, Default)]
+pub struct StateChanges {
+    subscribers: Mutex<Vec<mpsc::Sender<StateChange>>>,
+}
+
+impl StateChanges {
+    pub fn subscribe(&self) -> mpsc::Receiver<StateChange> {
+        let (sender, receiver) = mpsc::channel(SUBSCRIBER_BUFFER);
+        self.subscribers.lock().unwrap().push(sender);
+
+        receiver
+    }
+
+    pub fn publish(&self, change: StateChange) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+
+        // Subscribers which dropped their receiver are no longer interested,
+        // the ones which cannot keep up are dropped rather than buffering
+        // changes for them without bound
+        let remaining = subscribers
+            .drain(..)
+            .filter_map(|mut subscriber| match subscriber.try_send(change.clone()) {
+                Ok(()) => Some(subscriber),
+                Err(ref e) if e.is_full() => {
+                    warn!("Dropping subscriber of state changes which fell behind");
+                    None
+                }
+                Err(_) => None,
+            })
+            .collect();
+        *subscribers = remaining;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{Future, Stream};
+    use spectral::prelude::*;
+
+    fn change(id: SwapId) -> StateChange {
+        StateChange {
+            id,
+            transition: Transition::new(String::from("Accepted"), None),
+        }
+    }
+
+    #[test]
+    fn every_subscriber_receives_published_changes() {
+        let state_changes = StateChanges::default();
+        let first = state_changes.subscribe();
+        let second = state_changes.subscribe();
+        let change = change(SwapId::default());
+
+        state_changes.publish(change.clone());
+
+        assert_that(&first.take(1).collect().wait()).is_ok_containing(vec![change.clone()]);
+        assert_that(&second.take(1).collect().wait()).is_ok_containing(vec![change]);
+    }
+
+    #[test]
+    fn forgets_subscribers_which_are_gone() {
+        let state_changes = StateChanges::default();
+        drop(state_changes.subscribe());
+
+        state_changes.publish(change(SwapId::default()));
+
+        assert_that(&state_changes.subscribers.lock().unwrap().len()).is_equal_to(0);
+    }
+
+    #[test]
+    fn drops_subscribers_which_fall_behind() {
+        let state_changes = StateChanges::default();
+        let _lagging = state_changes.subscribe();
+
+        for _ in 0..=SUBSCRIBER_BUFFER + 1 {
+            state_changes.publish(change(SwapId::default()));
+        }
+
+        assert_that(&state_changes.subscribers.lock().unwrap().len()).is_equal_to(0);
+    }
+}
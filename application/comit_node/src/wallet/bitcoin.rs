@@ -0,0 +1,181 @@
+use crate::{
+    fee_estimation::bitcoin::{FeeEstimator, FeeTarget},
+    swap_protocols::rfc003::bitcoin::SpendOutput,
+};
+use bitcoin_rpc_client::{
+    rpc::SerializedRawTransaction, BitcoinCoreClient, BitcoinRpcApi, ClientError, RpcError,
+};
+use bitcoin_support::{serialize::serialize_hex, Address, BitcoinQuantity, Network, TransactionId};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum Error {
+    Connection(ClientError),
+    Rpc(RpcError),
+    Serialization(bitcoin_support::Error),
+    WrongNetwork(Network),
+}
+
+pub trait BitcoinWallet: Send + Sync + 'static {
+    /// Pays `value` to `address` from the funds of the wallet
+    fn send_to_address(
+        &self,
+        address: Address,
+        value: BitcoinQuantity,
+    ) -> Result<TransactionId, Error>;
+
+    /// Signs and broadcasts a transaction which spends the output to a new
+    /// address of the wallet
+    fn spend_output(&self, spend_output: SpendOutput) -> Result<TransactionId, Error>;
+}
+
+/// A wallet backed by the wallet of a bitcoind node, which takes care of
+/// selecting UTXOs and change addresses. Outputs are spent at the fee rate
+/// estimated for `fee_target`.
+#[allow(missing_debug_implementations)]
+pub struct BitcoindWallet<C> {
+    client: C,
+    fee_estimator: Arc<dyn FeeEstimator>,
+    fee_target: FeeTarget,
+}
+
+impl BitcoindWallet<BitcoinCoreClient> {
+    pub fn new(
+        url: &str,
+        username: &str,
+        password: &str,
+        fee_estimator: Arc<dyn FeeEstimator>,
+        fee_target: FeeTarget,
+    ) -> Self {
+        Self::with_client(
+            BitcoinCoreClient::new(url, username, password),
+            fee_estimator,
+            fee_target,
+        )
+    }
+}
+
+impl<C: BitcoinRpcApi> BitcoindWallet<C> {
+    pub fn with_client(
+        client: C,
+        fee_estimator: Arc<dyn FeeEstimator>,
+        fee_target: FeeTarget,
+    ) -> Self {
+        BitcoindWallet {
+            client,
+            fee_estimator,
+            fee_target,
+        }
+    }
+}
+
+impl<C: BitcoinRpcApi + Send + Sync + 'static> BitcoinWallet for BitcoindWallet<C> {
+    fn send_to_address(
+        &self,
+        address: Address,
+        value: BitcoinQuantity,
+    ) -> Result<TransactionId, Error> {
+        rpc(self.client.send_to_address(&address, value.bitcoin()))
+    }
+
+    fn spend_output(&self, spend_output: SpendOutput) -> Result<TransactionId, Error> {
+        let address = rpc(self.client.get_new_address())?;
+        if address.network != spend_output.network {
+            return Err(Error::WrongNetwork(address.network));
+        }
+        let transaction = spend_output
+            .spend_to(address)
+            .sign_with_rate(self.fee_estimator.fee_per_byte(self.fee_target));
+        let hex = serialize_hex(&transaction).map_err(Error::Serialization)?;
+
+        rpc(self
+            .client
+            .send_raw_transaction(SerializedRawTransaction(hex)))
+    }
+}
+
+fn rpc<T>(result: Result<Result<T, RpcError>, ClientError>) -> Result<T, Error> {
+    result.map_err(Error::Connection)?.map_err(Error::Rpc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fee_estimation::bitcoin::StaticFees;
+    use bitcoin_support::{OutPoint, Sha256dHash};
+    use bitcoin_witness::{PrimedInput, UnlockP2wpkh};
+    use secp256k1_support::KeyPair;
+    use spectral::prelude::*;
+    use std::str::FromStr;
+
+    struct FakeBitcoind {
+        transaction_id: TransactionId,
+    }
+
+    impl BitcoinRpcApi for FakeBitcoind {
+        fn send_to_address(
+            &self,
+            _address: &Address,
+            _amount: f64,
+        ) -> Result<Result<TransactionId, RpcError>, ClientError> {
+            Ok(Ok(self.transaction_id))
+        }
+    }
+
+    /// Hands out mainnet addresses only
+    struct MainnetBitcoind;
+
+    impl BitcoinRpcApi for MainnetBitcoind {
+        fn get_new_address(&self) -> Result<Result<Address, RpcError>, ClientError> {
+            Ok(Ok(Address::from_str(
+                "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+            )
+            .unwrap()))
+        }
+    }
+
+    #[test]
+    fn send_to_address_returns_the_id_of_the_funding_transaction() {
+        let transaction_id = Sha256dHash::from_data(b"funding");
+        let wallet = BitcoindWallet::with_client(
+            FakeBitcoind { transaction_id },
+            Arc::new(StaticFees::default()),
+            FeeTarget::default(),
+        );
+
+        let result = wallet.send_to_address(
+            Address::from_str("bcrt1qcqslz7lfn34dl096t5uwurff9spen5h4v2pmap").unwrap(),
+            BitcoinQuantity::from_bitcoin(1.0),
+        );
+
+        assert_that(&result).is_ok_containing(transaction_id);
+    }
+
+    #[test]
+    fn does_not_spend_output_to_address_of_other_network() {
+        let keypair = KeyPair::from_secret_key_slice(&[1u8; 32]).unwrap();
+        let spend_output = SpendOutput {
+            output: PrimedInput::new(
+                OutPoint {
+                    txid: Sha256dHash::from_data(b"htlc"),
+                    vout: 0,
+                },
+                BitcoinQuantity::from_bitcoin(1.0),
+                keypair.p2wpkh_unlock_parameters(),
+            ),
+            network: Network::Regtest,
+        };
+        let wallet = BitcoindWallet::with_client(
+            MainnetBitcoind,
+            Arc::new(StaticFees::default()),
+            FeeTarget::default(),
+        );
+
+        let result = wallet.spend_output(spend_output);
+
+        assert!(match result {
+            Err(Error::WrongNetwork(Network::Bitcoin)) => true,
+            _ => false,
+        });
+    }
+}
@@ -0,0 +1,69 @@
+use crate::wallet::ethereum::transaction::{SignedTransaction, UnsignedTransaction};
+use ethereum_support::{Address, ToEthereumAddress};
+use secp256k1_support::{KeyPair, RecoverableSignature};
+
+pub trait Wallet: Send + Sync {
+    fn sign<'a>(&self, tx: &'a UnsignedTransaction) -> SignedTransaction<'a>;
+    fn address(&self) -> Address;
+}
+
+#[derive(Debug)]
+pub struct InMemoryWallet {
+    keypair: KeyPair,
+    chain_id: u64,
+}
+
+impl InMemoryWallet {
+    pub fn new(keypair: KeyPair, chain_id: u64) -> Self {
+        InMemoryWallet { keypair, chain_id }
+    }
+
+    // https://github.com/ethereum/EIPs/blob/master/EIPS/eip-155.md#specification
+    fn chain_replay_protection_offset(&self) -> u64 {
+        35 + self.chain_id * 2
+    }
+}
+
+impl Wallet for InMemoryWallet {
+    fn sign<'a>(&self, tx: &'a UnsignedTransaction) -> SignedTransaction<'a> {
+        let hash: [u8; 32] = tx.hash(self.chain_id).into();
+
+        let signature = self.keypair.sign_ecdsa_recoverable(hash.into());
+
+        let (rec_id, signature) = RecoverableSignature::serialize_compact(&signature);
+
+        let v = rec_id.to_i32() as u64 + self.chain_replay_protection_offset();
+
+        SignedTransaction::new(tx, v, signature)
+    }
+
+    fn address(&self) -> Address {
+        self.keypair.public_key().to_ethereum_address()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_support::U256;
+    use rlp::UntrustedRlp;
+
+    #[test]
+    fn v_does_not_overflow_for_big_chain_ids() {
+        let keypair = KeyPair::from_secret_key_slice(&[1u8; 32]).unwrap();
+        let wallet = InMemoryWallet::new(keypair, 2_147_483_647);
+        let transaction = UnsignedTransaction {
+            nonce: U256::from(0),
+            gas_price: U256::from(0),
+            gas_limit: U256::from(21_000),
+            to: Some(Address::from(0xBB)),
+            value: U256::from(0),
+            data: None,
+        };
+
+        let signed: ethereum_support::Bytes = wallet.sign(&transaction).into();
+        let v: u64 = UntrustedRlp::new(&signed.0).val_at(6).unwrap();
+
+        assert!(v == 4_294_967_329 || v == 4_294_967_330, "v is {}", v);
+    }
+}
@@ -0,0 +1,188 @@
+mod in_memory;
+mod node;
+mod transaction;
+
+pub use self::{
+    in_memory::{InMemoryWallet, Wallet},
+    node::{EthereumNode, Web3Node},
+    transaction::{SignedTransaction, UnsignedTransaction},
+};
+
+use ethereum_support::{web3, Address, Bytes, EtherQuantity, H256, U256};
+use std::sync::Mutex;
+
+#[derive(Debug)]
+pub enum Error {
+    Node(web3::Error),
+}
+
+pub trait EthereumWallet: Send + Sync + 'static {
+    /// Signs a transaction with the key of the wallet and broadcasts it,
+    /// a missing `to` address deploys `data` as a contract
+    fn send_transaction(
+        &self,
+        to: Option<Address>,
+        value: EtherQuantity,
+        data: Bytes,
+        gas_limit: U256,
+    ) -> Result<H256, Error>;
+
+    fn address(&self) -> Address;
+}
+
+/// A wallet which signs transactions with a key held by the node and keeps
+/// track of its nonce, so that transactions can be sent back-to-back without
+/// waiting for them to be mined
+#[allow(missing_debug_implementations)]
+pub struct NodeWallet<N> {
+    node: N,
+    signer: InMemoryWallet,
+    gas_price: Option<U256>,
+    nonce: Mutex<Option<U256>>,
+}
+
+impl<N: EthereumNode> NodeWallet<N> {
+    /// Without a `gas_price`, the one suggested by the node is used
+    pub fn new(node: N, signer: InMemoryWallet, gas_price: Option<U256>) -> Self {
+        NodeWallet {
+            node,
+            signer,
+            gas_price,
+            nonce: Mutex::new(None),
+        }
+    }
+}
+
+impl<N: EthereumNode> EthereumWallet for NodeWallet<N> {
+    fn send_transaction(
+        &self,
+        to: Option<Address>,
+        value: EtherQuantity,
+        data: Bytes,
+        gas_limit: U256,
+    ) -> Result<H256, Error> {
+        let mut nonce = self.nonce.lock().unwrap();
+
+        let current_nonce = match *nonce {
+            Some(nonce) => nonce,
+            None => self.node.transaction_count(self.signer.address())?,
+        };
+        let gas_price = match self.gas_price {
+            Some(gas_price) => gas_price,
+            None => self.node.gas_price()?,
+        };
+
+        let transaction = UnsignedTransaction {
+            nonce: current_nonce,
+            gas_price,
+            gas_limit,
+            to,
+            value: value.wei(),
+            data: Some(data),
+        };
+        let signed_transaction = self.signer.sign(&transaction);
+
+        match self.node.send_raw_transaction(signed_transaction.into()) {
+            Ok(transaction_id) => {
+                *nonce = Some(current_nonce + U256::from(1));
+                Ok(transaction_id)
+            }
+            Err(e) => {
+                // The node knows best which nonce is next after a failure
+                *nonce = None;
+                Err(e)
+            }
+        }
+    }
+
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1_support::KeyPair;
+    use spectral::prelude::*;
+
+    #[derive(Default)]
+    struct FakeNode {
+        transaction_count: U256,
+        fail_next_transaction: Mutex<bool>,
+        sent_transactions: Mutex<Vec<Bytes>>,
+    }
+
+    impl EthereumNode for FakeNode {
+        fn gas_price(&self) -> Result<U256, Error> {
+            Ok(U256::from(1))
+        }
+
+        fn transaction_count(&self, _address: Address) -> Result<U256, Error> {
+            Ok(self.transaction_count)
+        }
+
+        fn estimate_gas(
+            &self,
+            _from: Address,
+            _to: Option<Address>,
+            _value: EtherQuantity,
+            _data: &Bytes,
+        ) -> Result<U256, Error> {
+            Ok(U256::from(21000))
+        }
+
+        fn send_raw_transaction(&self, transaction: Bytes) -> Result<H256, Error> {
+            let mut fail = self.fail_next_transaction.lock().unwrap();
+            if *fail {
+                *fail = false;
+                return Err(Error::Node(web3::ErrorKind::Unreachable.into()));
+            }
+
+            let mut sent_transactions = self.sent_transactions.lock().unwrap();
+            sent_transactions.push(transaction);
+            Ok(H256::from(sent_transactions.len() as u64))
+        }
+    }
+
+    fn wallet(node: FakeNode) -> NodeWallet<FakeNode> {
+        let keypair = KeyPair::from_secret_key_slice(&[1u8; 32]).unwrap();
+        NodeWallet::new(node, InMemoryWallet::new(keypair, 17), None)
+    }
+
+    fn send(wallet: &NodeWallet<FakeNode>) -> Result<H256, Error> {
+        wallet.send_transaction(
+            None,
+            EtherQuantity::zero(),
+            Bytes(vec![]),
+            U256::from(21000),
+        )
+    }
+
+    #[test]
+    fn consecutive_transactions_use_consecutive_nonces() {
+        let wallet = wallet(FakeNode {
+            transaction_count: U256::from(5),
+            ..FakeNode::default()
+        });
+
+        send(&wallet).unwrap();
+        send(&wallet).unwrap();
+
+        assert_that(&*wallet.nonce.lock().unwrap()).is_equal_to(Some(U256::from(7)));
+    }
+
+    #[test]
+    fn failed_transaction_resets_the_nonce() {
+        let wallet = wallet(FakeNode {
+            transaction_count: U256::from(5),
+            ..FakeNode::default()
+        });
+
+        send(&wallet).unwrap();
+        *wallet.node.fail_next_transaction.lock().unwrap() = true;
+
+        assert_that(&send(&wallet)).is_err();
+        assert_that(&*wallet.nonce.lock().unwrap()).is_none();
+    }
+}
@@ -0,0 +1,90 @@
+use crate::wallet::ethereum::Error;
+use ethereum_support::{
+    web3::{
+        helpers::CallFuture,
+        transports::{EventLoopHandle, Http},
+        Transport, Web3,
+    },
+    Address, BlockNumber, Bytes, EtherQuantity, Future, H256, U256,
+};
+
+/// The calls the wallet and the gas estimation need from an Ethereum node
+pub trait EthereumNode: Send + Sync + 'static {
+    fn gas_price(&self) -> Result<U256, Error>;
+    /// The nonce of the next transaction of `address`, including the
+    /// transactions in the mempool
+    fn transaction_count(&self, address: Address) -> Result<U256, Error>;
+    fn send_raw_transaction(&self, transaction: Bytes) -> Result<H256, Error>;
+    /// The gas the transaction `from` sends would use if it was mined now, a
+    /// missing `to` address deploys `data` as a contract
+    fn estimate_gas(
+        &self,
+        from: Address,
+        to: Option<Address>,
+        value: EtherQuantity,
+        data: &Bytes,
+    ) -> Result<U256, Error>;
+}
+
+#[allow(missing_debug_implementations)]
+pub struct Web3Node {
+    web3: Web3<Http>,
+    _event_loop: EventLoopHandle,
+}
+
+impl Web3Node {
+    pub fn new(url: &str) -> Result<Self, Error> {
+        let (event_loop, transport) = Http::new(url).map_err(Error::Node)?;
+
+        Ok(Web3Node {
+            web3: Web3::new(transport),
+            _event_loop: event_loop,
+        })
+    }
+}
+
+impl EthereumNode for Web3Node {
+    fn gas_price(&self) -> Result<U256, Error> {
+        self.web3.eth().gas_price().wait().map_err(Error::Node)
+    }
+
+    fn transaction_count(&self, address: Address) -> Result<U256, Error> {
+        self.web3
+            .eth()
+            .transaction_count(address, Some(BlockNumber::Pending))
+            .wait()
+            .map_err(Error::Node)
+    }
+
+    fn send_raw_transaction(&self, transaction: Bytes) -> Result<H256, Error> {
+        self.web3
+            .eth()
+            .send_raw_transaction(transaction)
+            .wait()
+            .map_err(Error::Node)
+    }
+
+    fn estimate_gas(
+        &self,
+        from: Address,
+        to: Option<Address>,
+        value: EtherQuantity,
+        data: &Bytes,
+    ) -> Result<U256, Error> {
+        // `CallRequest` of web3 requires a `to` address, which contract
+        // deployments do not have
+        let mut request = json!({
+            "from": from,
+            "value": value.wei(),
+            "data": data,
+        });
+        if let Some(to) = to {
+            request["to"] = json!(to);
+        }
+
+        let transport = self.web3.transport();
+        CallFuture::new(transport.execute("eth_estimateGas", vec![request]))
+            .wait()
+            .map_err(Error::Node)
+    }
+}
@@ -24,14 +24,14 @@ impl fmt::Debug for Signature {
 #[derive(Debug)]
 pub struct SignedTransaction<'a> {
     unsigned_transaction: &'a UnsignedTransaction,
-    v: u8,
+    v: u64,
     signature: Signature,
 }
 
 impl<'a> SignedTransaction<'a> {
     pub(crate) fn new(
         unsigned_transaction: &'a UnsignedTransaction,
-        v: u8,
+        v: u64,
         signature: [u8; 64],
     ) -> Self {
         SignedTransaction {
@@ -88,7 +88,7 @@ impl Encodable for UnsignedTransaction {
 }
 
 impl UnsignedTransaction {
-    pub(crate) fn hash(&self, chain_id: u8) -> H256 {
+    pub(crate) fn hash(&self, chain_id: u64) -> H256 {
         let mut stream = RlpStream::new();
         let bytes = stream
             .append_internal(self)
@@ -0,0 +1,18 @@
+pub mod bitcoin;
+pub mod ethereum;
+
+pub use self::{
+    bitcoin::{BitcoinWallet, BitcoindWallet},
+    ethereum::{EthereumWallet, NodeWallet},
+};
+
+use std::sync::Arc;
+
+/// The wallets the node can use to execute actions on its own, a missing
+/// wallet means actions on that ledger have to be executed manually
+#[derive(Clone, Default)]
+#[allow(missing_debug_implementations)]
+pub struct Wallets {
+    pub bitcoin: Option<Arc<dyn BitcoinWallet>>,
+    pub ethereum: Option<Arc<dyn EthereumWallet>>,
+}
@@ -0,0 +1,391 @@
+use crate::{
+    logging::{self, Instrument},
+    swap_protocols::SwapId,
+};
+use chrono::{DateTime, Utc};
+use crypto::{hmac::Hmac, mac::Mac, sha2::Sha256};
+use futures::{
+    future::{self, Either, Loop},
+    Future,
+};
+use reqwest::{header::CONTENT_TYPE, r#async::Client, Url};
+use std::{
+    cmp,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::timer::Delay;
+use uuid::Uuid;
+
+mod sqlite;
+
+pub use self::sqlite::SqlitePendingDeliveries;
+
+/// Carries the hex encoded HMAC-SHA256 of `<timestamp>.<body>`, keyed with the
+/// secret of the webhook
+pub const SIGNATURE_HEADER: &str = "X-Comit-Signature";
+/// Carries the unix time in seconds at which the request was signed, which
+/// lets receivers reject replayed requests
+pub const TIMESTAMP_HEADER: &str = "X-Comit-Timestamp";
+
+const INITIAL_RETRY_DELAY_SECS: u64 = 1;
+const MAX_RETRY_DELAY_SECS: u64 = 10 * 60;
+/// Together with the backoff, a delivery is given up after about an hour
+const MAX_ATTEMPTS: u32 = 15;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapEvent {
+    Requested,
+    Accepted,
+    Rejected,
+    Funded,
+    Redeemed,
+    Refunded,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Notification {
+    /// Retries of a notification have the same id, which lets receivers drop
+    /// duplicates
+    pub id: Uuid,
+    pub swap_id: SwapId,
+    pub event: SwapEvent,
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Webhook {
+    pub url: Url,
+    pub secret: String,
+}
+
+/// A notification which has not been delivered to a webhook yet
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingDelivery {
+    pub notification_id: Uuid,
+    pub url: Url,
+    pub body: Vec<u8>,
+    /// How many times delivering the notification failed so far
+    pub failed_attempts: u32,
+}
+
+#[derive(Debug, Fail, PartialEq)]
+pub enum Error {
+    #[fail(display = "The storage backend failed: {}", _0)]
+    Backend(String),
+}
+
+/// Keeps the deliveries which are still being retried, so they survive a
+/// restart of the node
+pub trait PendingDeliveries: Debug + Send + Sync + 'static {
+    /// Inserts the delivery or replaces the one of the same notification to
+    /// the same url
+    fn save(&self, delivery: &PendingDelivery) -> Result<(), Error>;
+    fn remove(&self, notification_id: Uuid, url: &Url) -> Result<(), Error>;
+    fn all(&self) -> Result<Vec<PendingDelivery>, Error>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryPendingDeliveries {
+    deliveries: Mutex<Vec<PendingDelivery>>,
+}
+
+impl PendingDeliveries for InMemoryPendingDeliveries {
+    fn save(&self, delivery: &PendingDelivery) -> Result<(), Error> {
+        let mut deliveries = self.deliveries.lock().unwrap();
+
+        deliveries.retain(|pending| {
+            pending.notification_id != delivery.notification_id || pending.url != delivery.url
+        });
+        deliveries.push(delivery.clone());
+
+        Ok(())
+    }
+
+    fn remove(&self, notification_id: Uuid, url: &Url) -> Result<(), Error> {
+        self.deliveries
+            .lock()
+            .unwrap()
+            .retain(|pending| pending.notification_id != notification_id || pending.url != *url);
+
+        Ok(())
+    }
+
+    fn all(&self) -> Result<Vec<PendingDelivery>, Error> {
+        Ok(self.deliveries.lock().unwrap().clone())
+    }
+}
+
+/// Delivers notifications to every webhook, retrying failed deliveries with
+/// exponential backoff until `MAX_ATTEMPTS` is reached
+#[derive(Debug)]
+pub struct Webhooks {
+    client: Client,
+    webhooks: Vec<Webhook>,
+    pending_deliveries: Arc<dyn PendingDeliveries>,
+}
+
+impl Webhooks {
+    pub fn new(webhooks: Vec<Webhook>) -> Self {
+        Self::with_pending_deliveries(webhooks, Arc::new(InMemoryPendingDeliveries::default()))
+    }
+
+    pub fn with_pending_deliveries(
+        webhooks: Vec<Webhook>,
+        pending_deliveries: Arc<dyn PendingDeliveries>,
+    ) -> Self {
+        Webhooks {
+            client: Client::new(),
+            webhooks,
+            pending_deliveries,
+        }
+    }
+
+    /// Has to be called from within a tokio runtime
+    pub fn notify(&self, swap_id: SwapId, event: SwapEvent, state: String) {
+        if self.webhooks.is_empty() {
+            return;
+        }
+
+        let notification = Notification {
+            id: Uuid::new_v4(),
+            swap_id,
+            event,
+            state,
+            created_at: Utc::now(),
+        };
+        let body = serde_json::to_vec(&notification).expect("notification should always serialize");
+
+        for webhook in &self.webhooks {
+            let delivery = PendingDelivery {
+                notification_id: notification.id,
+                url: webhook.url.clone(),
+                body: body.clone(),
+                failed_attempts: 0,
+            };
+
+            if let Err(e) = self.pending_deliveries.save(&delivery) {
+                error!(
+                    "Failed to persist delivery of notification {} to {}: {:?}",
+                    delivery.notification_id, delivery.url, e
+                );
+            }
+
+            tokio::spawn(
+                self.deliver(webhook.clone(), delivery)
+                    .instrument(logging::swap_span(swap_id)),
+            );
+        }
+    }
+
+    /// Picks up the deliveries which were still pending when the node
+    /// stopped. Has to be called from within a tokio runtime
+    pub fn resume(&self) {
+        let deliveries = match self.pending_deliveries.all() {
+            Ok(deliveries) => deliveries,
+            Err(e) => {
+                error!("Failed to load pending webhook deliveries: {:?}", e);
+                return;
+            }
+        };
+
+        for delivery in deliveries {
+            match self
+                .webhooks
+                .iter()
+                .find(|webhook| webhook.url == delivery.url)
+            {
+                Some(webhook) => {
+                    tokio::spawn(self.deliver(webhook.clone(), delivery));
+                }
+                None => {
+                    warn!(
+                        "Dropping notification {} to {} which is no longer a configured webhook",
+                        delivery.notification_id, delivery.url
+                    );
+                    self.forget(&delivery);
+                }
+            }
+        }
+    }
+
+    fn deliver(
+        &self,
+        webhook: Webhook,
+        delivery: PendingDelivery,
+    ) -> impl Future<Item = (), Error = ()> + Send {
+        let client = self.client.clone();
+        let pending_deliveries = Arc::clone(&self.pending_deliveries);
+
+        future::loop_fn(delivery, move |mut delivery| {
+            let timestamp = Utc::now().timestamp();
+            let signature = sign(&webhook.secret, timestamp, &delivery.body);
+            let pending_deliveries = Arc::clone(&pending_deliveries);
+
+            client
+                .post(webhook.url.clone())
+                .header(CONTENT_TYPE, "application/json")
+                .header(TIMESTAMP_HEADER, timestamp.to_string().as_str())
+                .header(SIGNATURE_HEADER, signature.as_str())
+                .body(delivery.body.clone())
+                .send()
+                .then(move |result| match result {
+                    Ok(ref response) if response.status().is_success() => {
+                        forget(pending_deliveries.as_ref(), &delivery);
+                        Either::A(future::ok(Loop::Break(())))
+                    }
+                    result => {
+                        delivery.failed_attempts += 1;
+
+                        if delivery.failed_attempts >= MAX_ATTEMPTS {
+                            error!(
+                                "Giving up on notification {} to {} after {} attempts: {:?}",
+                                delivery.notification_id,
+                                delivery.url,
+                                delivery.failed_attempts,
+                                result
+                            );
+                            forget(pending_deliveries.as_ref(), &delivery);
+                            return Either::A(future::ok(Loop::Break(())));
+                        }
+
+                        let retry_delay = retry_delay(delivery.failed_attempts);
+                        warn!(
+                            "Failed to deliver notification {} to {}, retrying in {:?}: {:?}",
+                            delivery.notification_id, delivery.url, retry_delay, result
+                        );
+                        if let Err(e) = pending_deliveries.save(&delivery) {
+                            error!(
+                                "Failed to persist delivery of notification {} to {}: {:?}",
+                                delivery.notification_id, delivery.url, e
+                            );
+                        }
+
+                        Either::B(
+                            Delay::new(Instant::now() + retry_delay)
+                                .map(move |_| Loop::Continue(delivery))
+                                .map_err(|e| error!("Timer failed: {:?}", e)),
+                        )
+                    }
+                })
+        })
+    }
+
+    fn forget(&self, delivery: &PendingDelivery) {
+        forget(self.pending_deliveries.as_ref(), delivery)
+    }
+}
+
+fn forget(pending_deliveries: &dyn PendingDeliveries, delivery: &PendingDelivery) {
+    if let Err(e) = pending_deliveries.remove(delivery.notification_id, &delivery.url) {
+        error!(
+            "Failed to remove delivery of notification {} to {}: {:?}",
+            delivery.notification_id, delivery.url, e
+        );
+    }
+}
+
+/// Doubles with every failed attempt, up to `MAX_RETRY_DELAY_SECS`
+fn retry_delay(failed_attempts: u32) -> Duration {
+    let secs = 1u64
+        .checked_shl(failed_attempts.saturating_sub(1))
+        .and_then(|factor| factor.checked_mul(INITIAL_RETRY_DELAY_SECS))
+        .unwrap_or(MAX_RETRY_DELAY_SECS);
+
+    Duration::from_secs(cmp::min(secs, MAX_RETRY_DELAY_SECS))
+}
+
+/// Signs the body together with the timestamp it is sent at, so a captured
+/// request cannot be replayed later on with a fresh timestamp
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut payload = format!("{}.", timestamp).into_bytes();
+    payload.extend_from_slice(body);
+
+    hmac_sha256(secret, &payload)
+}
+
+fn hmac_sha256(secret: &str, payload: &[u8]) -> String {
+    let mut hmac = Hmac::new(Sha256::new(), secret.as_bytes());
+    hmac.input(payload);
+
+    hex::encode(hmac.result().code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    fn delivery(notification_id: Uuid, failed_attempts: u32) -> PendingDelivery {
+        PendingDelivery {
+            notification_id,
+            url: Url::parse("http://localhost:9000/swap-events").unwrap(),
+            body: b"{}".to_vec(),
+            failed_attempts,
+        }
+    }
+
+    #[test]
+    fn hmac_is_sha256() {
+        // Test case 2 of RFC 4231
+        let signature = hmac_sha256("Jefe", b"what do ya want for nothing?");
+
+        assert_that(&signature).is_equal_to(String::from(
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        ));
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let signature = sign("secret", 1_545_000_000, b"{}");
+
+        assert_that(&signature).is_equal_to(hmac_sha256("secret", b"1545000000.{}"));
+        assert_that(&signature).is_not_equal_to(sign("secret", 1_545_000_001, b"{}"));
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_maximum() {
+        assert_that(&retry_delay(1)).is_equal_to(Duration::from_secs(1));
+        assert_that(&retry_delay(2)).is_equal_to(Duration::from_secs(2));
+        assert_that(&retry_delay(5)).is_equal_to(Duration::from_secs(16));
+        assert_that(&retry_delay(11)).is_equal_to(Duration::from_secs(MAX_RETRY_DELAY_SECS));
+        assert_that(&retry_delay(100)).is_equal_to(Duration::from_secs(MAX_RETRY_DELAY_SECS));
+    }
+
+    #[test]
+    fn saving_a_pending_delivery_again_replaces_it() {
+        let pending_deliveries = InMemoryPendingDeliveries::default();
+        let id = Uuid::new_v4();
+
+        pending_deliveries.save(&delivery(id, 0)).unwrap();
+        pending_deliveries.save(&delivery(id, 3)).unwrap();
+
+        assert_that(&pending_deliveries.all().unwrap()).is_equal_to(vec![delivery(id, 3)]);
+    }
+
+    #[test]
+    fn removed_deliveries_are_no_longer_pending() {
+        let pending_deliveries = InMemoryPendingDeliveries::default();
+        let delivered = delivery(Uuid::new_v4(), 0);
+        let pending = delivery(Uuid::new_v4(), 1);
+
+        pending_deliveries.save(&delivered).unwrap();
+        pending_deliveries.save(&pending).unwrap();
+        pending_deliveries
+            .remove(delivered.notification_id, &delivered.url)
+            .unwrap();
+
+        assert_that(&pending_deliveries.all().unwrap()).is_equal_to(vec![pending]);
+    }
+
+    #[test]
+    fn events_are_serialized_in_snake_case() {
+        let event = serde_json::to_string(&SwapEvent::Requested).unwrap();
+
+        assert_that(&event).is_equal_to(String::from(r#""requested""#));
+    }
+}
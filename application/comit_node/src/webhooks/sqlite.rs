@@ -0,0 +1,146 @@
+use crate::webhooks::{Error, PendingDeliveries, PendingDelivery};
+use reqwest::Url;
+use rusqlite::{types::ToSql, Connection, NO_PARAMS};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use uuid::Uuid;
+
+/// Persists the pending webhook deliveries in a SQLite database.
+#[derive(Clone, DebugStub)]
+pub struct SqlitePendingDeliveries {
+    #[debug_stub = "Connection"]
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqlitePendingDeliveries {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, Error> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS pending_webhook_deliveries (
+                notification_id TEXT NOT NULL,
+                url TEXT NOT NULL,
+                body BLOB NOT NULL,
+                failed_attempts INTEGER NOT NULL,
+                PRIMARY KEY (notification_id, url)
+            )",
+            NO_PARAMS,
+        )?;
+
+        Ok(SqlitePendingDeliveries {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+}
+
+impl PendingDeliveries for SqlitePendingDeliveries {
+    fn save(&self, delivery: &PendingDelivery) -> Result<(), Error> {
+        let connection = self.connection.lock().unwrap();
+
+        connection.execute(
+            "INSERT OR REPLACE INTO pending_webhook_deliveries
+                (notification_id, url, body, failed_attempts) VALUES (?1, ?2, ?3, ?4)",
+            &[
+                &delivery.notification_id.to_string() as &dyn ToSql,
+                &delivery.url.as_str(),
+                &delivery.body,
+                &delivery.failed_attempts,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn remove(&self, notification_id: Uuid, url: &Url) -> Result<(), Error> {
+        let connection = self.connection.lock().unwrap();
+
+        connection.execute(
+            "DELETE FROM pending_webhook_deliveries WHERE notification_id = ?1 AND url = ?2",
+            &[&notification_id.to_string() as &dyn ToSql, &url.as_str()],
+        )?;
+
+        Ok(())
+    }
+
+    fn all(&self) -> Result<Vec<PendingDelivery>, Error> {
+        let connection = self.connection.lock().unwrap();
+
+        let mut statement = connection.prepare(
+            "SELECT notification_id, url, body, failed_attempts
+                FROM pending_webhook_deliveries ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(NO_PARAMS, |row| {
+            let notification_id: String = row.get(0);
+            let url: String = row.get(1);
+            let body: Vec<u8> = row.get(2);
+            let failed_attempts: u32 = row.get(3);
+            (notification_id, url, body, failed_attempts)
+        })?;
+
+        let mut deliveries = Vec::new();
+        for row in rows {
+            let (notification_id, url, body, failed_attempts) = row?;
+            deliveries.push(PendingDelivery {
+                notification_id: Uuid::parse_str(&notification_id)
+                    .map_err(|e| Error::Backend(e.to_string()))?,
+                url: Url::parse(&url).map_err(|e| Error::Backend(e.to_string()))?,
+                body,
+                failed_attempts,
+            });
+        }
+
+        Ok(deliveries)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Backend(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    fn delivery(failed_attempts: u32) -> PendingDelivery {
+        PendingDelivery {
+            notification_id: Uuid::new_v4(),
+            url: Url::parse("http://localhost:9000/swap-events").unwrap(),
+            body: br#"{"event":"funded"}"#.to_vec(),
+            failed_attempts,
+        }
+    }
+
+    #[test]
+    fn pending_deliveries_are_saved_updated_and_removed() {
+        let pending_deliveries = SqlitePendingDeliveries::open_in_memory().unwrap();
+        let first = delivery(0);
+        let second = delivery(0);
+
+        pending_deliveries.save(&first).unwrap();
+        pending_deliveries.save(&second).unwrap();
+        assert_that(&pending_deliveries.all().unwrap())
+            .is_equal_to(vec![first.clone(), second.clone()]);
+
+        let retried = PendingDelivery {
+            failed_attempts: 2,
+            ..second.clone()
+        };
+        pending_deliveries.save(&retried).unwrap();
+        pending_deliveries
+            .remove(first.notification_id, &first.url)
+            .unwrap();
+
+        assert_that(&pending_deliveries.all().unwrap()).is_equal_to(vec![retried]);
+    }
+}
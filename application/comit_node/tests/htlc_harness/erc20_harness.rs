@@ -1,11 +1,13 @@
 use crate::{
-    ethereum_wallet::InMemoryWallet,
     htlc_harness::{new_account, HTLC_TIMEOUT, SECRET},
     parity_client::ParityClient,
 };
-use comit_node::swap_protocols::rfc003::{
-    ethereum::{Erc20Htlc, Seconds},
-    Secret,
+use comit_node::{
+    swap_protocols::rfc003::{
+        ethereum::{Erc20Htlc, Seconds},
+        Secret,
+    },
+    wallet::ethereum::InMemoryWallet,
 };
 use ethereum_support::{
     web3::{
@@ -1,7 +1,9 @@
-use crate::ethereum_wallet::{UnsignedTransaction, Wallet};
-use comit_node::swap_protocols::rfc003::{
-    ethereum::{Htlc, Seconds},
-    SecretHash,
+use comit_node::{
+    swap_protocols::rfc003::{
+        ethereum::{Htlc, Seconds},
+        SecretHash,
+    },
+    wallet::ethereum::{UnsignedTransaction, Wallet},
 };
 use ethereum_support::{
     web3::{transports::Http, Web3},
@@ -6,14 +6,11 @@ extern crate log;
 #[macro_use]
 extern crate lazy_static;
 
-pub mod ethereum_wallet;
 pub mod htlc_harness;
 pub mod parity_client;
 
-use crate::{
-    ethereum_wallet::transaction::UnsignedTransaction,
-    htlc_harness::{erc20_harness, Erc20HarnessParams, HTLC_TIMEOUT, SECRET},
-};
+use crate::htlc_harness::{erc20_harness, Erc20HarnessParams, HTLC_TIMEOUT, SECRET};
+use comit_node::wallet::ethereum::UnsignedTransaction;
 use ethereum_support::{Bytes, H256, U256};
 use spectral::prelude::*;
 use testcontainers::clients::Cli;
@@ -6,7 +6,6 @@ extern crate log;
 #[macro_use]
 extern crate lazy_static;
 
-pub mod ethereum_wallet;
 pub mod htlc_harness;
 pub mod parity_client;
 
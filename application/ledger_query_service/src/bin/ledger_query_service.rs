@@ -17,7 +17,7 @@ use ledger_query_service::{
     BlockProcessor, DefaultBlockProcessor, InMemoryQueryRepository, InMemoryQueryResultRepository,
     QueryResultRepository, RouteFactory,
 };
-use std::{env::var, sync::Arc};
+use std::{env::var, net::SocketAddr, path::PathBuf, sync::Arc};
 use tokio::runtime::Runtime;
 use warp::{self, filters::BoxedFilter, Filter, Reply};
 
@@ -36,9 +36,28 @@ fn main() -> Result<(), failure::Error> {
     let (ethereum_routes, _event_loop_handle) =
         create_ethereum_routes(&mut runtime, &route_factory, settings.ethereum);
 
-    let routes = bitcoin_routes.or(ethereum_routes);
+    let routes = bitcoin_routes
+        .or(ethereum_routes)
+        .or(route_factory.create_metrics());
+
+    let address = SocketAddr::new(settings.http_api.address_bind, settings.http_api.port_bind);
+
+    match settings.http_api.tls {
+        Some(tls_settings) => {
+            let acceptor = tls_support::acceptor(
+                &tls_settings.cert_path,
+                &tls_settings.key_path,
+                tls_settings.client_ca_path.as_ref().map(PathBuf::as_path),
+            )?;
+
+            info!("Serving the HTTP API over TLS on {}", address);
+            tokio::run(
+                warp::serve(routes).serve_incoming(tls_support::incoming(address, acceptor)?),
+            );
+        }
+        None => warp::serve(routes).run(address),
+    }
 
-    warp::serve(routes).run((settings.http_api.address_bind, settings.http_api.port_bind));
     Ok(())
 }
 
@@ -102,7 +121,7 @@ fn create_bitcoin_routes(
     let block_routes = route_factory.create(
         block_query_repository,
         block_query_result_repository,
-        None,
+        Some(client),
         ledger_name,
     );
 
@@ -167,7 +186,7 @@ fn create_ethereum_routes(
     let block_routes = route_factory.create(
         block_query_repository,
         block_query_result_repository,
-        None,
+        Some(web3_client),
         ledger_name,
     );
 
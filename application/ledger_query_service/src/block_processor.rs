@@ -1,4 +1,6 @@
-use crate::{query_repository::QueryRepository, query_result_repository::QueryResultRepository};
+use crate::{
+    metrics, query_repository::QueryRepository, query_result_repository::QueryResultRepository,
+};
 use futures::{future::join_all, Future};
 use std::{
     fmt::Debug,
@@ -23,6 +25,9 @@ pub trait Transaction: Debug + 'static + Clone {
 pub trait Block: Debug + 'static + Clone {
     type Transaction: Transaction;
 
+    /// The name under which metrics of this ledger are reported
+    fn ledger_name() -> &'static str;
+    fn blockheight(&self) -> Option<u64>;
     fn blockhash(&self) -> String;
     fn prev_blockhash(&self) -> String;
     fn transactions(&self) -> &[Self::Transaction];
@@ -53,6 +58,12 @@ impl QueryMatchResult {
     pub fn no() -> Self {
         QueryMatchResult::No
     }
+    pub fn is_yes(&self) -> bool {
+        match self {
+            QueryMatchResult::Yes { .. } => true,
+            QueryMatchResult::No => false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -101,6 +112,17 @@ impl<T: Transaction, B: Block<Transaction = T>, TQ: Query<T> + 'static, BQ: Quer
             }
         }
 
+        let ledger = B::ledger_name();
+        metrics::record_block(
+            ledger,
+            self.blockhashes.last().map(String::as_str),
+            &block.blockhash(),
+            block.blockheight(),
+        );
+        let timer = metrics::MATCH_DURATION
+            .with_label_values(&[ledger])
+            .start_timer();
+
         self.blockhashes.push(block.blockhash());
         self.update_pending_transactions();
 
@@ -118,7 +140,10 @@ impl<T: Transaction, B: Block<Transaction = T>, TQ: Query<T> + 'static, BQ: Quer
         let tx_results = join_all(tx_result_vecs)
             .map(|tx_result_vec| tx_result_vec.into_iter().flatten().collect());
 
-        Box::new(block_results.join(tx_results))
+        Box::new(block_results.join(tx_results).then(move |results| {
+            timer.observe_duration();
+            results
+        }))
     }
 }
 
@@ -329,6 +354,12 @@ mod tests {
     impl Block for GenericBlock {
         type Transaction = GenericTransaction;
 
+        fn ledger_name() -> &'static str {
+            "generic"
+        }
+        fn blockheight(&self) -> Option<u64> {
+            None
+        }
         fn blockhash(&self) -> String {
             self.id.to_string()
         }
@@ -1,3 +1,4 @@
+use crate::metrics;
 use bitcoin_support::{serialize::deserialize, MinedBlock};
 use byteorder::{LittleEndian, ReadBytesExt};
 use futures::sync::mpsc::{self, UnboundedReceiver};
@@ -23,10 +24,15 @@ pub fn bitcoin_block_listener(endpoint: &str) -> Result<UnboundedReceiver<MinedB
         let _context = context;
 
         loop {
-            let result = receive_block(&mut socket);
-
-            if let Ok(Some(block)) = result {
-                let _ = state_sender.unbounded_send(block);
+            match receive_block(&mut socket) {
+                Ok(Some(block)) => {
+                    let _ = state_sender.unbounded_send(block);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("Failed to receive message from bitcoind: {:?}", e);
+                    metrics::record_connector_error(metrics::BITCOIND_ZMQ);
+                }
             }
         }
     });
@@ -51,6 +57,7 @@ fn receive_block(socket: &mut Socket) -> Result<Option<MinedBlock>, zmq::Error>
                     Ok(Some(MinedBlock::new(block, height)))
                 }
                 (Ok(_), Err(e)) => {
+                    metrics::record_connector_error(metrics::BITCOIND_ZMQ);
                     error!(
                         "Got new block but failed to extract the height because {:?}",
                         e
@@ -59,6 +66,7 @@ fn receive_block(socket: &mut Socket) -> Result<Option<MinedBlock>, zmq::Error>
                 }
 
                 (Err(e), _) => {
+                    metrics::record_connector_error(metrics::BITCOIND_ZMQ);
                     error!("Got new block but failed to deserialize it because {:?}", e);
                     Ok(None)
                 }
@@ -1,9 +1,12 @@
-use crate::web3::{
-    self,
-    futures::{Future, Stream},
-    transports::Http,
-    types::{Block, BlockId, Transaction},
-    Web3,
+use crate::{
+    metrics,
+    web3::{
+        self,
+        futures::{Future, Stream},
+        transports::Http,
+        types::{Block, BlockId, Transaction},
+        Web3,
+    },
 };
 use std::{sync::Arc, time::Duration};
 
@@ -27,6 +30,9 @@ pub fn ethereum_block_listener(
             .stream(polling_wait_time)
             .and_then(move |block_hash| client.eth().block_with_txs(BlockId::from(block_hash)))
             .filter_map(|item| item)
-            .map_err(|error| error!("Could not read block: {:?}", error)),
+            .map_err(|error| {
+                metrics::record_connector_error(metrics::ETHEREUM_WEB3);
+                error!("Could not read block: {:?}", error)
+            }),
     ))
 }
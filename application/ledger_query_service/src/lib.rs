@@ -7,11 +7,16 @@ extern crate debug_stub_derive;
 extern crate serde_derive;
 #[macro_use]
 extern crate log;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate prometheus;
 
 mod block_processor;
 mod connectors;
 mod in_memory_query_repository;
 mod in_memory_query_result_repository;
+pub mod metrics;
 mod queries;
 mod query_repository;
 mod query_result_repository;
@@ -0,0 +1,94 @@
+use hyper::{header, Body, Response};
+use prometheus::{Encoder, HistogramVec, IntCounterVec, IntGaugeVec, TextEncoder};
+
+lazy_static! {
+    pub static ref BLOCKS_PROCESSED: IntCounterVec = register_int_counter_vec!(
+        "ledger_query_service_blocks_processed_total",
+        "Number of blocks processed per ledger",
+        &["ledger"]
+    )
+    .unwrap();
+    pub static ref LAST_BLOCK_HEIGHT: IntGaugeVec = register_int_gauge_vec!(
+        "ledger_query_service_last_block_height",
+        "Height of the last block processed per ledger",
+        &["ledger"]
+    )
+    .unwrap();
+    pub static ref LAST_BLOCK_HASH: IntGaugeVec = register_int_gauge_vec!(
+        "ledger_query_service_last_block_hash",
+        "Always 1, labelled with the hash of the last block processed per ledger",
+        &["ledger", "hash"]
+    )
+    .unwrap();
+    pub static ref ACTIVE_QUERIES: IntGaugeVec = register_int_gauge_vec!(
+        "ledger_query_service_active_queries",
+        "Number of queries which are matched against new blocks",
+        &["ledger", "query_type"]
+    )
+    .unwrap();
+    pub static ref MATCH_DURATION: HistogramVec = register_histogram_vec!(
+        "ledger_query_service_match_duration_seconds",
+        "Time it takes to match a block and its transactions against all queries",
+        &["ledger"]
+    )
+    .unwrap();
+    pub static ref CONNECTOR_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "ledger_query_service_connector_errors_total",
+        "Number of errors while receiving blocks from a node",
+        &["connector"]
+    )
+    .unwrap();
+}
+
+pub const BITCOIND_ZMQ: &str = "bitcoind_zmq";
+pub const ETHEREUM_WEB3: &str = "ethereum_web3";
+
+pub fn record_block(ledger: &str, previous_hash: Option<&str>, hash: &str, height: Option<u64>) {
+    BLOCKS_PROCESSED.with_label_values(&[ledger]).inc();
+
+    if let Some(height) = height {
+        LAST_BLOCK_HEIGHT
+            .with_label_values(&[ledger])
+            .set(height as i64);
+    }
+
+    if let Some(previous_hash) = previous_hash {
+        // The previous block is no longer the last one
+        let _ = LAST_BLOCK_HASH.remove_label_values(&[ledger, previous_hash]);
+    }
+    LAST_BLOCK_HASH.with_label_values(&[ledger, hash]).set(1);
+}
+
+pub fn record_connector_error(connector: &str) {
+    CONNECTOR_ERRORS.with_label_values(&[connector]).inc();
+}
+
+/// Renders all metrics in the Prometheus text format
+pub fn render() -> Response<Body> {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    encoder
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("metrics should always encode");
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, encoder.format_type())
+        .body(Body::from(buffer))
+        .expect("response with valid headers should always build")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    #[test]
+    fn only_the_last_block_hash_is_reported() {
+        record_block("test", None, "first", Some(1));
+        record_block("test", Some("first"), "second", Some(2));
+
+        assert_that(&LAST_BLOCK_HASH.remove_label_values(&["test", "first"])).is_err();
+        assert_that(&LAST_BLOCK_HASH.with_label_values(&["test", "second"]).get()).is_equal_to(1);
+        assert_that(&LAST_BLOCK_HEIGHT.with_label_values(&["test"]).get()).is_equal_to(2);
+    }
+}
@@ -5,10 +5,10 @@ use crate::{
 };
 use bitcoin_rpc_client::{BitcoinCoreClient, BitcoinRpcApi};
 use bitcoin_support::{
-    serialize::BitcoinHash, Address, MinedBlock as BitcoinBlock, OutPoint, SpendsFrom,
+    serialize::BitcoinHash, Address, MinedBlock as BitcoinBlock, OutPoint, Sha256dHash, SpendsFrom,
     SpendsFromWith, SpendsTo, SpendsWith, Transaction as BitcoinTransaction, TransactionId,
 };
-use futures::Future;
+use futures::{future::join_all, Future};
 use std::sync::Arc;
 
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
@@ -112,6 +112,12 @@ impl Transaction for BitcoinTransaction {
 impl Block for BitcoinBlock {
     type Transaction = BitcoinTransaction;
 
+    fn ledger_name() -> &'static str {
+        "bitcoin"
+    }
+    fn blockheight(&self) -> Option<u64> {
+        Some(u64::from(self.height))
+    }
     fn blockhash(&self) -> String {
         format!("{:x}", self.as_ref().header.bitcoin_hash())
     }
@@ -123,9 +129,12 @@ impl Block for BitcoinBlock {
     }
 }
 
+/// Matches blocks which are at least `min_height` high and, if given, include a
+/// transaction matching `containing_transaction`
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
 pub struct BitcoinBlockQuery {
     pub min_height: Option<u32>,
+    pub containing_transaction: Option<BitcoinTransactionQuery>,
 }
 
 impl QueryType for BitcoinBlockQuery {
@@ -134,18 +143,42 @@ impl QueryType for BitcoinBlockQuery {
     }
 }
 
+/// An expanded block match, the height is what clients need to work with
+/// relative timelocks
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BitcoinBlockSummary {
+    pub hash: String,
+    pub height: u32,
+}
+
 impl ShouldExpand for BitcoinBlockQuery {
-    fn should_expand(_: &QueryParams) -> bool {
-        false
+    fn should_expand(query_params: &QueryParams) -> bool {
+        query_params.expand_results
     }
 }
 
 impl ExpandResult for BitcoinBlockQuery {
-    type Client = ();
-    type Item = ();
+    type Client = BitcoinCoreClient;
+    type Item = BitcoinBlockSummary;
 
-    fn expand_result(_result: &QueryResult, _client: Arc<()>) -> Result<Vec<Self::Item>, Error> {
-        unimplemented!()
+    fn expand_result(
+        result: &QueryResult,
+        client: Arc<BitcoinCoreClient>,
+    ) -> Result<Vec<BitcoinBlockSummary>, Error> {
+        let mut expanded_result = Vec::new();
+        for block_hash in result.clone().0 {
+            let hash = Sha256dHash::from_hex(block_hash.as_str()).map_err(|_| Error::InvalidHex)?;
+
+            let block = client
+                .get_block(&hash)
+                .map_err(Error::BitcoinRpcConnection)?
+                .map_err(Error::BitcoinRpcResponse)?;
+            expanded_result.push(BitcoinBlockSummary {
+                hash: block_hash,
+                height: block.height,
+            });
+        }
+        Ok(expanded_result)
     }
 }
 
@@ -154,23 +187,39 @@ impl Query<BitcoinBlock> for BitcoinBlockQuery {
         &self,
         block: &BitcoinBlock,
     ) -> Box<dyn Future<Item = QueryMatchResult, Error = ()> + Send> {
-        Box::new(futures::future::ok(match self.min_height {
-            Some(height) => {
-                if height <= block.height {
-                    QueryMatchResult::yes()
-                } else {
-                    QueryMatchResult::no()
-                }
+        if self.is_empty() {
+            warn!("min_height and containing_transaction not set, nothing to compare");
+            return Box::new(futures::future::ok(QueryMatchResult::no()));
+        }
+
+        if let Some(height) = self.min_height {
+            if height > block.height {
+                return Box::new(futures::future::ok(QueryMatchResult::no()));
             }
-            None => {
-                warn!("min_height not set, nothing to compare");
-                QueryMatchResult::no()
+        }
+
+        match &self.containing_transaction {
+            Some(transaction_query) => {
+                let transaction_matches = block
+                    .transactions()
+                    .iter()
+                    .map(|transaction| transaction_query.matches(transaction))
+                    .collect::<Vec<_>>();
+
+                Box::new(join_all(transaction_matches).map(|results| {
+                    if results.iter().any(QueryMatchResult::is_yes) {
+                        QueryMatchResult::yes()
+                    } else {
+                        QueryMatchResult::no()
+                    }
+                }))
             }
-        }))
+            None => Box::new(futures::future::ok(QueryMatchResult::yes())),
+        }
     }
 
     fn is_empty(&self) -> bool {
-        self.min_height.is_none()
+        self.min_height.is_none() && self.containing_transaction.is_none()
     }
 }
 
@@ -223,6 +272,7 @@ mod tests {
 
         let query = BitcoinBlockQuery {
             min_height: Some(42),
+            containing_transaction: None,
         };
 
         let result = exec_future(query.matches(&block));
@@ -250,6 +300,7 @@ mod tests {
 
         let query = BitcoinBlockQuery {
             min_height: Some(42),
+            containing_transaction: None,
         };
 
         let result = exec_future(query.matches(&block));
@@ -277,12 +328,53 @@ mod tests {
 
         let query = BitcoinBlockQuery {
             min_height: Some(42),
+            containing_transaction: None,
+        };
+
+        let result = exec_future(query.matches(&block));
+        assert_that(&result).is_equal_to(QueryMatchResult::yes());
+    }
+
+    #[test]
+    fn given_query_containing_transaction_then_block_with_matching_transaction_matches() {
+        let block = block_with_transaction(parse_raw_tx(WITNESS_TX));
+
+        let query = BitcoinBlockQuery {
+            min_height: None,
+            containing_transaction: Some(BitcoinTransactionQuery {
+                to_address: Some("329XTScM6cJgu8VZvaqYWpfuxT1eQDSJkP".parse().unwrap()),
+                from_outpoint: None,
+                unlock_script: None,
+                confirmations_needed: 1,
+            }),
         };
 
         let result = exec_future(query.matches(&block));
         assert_that(&result).is_equal_to(QueryMatchResult::yes());
     }
 
+    #[test]
+    fn given_query_containing_transaction_then_block_without_matching_transaction_does_not_match() {
+        let block = block_with_transaction(parse_raw_tx(WITNESS_TX));
+
+        let query = BitcoinBlockQuery {
+            min_height: None,
+            containing_transaction: Some(BitcoinTransactionQuery {
+                to_address: Some(
+                    "bcrt1qcqslz7lfn34dl096t5uwurff9spen5h4v2pmap"
+                        .parse()
+                        .unwrap(),
+                ),
+                from_outpoint: None,
+                unlock_script: None,
+                confirmations_needed: 1,
+            }),
+        };
+
+        let result = exec_future(query.matches(&block));
+        assert_that(&result).is_equal_to(QueryMatchResult::no());
+    }
+
     #[test]
     fn given_transaction_with_to_then_to_address_query_matches() {
         let tx = parse_raw_tx(WITNESS_TX);
@@ -357,11 +449,29 @@ mod tests {
         assert_that(&result).is_equal_to(QueryMatchResult::yes());
     }
 
+    fn block_with_transaction(transaction: BitcoinTransaction) -> MinedBlock {
+        let block_header = BlockHeader {
+            version: 1,
+            prev_blockhash: Sha256dHash::default(),
+            merkle_root: Sha256dHash::default(),
+            time: 0,
+            bits: 1,
+            nonce: 0,
+        };
+
+        MinedBlock::new(
+            Block {
+                header: block_header,
+                txdata: vec![transaction],
+            },
+            42,
+        )
+    }
+
     fn exec_future(
         future: Box<dyn Future<Item = QueryMatchResult, Error = ()> + Send>,
     ) -> QueryMatchResult {
         let mut runtime = tokio::runtime::Runtime::new().unwrap();
         runtime.block_on(future).map_err(|_| ()).unwrap()
     }
-
 }
@@ -6,13 +6,13 @@ use crate::{
 use ethereum_support::{
     web3::{
         transports::Http,
-        types::{H256, U256},
+        types::{BlockId, H256, U256},
         Web3,
     },
     Address, Block as EthereumBlock, Bytes, Transaction as EthereumTransaction, TransactionId,
 };
 use futures::{
-    future::Future,
+    future::{join_all, Future},
     stream::{self, Stream},
 };
 use std::sync::Arc;
@@ -144,6 +144,12 @@ impl Transaction for EthereumTransaction {
 
 impl Block for EthereumBlock<EthereumTransaction> {
     type Transaction = EthereumTransaction;
+    fn ledger_name() -> &'static str {
+        "ethereum"
+    }
+    fn blockheight(&self) -> Option<u64> {
+        self.number.map(|number| number.as_u64())
+    }
     fn blockhash(&self) -> String {
         format!("{:x}", self.hash.unwrap())
     }
@@ -160,29 +166,47 @@ impl Query<EthereumBlock<EthereumTransaction>> for EthereumBlockQuery {
         &self,
         block: &EthereumBlock<EthereumTransaction>,
     ) -> Box<dyn Future<Item = QueryMatchResult, Error = ()> + Send> {
-        match self.min_timestamp_secs {
-            Some(min_timestamp_secs) => {
-                let min_timestamp_secs = U256::from(min_timestamp_secs);
-                if min_timestamp_secs <= block.timestamp {
-                    Box::new(futures::future::ok(QueryMatchResult::yes()))
-                } else {
-                    Box::new(futures::future::ok(QueryMatchResult::no()))
-                }
+        if self.is_empty() {
+            warn!("min_timestamp and containing_transaction not set, nothing to compare");
+            return Box::new(futures::future::ok(QueryMatchResult::no()));
+        }
+
+        if let Some(min_timestamp_secs) = self.min_timestamp_secs {
+            if U256::from(min_timestamp_secs) > block.timestamp {
+                return Box::new(futures::future::ok(QueryMatchResult::no()));
             }
-            None => {
-                warn!("min_timestamp not set, nothing to compare");
-                Box::new(futures::future::ok(QueryMatchResult::no()))
+        }
+
+        match &self.containing_transaction {
+            Some(transaction_query) => {
+                let transaction_matches = block
+                    .transactions()
+                    .iter()
+                    .map(|transaction| transaction_query.matches(transaction))
+                    .collect::<Vec<_>>();
+
+                Box::new(join_all(transaction_matches).map(|results| {
+                    if results.iter().any(QueryMatchResult::is_yes) {
+                        QueryMatchResult::yes()
+                    } else {
+                        QueryMatchResult::no()
+                    }
+                }))
             }
+            None => Box::new(futures::future::ok(QueryMatchResult::yes())),
         }
     }
     fn is_empty(&self) -> bool {
-        self.min_timestamp_secs.is_none()
+        self.min_timestamp_secs.is_none() && self.containing_transaction.is_none()
     }
 }
 
+/// Matches blocks which are not older than `min_timestamp_secs` and, if given,
+/// include a transaction matching `containing_transaction`
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
 pub struct EthereumBlockQuery {
     pub min_timestamp_secs: Option<u64>,
+    pub containing_transaction: Option<EthereumTransactionQuery>,
 }
 
 impl QueryType for EthereumBlockQuery {
@@ -191,18 +215,59 @@ impl QueryType for EthereumBlockQuery {
     }
 }
 
+/// An expanded block match, the timestamp is what clients need to work with
+/// timestamp based timelocks
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EthereumBlockSummary {
+    pub hash: String,
+    pub timestamp: u64,
+}
+
 impl ShouldExpand for EthereumBlockQuery {
-    fn should_expand(_: &QueryParams) -> bool {
-        false
+    fn should_expand(params: &QueryParams) -> bool {
+        params.expand_results
     }
 }
 
 impl ExpandResult for EthereumBlockQuery {
-    type Client = ();
-    type Item = ();
+    type Client = Web3<Http>;
+    type Item = EthereumBlockSummary;
+
+    fn expand_result(
+        result: &QueryResult,
+        client: Arc<Web3<Http>>,
+    ) -> Result<Vec<Self::Item>, Error> {
+        let futures: Vec<_> = result
+            .0
+            .iter()
+            .filter_map(|block_hash| match hex::decode(clean_0x(block_hash)) {
+                Ok(bytes) => Some((block_hash.clone(), bytes)),
+                Err(e) => {
+                    warn!(
+                        "Skipping {} because it is not valid hex: {:?}",
+                        block_hash, e
+                    );
+                    None
+                }
+            })
+            .map(|(hash, id)| {
+                client
+                    .eth()
+                    .block(BlockId::Hash(H256::from_slice(id.as_ref())))
+                    .map_err(Error::Web3)
+                    .map(move |block| {
+                        block.map(|block| EthereumBlockSummary {
+                            hash,
+                            timestamp: block.timestamp.low_u64(),
+                        })
+                    })
+            })
+            .collect();
 
-    fn expand_result(_result: &QueryResult, _client: Arc<()>) -> Result<Vec<Self::Item>, Error> {
-        unimplemented!()
+        stream::futures_ordered(futures)
+            .filter_map(|item| item)
+            .collect()
+            .wait()
     }
 }
 
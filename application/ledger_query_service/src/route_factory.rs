@@ -103,6 +103,8 @@ impl RouteFactory {
         let delete = warp::delete2()
             .and(query_repository)
             .and(query_result_repository)
+            .and(warp::any().map(move || ledger_name))
+            .and(warp::any().map(move || route))
             .and(warp::path::param::<u32>())
             .and_then(routes::delete_query);
 
@@ -110,4 +112,12 @@ impl RouteFactory {
             .recover(routes::customize_error)
             .boxed()
     }
+
+    pub fn create_metrics(&self) -> BoxedFilter<(impl Reply,)> {
+        warp::path("metrics")
+            .and(warp::get2())
+            .and(warp::path::end())
+            .and_then(routes::get_metrics)
+            .boxed()
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{
     block_processor::Query,
+    metrics,
     query_repository::QueryRepository,
     query_result_repository::QueryResultRepository,
     route_factory::{ExpandResult, QueryParams, ShouldExpand},
@@ -95,6 +96,10 @@ pub fn create_query<O, Q: Query<O> + Send, QR: QueryRepository<Q>>(
 
     match result {
         Ok(id) => {
+            metrics::ACTIVE_QUERIES
+                .with_label_values(&[ledger_name, query_type])
+                .inc();
+
             let uri = external_url
                 .join(format!("/queries/{}/{}/{}", ledger_name, query_type, id).as_str())
                 .expect("Should be able to join urls")
@@ -171,8 +176,16 @@ pub fn delete_query<
 >(
     query_repository: Arc<QR>,
     query_result_repository: Arc<QRR>,
+    ledger_name: &'static str,
+    query_type: &'static str,
     id: u32,
 ) -> Result<impl Reply, Rejection> {
+    if query_repository.get(id).is_some() {
+        metrics::ACTIVE_QUERIES
+            .with_label_values(&[ledger_name, query_type])
+            .dec();
+    }
+
     query_repository.delete(id);
     query_result_repository.delete(id);
 
@@ -182,6 +195,10 @@ pub fn delete_query<
     ))
 }
 
+pub fn get_metrics() -> Result<impl Reply, Rejection> {
+    Ok(metrics::render())
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 enum ResponsePayload<T> {
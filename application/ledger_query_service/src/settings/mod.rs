@@ -2,7 +2,12 @@ mod serde;
 
 use bitcoin_support::Network;
 use config::{Config, ConfigError, File};
-use std::{ffi::OsStr, net::IpAddr, path::Path, time::Duration};
+use std::{
+    ffi::OsStr,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
@@ -17,6 +22,20 @@ pub struct HttpApi {
     pub port_bind: u16,
     #[serde(with = "serde::url")]
     pub external_url: url::Url,
+    /// If omitted, the HTTP API is served over plain HTTP
+    #[serde(default)]
+    pub tls: Option<Tls>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Tls {
+    /// PEM encoded certificate chain, starting with the server's certificate
+    pub cert_path: PathBuf,
+    /// PEM encoded private key of the server's certificate
+    pub key_path: PathBuf,
+    /// If given, clients have to present a certificate signed by this CA
+    #[serde(default)]
+    pub client_ca_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -69,4 +88,12 @@ mod tests {
         assert_that(&settings).is_ok();
     }
 
+    #[test]
+    fn tls_defaults_to_none() {
+        let settings = Settings::create("./config/default.toml");
+
+        assert_that(&settings).is_ok();
+        assert_that(&settings.unwrap().http_api.tls).is_none();
+    }
+
 }